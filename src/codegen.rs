@@ -1,416 +1,454 @@
 #![allow(clippy::wildcard_imports)]
 
-use std::fmt::Write;
+use std::{
+    error::Error,
+    fmt::{self, Write},
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+};
 
-use crate::{Context, Op, Program};
+use crate::{
+    parse::{CellWidth, Signature},
+    Context, Op, Program,
+};
 
-pub mod fasm_x86_64_linux {
-    use super::*;
+pub mod aarch64_linux;
+pub mod c;
+pub mod gas_x86_64_linux;
+pub mod llvm_ir;
+pub mod mos_6502_nes;
+pub mod mos_6502_nesulator;
+pub mod qbe;
+pub mod riscv64_linux;
+pub mod rust;
+pub mod wasm32_wasi;
+pub mod x86_64_linux;
+pub mod x86_64_multiboot;
+pub mod x86_linux;
 
-    #[allow(clippy::too_many_lines)]
-    fn write_op(
-        op: &Op,
-        count_ops: &mut usize,
-        buffer: &mut String,
-        ctx: &Context,
-    ) -> std::fmt::Result {
-        match op {
-            Op::CallFn(index) => write!(
-                buffer,
-                "\tmov\trax, [ret_stack_rsp]\t; Op::CallFn({index})
-\tsub\trax, 8
-\tmov\t[ret_stack_rsp], rax
-\tmov\tqword [rax], RET{count_ops}
-\tjmp\tfn_{func}
-RET{count_ops}:
-\tmov\trax, [ret_stack_rsp]
-\tadd\trax, 8
-\tmov\t[ret_stack_rsp], rax
-",
-                // TODO: this nonsense obviously needs to be refactored. But I don't care right now.
-                func = ctx
-                    .lookup
-                    .iter()
-                    .find(|(_, v)| *v == index)
-                    .expect("the index was taken from the same context lookup")
-                    .0
-            )?,
-            // Small optimisation for the 0 case.
-            Op::Ret(0) => write!(
-                buffer,
-                "\tmov\trax, qword [ret_stack_rsp]\t; Op::Ret(0)
-\tjmp qword [rax]
-"
-            )?,
-            Op::Ret(count) => write!(
-                buffer,
-                "\tmov\trax, [ret_stack_rsp]\t; Op::Ret({count})
-\tadd\trax, {}
-\tmov\tqword [ret_stack_rsp], rax
-\tjmp\tqword [rax]
-",
-                count * 8
-            )?,
-            Op::Bind { count, peek, body } => {
-                write!(
-                    buffer,
-                    "\tmov\trax, [ret_stack_rsp]\t; Op::Bind({count})
-\tsub\trax, {}
-\tmov\t[ret_stack_rsp], rax
-",
-                    count * 8
-                )?;
-                for i in 0..*count {
-                    write!(
-                        buffer,
-                        "\tmov\trbx, [rsp + {0}]
-\tmov\t[rax+{0}], rbx
-",
-                        i * 8
-                    )?;
-                }
-                if !peek {
-                    writeln!(buffer, "\tadd\trsp, {}", count * 8)?;
-                }
-                for op in body {
-                    write_op(op, count_ops, buffer, ctx)?;
-                }
-                // Remove the bindings from the return stack.
-                write!(
-                    buffer,
-                    "\tmov\trax, [ret_stack_rsp]
-\tadd\trax, {}
-\tmov\tqword [ret_stack_rsp], rax
-",
-                    count * 8
-                )?;
-            }
-            Op::PushBind(index) => write!(
-                buffer,
-                "\tmov\trax, [ret_stack_rsp]\t; Op::PushBind({index})
-\tadd\trax, {}
-\tpush\tqword [rax]
-",
-                index * 8
-            )?,
-            Op::PushInt(val) => writeln!(buffer, "\tpush\t{val}\t\t\t; Op::PushInt({val})")?,
-            Op::PushStrPtr(index) => {
-                writeln!(buffer, "\tpush\tstr_{index}\t\t\t; Op::PushStrPtr({index})")?;
-            }
-            Op::Plus => write!(
-                buffer,
-                "\tpop\trax\t\t\t; Op::Plus
-\tpop\trbx
-\tadd\trax, rbx
-\tpush\trax
-",
-            )?,
-            Op::Minus => write!(
-                buffer,
-                "\tpop\trbx\t\t\t; Op::Minus
-\tpop\trax
-\tsub\trax, rbx
-\tpush\trax
-",
-            )?,
-            Op::DivMod => write!(
-                buffer,
-                "\tpop\trbx\t\t\t; Op::DivMod
-\tpop\trax
-\tmov\trdx, 0
-\tdiv\trbx
-\tpush\trax
-\tpush\trdx
-",
-            )?,
-            Op::Dup => writeln!(buffer, "\tpush\tqword [rsp]\t\t; Op::Dup",)?,
-            Op::Drop => writeln!(buffer, "\tadd\trsp, 8\t\t\t; Op::Drop",)?,
-            Op::Swap => write!(
-                buffer,
-                "\tpop\trax\t\t\t; Op::Swap
-\tpop\trbx
-\tpush\trax
-\tpush\trbx
-",
-            )?,
-            Op::Over => write!(
-                buffer,
-                "\tpop\trax\t\t\t; Op::Over
-\tpop\trbx
-\tpop\trcx
-\tpush\trbx
-\tpush\trax
-\tpush\trcx
-"
-            )?,
-            Op::Equals => {
-                write!(
-                    buffer,
-                    "\tpop\trax\t\t\t; Op::Equals
-\tpop\trbx
-\tcmp\trax, rbx
-\tje\tJ{0}
-\tpush\t0
-\tjmp\tJ{1}
-J{0}:
-\tpush\t1
-J{1}:
-",
-                    count_ops,
-                    *count_ops + 1,
-                )?;
-                *count_ops += 1;
-            }
-            Op::Neq => {
-                write!(
-                    buffer,
-                    "\tpop\trax\t\t\t; Op::Neq
-\tpop\trbx
-\tcmp\trax, rbx
-\tjne\tJ{0}
-\tpush\t0
-\tjmp\tJ{1}
-J{0}:
-\tpush\t1
-J{1}:
-",
-                    count_ops,
-                    *count_ops + 1,
-                )?;
-                *count_ops += 1;
-            }
-            Op::Not => write!(
-                buffer,
-                "\tpop\trax\t\t\t; Op::Not
-\tmov\trbx, 1
-\tsub\trbx, rax
-\tpush\trbx
-"
-            )?,
-            Op::GreaterThan => {
-                write!(
-                    buffer,
-                    "\tpop\trax\t\t\t; Op::GreaterThan
-\tpop\trbx
-\tcmp\trax, rbx
-\tjb\tJ{0}
-\tpush\t0
-\tjmp\tJ{1}
-J{0}:
-\tpush\t1
-J{1}:
-",
-                    count_ops,
-                    *count_ops + 1,
-                )?;
-                *count_ops += 1;
-            }
-            Op::LessThan => {
-                write!(
-                    buffer,
-                    "\tpop\trax\t\t\t; Op::LessThan
-\tpop\trbx
-\tcmp\trbx, rax
-\tjb\tJ{0}
-\tpush\t0
-\tjmp\tJ{1}
-J{0}:
-\tpush\t1
-J{1}:
-",
-                    count_ops,
-                    *count_ops + 1,
-                )?;
-                *count_ops += 1;
-            }
-            Op::Or => {
-                write!(
-                    buffer,
-                    "\tpop\trax\t\t\t; Op::Or
-\tpop\trbx
-\tcmp\trax, 1
-\tje\tJ{0}
-\tcmp\trbx, 1
-\tje\tJ{0}
-\tpush\t0
-\tjmp\tJ{1}
-J{0}:
-\tpush\t1
-J{1}:
-",
-                    count_ops,
-                    *count_ops + 1,
-                )?;
-                *count_ops += 1;
-            }
-            Op::And => {
-                write!(
-                    buffer,
-                    "\tpop\trax\t\t\t; Op::And
-\tpop\trbx
-\tcmp\trax, rbx
-\tjne\tJ{0}
-\tcmp\trax, 1
-\tjne\tJ{0}
-\tpush\t1
-\tjmp\tJ{1}
-J{0}:
-\tpush\t0
-J{1}:
-",
-                    count_ops,
-                    *count_ops + 1,
-                )?;
-                *count_ops += 1;
-            }
-            Op::ReadByte => write!(
-                buffer,
-                "\tpop\trbx\t\t\t; Op::ReadByte
-\tmov\trax, 0
-\tmov\tal, byte [rbx]
-\tpush\trax
-"
-            )?,
-            Op::If(ops) => {
-                let jump_to = *count_ops;
-                *count_ops += 1;
-                write!(
-                    buffer,
-                    "\tpop\trax\t\t\t; Op::If
-\tcmp\trax, 1
-\tjne\tF{jump_to}
-"
-                )?;
-                for op in ops {
-                    write_op(op, count_ops, buffer, ctx)?;
-                }
-                writeln!(buffer, "F{jump_to}:")?;
-            }
-            Op::While { condn, body } => {
-                let condn_jump = *count_ops;
-                let end_jump = *count_ops + 1;
-                *count_ops += 2;
-                writeln!(buffer, "F{condn_jump}:\t\t\t\t\t; Op::While")?;
-                for op in condn {
-                    write_op(op, count_ops, buffer, ctx)?;
-                }
-                // Check the while condition and jump to end if not met.
-                write!(
-                    buffer,
-                    "\tpop\trax
-\tcmp\trax, 1
-\tjne\tF{end_jump}
-"
-                )?;
-                for op in body {
-                    write_op(op, count_ops, buffer, ctx)?;
-                }
-                writeln!(buffer, "\tjmp F{condn_jump}\nF{end_jump}:")?;
-            }
-            Op::Print => write!(buffer, "\tpop\trdi\t\t\t; Op::Print\n\tcall\tprint\n")?,
-            Op::Puts => write!(
-                buffer,
-                "\tmov\trdi, 1\t\t\t; Op::Puts
-\tpop\trsi
-\tpop\trdx
-\tmov\trax, 1
-\tsyscall
-"
-            )?,
+/// Every backend, in the order they are listed in the help. The first one is the default target.
+pub const BACKENDS: &[&dyn Backend] = &[
+    &x86_64_linux::Executable,
+    &x86_64_linux::Source,
+    &x86_64_linux::Nasm,
+    &gas_x86_64_linux::Gas,
+    &x86_linux::Executable,
+    &x86_64_multiboot::Image,
+    &c::C,
+    &rust::Rust,
+    &llvm_ir::LlvmIr,
+    &qbe::Qbe,
+    &aarch64_linux::Executable,
+    &riscv64_linux::Executable,
+    &wasm32_wasi::Wasi,
+    &wasm32_wasi::Text,
+    &mos_6502_nes::Nes,
+    &mos_6502_nesulator::Nesulator,
+];
+
+/// The output of a backend's code generator.
+pub enum Artifact {
+    /// Source code, such as assembly.
+    Text(String),
+    /// A binary ready to be loaded, such as a ROM image.
+    Binary(Vec<u8>),
+}
+
+impl Artifact {
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::Text(text) => text.as_bytes(),
+            Self::Binary(bytes) => bytes,
         }
-        *count_ops += 1;
-        Ok(())
     }
+}
 
-    pub fn generate(program: Program) -> Result<String, std::fmt::Error> {
-        let mut outbuf = String::from(
-            "format ELF64 executable 3
-entry main
-segment readable executable
-",
-        );
+/// A code generator for a target, along with the tools needed to build and run its output.
+pub trait Backend: Sync {
+    /// The name of the target, as given to `--target`.
+    fn name(&self) -> &'static str;
 
-        outbuf += "print:
-\tmov\tr9, -3689348814741910323
-\tsub\trsp, 40
-\tmov\tBYTE [rsp+31], 10
-\tlea\trcx, [rsp+30]
-.L2:
-\tmov\trax, rdi
-\tlea\tr8, [rsp+32]
-\tmul\tr9
-\tmov\trax, rdi
-\tsub\tr8, rcx
-\tshr\trdx, 3
-\tlea\trsi, [rdx+rdx*4]
-\tadd\trsi, rsi
-\tsub\trax, rsi
-\tadd\teax, 48
-\tmov\tBYTE [rcx], al
-\tmov\trax, rdi
-\tmov\trdi, rdx
-\tmov\trdx, rcx
-\tsub\trcx, 1
-\tcmp\trax, 9
-\tja\t.L2
-\tlea\trax, [rsp+32]
-\tmov\tedi, 1
-\tsub\trdx, rax
-\txor\teax, eax
-\tlea\trsi, [rsp+32+rdx]
-\tmov\trdx, r8
-\tmov\trax, 1
-\tsyscall
-\tadd\trsp, 40
-\tret
-";
-
-        let mut count_ops = 0;
-
-        for func in program.funcs.iter() {
-            writeln!(outbuf, "fn_{}:", func.ident)?;
-            for op in &func.body {
-                write_op(op, &mut count_ops, &mut outbuf, &program.ctx)?;
-            }
-        }
+    /// The extension of the default output file, `./out`.
+    fn extension(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// The width of a cell on the stacks, which bounds the integer literals a program may use.
+    fn cell_width(&self) -> CellWidth {
+        CellWidth::default()
+    }
+
+    fn generate(&self, program: Program) -> Result<Artifact, Box<dyn Error>>;
+
+    /// The extension of the generated artifact, if it is only an intermediate step towards the
+    /// output and has to be built by the commands returned by [`Backend::build`].
+    fn intermediate(&self) -> Option<&'static str> {
+        None
+    }
 
+    /// The commands that build the output at `out` from the artifact at `artifact`, in order.
+    fn build(&self, _artifact: &Path, _out: &Path) -> Vec<Command> {
+        Vec::new()
+    }
+
+    /// The command that runs the output at `out`, if it can be run on this machine.
+    fn run(&self, _out: &Path) -> Option<Command> {
+        None
+    }
+
+    /// The exit code of the program, given the exit code of the command from [`Backend::run`].
+    fn exit_code(&self, code: i32) -> i32 {
+        code
+    }
+
+    /// The architecture of the target's assembly, which inline assembly is passed through into,
+    /// if the target generates assembly.
+    fn arch(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Rack source defining the functions that the target provides, such as access to its
+    /// hardware, which is parsed ahead of every program compiled for the target.
+    fn prelude(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Whether programs for the target may call C functions declared with `extern fn`. Such
+    /// programs are generated with [`Backend::generate_object`] and linked with [`Backend::link`].
+    fn extern_fns(&self) -> bool {
+        false
+    }
+
+    /// Generate a relocatable object from a program parsed with [`Context::object`] set, which
+    /// exports each function it defines and imports the ones it only calls. The object that
+    /// defines `main` also holds it, as a C `main` called by the C library's startup code.
+    ///
+    /// [`Context::object`]: crate::parse::Context::object
+    fn generate_object(&self, _program: Program) -> Result<Artifact, Box<dyn Error>> {
+        Err(format!("objects can't be emitted for `{}`", self.name()).into())
+    }
+
+    /// The commands that build the object at `out` from the artifact generated by
+    /// [`Backend::generate_object`], in order.
+    fn build_object(&self, _artifact: &Path, _out: &Path) -> Vec<Command> {
+        Vec::new()
+    }
+
+    /// The command that links `objects` and the C library into an executable at `out`, if the
+    /// target supports separate compilation.
+    fn link(&self, _objects: &[PathBuf], _out: &Path) -> Option<Command> {
+        None
+    }
+
+    /// The command that archives `objects` into a static library at `out`, if the target supports
+    /// separate compilation.
+    fn archive(&self, _objects: &[PathBuf], _out: &Path) -> Option<Command> {
+        None
+    }
+}
+
+/// Whether `program` defines `main`, so that it has the entry point.
+fn defines_main(program: &Program) -> bool {
+    program
+        .funcs
+        .iter()
+        .any(|func| func.ident == "main" && !func.is_import())
+}
+
+/// The functions of `program` that are exported to C, with their signatures. Only objects
+/// export anything, since a whole program has nothing to call it.
+fn exports<'src>(program: &Program<'src>, emit: Emit) -> Vec<(&'src str, Signature)> {
+    if emit == Emit::Exe {
+        return Vec::new();
+    }
+    program
+        .funcs
+        .iter()
+        .filter_map(|func| Some((func.ident, func.export?)))
+        .collect()
+}
+
+/// A C header declaring the functions that `program` exports, guarded by `guard`.
+pub fn c_header(program: &Program, guard: &str) -> Result<String, fmt::Error> {
+    let mut header = format!(
+        "/* Functions exported from Rack. They share one return stack, so they are not reentrant. */
+#ifndef {guard}
+#define {guard}
+
+#include <stdint.h>
+
+"
+    );
+    for (name, signature) in exports(program, Emit::Obj) {
+        let result = if signature.outputs == 0 {
+            "void"
+        } else {
+            "uint64_t"
+        };
+        let params = match signature.inputs {
+            0 => "void".to_owned(),
+            inputs => vec!["uint64_t"; inputs].join(", "),
+        };
+        writeln!(header, "{result} {name}({params});")?;
+    }
+    writeln!(header, "\n#endif")?;
+    Ok(header)
+}
+
+/// What is generated from a program.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Emit {
+    /// A whole program, built into an executable or whatever else the target produces.
+    #[default]
+    Exe,
+    /// A relocatable object, to be linked with objects compiled from other files.
+    Obj,
+    /// A static library holding an object, for linking into programs written in other languages.
+    Lib,
+}
+
+#[derive(Debug)]
+pub struct UnknownEmitError;
+
+impl Error for UnknownEmitError {}
+
+impl fmt::Display for UnknownEmitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
-            outbuf,
-            "main:
-\tmov\trax, ret_stack_rsp
-\tsub\trax, 8
-\tmov\tqword [ret_stack_rsp], rax
-\tmov\tqword [rax], RET_MAIN
-\tcall\tfn_main
-RET_MAIN:
-\tmov\trax, 60
-\tmov\trdi, 0
-\tsyscall
-segment readable
-",
-        )?;
-        for (i, s) in program.ctx.strings.iter().enumerate() {
-            let mut s_bytes = String::new();
-            for b in s.as_bytes() {
-                write!(&mut s_bytes, "{b},").unwrap();
-            }
-            writeln!(outbuf, "str_{i}: db {}", s_bytes.trim_end_matches(','))?;
+            f,
+            "output kind not supported (expected `exe`, `obj` or `lib`)"
+        )
+    }
+}
+
+impl FromStr for Emit {
+    type Err = UnknownEmitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "exe" => Ok(Self::Exe),
+            "obj" => Ok(Self::Obj),
+            "lib" => Ok(Self::Lib),
+            _ => Err(UnknownEmitError),
         }
+    }
+}
 
-        Ok(outbuf
-            + "segment readable writable
-ret_stack_rsp: rq 1
-ret_stack: rb 65536
-ret_stack_end:
-")
+/// Look up a backend by the name of its target.
+pub fn lookup(name: &str) -> Option<&'static dyn Backend> {
+    let name = name.to_lowercase();
+    BACKENDS
+        .iter()
+        .copied()
+        .find(|backend| backend.name() == name)
+}
+
+/// A backend selected by the name of its target.
+#[derive(Clone, Copy)]
+pub struct Target(pub &'static dyn Backend);
+
+impl Default for Target {
+    fn default() -> Self {
+        Self(BACKENDS[0])
     }
 }
 
-pub mod mos_6502_nesulator {
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0.name())
+    }
+}
+
+impl FromStr for Target {
+    type Err = TargetNotFoundError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        lookup(s).map(Self).ok_or(TargetNotFoundError)
+    }
+}
+
+#[derive(Debug)]
+pub struct TargetNotFoundError;
+
+impl Error for TargetNotFoundError {}
+
+impl fmt::Display for TargetNotFoundError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "target not supported (expected one of ")?;
+        for (i, backend) in BACKENDS.iter().enumerate() {
+            let sep = if i == 0 { "" } else { ", " };
+            write!(f, "{sep}`{}`", backend.name())?;
+        }
+        write!(f, ")")
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use std::{env, fs, ops::Deref, path::PathBuf};
+
     use super::*;
+    use crate::{parse, Lexer};
+
+    /// A directory for a test's files, which is removed when dropped, even if the test fails.
+    pub struct TempDir(PathBuf);
 
-    #[must_use]
-    pub fn generate(_program: Program) -> [u8; 65536 - 0x4020] {
-        todo!()
+    impl TempDir {
+        /// Create a fresh directory named after `name` and this process.
+        pub fn new(name: &str) -> Self {
+            let dir = env::temp_dir().join(format!("rack-{name}-{}", std::process::id()));
+            fs::remove_dir_all(&dir).ok();
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Deref for TempDir {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            fs::remove_dir_all(&self.0).ok();
+        }
+    }
+
+    /// Test programs whose output depends on the target, such as the address of a string.
+    pub const TARGET_DEPENDENT: [&str; 1] = ["strings"];
+
+    /// The test programs from `tests/src` that have an entry point, as their stem, path and
+    /// source.
+    pub fn programs() -> Vec<(String, PathBuf, String)> {
+        let mut programs = fs::read_dir("tests/src")
+            .unwrap()
+            .map(|f| f.unwrap().path())
+            .map(|path| {
+                let stem = path.file_stem().unwrap().to_str().unwrap().to_owned();
+                let source = fs::read_to_string(&path).unwrap();
+                (stem, path, source)
+            })
+            .filter(|(_, path, source)| {
+                parse::parse_tokens(&mut Lexer::new(source, path.to_str()))
+                    .is_ok_and(|program| program.ctx.lookup.contains_key("main"))
+            })
+            .collect::<Vec<_>>();
+        programs.sort();
+        programs
+    }
+
+    /// Compare the output of `backend` for each test program whose literals fit in its cells with
+    /// its snapshot in `tests/snapshots/<TARGET>`. The snapshots are rewritten instead if
+    /// `UPDATE_SNAPSHOTS` is set.
+    pub fn check_snapshots(backend: &dyn Backend) {
+        let dir = Path::new("tests/snapshots").join(backend.name());
+        let update = env::var_os("UPDATE_SNAPSHOTS").is_some();
+        if update {
+            fs::create_dir_all(&dir).unwrap();
+        }
+        let extension = backend
+            .intermediate()
+            .or_else(|| backend.extension())
+            .unwrap_or("out");
+        let mut mismatches = Vec::new();
+        for (stem, path, source) in programs() {
+            let mut lexer = Lexer::new(&source, path.to_str());
+            let Ok(program) = parse::parse_tokens_for(&mut lexer, backend.cell_width()) else {
+                continue;
+            };
+            let artifact = backend.generate(program).unwrap();
+            let snapshot = dir.join(&stem).with_extension(extension);
+            if update {
+                fs::write(&snapshot, artifact.as_bytes()).unwrap();
+            } else if fs::read(&snapshot).ok().as_deref() != Some(artifact.as_bytes()) {
+                mismatches.push(snapshot.display().to_string());
+            }
+        }
+        assert!(
+            mismatches.is_empty(),
+            "output differs from the snapshots (rerun with `UPDATE_SNAPSHOTS=1` to update them): {}",
+            mismatches.join(", ")
+        );
+    }
+
+    /// The expected output of the test program `stem`, from `tests/expected`.
+    pub fn expected_stdout(stem: &str) -> String {
+        let expected = fs::read_to_string(format!("tests/expected/{stem}.out")).unwrap();
+        expected
+            .strip_prefix("----STDOUT----\n")
+            .and_then(|e| e.split_once("\n----STDERR----\n"))
+            .unwrap()
+            .0
+            .to_owned()
+    }
+
+    /// Build and run each test program whose literals fit in the cells of `backend`, and compare
+    /// its output with the expected output in `tests/expected`. This is skipped if any of the
+    /// tools needed are missing.
+    pub fn check_runs(backend: &dyn Backend) {
+        check_runs_with(backend, &[]);
+    }
+
+    /// Like `check_runs`, with `build_args` added to the last command that builds each program.
+    pub fn check_runs_with(backend: &dyn Backend, build_args: &[&str]) {
+        let dir = TempDir::new(backend.name());
+        let out = dir.join("out");
+        let artifact_path = backend
+            .intermediate()
+            .map_or_else(|| out.clone(), |extension| out.with_extension(extension));
+
+        let mut tools = backend
+            .build(&artifact_path, &out)
+            .iter()
+            .map(|cmd| cmd.get_program().to_owned())
+            .collect::<Vec<_>>();
+        let run = backend.run(&out).expect("the backend can run programs");
+        if run.get_args().len() > 0 {
+            tools.push(run.get_program().to_owned());
+        }
+        if let Some(tool) = tools
+            .iter()
+            .find(|tool| Command::new(tool).arg("--version").output().is_err())
+        {
+            eprintln!("skipping, `{}` was not found", tool.to_string_lossy());
+            return;
+        }
+
+        for (stem, path, source) in programs() {
+            if TARGET_DEPENDENT.contains(&stem.as_str()) {
+                continue;
+            }
+            let mut lexer = Lexer::new(&source, path.to_str());
+            let Ok(program) = parse::parse_tokens_for(&mut lexer, backend.cell_width()) else {
+                continue;
+            };
+            fs::write(
+                &artifact_path,
+                backend.generate(program).unwrap().as_bytes(),
+            )
+            .unwrap();
+            let mut build = backend.build(&artifact_path, &out);
+            if let Some(cmd) = build.last_mut() {
+                cmd.args(build_args);
+            }
+            for mut cmd in build {
+                assert!(cmd.status().unwrap().success(), "failed to build `{stem}`");
+            }
+            let output = backend.run(&out).unwrap().output().unwrap();
+            assert!(
+                output.status.success(),
+                "`{stem}` failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+            assert_eq!(
+                String::from_utf8_lossy(&output.stdout),
+                expected_stdout(&stem),
+                "`{stem}`"
+            );
+        }
     }
 }
@@ -0,0 +1,473 @@
+use super::*;
+
+/// The size of the data stack, which is the same as the default native stack on x86-64.
+const DATA_STACK_BYTES: usize = 8 << 20;
+
+/// The register holding the data stack pointer. The data stack can't live on `sp`, which has to
+/// stay 16-byte aligned whenever it is used to access memory.
+const DSP: &str = "x19";
+
+/// Load the address of `symbol` into `reg`.
+fn load_addr(buffer: &mut String, reg: &str, symbol: &str) -> std::fmt::Result {
+    write!(
+        buffer,
+        "\tadrp\t{reg}, {symbol}
+\tadd\t{reg}, {reg}, :lo12:{symbol}
+"
+    )
+}
+
+/// Move an arbitrary 64-bit value into `reg`, 16 bits at a time.
+fn load_imm(buffer: &mut String, reg: &str, val: u64) -> std::fmt::Result {
+    writeln!(buffer, "\tmovz\t{reg}, #{}", val & 0xffff)?;
+    for shift in [16, 32, 48] {
+        let chunk = (val >> shift) & 0xffff;
+        if chunk != 0 {
+            writeln!(buffer, "\tmovk\t{reg}, #{chunk}, lsl #{shift}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Apply `op`, `add` or `sub`, to `reg` and `imm`. Only 12-bit immediates can be encoded in the
+/// instruction, so larger values go through `x12`.
+fn add_imm(buffer: &mut String, op: &str, reg: &str, imm: usize) -> std::fmt::Result {
+    if imm < 4096 {
+        writeln!(buffer, "\t{op}\t{reg}, {reg}, #{imm}")
+    } else {
+        load_imm(buffer, "x12", imm as u64)?;
+        writeln!(buffer, "\t{op}\t{reg}, {reg}, x12")
+    }
+}
+
+/// Grow (`sub`) or shrink (`add`) the return stack by `bytes`, leaving the new return stack
+/// pointer in `x10`.
+fn move_ret_stack(buffer: &mut String, op: &str, bytes: usize) -> std::fmt::Result {
+    write!(
+        buffer,
+        "\tadrp\tx9, ret_stack_rsp
+\tldr\tx10, [x9, :lo12:ret_stack_rsp]
+"
+    )?;
+    add_imm(buffer, op, "x10", bytes)?;
+    writeln!(buffer, "\tstr\tx10, [x9, :lo12:ret_stack_rsp]")
+}
+
+/// Compare the top two values and push 1 if `cond` holds for the second value against the top
+/// one, and 0 otherwise.
+fn write_comparison(buffer: &mut String, name: &str, cond: &str) -> std::fmt::Result {
+    write!(
+        buffer,
+        "\tPOP\tx1\t\t\t// Op::{name}
+\tPOP\tx0
+\tcmp\tx0, x1
+\tcset\tx0, {cond}
+\tPUSH\tx0
+"
+    )
+}
+
+#[allow(clippy::too_many_lines)]
+fn write_op(
+    op: &Op,
+    count_ops: &mut usize,
+    buffer: &mut String,
+    ctx: &Context,
+) -> std::fmt::Result {
+    match op {
+        Op::CallFn(index) => {
+            writeln!(buffer, "\t\t\t\t\t// Op::CallFn({index})")?;
+            move_ret_stack(buffer, "sub", 8)?;
+            write!(
+                buffer,
+                "\tadr\tx11, RET{count_ops}
+\tstr\tx11, [x10]
+\tb\tfn_{func}
+RET{count_ops}:
+",
+                func = ctx
+                    .lookup
+                    .iter()
+                    .find(|(_, v)| *v == index)
+                    .expect("the index was taken from the same context lookup")
+                    .0
+            )?;
+            move_ret_stack(buffer, "add", 8)?;
+        }
+        Op::Ret(count) => {
+            writeln!(buffer, "\t\t\t\t\t// Op::Ret({count})")?;
+            move_ret_stack(buffer, "add", count * 8)?;
+            write!(
+                buffer,
+                "\tldr\tx11, [x10]
+\tbr\tx11
+"
+            )?;
+        }
+        Op::Bind {
+            count, peek, body, ..
+        } => {
+            writeln!(buffer, "\t\t\t\t\t// Op::Bind({count})")?;
+            move_ret_stack(buffer, "sub", count * 8)?;
+            for i in 0..*count {
+                write!(
+                    buffer,
+                    "\tldr\tx11, [{DSP}, #{0}]
+\tstr\tx11, [x10, #{0}]
+",
+                    i * 8
+                )?;
+            }
+            if !peek {
+                add_imm(buffer, "add", DSP, count * 8)?;
+            }
+            for op in body {
+                write_op(op, count_ops, buffer, ctx)?;
+            }
+            // Remove the bindings from the return stack.
+            move_ret_stack(buffer, "add", count * 8)?;
+        }
+        Op::PushBind(index) => write!(
+            buffer,
+            "\tadrp\tx9, ret_stack_rsp\t\t// Op::PushBind({index})
+\tldr\tx10, [x9, :lo12:ret_stack_rsp]
+\tldr\tx11, [x10, #{}]
+\tPUSH\tx11
+",
+            index * 8
+        )?,
+        Op::PushInt(val) => {
+            writeln!(buffer, "\t\t\t\t\t// Op::PushInt({val})")?;
+            load_imm(buffer, "x0", *val)?;
+            writeln!(buffer, "\tPUSH\tx0")?;
+        }
+        Op::PushStrPtr(index) => {
+            writeln!(buffer, "\t\t\t\t\t// Op::PushStrPtr({index})")?;
+            load_addr(buffer, "x0", &format!("str_{index}"))?;
+            writeln!(buffer, "\tPUSH\tx0")?;
+        }
+        Op::Plus => write!(
+            buffer,
+            "\tPOP\tx1\t\t\t// Op::Plus
+\tPOP\tx0
+\tadd\tx0, x0, x1
+\tPUSH\tx0
+",
+        )?,
+        Op::Minus => write!(
+            buffer,
+            "\tPOP\tx1\t\t\t// Op::Minus
+\tPOP\tx0
+\tsub\tx0, x0, x1
+\tPUSH\tx0
+",
+        )?,
+        Op::DivMod => write!(
+            buffer,
+            "\tPOP\tx1\t\t\t// Op::DivMod
+\tPOP\tx0
+\tudiv\tx2, x0, x1
+\tmsub\tx3, x2, x1, x0
+\tPUSH\tx2
+\tPUSH\tx3
+",
+        )?,
+        Op::Dup => write!(
+            buffer,
+            "\tldr\tx0, [{DSP}]\t\t// Op::Dup
+\tPUSH\tx0
+"
+        )?,
+        Op::Drop => writeln!(buffer, "\tadd\t{DSP}, {DSP}, #8\t\t// Op::Drop")?,
+        Op::Swap => write!(
+            buffer,
+            "\tPOP\tx1\t\t\t// Op::Swap
+\tPOP\tx0
+\tPUSH\tx1
+\tPUSH\tx0
+",
+        )?,
+        Op::Over => write!(
+            buffer,
+            "\tldr\tx0, [{DSP}, #8]\t\t// Op::Over
+\tPUSH\tx0
+"
+        )?,
+        // The comparisons don't need labels, but they still count them so that the labels of the
+        // rest of the program are numbered as on x86-64.
+        Op::Equals => {
+            write_comparison(buffer, "Equals", "eq")?;
+            *count_ops += 1;
+        }
+        Op::Neq => {
+            write_comparison(buffer, "Neq", "ne")?;
+            *count_ops += 1;
+        }
+        Op::GreaterThan => {
+            write_comparison(buffer, "GreaterThan", "hi")?;
+            *count_ops += 1;
+        }
+        Op::LessThan => {
+            write_comparison(buffer, "LessThan", "lo")?;
+            *count_ops += 1;
+        }
+        Op::Not => write!(
+            buffer,
+            "\tPOP\tx0\t\t\t// Op::Not
+\tmov\tx1, #1
+\tsub\tx0, x1, x0
+\tPUSH\tx0
+"
+        )?,
+        Op::Or => {
+            // If the first value is 1 the second comparison is skipped and the flags say equal.
+            write!(
+                buffer,
+                "\tPOP\tx1\t\t\t// Op::Or
+\tPOP\tx0
+\tcmp\tx0, #1
+\tccmp\tx1, #1, #4, ne
+\tcset\tx0, eq
+\tPUSH\tx0
+"
+            )?;
+            *count_ops += 1;
+        }
+        Op::And => {
+            write!(
+                buffer,
+                "\tPOP\tx1\t\t\t// Op::And
+\tPOP\tx0
+\tcmp\tx0, x1
+\tccmp\tx0, #1, #0, eq
+\tcset\tx0, eq
+\tPUSH\tx0
+"
+            )?;
+            *count_ops += 1;
+        }
+        Op::ReadByte => write!(
+            buffer,
+            "\tPOP\tx0\t\t\t// Op::ReadByte
+\tldrb\tw0, [x0]
+\tPUSH\tx0
+"
+        )?,
+        Op::If(ops) => {
+            let jump_to = *count_ops;
+            *count_ops += 1;
+            write!(
+                buffer,
+                "\tPOP\tx0\t\t\t// Op::If
+\tcmp\tx0, #1
+\tb.ne\tF{jump_to}
+"
+            )?;
+            for op in ops {
+                write_op(op, count_ops, buffer, ctx)?;
+            }
+            writeln!(buffer, "F{jump_to}:")?;
+        }
+        Op::While { condn, body } => {
+            let condn_jump = *count_ops;
+            let end_jump = *count_ops + 1;
+            *count_ops += 2;
+            writeln!(buffer, "F{condn_jump}:\t\t\t\t\t// Op::While")?;
+            for op in condn {
+                write_op(op, count_ops, buffer, ctx)?;
+            }
+            // Check the while condition and jump to end if not met.
+            write!(
+                buffer,
+                "\tPOP\tx0
+\tcmp\tx0, #1
+\tb.ne\tF{end_jump}
+"
+            )?;
+            for op in body {
+                write_op(op, count_ops, buffer, ctx)?;
+            }
+            writeln!(buffer, "\tb\tF{condn_jump}\nF{end_jump}:")?;
+        }
+        Op::Print => write!(buffer, "\tPOP\tx0\t\t\t// Op::Print\n\tbl\tprint\n")?,
+        Op::Puts => write!(
+            buffer,
+            "\tPOP\tx1\t\t\t// Op::Puts
+\tPOP\tx2
+\tmov\tx0, #1
+\tmov\tx8, #64
+\tsvc\t#0
+"
+        )?,
+        Op::Asm { signature, lines } => {
+            writeln!(
+                buffer,
+                "\t\t\t\t\t// Op::Asm {} -- {}",
+                signature.inputs, signature.outputs
+            )?;
+            for line in lines {
+                writeln!(buffer, "\t{line}")?;
+            }
+        }
+    }
+    *count_ops += 1;
+    Ok(())
+}
+
+pub fn generate(program: Program) -> Result<String, std::fmt::Error> {
+    let mut outbuf = format!(
+        "\t.macro\tPUSH reg
+\tstr\t\\reg, [{DSP}, #-8]!
+\t.endm
+\t.macro\tPOP reg
+\tldr\t\\reg, [{DSP}], #8
+\t.endm
+
+\t.globl\tmain
+\t.text
+"
+    );
+
+    // Write the decimal digits of x0 backwards from the end of a buffer on the stack.
+    outbuf += "print:
+\tsub\tsp, sp, #32
+\tmov\tx2, #10
+\tstrb\tw2, [sp, #31]
+\tadd\tx1, sp, #31
+\tmov\tx3, #10
+.L2:
+\tudiv\tx4, x0, x3
+\tmsub\tx5, x4, x3, x0
+\tadd\tx5, x5, #48
+\tstrb\tw5, [x1, #-1]!
+\tmov\tx0, x4
+\tcbnz\tx0, .L2
+\tadd\tx2, sp, #32
+\tsub\tx2, x2, x1
+\tmov\tx0, #1
+\tmov\tx8, #64
+\tsvc\t#0
+\tadd\tsp, sp, #32
+\tret
+";
+
+    let mut count_ops = 0;
+
+    for func in program.funcs.iter() {
+        writeln!(outbuf, "fn_{}:", func.ident)?;
+        for op in &func.body {
+            write_op(op, &mut count_ops, &mut outbuf, &program.ctx)?;
+        }
+    }
+
+    outbuf += "main:\n";
+    load_addr(&mut outbuf, DSP, "data_stack_end")?;
+    load_addr(&mut outbuf, "x10", "ret_stack_end")?;
+    write!(
+        outbuf,
+        "\tsub\tx10, x10, #8
+\tadrp\tx9, ret_stack_rsp
+\tstr\tx10, [x9, :lo12:ret_stack_rsp]
+\tadr\tx11, RET_MAIN
+\tstr\tx11, [x10]
+\tb\tfn_main
+RET_MAIN:
+\tmov\tx0, #0
+\tmov\tx8, #93
+\tsvc\t#0
+\t.section\t.rodata
+"
+    )?;
+    for (i, s) in program.ctx.strings.iter().enumerate() {
+        let mut s_bytes = String::new();
+        for b in s.as_bytes() {
+            write!(&mut s_bytes, "{b},").unwrap();
+        }
+        match s_bytes.trim_end_matches(',') {
+            "" => writeln!(outbuf, "str_{i}:")?,
+            bytes => writeln!(outbuf, "str_{i}:\t.byte\t{bytes}")?,
+        }
+    }
+
+    write!(
+        outbuf,
+        "\t.bss
+\t.balign\t8
+ret_stack_rsp:\t.skip\t8
+ret_stack:\t.skip\t65536
+ret_stack_end:
+data_stack:\t.skip\t{DATA_STACK_BYTES}
+data_stack_end:
+"
+    )?;
+    Ok(outbuf)
+}
+
+/// The prefix of the binutils used to build for 64-bit ARM, which is only needed when cross
+/// compiling.
+fn binutils_prefix() -> &'static str {
+    if cfg!(target_arch = "aarch64") {
+        ""
+    } else {
+        "aarch64-linux-gnu-"
+    }
+}
+
+/// An ELF executable for 64-bit ARM, assembled with GNU as and linked with `ld`.
+pub struct Executable;
+
+impl Backend for Executable {
+    fn name(&self) -> &'static str {
+        "aarch64-linux"
+    }
+
+    fn arch(&self) -> Option<&'static str> {
+        Some("aarch64")
+    }
+
+    fn generate(&self, program: Program) -> Result<Artifact, Box<dyn Error>> {
+        Ok(Artifact::Text(generate(program)?))
+    }
+
+    fn intermediate(&self) -> Option<&'static str> {
+        Some("s")
+    }
+
+    fn build(&self, artifact: &Path, out: &Path) -> Vec<Command> {
+        let object = out.with_extension("o");
+        let mut as_ = Command::new(format!("{}as", binutils_prefix()));
+        as_.arg("-o").arg(&object).arg(artifact);
+        let mut ld = Command::new(format!("{}ld", binutils_prefix()));
+        ld.args(["-e", "main", "-o"]).arg(out).arg(&object);
+        vec![as_, ld]
+    }
+
+    /// Runs the executable natively on 64-bit ARM, and under `qemu-aarch64` elsewhere.
+    fn run(&self, out: &Path) -> Option<Command> {
+        let out = Path::new(".").join(out);
+        if cfg!(target_arch = "aarch64") {
+            Some(Command::new(out))
+        } else {
+            let mut qemu = Command::new("qemu-aarch64");
+            qemu.arg(out);
+            Some(qemu)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::codegen::test::{check_runs, check_snapshots};
+
+    use super::*;
+
+    #[test]
+    fn snapshots() {
+        check_snapshots(&Executable);
+    }
+
+    #[test]
+    fn run() {
+        check_runs(&Executable);
+    }
+}
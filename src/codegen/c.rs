@@ -0,0 +1,228 @@
+use std::env;
+
+use super::*;
+
+/// The number of cells in the data stack, which is 8 MiB like the default native stack.
+const STACK_CELLS: usize = 1 << 20;
+/// The number of cells in the return stack, the same as the 64 KiB of the native targets.
+const RET_STACK_CELLS: usize = 65536 / 8;
+
+const RUNTIME: &str = "\
+#include <inttypes.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+
+static uint64_t stack[STACK_CELLS];
+static size_t sp;
+static uint64_t ret_stack[RET_STACK_CELLS];
+static size_t rsp = RET_STACK_CELLS;
+
+static void push(uint64_t x) { stack[sp++] = x; }
+
+static uint64_t pop(void) { return stack[--sp]; }
+
+/* Copy the top `count` values to the return stack, the top one ending up at `ret_stack[rsp]`. */
+static void bind(size_t count, int peek) {
+\tsize_t i;
+\trsp -= count;
+\tfor (i = 0; i < count; i++) ret_stack[rsp + i] = stack[sp - 1 - i];
+\tif (!peek) sp -= count;
+}
+";
+
+/// Make `s` safe to put in a C comment.
+fn comment(s: &str) -> String {
+    s.replace("*/", "* /")
+}
+
+fn indent(buffer: &mut String, depth: usize) {
+    buffer.extend(std::iter::repeat_n('\t', depth));
+}
+
+#[allow(clippy::too_many_lines)]
+fn write_op(op: &Op, depth: usize, buffer: &mut String) -> std::fmt::Result {
+    indent(buffer, depth);
+    match op {
+        Op::CallFn(index) => writeln!(buffer, "fn_{index}();")?,
+        Op::Ret(0) => writeln!(buffer, "return;")?,
+        Op::Ret(count) => {
+            writeln!(buffer, "rsp += {count};")?;
+            indent(buffer, depth);
+            writeln!(buffer, "return;")?;
+        }
+        Op::Bind {
+            count,
+            names,
+            peek,
+            body,
+        } => {
+            writeln!(
+                buffer,
+                "bind({count}, {}); /* {} */",
+                i32::from(*peek),
+                comment(&names.join(" "))
+            )?;
+            for op in body {
+                write_op(op, depth, buffer)?;
+            }
+            indent(buffer, depth);
+            writeln!(buffer, "rsp += {count};")?;
+        }
+        Op::PushBind(index) => writeln!(buffer, "push(ret_stack[rsp + {index}]);")?,
+        Op::PushInt(val) => writeln!(buffer, "push(UINT64_C({val}));")?,
+        Op::PushStrPtr(index) => writeln!(buffer, "push((uint64_t)(uintptr_t)str_{index});")?,
+        Op::Plus => writeln!(buffer, "{{ uint64_t b = pop(), a = pop(); push(a + b); }}")?,
+        Op::Minus => writeln!(buffer, "{{ uint64_t b = pop(), a = pop(); push(a - b); }}")?,
+        Op::DivMod => writeln!(
+            buffer,
+            "{{ uint64_t b = pop(), a = pop(); push(a / b); push(a % b); }}"
+        )?,
+        Op::Dup => writeln!(buffer, "push(stack[sp - 1]);")?,
+        Op::Drop => writeln!(buffer, "sp--;")?,
+        Op::Swap => writeln!(buffer, "{{ uint64_t b = pop(), a = pop(); push(b); push(a); }}")?,
+        Op::Over => writeln!(buffer, "push(stack[sp - 2]);")?,
+        Op::Equals => writeln!(buffer, "{{ uint64_t b = pop(), a = pop(); push(a == b); }}")?,
+        Op::Neq => writeln!(buffer, "{{ uint64_t b = pop(), a = pop(); push(a != b); }}")?,
+        Op::Not => writeln!(buffer, "push(1 - pop());")?,
+        Op::GreaterThan => writeln!(buffer, "{{ uint64_t b = pop(), a = pop(); push(a > b); }}")?,
+        Op::LessThan => writeln!(buffer, "{{ uint64_t b = pop(), a = pop(); push(a < b); }}")?,
+        Op::Or => writeln!(
+            buffer,
+            "{{ uint64_t b = pop(), a = pop(); push(a == 1 || b == 1); }}"
+        )?,
+        Op::And => writeln!(
+            buffer,
+            "{{ uint64_t b = pop(), a = pop(); push(a == b && a == 1); }}"
+        )?,
+        Op::ReadByte => writeln!(buffer, "push(*(const unsigned char *)(uintptr_t)pop());")?,
+        Op::If(ops) => {
+            writeln!(buffer, "if (pop() == 1) {{")?;
+            for op in ops {
+                write_op(op, depth + 1, buffer)?;
+            }
+            indent(buffer, depth);
+            writeln!(buffer, "}}")?;
+        }
+        Op::While { condn, body } => {
+            writeln!(buffer, "for (;;) {{")?;
+            for op in condn {
+                write_op(op, depth + 1, buffer)?;
+            }
+            indent(buffer, depth + 1);
+            writeln!(buffer, "if (pop() != 1) break;")?;
+            for op in body {
+                write_op(op, depth + 1, buffer)?;
+            }
+            indent(buffer, depth);
+            writeln!(buffer, "}}")?;
+        }
+        Op::Print => writeln!(buffer, "printf(\"%\" PRIu64 \"\\n\", pop());")?,
+        Op::Puts => writeln!(
+            buffer,
+            "{{ const void *s = (const void *)(uintptr_t)pop(); fwrite(s, 1, (size_t)pop(), stdout); }}"
+        )?,
+        Op::Asm { .. } => unreachable!("inline assembly isn't parsed for this target"),
+    }
+    Ok(())
+}
+
+pub fn generate(program: Program) -> Result<String, std::fmt::Error> {
+    let mut outbuf = format!(
+        "#define STACK_CELLS {STACK_CELLS}\n#define RET_STACK_CELLS {RET_STACK_CELLS}\n{RUNTIME}\n"
+    );
+
+    for (i, s) in program.ctx.strings.iter().enumerate() {
+        write!(outbuf, "static const unsigned char str_{i}[] = {{")?;
+        for b in s.as_bytes() {
+            write!(outbuf, "{b},")?;
+        }
+        // An empty array isn't valid C.
+        writeln!(outbuf, "0}};")?;
+    }
+    outbuf.push('\n');
+
+    for (i, func) in program.funcs.iter().enumerate() {
+        writeln!(
+            outbuf,
+            "static void fn_{i}(void); /* {} */",
+            comment(func.ident)
+        )?;
+    }
+    for (i, func) in program.funcs.iter().enumerate() {
+        writeln!(
+            outbuf,
+            "\n/* {} */\nstatic void fn_{i}(void) {{",
+            comment(func.ident)
+        )?;
+        for op in &func.body {
+            write_op(op, 1, &mut outbuf)?;
+        }
+        writeln!(outbuf, "}}")?;
+    }
+
+    writeln!(
+        outbuf,
+        "\nint main(void) {{\n\tfn_{}();\n\treturn 0;\n}}",
+        program.ctx.lookup["main"]
+    )?;
+    Ok(outbuf)
+}
+
+/// C99 source, compiled with `$CC` (or `cc`) and any flags in `$CFLAGS`.
+pub struct C;
+
+impl Backend for C {
+    fn name(&self) -> &'static str {
+        "c"
+    }
+
+    fn generate(&self, program: Program) -> Result<Artifact, Box<dyn Error>> {
+        Ok(Artifact::Text(generate(program)?))
+    }
+
+    fn intermediate(&self) -> Option<&'static str> {
+        Some("c")
+    }
+
+    fn build(&self, artifact: &Path, out: &Path) -> Vec<Command> {
+        let mut cc = Command::new(env::var("CC").unwrap_or_else(|_| "cc".to_owned()));
+        cc.args(["-std=c99", "-O2"]);
+        if let Ok(flags) = env::var("CFLAGS") {
+            cc.args(flags.split_whitespace());
+        }
+        cc.arg("-o").arg(out).arg(artifact);
+        vec![cc]
+    }
+
+    fn run(&self, out: &Path) -> Option<Command> {
+        Some(Command::new(Path::new(".").join(out)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::codegen::test::{check_runs, check_runs_with, check_snapshots};
+
+    use super::*;
+
+    #[test]
+    fn snapshots() {
+        check_snapshots(&C);
+    }
+
+    #[test]
+    fn run() {
+        check_runs(&C);
+    }
+
+    /// The generated C has no undefined behaviour on the test programs. Sanitizer reports abort
+    /// the program, which fails the run.
+    #[test]
+    fn sanitizers() {
+        check_runs_with(
+            &C,
+            &["-fsanitize=address,undefined", "-fno-sanitize-recover=all"],
+        );
+    }
+}
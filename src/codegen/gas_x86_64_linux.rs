@@ -0,0 +1,716 @@
+use super::*;
+
+#[allow(clippy::too_many_lines)]
+fn write_op(
+    op: &Op,
+    count_ops: &mut usize,
+    buffer: &mut String,
+    ctx: &Context,
+) -> std::fmt::Result {
+    match op {
+        Op::CallFn(index) => {
+            let func = ctx
+                .lookup
+                .iter()
+                .find(|(_, v)| *v == index)
+                .expect("the index was taken from the same context lookup")
+                .0;
+            if let Some(signature) = ctx.externs.get(func) {
+                write_c_call(func, *signature, buffer)?;
+            } else {
+                write!(
+                    buffer,
+                    "\tmovq\tret_stack_rsp, %rax\t# Op::CallFn({index})
+\tsubq\t$8, %rax
+\tmovq\t%rax, ret_stack_rsp
+\tmovq\t$RET{count_ops}, (%rax)
+\tjmp\tfn_{func}
+RET{count_ops}:
+\tmovq\tret_stack_rsp, %rax
+\taddq\t$8, %rax
+\tmovq\t%rax, ret_stack_rsp
+"
+                )?;
+            }
+        }
+        // Small optimisation for the 0 case.
+        Op::Ret(0) => write!(
+            buffer,
+            "\tmovq\tret_stack_rsp, %rax\t# Op::Ret(0)
+\tjmp\t*(%rax)
+"
+        )?,
+        Op::Ret(count) => write!(
+            buffer,
+            "\tmovq\tret_stack_rsp, %rax\t# Op::Ret({count})
+\taddq\t${}, %rax
+\tmovq\t%rax, ret_stack_rsp
+\tjmp\t*(%rax)
+",
+            count * x86_64_linux::CELL_SIZE
+        )?,
+        Op::Bind {
+            count, peek, body, ..
+        } => {
+            write!(
+                buffer,
+                "\tmovq\tret_stack_rsp, %rax\t# Op::Bind({count})
+\tsubq\t${}, %rax
+\tmovq\t%rax, ret_stack_rsp
+",
+                count * x86_64_linux::CELL_SIZE
+            )?;
+            for i in 0..*count {
+                write!(
+                    buffer,
+                    "\tmovq\t{0}(%rsp), %rbx
+\tmovq\t%rbx, {0}(%rax)
+",
+                    i * x86_64_linux::CELL_SIZE
+                )?;
+            }
+            if !peek {
+                writeln!(buffer, "\taddq\t${}, %rsp", count * x86_64_linux::CELL_SIZE)?;
+            }
+            for op in body {
+                write_op(op, count_ops, buffer, ctx)?;
+            }
+            // Remove the bindings from the return stack.
+            write!(
+                buffer,
+                "\tmovq\tret_stack_rsp, %rax
+\taddq\t${}, %rax
+\tmovq\t%rax, ret_stack_rsp
+",
+                count * x86_64_linux::CELL_SIZE
+            )?;
+        }
+        Op::PushBind(index) => write!(
+            buffer,
+            "\tmovq\tret_stack_rsp, %rax\t# Op::PushBind({index})
+\taddq\t${}, %rax
+\tpushq\t(%rax)
+",
+            index * x86_64_linux::CELL_SIZE
+        )?,
+        // `push` only takes a sign-extended 32-bit immediate.
+        Op::PushInt(val) if *val > i32::MAX as u64 => write!(
+            buffer,
+            "\tmovabsq\t${val}, %rax\t# Op::PushInt({val})
+\tpushq\t%rax
+"
+        )?,
+        Op::PushInt(val) => writeln!(buffer, "\tpushq\t${val}\t\t\t# Op::PushInt({val})")?,
+        Op::PushStrPtr(index) => {
+            writeln!(buffer, "\tpushq\t$str_{index}\t\t# Op::PushStrPtr({index})")?;
+        }
+        Op::Plus => write!(
+            buffer,
+            "\tpopq\t%rax\t\t\t# Op::Plus
+\tpopq\t%rbx
+\taddq\t%rbx, %rax
+\tpushq\t%rax
+",
+        )?,
+        Op::Minus => write!(
+            buffer,
+            "\tpopq\t%rbx\t\t\t# Op::Minus
+\tpopq\t%rax
+\tsubq\t%rbx, %rax
+\tpushq\t%rax
+",
+        )?,
+        Op::DivMod => write!(
+            buffer,
+            "\tpopq\t%rbx\t\t\t# Op::DivMod
+\tpopq\t%rax
+\tmovq\t$0, %rdx
+\tdivq\t%rbx
+\tpushq\t%rax
+\tpushq\t%rdx
+",
+        )?,
+        Op::Dup => writeln!(buffer, "\tpushq\t(%rsp)\t\t\t# Op::Dup")?,
+        Op::Drop => writeln!(
+            buffer,
+            "\taddq\t${}, %rsp\t\t# Op::Drop",
+            x86_64_linux::CELL_SIZE
+        )?,
+        Op::Swap => write!(
+            buffer,
+            "\tpopq\t%rax\t\t\t# Op::Swap
+\tpopq\t%rbx
+\tpushq\t%rax
+\tpushq\t%rbx
+",
+        )?,
+        Op::Over => writeln!(
+            buffer,
+            "\tpushq\t{}(%rsp)\t\t\t# Op::Over",
+            x86_64_linux::CELL_SIZE
+        )?,
+        Op::Equals | Op::Neq | Op::GreaterThan | Op::LessThan => {
+            // The flags are set by comparing the second value with the top one.
+            let (name, jump) = match op {
+                Op::Equals => ("Equals", "je"),
+                Op::Neq => ("Neq", "jne"),
+                Op::GreaterThan => ("GreaterThan", "ja"),
+                _ => ("LessThan", "jb"),
+            };
+            write!(
+                buffer,
+                "\tpopq\t%rax\t\t\t# Op::{name}
+\tpopq\t%rbx
+\tcmpq\t%rax, %rbx
+\t{jump}\tJ{0}
+\tpushq\t$0
+\tjmp\tJ{1}
+J{0}:
+\tpushq\t$1
+J{1}:
+",
+                count_ops,
+                *count_ops + 1,
+            )?;
+            *count_ops += 1;
+        }
+        Op::Not => write!(
+            buffer,
+            "\tpopq\t%rax\t\t\t# Op::Not
+\tmovq\t$1, %rbx
+\tsubq\t%rax, %rbx
+\tpushq\t%rbx
+"
+        )?,
+        Op::Or => {
+            write!(
+                buffer,
+                "\tpopq\t%rax\t\t\t# Op::Or
+\tpopq\t%rbx
+\tcmpq\t$1, %rax
+\tje\tJ{0}
+\tcmpq\t$1, %rbx
+\tje\tJ{0}
+\tpushq\t$0
+\tjmp\tJ{1}
+J{0}:
+\tpushq\t$1
+J{1}:
+",
+                count_ops,
+                *count_ops + 1,
+            )?;
+            *count_ops += 1;
+        }
+        Op::And => {
+            write!(
+                buffer,
+                "\tpopq\t%rax\t\t\t# Op::And
+\tpopq\t%rbx
+\tcmpq\t%rbx, %rax
+\tjne\tJ{0}
+\tcmpq\t$1, %rax
+\tjne\tJ{0}
+\tpushq\t$1
+\tjmp\tJ{1}
+J{0}:
+\tpushq\t$0
+J{1}:
+",
+                count_ops,
+                *count_ops + 1,
+            )?;
+            *count_ops += 1;
+        }
+        Op::ReadByte => write!(
+            buffer,
+            "\tpopq\t%rbx\t\t\t# Op::ReadByte
+\tmovzbq\t(%rbx), %rax
+\tpushq\t%rax
+"
+        )?,
+        Op::If(ops) => {
+            let jump_to = *count_ops;
+            *count_ops += 1;
+            write!(
+                buffer,
+                "\tpopq\t%rax\t\t\t# Op::If
+\tcmpq\t$1, %rax
+\tjne\tF{jump_to}
+"
+            )?;
+            for op in ops {
+                write_op(op, count_ops, buffer, ctx)?;
+            }
+            writeln!(buffer, "F{jump_to}:")?;
+        }
+        Op::While { condn, body } => {
+            let condn_jump = *count_ops;
+            let end_jump = *count_ops + 1;
+            *count_ops += 2;
+            writeln!(buffer, "F{condn_jump}:\t\t\t\t\t# Op::While")?;
+            for op in condn {
+                write_op(op, count_ops, buffer, ctx)?;
+            }
+            // Check the while condition and jump to end if not met.
+            write!(
+                buffer,
+                "\tpopq\t%rax
+\tcmpq\t$1, %rax
+\tjne\tF{end_jump}
+"
+            )?;
+            for op in body {
+                write_op(op, count_ops, buffer, ctx)?;
+            }
+            writeln!(buffer, "\tjmp\tF{condn_jump}\nF{end_jump}:")?;
+        }
+        Op::Print => write!(buffer, "\tpopq\t%rdi\t\t\t# Op::Print\n\tcall\tprint\n")?,
+        Op::Puts => write!(
+            buffer,
+            "\tpopq\t%rsi\t\t\t# Op::Puts
+\tpopq\t%rdx
+\tcall\twrite
+"
+        )?,
+        // Inline assembly is in Intel syntax on every x86 target.
+        Op::Asm { signature, lines } => {
+            writeln!(
+                buffer,
+                "\t.intel_syntax\tnoprefix\t# Op::Asm {} -- {}",
+                signature.inputs, signature.outputs
+            )?;
+            for line in lines {
+                writeln!(buffer, "\t{line}")?;
+            }
+            writeln!(buffer, "\t.att_syntax\tprefix")?;
+        }
+    }
+    *count_ops += 1;
+    Ok(())
+}
+
+/// Write the functions of `program` and the `print` routine they use. All output goes through a
+/// `write` routine that the caller provides, which writes `%rdx` bytes from `%rsi` and may
+/// clobber any register except `%rsp`.
+pub fn write_funcs(program: &Program, outbuf: &mut String) -> std::fmt::Result {
+    *outbuf += "print:
+\tmovabsq\t$-3689348814741910323, %r9
+\tsubq\t$40, %rsp
+\tmovb\t$10, 31(%rsp)
+\tleaq\t30(%rsp), %rcx
+.L2:
+\tmovq\t%rdi, %rax
+\tleaq\t32(%rsp), %r8
+\tmulq\t%r9
+\tmovq\t%rdi, %rax
+\tsubq\t%rcx, %r8
+\tshrq\t$3, %rdx
+\tleaq\t(%rdx,%rdx,4), %rsi
+\taddq\t%rsi, %rsi
+\tsubq\t%rsi, %rax
+\taddl\t$48, %eax
+\tmovb\t%al, (%rcx)
+\tmovq\t%rdi, %rax
+\tmovq\t%rdx, %rdi
+\tmovq\t%rcx, %rdx
+\tsubq\t$1, %rcx
+\tcmpq\t$9, %rax
+\tja\t.L2
+\tleaq\t32(%rsp), %rax
+\tsubq\t%rax, %rdx
+\tleaq\t32(%rsp,%rdx), %rsi
+\tmovq\t%r8, %rdx
+\tcall\twrite
+\taddq\t$40, %rsp
+\tret
+";
+
+    let mut count_ops = 0;
+
+    for func in program.funcs.iter().filter(|func| !func.is_import()) {
+        writeln!(outbuf, "fn_{}:", func.ident)?;
+        for op in &func.body {
+            write_op(op, &mut count_ops, outbuf, &program.ctx)?;
+        }
+    }
+    Ok(())
+}
+
+/// Write the string literals of `program`.
+fn write_strings(program: &Program, outbuf: &mut String) -> std::fmt::Result {
+    *outbuf += "\t.section\t.rodata\n";
+    for (i, s) in program.ctx.strings.iter().enumerate() {
+        let mut s_bytes = String::new();
+        for b in s.as_bytes() {
+            write!(&mut s_bytes, "{b},")?;
+        }
+        match s_bytes.trim_end_matches(',') {
+            "" => writeln!(outbuf, "str_{i}:")?,
+            bytes => writeln!(outbuf, "str_{i}:\t.byte\t{bytes}")?,
+        }
+    }
+    Ok(())
+}
+
+/// Write the string literals of `program` and the return stack, which `ret_stack_rsp` has to be
+/// pointed at the end of before calling any function.
+pub fn write_data(program: &Program, outbuf: &mut String) -> std::fmt::Result {
+    write_strings(program, outbuf)?;
+    *outbuf += "\t.bss
+ret_stack_rsp:\t.skip\t8
+ret_stack:\t.skip\t65536
+ret_stack_end:
+";
+    Ok(())
+}
+
+/// Write a call to the C function `name`, which takes its arguments from the data stack and
+/// pushes its result. The data stack is aligned for the call, and `%rbx` holds the old top across
+/// it since the callee preserves it. `%al` is cleared for variadic functions such as `printf`.
+fn write_c_call(name: &str, signature: Signature, buffer: &mut String) -> std::fmt::Result {
+    writeln!(buffer, "\t\t\t\t\t# call {name}")?;
+    for register in x86_64_linux::ARG_REGISTERS[..signature.inputs].iter().rev() {
+        writeln!(buffer, "\tpopq\t%{register}")?;
+    }
+    write!(
+        buffer,
+        "\tmovq\t%rsp, %rbx
+\tandq\t$-16, %rsp
+\txorl\t%eax, %eax
+\tcall\t{name}
+\tmovq\t%rbx, %rsp
+"
+    )?;
+    if signature.outputs == 1 {
+        writeln!(buffer, "\tpushq\t%rax")?;
+    }
+    Ok(())
+}
+
+/// Write a function called `name` with the C ABI, which pushes its arguments onto the data stack
+/// and calls `fn_<name>` with an empty return stack, returning the top of the data stack or 0.
+fn write_export(name: &str, signature: Signature, outbuf: &mut String) -> std::fmt::Result {
+    writeln!(
+        outbuf,
+        "{name}:
+\tpushq\t%rbx
+\tpushq\t%rbp
+\tmovq\t%rsp, %rbp"
+    )?;
+    for register in &x86_64_linux::ARG_REGISTERS[..signature.inputs] {
+        writeln!(outbuf, "\tpushq\t%{register}")?;
+    }
+    write!(
+        outbuf,
+        "\tmovq\t$ret_stack_end, %rax
+\tsubq\t$8, %rax
+\tmovq\t%rax, ret_stack_rsp
+\tmovq\t$RET_EXPORT_{name}, (%rax)
+\tjmp\tfn_{name}
+RET_EXPORT_{name}:
+"
+    )?;
+    if signature.outputs == 1 {
+        writeln!(outbuf, "\tmovq\t(%rsp), %rax")?;
+    } else {
+        writeln!(outbuf, "\txorl\t%eax, %eax")?;
+    }
+    write!(
+        outbuf,
+        "\tmovq\t%rbp, %rsp
+\tpopq\t%rbp
+\tpopq\t%rbx
+\tret
+"
+    )
+}
+
+/// Generate a whole program, or an object that exports its functions. Only the object defining
+/// `main` or exporting functions to C has the return stack, which the others import along with
+/// the functions they call, since GNU as treats every undefined symbol as external.
+pub fn generate(program: Program, emit: Emit) -> Result<String, std::fmt::Error> {
+    let entry = defines_main(&program);
+    let exports = exports(&program, emit);
+    let mut outbuf = String::new();
+    if entry {
+        outbuf += "\t.globl\tmain\n";
+    }
+    if emit != Emit::Exe {
+        if entry || !exports.is_empty() {
+            outbuf += "\t.globl\tret_stack_rsp\n";
+        }
+        for func in program.funcs.iter().filter(|func| !func.is_import()) {
+            writeln!(outbuf, "\t.globl\tfn_{}", func.ident)?;
+        }
+        for (name, _) in &exports {
+            writeln!(outbuf, "\t.globl\t{name}")?;
+        }
+        // Objects may be linked by a C compiler, which expects to be told that the stack need not
+        // be executable.
+        outbuf += "\t.section\t.note.GNU-stack,\"\",@progbits\n";
+    }
+    outbuf += "\t.text\nwrite:\n";
+    // Objects are linked with the C library, whose `stdio` buffers its output, so that is flushed
+    // first to keep the two in order.
+    if emit != Emit::Exe {
+        outbuf += "\tmovq\t%rsp, %rbx
+\tandq\t$-16, %rsp
+\tpushq\t%rsi
+\tpushq\t%rdx
+\txorl\t%edi, %edi
+\tcall\tfflush
+\tpopq\t%rdx
+\tpopq\t%rsi
+\tmovq\t%rbx, %rsp
+";
+    }
+    outbuf += "\tmovq\t$1, %rax
+\tmovq\t$1, %rdi
+\tsyscall
+\tret
+";
+    write_funcs(&program, &mut outbuf)?;
+    for (name, signature) in &exports {
+        write_export(name, *signature, &mut outbuf)?;
+    }
+    // Objects are linked with the C library, whose startup code calls `main` and flushes its
+    // buffers when it returns.
+    if entry && emit != Emit::Exe {
+        let signature = Signature {
+            inputs: 0,
+            outputs: 0,
+        };
+        write_export("main", signature, &mut outbuf)?;
+    } else if entry {
+        outbuf += "main:
+\tmovq\t$ret_stack_end, %rax
+\tsubq\t$8, %rax
+\tmovq\t%rax, ret_stack_rsp
+\tmovq\t$RET_MAIN, (%rax)
+\tcall\tfn_main
+RET_MAIN:
+\tmovq\t$60, %rax
+\tmovq\t$0, %rdi
+\tsyscall
+";
+    }
+    if entry || !exports.is_empty() {
+        write_data(&program, &mut outbuf)?;
+    } else {
+        write_strings(&program, &mut outbuf)?;
+    }
+    Ok(outbuf)
+}
+
+/// An ELF executable, assembled with GNU as and linked with `ld`.
+pub struct Gas;
+
+impl Backend for Gas {
+    fn name(&self) -> &'static str {
+        "x86_64-gas"
+    }
+
+    fn generate(&self, program: Program) -> Result<Artifact, Box<dyn Error>> {
+        Ok(Artifact::Text(generate(program, Emit::Exe)?))
+    }
+
+    fn intermediate(&self) -> Option<&'static str> {
+        Some("s")
+    }
+
+    fn build(&self, artifact: &Path, out: &Path) -> Vec<Command> {
+        let object = out.with_extension("o");
+        let mut build = self.build_object(artifact, &object);
+        build.push(x86_64_linux::link(&[object], out));
+        build
+    }
+
+    fn run(&self, out: &Path) -> Option<Command> {
+        Some(Command::new(Path::new(".").join(out)))
+    }
+
+    fn arch(&self) -> Option<&'static str> {
+        Some("x86_64")
+    }
+
+    fn extern_fns(&self) -> bool {
+        true
+    }
+
+    fn generate_object(&self, program: Program) -> Result<Artifact, Box<dyn Error>> {
+        x86_64_linux::check_c_names(&program)?;
+        Ok(Artifact::Text(generate(program, Emit::Obj)?))
+    }
+
+    fn build_object(&self, artifact: &Path, out: &Path) -> Vec<Command> {
+        let mut as_ = Command::new("as");
+        as_.arg("-o").arg(out).arg(artifact);
+        vec![as_]
+    }
+
+    fn link(&self, objects: &[PathBuf], out: &Path) -> Option<Command> {
+        Some(x86_64_linux::link_c(objects, out))
+    }
+
+    fn archive(&self, objects: &[PathBuf], out: &Path) -> Option<Command> {
+        Some(x86_64_linux::archive(objects, out))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, process::Output};
+
+    use crate::{
+        codegen::test::{check_runs, check_snapshots, TempDir},
+        parse, Lexer,
+    };
+
+    use super::*;
+
+    #[test]
+    fn snapshots() {
+        check_snapshots(&Gas);
+    }
+
+    #[test]
+    fn run() {
+        check_runs(&Gas);
+    }
+
+    /// Parse `source` as an object that may use C functions and inline assembly.
+    fn parse(source: &str) -> Program<'_> {
+        let mut program = Program::default();
+        program.ctx.object = true;
+        program.ctx.allow_extern = true;
+        program.ctx.arch = Gas.arch();
+        parse::parse_items(&mut Lexer::new(source, None), &mut program).unwrap();
+        program
+    }
+
+    /// Compile each of `sources`, given as a file stem and its source, into an object in a fresh
+    /// directory named after `name`, with a header declaring its exports. The objects are linked
+    /// into an executable, or if there is a `main_c` archived into `librack.a` and linked with it,
+    /// which is then run. Returns `None` if `as` or `cc` was not found.
+    fn build_and_run(name: &str, sources: &[(&str, &str)], main_c: Option<&str>) -> Option<Output> {
+        if ["as", "cc"]
+            .iter()
+            .any(|tool| Command::new(tool).arg("--version").output().is_err())
+        {
+            eprintln!("skipping, `as` or `cc` was not found");
+            return None;
+        }
+        let dir = TempDir::new(name);
+        let mut objects = Vec::new();
+        for (stem, source) in sources {
+            let program = parse(source);
+            let artifact = dir.join(stem).with_extension("s");
+            fs::write(
+                dir.join(stem).with_extension("h"),
+                c_header(&program, "RACK_H").unwrap(),
+            )
+            .unwrap();
+            fs::write(&artifact, Gas.generate_object(program).unwrap().as_bytes()).unwrap();
+            let object = artifact.with_extension("o");
+            for mut cmd in Gas.build_object(&artifact, &object) {
+                assert!(cmd.status().unwrap().success(), "failed to build `{stem}`");
+            }
+            objects.push(object);
+        }
+        let out = dir.join("out");
+        let mut link = match main_c {
+            Some(main_c) => {
+                let library = dir.join("librack.a");
+                let archive = Gas.archive(&objects, &library).unwrap().status().unwrap();
+                assert!(archive.success(), "failed to archive the objects");
+                let main = dir.join("main.c");
+                fs::write(&main, main_c).unwrap();
+                let mut cc = Command::new("cc");
+                cc.arg("-no-pie")
+                    .arg("-o")
+                    .arg(&out)
+                    .arg(&main)
+                    .arg(&library);
+                cc
+            }
+            None => Gas.link(&objects, &out).unwrap(),
+        };
+        assert!(link.status().unwrap().success(), "failed to link");
+        Some(Command::new(&out).output().unwrap())
+    }
+
+    #[test]
+    fn separate_compilation() {
+        let Some(output) = build_and_run(
+            "objects",
+            &[
+                (
+                    "app",
+                    "fn main in\n  21 double print\n  later\nend\n\nfn later in\n  greet\nend\n",
+                ),
+                (
+                    "lib",
+                    "fn double in\n  dup +\nend\n\nfn greet in\n  \"hello\\n\" puts\nend\n",
+                ),
+            ],
+            None,
+        ) else {
+            return;
+        };
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "42\nhello\n");
+    }
+
+    #[test]
+    fn exports() {
+        let source = "export fn add 2 -- 1 in\n  +\nend\n\nexport fn sub 2 -- 1 in\n  -\nend\n";
+        let header = c_header(&parse(source), "RACK_H").unwrap();
+        assert!(header.contains("uint64_t sub(uint64_t, uint64_t);"));
+        let Some(output) = build_and_run(
+            "exports",
+            &[("rack", source)],
+            Some(
+                "#include <stdio.h>\n#include \"rack.h\"\n\
+                 int main(void) { printf(\"%lu %lu\\n\", (unsigned long)add(40, 2), (unsigned long)sub(10, 3)); }\n",
+            ),
+        ) else {
+            return;
+        };
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "42 7\n");
+    }
+
+    #[test]
+    fn extern_fns() {
+        let Some(output) = build_and_run(
+            "externs",
+            &[(
+                "app",
+                "extern fn strlen 1 -- 1\nextern fn labs 1 -- 1\nextern fn printf 2 -- 1\n\n\
+                 fn main in\n  \"hello\\0\" strlen print drop\n  0 7 - labs print\n  \
+                 \"%ld\\n\\0\" swap drop 6 printf drop\n  8 print\nend\n",
+            )],
+            None,
+        ) else {
+            return;
+        };
+        assert!(output.status.success());
+        // The output of `printf` is flushed before `print` writes, so it comes out in order.
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "5\n7\n6\n8\n");
+    }
+
+    #[test]
+    fn inline_asm() {
+        let Some(output) = build_and_run(
+            "asm",
+            &[(
+                "app",
+                "fn main in\n  6 7\n  asm x86_64 2 -- 1\n    \"pop rax\"\n    \"pop rdx\"\n    \
+                 \"imul rax, rdx\"\n    \"push rax\"\n  end\n  print\nend\n",
+            )],
+            None,
+        ) else {
+            return;
+        };
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "42\n");
+    }
+}
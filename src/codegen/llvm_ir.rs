@@ -0,0 +1,486 @@
+use std::env;
+
+use super::*;
+
+/// The number of cells in the data stack. Both stacks are allocated on the native stack by
+/// `main`, so this is smaller than on the other targets.
+const STACK_CELLS: usize = 1 << 16;
+/// The number of cells in the return stack, the same as the 64 KiB of the native targets.
+const RET_STACK_CELLS: usize = 65536 / 8;
+
+/// The parameters of every Rack function, which are passed on unchanged to the functions it
+/// calls: the two stacks and the slots holding the index of their tops.
+const PARAMS: &str = "ptr %stack, ptr %sp, ptr %ret, ptr %rsp";
+
+/// The runtime, written with helpers that are always inlined so that once the stacks of `main`
+/// reach them they can be promoted to registers. The data stack grows up from 0, and the return
+/// stack down from its end.
+const RUNTIME: &str = "\
+declare i64 @write(i32, ptr, i64)
+declare void @exit(i32)
+
+define private void @push(ptr %stack, ptr %sp, i64 %x) #0 {
+  %n = load i64, ptr %sp
+  %p = getelementptr i64, ptr %stack, i64 %n
+  store i64 %x, ptr %p
+  %m = add i64 %n, 1
+  store i64 %m, ptr %sp
+  ret void
+}
+
+define private i64 @pop(ptr %stack, ptr %sp) #0 {
+  %n = load i64, ptr %sp
+  %m = sub i64 %n, 1
+  store i64 %m, ptr %sp
+  %p = getelementptr i64, ptr %stack, i64 %m
+  %x = load i64, ptr %p
+  ret i64 %x
+}
+
+; The value `depth` cells below the top of the data stack.
+define private i64 @peek(ptr %stack, ptr %sp, i64 %depth) #0 {
+  %n = load i64, ptr %sp
+  %m = sub i64 %n, 1
+  %i = sub i64 %m, %depth
+  %p = getelementptr i64, ptr %stack, i64 %i
+  %x = load i64, ptr %p
+  ret i64 %x
+}
+
+; Move the return stack by `delta` cells.
+define private void @rsp_add(ptr %rsp, i64 %delta) #0 {
+  %n = load i64, ptr %rsp
+  %m = add i64 %n, %delta
+  store i64 %m, ptr %rsp
+  ret void
+}
+
+define private i64 @ret_get(ptr %ret, ptr %rsp, i64 %index) #0 {
+  %n = load i64, ptr %rsp
+  %i = add i64 %n, %index
+  %p = getelementptr i64, ptr %ret, i64 %i
+  %x = load i64, ptr %p
+  ret i64 %x
+}
+
+define private void @ret_set(ptr %ret, ptr %rsp, i64 %index, i64 %x) #0 {
+  %n = load i64, ptr %rsp
+  %i = add i64 %n, %index
+  %p = getelementptr i64, ptr %ret, i64 %i
+  store i64 %x, ptr %p
+  ret void
+}
+
+; Exit with status 1 if `x` is 0, since dividing by it is undefined behaviour that the optimiser
+; would be free to assume away.
+define private void @check_divisor(i64 %x) #0 {
+  %zero = icmp eq i64 %x, 0
+  br i1 %zero, label %fail, label %ok
+fail:
+  call void @exit(i32 1)
+  unreachable
+ok:
+  ret void
+}
+
+; Write the decimal digits of `x` backwards from the end of a buffer.
+define private void @print(i64 %x) {
+entry:
+  %buf = alloca [21 x i8]
+  %end = getelementptr [21 x i8], ptr %buf, i64 0, i64 20
+  store i8 10, ptr %end
+  br label %loop
+loop:
+  %n = phi i64 [ %x, %entry ], [ %q, %loop ]
+  %p = phi ptr [ %end, %entry ], [ %d, %loop ]
+  %q = udiv i64 %n, 10
+  %r = urem i64 %n, 10
+  %c = trunc i64 %r to i8
+  %digit = add i8 %c, 48
+  %d = getelementptr i8, ptr %p, i64 -1
+  store i8 %digit, ptr %d
+  %more = icmp ne i64 %q, 0
+  br i1 %more, label %loop, label %done
+done:
+  %start = ptrtoint ptr %d to i64
+  %stop = ptrtoint ptr %end to i64
+  %len = sub i64 %stop, %start
+  %len1 = add i64 %len, 1
+  %w = call i64 @write(i32 1, ptr %d, i64 %len1)
+  ret void
+}
+
+attributes #0 = { alwaysinline }
+";
+
+/// Writes the body of a function, numbering the temporaries and labels.
+struct Writer<'a> {
+    buffer: &'a mut String,
+    temps: usize,
+    labels: usize,
+}
+
+impl Writer<'_> {
+    /// A new temporary.
+    fn temp(&mut self) -> String {
+        self.temps += 1;
+        format!("%t{}", self.temps)
+    }
+
+    /// A new label, with a suffix to make the output easier to follow.
+    fn label(&mut self, suffix: &str) -> String {
+        self.labels += 1;
+        format!("L{}.{suffix}", self.labels)
+    }
+
+    fn push(&mut self, val: &str) -> fmt::Result {
+        writeln!(
+            self.buffer,
+            "  call void @push(ptr %stack, ptr %sp, i64 {val})"
+        )
+    }
+
+    fn pop(&mut self) -> Result<String, fmt::Error> {
+        let temp = self.temp();
+        writeln!(self.buffer, "  {temp} = call i64 @pop(ptr %stack, ptr %sp)")?;
+        Ok(temp)
+    }
+
+    fn move_ret_stack(&mut self, delta: i64) -> fmt::Result {
+        writeln!(self.buffer, "  call void @rsp_add(ptr %rsp, i64 {delta})")
+    }
+
+    /// Pop the top two values and push the result of `instr` on the second and the top.
+    fn binary(&mut self, instr: &str) -> fmt::Result {
+        let b = self.pop()?;
+        let a = self.pop()?;
+        let result = self.temp();
+        writeln!(self.buffer, "  {result} = {instr} i64 {a}, {b}")?;
+        self.push(&result)
+    }
+
+    /// Pop the top two values and push 1 if `cond` holds for the second and the top, and 0
+    /// otherwise.
+    fn comparison(&mut self, cond: &str) -> fmt::Result {
+        let b = self.pop()?;
+        let a = self.pop()?;
+        let flag = self.temp();
+        let result = self.temp();
+        writeln!(
+            self.buffer,
+            "  {flag} = icmp {cond} i64 {a}, {b}\n  {result} = zext i1 {flag} to i64"
+        )?;
+        self.push(&result)
+    }
+
+    /// Pop a value and check whether it is true.
+    fn pop_bool(&mut self) -> Result<String, fmt::Error> {
+        let val = self.pop()?;
+        let flag = self.temp();
+        writeln!(self.buffer, "  {flag} = icmp eq i64 {val}, 1")?;
+        Ok(flag)
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn write_op(&mut self, op: &Op) -> fmt::Result {
+        match op {
+            Op::CallFn(index) => writeln!(self.buffer, "  call void @fn_{index}({PARAMS})")?,
+            Op::Ret(count) => {
+                if *count > 0 {
+                    self.move_ret_stack(cells(*count))?;
+                }
+                // Anything after an early return is unreachable, but it still needs a block.
+                let dead = self.label("dead");
+                writeln!(self.buffer, "  ret void\n{dead}:")?;
+            }
+            Op::Bind {
+                count,
+                names,
+                peek,
+                body,
+            } => {
+                writeln!(self.buffer, "  ; let {}", names.join(" "))?;
+                self.move_ret_stack(-cells(*count))?;
+                for i in 0..*count {
+                    let val = self.temp();
+                    writeln!(
+                        self.buffer,
+                        "  {val} = call i64 @peek(ptr %stack, ptr %sp, i64 {i})
+  call void @ret_set(ptr %ret, ptr %rsp, i64 {i}, i64 {val})"
+                    )?;
+                }
+                if !peek {
+                    let sp = self.temp();
+                    let new_sp = self.temp();
+                    writeln!(
+                        self.buffer,
+                        "  {sp} = load i64, ptr %sp
+  {new_sp} = sub i64 {sp}, {count}
+  store i64 {new_sp}, ptr %sp"
+                    )?;
+                }
+                for op in body {
+                    self.write_op(op)?;
+                }
+                // Remove the bindings from the return stack.
+                self.move_ret_stack(cells(*count))?;
+            }
+            Op::PushBind(index) => {
+                let val = self.temp();
+                writeln!(
+                    self.buffer,
+                    "  {val} = call i64 @ret_get(ptr %ret, ptr %rsp, i64 {index})"
+                )?;
+                self.push(&val)?;
+            }
+            Op::PushInt(val) => self.push(&val.to_string())?,
+            Op::PushStrPtr(index) => self.push(&format!("ptrtoint (ptr @str.{index} to i64)"))?,
+            Op::Plus => self.binary("add")?,
+            Op::Minus => self.binary("sub")?,
+            Op::DivMod => {
+                let b = self.pop()?;
+                let a = self.pop()?;
+                let quotient = self.temp();
+                let remainder = self.temp();
+                writeln!(
+                    self.buffer,
+                    "  call void @check_divisor(i64 {b})
+  {quotient} = udiv i64 {a}, {b}
+  {remainder} = urem i64 {a}, {b}"
+                )?;
+                self.push(&quotient)?;
+                self.push(&remainder)?;
+            }
+            Op::Dup => {
+                let val = self.temp();
+                writeln!(
+                    self.buffer,
+                    "  {val} = call i64 @peek(ptr %stack, ptr %sp, i64 0)"
+                )?;
+                self.push(&val)?;
+            }
+            Op::Drop => {
+                self.pop()?;
+            }
+            Op::Swap => {
+                let b = self.pop()?;
+                let a = self.pop()?;
+                self.push(&b)?;
+                self.push(&a)?;
+            }
+            Op::Over => {
+                let val = self.temp();
+                writeln!(
+                    self.buffer,
+                    "  {val} = call i64 @peek(ptr %stack, ptr %sp, i64 1)"
+                )?;
+                self.push(&val)?;
+            }
+            Op::Equals => self.comparison("eq")?,
+            Op::Neq => self.comparison("ne")?,
+            Op::GreaterThan => self.comparison("ugt")?,
+            Op::LessThan => self.comparison("ult")?,
+            Op::Not => {
+                let val = self.pop()?;
+                let result = self.temp();
+                writeln!(self.buffer, "  {result} = sub i64 1, {val}")?;
+                self.push(&result)?;
+            }
+            Op::Or | Op::And => {
+                let b = self.pop_bool()?;
+                let a = self.pop_bool()?;
+                let flag = self.temp();
+                let result = self.temp();
+                let instr = if matches!(op, Op::Or) { "or" } else { "and" };
+                writeln!(
+                    self.buffer,
+                    "  {flag} = {instr} i1 {a}, {b}\n  {result} = zext i1 {flag} to i64"
+                )?;
+                self.push(&result)?;
+            }
+            Op::ReadByte => {
+                let addr = self.pop()?;
+                let ptr = self.temp();
+                let byte = self.temp();
+                let result = self.temp();
+                writeln!(
+                    self.buffer,
+                    "  {ptr} = inttoptr i64 {addr} to ptr
+  {byte} = load i8, ptr {ptr}
+  {result} = zext i8 {byte} to i64"
+                )?;
+                self.push(&result)?;
+            }
+            Op::If(ops) => {
+                let flag = self.pop_bool()?;
+                let then = self.label("then");
+                let end = self.label("end");
+                writeln!(
+                    self.buffer,
+                    "  br i1 {flag}, label %{then}, label %{end}\n{then}:"
+                )?;
+                for op in ops {
+                    self.write_op(op)?;
+                }
+                writeln!(self.buffer, "  br label %{end}\n{end}:")?;
+            }
+            Op::While { condn, body } => {
+                let condn_label = self.label("while");
+                let body_label = self.label("do");
+                let end = self.label("end");
+                writeln!(self.buffer, "  br label %{condn_label}\n{condn_label}:")?;
+                for op in condn {
+                    self.write_op(op)?;
+                }
+                let flag = self.pop_bool()?;
+                writeln!(
+                    self.buffer,
+                    "  br i1 {flag}, label %{body_label}, label %{end}\n{body_label}:"
+                )?;
+                for op in body {
+                    self.write_op(op)?;
+                }
+                writeln!(self.buffer, "  br label %{condn_label}\n{end}:")?;
+            }
+            Op::Print => {
+                let val = self.pop()?;
+                writeln!(self.buffer, "  call void @print(i64 {val})")?;
+            }
+            Op::Puts => {
+                let addr = self.pop()?;
+                let len = self.pop()?;
+                let ptr = self.temp();
+                let written = self.temp();
+                writeln!(
+                    self.buffer,
+                    "  {ptr} = inttoptr i64 {addr} to ptr
+  {written} = call i64 @write(i32 1, ptr {ptr}, i64 {len})"
+                )?;
+            }
+            Op::Asm { .. } => unreachable!("inline assembly isn't parsed for this target"),
+        }
+        Ok(())
+    }
+}
+
+/// The number of cells `count` as an offset, which is never large enough to wrap.
+fn cells(count: usize) -> i64 {
+    i64::try_from(count).expect("the stacks fit in memory")
+}
+
+/// Make `s` safe to put in a quoted string.
+fn escape(s: &[u8]) -> String {
+    let mut escaped = String::new();
+    for b in s {
+        match b {
+            b' '..=b'~' if !matches!(b, b'"' | b'\\') => escaped.push(char::from(*b)),
+            _ => write!(escaped, "\\{b:02X}").unwrap(),
+        }
+    }
+    escaped
+}
+
+pub fn generate(program: Program) -> Result<String, fmt::Error> {
+    let mut outbuf = String::new();
+    for (i, s) in program.ctx.strings.iter().enumerate() {
+        writeln!(
+            outbuf,
+            "@str.{i} = private unnamed_addr constant [{} x i8] c\"{}\"",
+            s.len(),
+            escape(s.as_bytes())
+        )?;
+    }
+    outbuf.push('\n');
+    outbuf += RUNTIME;
+
+    for (i, func) in program.funcs.iter().enumerate() {
+        writeln!(
+            outbuf,
+            "\n; {}\ndefine private void @fn_{i}({PARAMS}) {{\nentry:",
+            func.ident
+        )?;
+        let mut writer = Writer {
+            buffer: &mut outbuf,
+            temps: 0,
+            labels: 0,
+        };
+        for op in &func.body {
+            writer.write_op(op)?;
+        }
+        writeln!(outbuf, "  ret void\n}}")?;
+    }
+
+    writeln!(
+        outbuf,
+        "
+define i32 @main() {{
+entry:
+  %stack = alloca [{STACK_CELLS} x i64]
+  %sp = alloca i64
+  store i64 0, ptr %sp
+  %ret = alloca [{RET_STACK_CELLS} x i64]
+  %rsp = alloca i64
+  store i64 {RET_STACK_CELLS}, ptr %rsp
+  call void @fn_{}({PARAMS})
+  call void @exit(i32 0)
+  unreachable
+}}",
+        program.ctx.lookup["main"]
+    )?;
+    Ok(outbuf)
+}
+
+/// LLVM IR, which can be optimised and compiled for any target by LLVM, and run with `$LLI` (or
+/// `lli`).
+pub struct LlvmIr;
+
+impl Backend for LlvmIr {
+    fn name(&self) -> &'static str {
+        "llvm-ir"
+    }
+
+    fn extension(&self) -> Option<&'static str> {
+        Some("ll")
+    }
+
+    fn generate(&self, program: Program) -> Result<Artifact, Box<dyn Error>> {
+        Ok(Artifact::Text(generate(program)?))
+    }
+
+    fn run(&self, out: &Path) -> Option<Command> {
+        let mut lli = Command::new(env::var("LLI").unwrap_or_else(|_| "lli".to_owned()));
+        lli.arg(out);
+        Some(lli)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::codegen::test::{check_runs, check_snapshots};
+
+    use super::*;
+
+    #[test]
+    fn snapshots() {
+        check_snapshots(&LlvmIr);
+    }
+
+    #[test]
+    fn run() {
+        // Opaque pointers are only read by default from LLVM 15.
+        let lli = LlvmIr.run(Path::new("")).unwrap();
+        let version = Command::new(lli.get_program())
+            .arg("--version")
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).into_owned());
+        let major = version.as_deref().ok().and_then(|version| {
+            let (_, rest) = version.split_once("LLVM version ")?;
+            rest.split('.').next()?.parse::<u32>().ok()
+        });
+        if major.is_some_and(|major| major < 15) {
+            eprintln!("skipping, `lli` is older than LLVM 15");
+            return;
+        }
+        check_runs(&LlvmIr);
+    }
+}
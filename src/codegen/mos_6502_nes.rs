@@ -0,0 +1,67 @@
+use super::*;
+
+/// The number of 16 KiB PRG ROM banks in the cartridge.
+const PRG_BANKS: u8 = 2;
+
+/// An iNES header for a cartridge without a mapper (NROM), with 32 KiB of PRG ROM and 8 KiB of
+/// CHR RAM in place of CHR ROM, so tiles are written to the PPU by the program. The nametables are
+/// mirrored vertically.
+const HEADER: [u8; 16] = [
+    b'N', b'E', b'S', 0x1a, PRG_BANKS, 0, 0x01, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+];
+
+/// Generate an iNES ROM holding `program`.
+pub fn generate(program: &Program) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut rom = HEADER.to_vec();
+    rom.extend(mos_6502_nesulator::generate_prg(program)?);
+    Ok(rom)
+}
+
+/// An iNES ROM (`.nes`) that runs in any NES emulator. The program starts at `main` on reset,
+/// and the `nmi` and `irq` functions, if defined, handle those interrupts.
+pub struct Nes;
+
+impl Backend for Nes {
+    fn name(&self) -> &'static str {
+        "mos_6502-nes"
+    }
+
+    fn extension(&self) -> Option<&'static str> {
+        Some("nes")
+    }
+
+    fn cell_width(&self) -> CellWidth {
+        CellWidth::new(16)
+    }
+
+    fn arch(&self) -> Option<&'static str> {
+        Some(mos_6502_nesulator::ARCH)
+    }
+
+    fn prelude(&self) -> Option<&'static str> {
+        Some(mos_6502_nesulator::PRELUDE)
+    }
+
+    fn generate(&self, program: Program) -> Result<Artifact, Box<dyn Error>> {
+        Ok(Artifact::Binary(generate(&program)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{parse, Lexer};
+
+    use super::*;
+
+    #[test]
+    fn layout() {
+        let mut program = Program::default();
+        program.ctx.cell_width = Nes.cell_width();
+        parse::parse_items(&mut Lexer::new("fn main in 1 end\n", None), &mut program).unwrap();
+        let rom = generate(&program).unwrap();
+        assert_eq!(rom[..16], HEADER);
+        assert_eq!(rom.len(), 16 + usize::from(PRG_BANKS) * 0x4000);
+        // The reset vector points at the start of PRG ROM.
+        assert_eq!(rom[rom.len() - 4..rom.len() - 2], [0x00, 0x80]);
+    }
+}
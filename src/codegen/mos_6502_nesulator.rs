@@ -0,0 +1,1413 @@
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use super::*;
+
+/// The address that PRG ROM is mapped at, which the whole program is assembled to.
+pub const PRG_ORIGIN: u16 = 0x8000;
+/// The size of PRG ROM, two 16 KiB banks with no mapper.
+pub const PRG_BYTES: usize = 0x8000;
+
+/// Zero page scratch bytes for the runtime routines and inline assembly, `$00` to `$07`. They are
+/// saved around interrupt handlers.
+const TEMP_BYTES: u8 = 8;
+/// The zero page byte that indexes the next free slot on the binding stack.
+const BSP: u8 = 0x08;
+/// The pages holding the low and high bytes of the bindings, indexed by `BSP`.
+const BIND_LO: u16 = 0x0300;
+const BIND_HI: u16 = 0x0400;
+
+/// Clear RAM, wait for the PPU to warm up and call `main`. The data stack is in the zero page,
+/// indexed by `X` and growing down from the top, with the low byte of each cell first.
+const RESET: &str = "\
+reset:
+\tSEI
+\tCLD
+\tLDX #$40
+\tSTX $4017
+\tLDX #$FF
+\tTXS
+\tINX
+\tSTX $2000
+\tSTX $2001
+\tSTX $4010
+\tBIT $2002
+reset_vblank1:
+\tBIT $2002
+\tBPL reset_vblank1
+\tTXA
+reset_clear:
+\tSTA $00,X
+\tSTA $0100,X
+\tSTA $0200,X
+\tSTA $0300,X
+\tSTA $0400,X
+\tSTA $0500,X
+\tSTA $0600,X
+\tSTA $0700,X
+\tINX
+\tBNE reset_clear
+reset_vblank2:
+\tBIT $2002
+\tBPL reset_vblank2
+";
+
+/// Divide the cell below the top of the stack by the one on top, leaving the quotient below the
+/// remainder. The remainder is shifted through `$00`-`$01` with its 17th bit in `$03`.
+const DIVMOD: &str = "\
+divmod:
+\tLDA #0
+\tSTA $00
+\tSTA $01
+\tLDA #16
+\tSTA $04
+divmod_loop:
+\tASL 2,X
+\tROL 3,X
+\tROL $00
+\tROL $01
+\tLDA #0
+\tROL A
+\tSTA $03
+\tLDA $00
+\tSEC
+\tSBC 0,X
+\tSTA $02
+\tLDA $01
+\tSBC 1,X
+\tSTA $05
+\tLDA $03
+\tSBC #0
+\tBCC divmod_skip
+\tLDA $02
+\tSTA $00
+\tLDA $05
+\tSTA $01
+\tINC 2,X
+divmod_skip:
+\tDEC $04
+\tBNE divmod_loop
+\tLDA $00
+\tSTA 0,X
+\tLDA $01
+\tSTA 1,X
+\tRTS
+";
+
+/// The architecture of the 6502 targets, for inline assembly.
+pub const ARCH: &str = "mos_6502";
+
+/// Functions for the NES hardware, which are parsed ahead of every program for the 6502 targets.
+pub const PRELUDE: &str = include_str!("nes.rk");
+
+#[derive(Error, Debug)]
+#[error("`{op}` can't be compiled for the NES, which has no standard output")]
+struct NoOutputError {
+    op: &'static str,
+}
+
+#[derive(Error, Debug)]
+#[error("a `let` or `peek` can bind at most 127 cells on the 6502")]
+struct TooManyBindingsError;
+
+/// Push the cell whose low and high bytes are `lo` and `hi`, making room for it on the stack.
+fn write_push(buffer: &mut String, lo: &str, hi: &str) -> fmt::Result {
+    write!(
+        buffer,
+        "\tDEX
+\tDEX
+\tLDA #{lo}
+\tSTA 0,X
+\tLDA #{hi}
+\tSTA 1,X
+"
+    )
+}
+
+/// Replace the two cells on top of the stack with the flag in `Y`.
+const PUSH_FLAG: &str = "\tINX
+\tINX
+\tSTY 0,X
+\tLDA #0
+\tSTA 1,X
+";
+
+#[allow(clippy::too_many_lines)]
+fn write_op(op: &Op, count_ops: &mut usize, buffer: &mut String) -> Result<(), Box<dyn Error>> {
+    match op {
+        Op::PushInt(val) => {
+            writeln!(buffer, "\t\t\t\t; Op::PushInt({val})")?;
+            write_push(
+                buffer,
+                &format!("${:02X}", val & 0xff),
+                &format!("${:02X}", val >> 8 & 0xff),
+            )?;
+        }
+        Op::PushStrPtr(index) => {
+            writeln!(buffer, "\t\t\t\t; Op::PushStrPtr({index})")?;
+            write_push(buffer, &format!("<str_{index}"), &format!(">str_{index}"))?;
+        }
+        Op::Plus => write!(
+            buffer,
+            "\tCLC\t\t\t; Op::Plus
+\tLDA 2,X
+\tADC 0,X
+\tSTA 2,X
+\tLDA 3,X
+\tADC 1,X
+\tSTA 3,X
+\tINX
+\tINX
+"
+        )?,
+        Op::Minus => write!(
+            buffer,
+            "\tSEC\t\t\t; Op::Minus
+\tLDA 2,X
+\tSBC 0,X
+\tSTA 2,X
+\tLDA 3,X
+\tSBC 1,X
+\tSTA 3,X
+\tINX
+\tINX
+"
+        )?,
+        Op::DivMod => writeln!(buffer, "\tJSR divmod\t\t; Op::DivMod")?,
+        Op::Dup => write!(
+            buffer,
+            "\tDEX\t\t\t; Op::Dup
+\tDEX
+\tLDA 2,X
+\tSTA 0,X
+\tLDA 3,X
+\tSTA 1,X
+"
+        )?,
+        Op::Drop => write!(buffer, "\tINX\t\t\t; Op::Drop\n\tINX\n")?,
+        Op::Swap => write!(
+            buffer,
+            "\tLDA 0,X\t\t\t; Op::Swap
+\tLDY 2,X
+\tSTA 2,X
+\tSTY 0,X
+\tLDA 1,X
+\tLDY 3,X
+\tSTA 3,X
+\tSTY 1,X
+"
+        )?,
+        Op::Over => write!(
+            buffer,
+            "\tDEX\t\t\t; Op::Over
+\tDEX
+\tLDA 4,X
+\tSTA 0,X
+\tLDA 5,X
+\tSTA 1,X
+"
+        )?,
+        Op::Equals | Op::Neq => {
+            let (name, init, found) = if *op == Op::Equals {
+                ("Equals", 0, "INY")
+            } else {
+                ("Neq", 1, "DEY")
+            };
+            write!(
+                buffer,
+                "\tLDY #{init}\t\t\t; Op::{name}
+\tLDA 0,X
+\tCMP 2,X
+\tBNE J{count_ops}
+\tLDA 1,X
+\tCMP 3,X
+\tBNE J{count_ops}
+\t{found}
+J{count_ops}:
+{PUSH_FLAG}"
+            )?;
+        }
+        Op::Not => write!(
+            buffer,
+            "\tSEC\t\t\t; Op::Not
+\tLDA #1
+\tSBC 0,X
+\tSTA 0,X
+\tLDA #0
+\tSBC 1,X
+\tSTA 1,X
+"
+        )?,
+        // Unsigned comparisons of the cell below the top with the top, through the borrow of a
+        // subtraction.
+        Op::GreaterThan | Op::LessThan => {
+            let (name, left, right) = if *op == Op::GreaterThan {
+                ("GreaterThan", 0, 2)
+            } else {
+                ("LessThan", 2, 0)
+            };
+            write!(
+                buffer,
+                "\tLDY #0\t\t\t; Op::{name}
+\tLDA {left},X
+\tCMP {right},X
+\tLDA {},X
+\tSBC {},X
+\tBCS J{count_ops}
+\tINY
+J{count_ops}:
+{PUSH_FLAG}",
+                left + 1,
+                right + 1
+            )?;
+        }
+        Op::Or => {
+            write!(
+                buffer,
+                "\tLDY #1\t\t\t; Op::Or
+\tLDA 0,X
+\tCMP #1
+\tBNE J{0}
+\tLDA 1,X
+\tBEQ J{1}
+J{0}:
+\tLDA 2,X
+\tCMP #1
+\tBNE J{2}
+\tLDA 3,X
+\tBEQ J{1}
+J{2}:
+\tLDY #0
+J{1}:
+{PUSH_FLAG}",
+                count_ops,
+                *count_ops + 1,
+                *count_ops + 2,
+            )?;
+            *count_ops += 2;
+        }
+        Op::And => write!(
+            buffer,
+            "\tLDY #0\t\t\t; Op::And
+\tLDA 0,X
+\tCMP #1
+\tBNE J{count_ops}
+\tLDA 1,X
+\tBNE J{count_ops}
+\tLDA 2,X
+\tCMP #1
+\tBNE J{count_ops}
+\tLDA 3,X
+\tBNE J{count_ops}
+\tINY
+J{count_ops}:
+{PUSH_FLAG}"
+        )?,
+        Op::ReadByte => write!(
+            buffer,
+            "\tLDA (0,X)\t\t; Op::ReadByte
+\tSTA 0,X
+\tLDA #0
+\tSTA 1,X
+"
+        )?,
+        Op::If(ops) => {
+            let jump_to = *count_ops;
+            *count_ops += 1;
+            writeln!(buffer, "\t\t\t\t; Op::If")?;
+            write_condition(buffer, jump_to + 1, jump_to)?;
+            for op in ops {
+                write_op(op, count_ops, buffer)?;
+            }
+            writeln!(buffer, "F{jump_to}:")?;
+        }
+        Op::While { condn, body } => {
+            let condn_jump = *count_ops;
+            let end_jump = *count_ops + 1;
+            *count_ops += 2;
+            writeln!(buffer, "F{condn_jump}:\t\t\t\t; Op::While")?;
+            for op in condn {
+                write_op(op, count_ops, buffer)?;
+            }
+            write_condition(buffer, condn_jump + 2, end_jump)?;
+            for op in body {
+                write_op(op, count_ops, buffer)?;
+            }
+            writeln!(buffer, "\tJMP F{condn_jump}\nF{end_jump}:")?;
+        }
+        Op::Print => return Err(NoOutputError { op: "print" }.into()),
+        Op::Puts => return Err(NoOutputError { op: "puts" }.into()),
+        Op::CallFn(index) => writeln!(buffer, "\tJSR fn{index}\t\t; Op::CallFn({index})")?,
+        Op::Ret(count) => {
+            writeln!(buffer, "\t\t\t\t; Op::Ret({count})")?;
+            write_unbind(buffer, *count)?;
+            writeln!(buffer, "\tRTS")?;
+        }
+        Op::Bind {
+            count, peek, body, ..
+        } => {
+            if *count > 127 {
+                return Err(TooManyBindingsError.into());
+            }
+            // The slots are taken before they are written, so that an interrupt handler doesn't
+            // bind over them.
+            write!(
+                buffer,
+                "\tLDA ${BSP:02X}\t\t\t; Op::Bind({count})
+\tTAY
+\tCLC
+\tADC #{count}
+\tSTA ${BSP:02X}
+"
+            )?;
+            for cell in (0..*count).rev() {
+                write!(
+                    buffer,
+                    "\tLDA {},X
+\tSTA ${BIND_LO:04X},Y
+\tLDA {},X
+\tSTA ${BIND_HI:04X},Y
+\tINY
+",
+                    cell * 2,
+                    cell * 2 + 1
+                )?;
+            }
+            if !peek {
+                write!(buffer, "\tTXA\n\tCLC\n\tADC #{}\n\tTAX\n", count * 2)?;
+            }
+            for op in body {
+                write_op(op, count_ops, buffer)?;
+            }
+            write_unbind(buffer, *count)?;
+        }
+        Op::PushBind(index) => {
+            // The binding `index` from the top is at `BSP - 1 - index`, so the offset is taken
+            // from the base address rather than the index.
+            write!(
+                buffer,
+                "\tLDY ${BSP:02X}\t\t\t; Op::PushBind({index})
+\tDEX
+\tDEX
+\tLDA ${:04X},Y
+\tSTA 0,X
+\tLDA ${:04X},Y
+\tSTA 1,X
+",
+                usize::from(BIND_LO) - 1 - index,
+                usize::from(BIND_HI) - 1 - index
+            )?;
+        }
+        Op::Asm { signature, lines } => {
+            writeln!(
+                buffer,
+                "\t\t\t\t; Op::Asm {} -- {}",
+                signature.inputs, signature.outputs
+            )?;
+            for line in lines {
+                writeln!(buffer, "\t{line}")?;
+            }
+        }
+    }
+    *count_ops += 1;
+    Ok(())
+}
+
+/// Pop a condition and carry on to the label `F{body}` if it is `true`, or jump to `F{end}`
+/// otherwise. Branches only reach 127 bytes, so the jump out is a `JMP`.
+fn write_condition(buffer: &mut String, body: usize, end: usize) -> fmt::Result {
+    write!(
+        buffer,
+        "\tLDA 0,X
+\tEOR #1
+\tORA 1,X
+\tINX
+\tINX
+\tCMP #0
+\tBEQ F{body}
+\tJMP F{end}
+F{body}:
+"
+    )
+}
+
+/// Drop `count` bindings from the binding stack.
+fn write_unbind(buffer: &mut String, count: usize) -> fmt::Result {
+    if count > 0 {
+        write!(
+            buffer,
+            "\tLDA ${BSP:02X}
+\tSEC
+\tSBC #{count}
+\tSTA ${BSP:02X}
+"
+        )?;
+    }
+    Ok(())
+}
+
+/// Write the handler for an interrupt, which calls the Rack function `func` if it is defined.
+/// The interrupted code may be in the middle of an op, so the registers and scratch bytes are
+/// saved, and the handler carries on using the data stack below where it was interrupted.
+fn write_interrupt(buffer: &mut String, label: &str, func: Option<usize>) -> fmt::Result {
+    let Some(func) = func else {
+        return writeln!(buffer, "{label}:\n\tRTI");
+    };
+    write!(
+        buffer,
+        "{label}:
+\tPHA
+\tTXA
+\tPHA
+\tTYA
+\tPHA
+\tLDY #0
+{label}_save:
+\tLDA $0000,Y
+\tPHA
+\tINY
+\tCPY #{TEMP_BYTES}
+\tBNE {label}_save
+\tJSR fn{func}
+\tLDY #{}
+{label}_restore:
+\tPLA
+\tSTA $0000,Y
+\tDEY
+\tBPL {label}_restore
+\tPLA
+\tTAY
+\tPLA
+\tTAX
+\tPLA
+\tRTI
+",
+        TEMP_BYTES - 1
+    )
+}
+
+/// Generate the assembly for `program`, laid out to fill PRG ROM with the vectors at the end.
+pub fn generate_source(program: &Program) -> Result<String, Box<dyn Error>> {
+    let defined = |name| {
+        program
+            .ctx
+            .lookup
+            .get(name)
+            .copied()
+            .filter(|index| !program.funcs[*index].is_import())
+    };
+    let main = defined("main").ok_or("no entry point `main` found")?;
+
+    let mut outbuf =
+        format!("\t.org ${PRG_ORIGIN:04X}\n{RESET}\tJSR fn{main}\nhalt:\n\tJMP halt\n");
+    write_interrupt(&mut outbuf, "nmi", defined("nmi"))?;
+    write_interrupt(&mut outbuf, "irq", defined("irq"))?;
+    outbuf += DIVMOD;
+
+    let mut count_ops = 0;
+    for (index, func) in program.funcs.iter().enumerate() {
+        writeln!(outbuf, "fn{index}:\t\t\t\t; {}", func.ident)?;
+        for op in &func.body {
+            write_op(op, &mut count_ops, &mut outbuf)?;
+        }
+    }
+
+    for (i, s) in program.ctx.strings.iter().enumerate() {
+        writeln!(outbuf, "str_{i}:")?;
+        for chunk in s.as_bytes().chunks(16) {
+            let bytes: Vec<_> = chunk.iter().map(u8::to_string).collect();
+            writeln!(outbuf, "\t.byte {}", bytes.join(", "))?;
+        }
+    }
+    outbuf += "\t.org $FFFA\n\t.word nmi, reset, irq\n";
+    Ok(outbuf)
+}
+
+/// Generate the contents of PRG ROM for `program`.
+pub fn generate_prg(program: &Program) -> Result<Vec<u8>, Box<dyn Error>> {
+    let prg = assemble(&generate_source(program)?)?;
+    debug_assert_eq!(prg.len(), PRG_BYTES);
+    Ok(prg)
+}
+
+/// Generate a memory image of the cartridge space from `$4020` to the end of memory, with PRG
+/// ROM in the top half.
+pub fn generate(program: &Program) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut image = vec![0; 0x1_0000 - 0x4020];
+    image[usize::from(PRG_ORIGIN) - 0x4020..].copy_from_slice(&generate_prg(program)?);
+    Ok(image)
+}
+
+/// A memory image for the nesulator emulator.
+pub struct Nesulator;
+
+impl Backend for Nesulator {
+    fn name(&self) -> &'static str {
+        "mos_6502-nesulator"
+    }
+
+    fn cell_width(&self) -> CellWidth {
+        CellWidth::new(16)
+    }
+
+    fn arch(&self) -> Option<&'static str> {
+        Some(ARCH)
+    }
+
+    fn prelude(&self) -> Option<&'static str> {
+        Some(PRELUDE)
+    }
+
+    fn generate(&self, program: Program) -> Result<Artifact, Box<dyn Error>> {
+        Ok(Artifact::Binary(generate(&program)?))
+    }
+}
+
+/// How an instruction finds its operand, which decides the size of the operand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Implied,
+    Accumulator,
+    Immediate,
+    ZeroPage,
+    ZeroPageX,
+    ZeroPageY,
+    Absolute,
+    AbsoluteX,
+    AbsoluteY,
+    Indirect,
+    IndirectX,
+    IndirectY,
+    Relative,
+}
+
+impl Mode {
+    const fn operand_bytes(self) -> u16 {
+        match self {
+            Self::Implied | Self::Accumulator => 0,
+            Self::Absolute | Self::AbsoluteX | Self::AbsoluteY | Self::Indirect => 2,
+            _ => 1,
+        }
+    }
+
+    /// The absolute mode that a zero page mode falls back to when the operand is too large, or
+    /// the instruction has no zero page form.
+    const fn widened(self) -> Self {
+        match self {
+            Self::ZeroPage => Self::Absolute,
+            Self::ZeroPageX => Self::AbsoluteX,
+            Self::ZeroPageY => Self::AbsoluteY,
+            mode => mode,
+        }
+    }
+}
+
+/// The opcodes of each official instruction, by addressing mode.
+#[rustfmt::skip]
+const OPCODES: &[(&str, &[(Mode, u8)])] = {
+    use Mode as M;
+    &[
+        ("ADC", &[(M::Immediate, 0x69), (M::ZeroPage, 0x65), (M::ZeroPageX, 0x75), (M::Absolute, 0x6D), (M::AbsoluteX, 0x7D), (M::AbsoluteY, 0x79), (M::IndirectX, 0x61), (M::IndirectY, 0x71)]),
+        ("AND", &[(M::Immediate, 0x29), (M::ZeroPage, 0x25), (M::ZeroPageX, 0x35), (M::Absolute, 0x2D), (M::AbsoluteX, 0x3D), (M::AbsoluteY, 0x39), (M::IndirectX, 0x21), (M::IndirectY, 0x31)]),
+        ("ASL", &[(M::Accumulator, 0x0A), (M::ZeroPage, 0x06), (M::ZeroPageX, 0x16), (M::Absolute, 0x0E), (M::AbsoluteX, 0x1E)]),
+        ("BCC", &[(M::Relative, 0x90)]),
+        ("BCS", &[(M::Relative, 0xB0)]),
+        ("BEQ", &[(M::Relative, 0xF0)]),
+        ("BIT", &[(M::ZeroPage, 0x24), (M::Absolute, 0x2C)]),
+        ("BMI", &[(M::Relative, 0x30)]),
+        ("BNE", &[(M::Relative, 0xD0)]),
+        ("BPL", &[(M::Relative, 0x10)]),
+        ("BRK", &[(M::Implied, 0x00)]),
+        ("BVC", &[(M::Relative, 0x50)]),
+        ("BVS", &[(M::Relative, 0x70)]),
+        ("CLC", &[(M::Implied, 0x18)]),
+        ("CLD", &[(M::Implied, 0xD8)]),
+        ("CLI", &[(M::Implied, 0x58)]),
+        ("CLV", &[(M::Implied, 0xB8)]),
+        ("CMP", &[(M::Immediate, 0xC9), (M::ZeroPage, 0xC5), (M::ZeroPageX, 0xD5), (M::Absolute, 0xCD), (M::AbsoluteX, 0xDD), (M::AbsoluteY, 0xD9), (M::IndirectX, 0xC1), (M::IndirectY, 0xD1)]),
+        ("CPX", &[(M::Immediate, 0xE0), (M::ZeroPage, 0xE4), (M::Absolute, 0xEC)]),
+        ("CPY", &[(M::Immediate, 0xC0), (M::ZeroPage, 0xC4), (M::Absolute, 0xCC)]),
+        ("DEC", &[(M::ZeroPage, 0xC6), (M::ZeroPageX, 0xD6), (M::Absolute, 0xCE), (M::AbsoluteX, 0xDE)]),
+        ("DEX", &[(M::Implied, 0xCA)]),
+        ("DEY", &[(M::Implied, 0x88)]),
+        ("EOR", &[(M::Immediate, 0x49), (M::ZeroPage, 0x45), (M::ZeroPageX, 0x55), (M::Absolute, 0x4D), (M::AbsoluteX, 0x5D), (M::AbsoluteY, 0x59), (M::IndirectX, 0x41), (M::IndirectY, 0x51)]),
+        ("INC", &[(M::ZeroPage, 0xE6), (M::ZeroPageX, 0xF6), (M::Absolute, 0xEE), (M::AbsoluteX, 0xFE)]),
+        ("INX", &[(M::Implied, 0xE8)]),
+        ("INY", &[(M::Implied, 0xC8)]),
+        ("JMP", &[(M::Absolute, 0x4C), (M::Indirect, 0x6C)]),
+        ("JSR", &[(M::Absolute, 0x20)]),
+        ("LDA", &[(M::Immediate, 0xA9), (M::ZeroPage, 0xA5), (M::ZeroPageX, 0xB5), (M::Absolute, 0xAD), (M::AbsoluteX, 0xBD), (M::AbsoluteY, 0xB9), (M::IndirectX, 0xA1), (M::IndirectY, 0xB1)]),
+        ("LDX", &[(M::Immediate, 0xA2), (M::ZeroPage, 0xA6), (M::ZeroPageY, 0xB6), (M::Absolute, 0xAE), (M::AbsoluteY, 0xBE)]),
+        ("LDY", &[(M::Immediate, 0xA0), (M::ZeroPage, 0xA4), (M::ZeroPageX, 0xB4), (M::Absolute, 0xAC), (M::AbsoluteX, 0xBC)]),
+        ("LSR", &[(M::Accumulator, 0x4A), (M::ZeroPage, 0x46), (M::ZeroPageX, 0x56), (M::Absolute, 0x4E), (M::AbsoluteX, 0x5E)]),
+        ("NOP", &[(M::Implied, 0xEA)]),
+        ("ORA", &[(M::Immediate, 0x09), (M::ZeroPage, 0x05), (M::ZeroPageX, 0x15), (M::Absolute, 0x0D), (M::AbsoluteX, 0x1D), (M::AbsoluteY, 0x19), (M::IndirectX, 0x01), (M::IndirectY, 0x11)]),
+        ("PHA", &[(M::Implied, 0x48)]),
+        ("PHP", &[(M::Implied, 0x08)]),
+        ("PLA", &[(M::Implied, 0x68)]),
+        ("PLP", &[(M::Implied, 0x28)]),
+        ("ROL", &[(M::Accumulator, 0x2A), (M::ZeroPage, 0x26), (M::ZeroPageX, 0x36), (M::Absolute, 0x2E), (M::AbsoluteX, 0x3E)]),
+        ("ROR", &[(M::Accumulator, 0x6A), (M::ZeroPage, 0x66), (M::ZeroPageX, 0x76), (M::Absolute, 0x6E), (M::AbsoluteX, 0x7E)]),
+        ("RTI", &[(M::Implied, 0x40)]),
+        ("RTS", &[(M::Implied, 0x60)]),
+        ("SBC", &[(M::Immediate, 0xE9), (M::ZeroPage, 0xE5), (M::ZeroPageX, 0xF5), (M::Absolute, 0xED), (M::AbsoluteX, 0xFD), (M::AbsoluteY, 0xF9), (M::IndirectX, 0xE1), (M::IndirectY, 0xF1)]),
+        ("SEC", &[(M::Implied, 0x38)]),
+        ("SED", &[(M::Implied, 0xF8)]),
+        ("SEI", &[(M::Implied, 0x78)]),
+        ("STA", &[(M::ZeroPage, 0x85), (M::ZeroPageX, 0x95), (M::Absolute, 0x8D), (M::AbsoluteX, 0x9D), (M::AbsoluteY, 0x99), (M::IndirectX, 0x81), (M::IndirectY, 0x91)]),
+        ("STX", &[(M::ZeroPage, 0x86), (M::ZeroPageY, 0x96), (M::Absolute, 0x8E)]),
+        ("STY", &[(M::ZeroPage, 0x84), (M::ZeroPageX, 0x94), (M::Absolute, 0x8C)]),
+        ("TAX", &[(M::Implied, 0xAA)]),
+        ("TAY", &[(M::Implied, 0xA8)]),
+        ("TSX", &[(M::Implied, 0xBA)]),
+        ("TXA", &[(M::Implied, 0x8A)]),
+        ("TXS", &[(M::Implied, 0x9A)]),
+        ("TYA", &[(M::Implied, 0x98)]),
+    ]
+};
+
+/// An error in a line of assembly, which may have come from inline assembly.
+#[derive(Error, Debug)]
+#[error("`{line}`: {message}")]
+pub struct AsmError {
+    line: String,
+    message: String,
+}
+
+/// A line of assembly that has been laid out, waiting for its labels to be resolved.
+enum Item<'a> {
+    Instr {
+        opcode: u8,
+        mode: Mode,
+        operand: String,
+    },
+    Bytes(Vec<&'a str>),
+    Words(Vec<&'a str>),
+    /// Padding up to an address.
+    Org(u16),
+}
+
+/// Evaluate an operand expression: numbers and labels added or subtracted, optionally reduced to
+/// the low (`<`) or high (`>`) byte of the result. Numbers are decimal, or hexadecimal with `$`
+/// and binary with `%`.
+fn eval(expr: &str, labels: &HashMap<&str, u16>) -> Result<u16, String> {
+    let (expr, byte) = match expr.strip_prefix('<') {
+        Some(expr) => (expr, Some(false)),
+        None => match expr.strip_prefix('>') {
+            Some(expr) => (expr, Some(true)),
+            None => (expr, None),
+        },
+    };
+    let mut value = 0_u16;
+    let mut rest = expr;
+    let mut negate = false;
+    loop {
+        let end = rest.find(['+', '-']).unwrap_or(rest.len());
+        let term = &rest[..end];
+        let term_value = if let Some(hex) = term.strip_prefix('$') {
+            u16::from_str_radix(hex, 16).map_err(|_| format!("invalid number `{term}`"))?
+        } else if let Some(bin) = term.strip_prefix('%') {
+            u16::from_str_radix(bin, 2).map_err(|_| format!("invalid number `{term}`"))?
+        } else if term.starts_with(|c: char| c.is_ascii_digit()) {
+            term.parse()
+                .map_err(|_| format!("invalid number `{term}`"))?
+        } else if term.is_empty() {
+            return Err("missing operand".to_owned());
+        } else {
+            *labels
+                .get(term)
+                .ok_or_else(|| format!("unknown label `{term}`"))?
+        };
+        value = if negate {
+            value.wrapping_sub(term_value)
+        } else {
+            value.wrapping_add(term_value)
+        };
+        let Some(op) = rest[end..].chars().next() else {
+            break;
+        };
+        negate = op == '-';
+        rest = &rest[end + 1..];
+    }
+    Ok(match byte {
+        Some(false) => value & 0xff,
+        Some(true) => value >> 8,
+        None => value,
+    })
+}
+
+/// Choose the opcode and addressing mode for `mnemonic` with `operand`, which has had its
+/// whitespace removed, returning them with the expression in the operand.
+fn parse_instr<'a>(mnemonic: &str, operand: &'a str) -> Result<(u8, Mode, &'a str), String> {
+    let opcodes = OPCODES
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(mnemonic))
+        .ok_or_else(|| format!("unknown instruction `{mnemonic}`"))?
+        .1;
+    let opcode = |mode| {
+        opcodes
+            .iter()
+            .find(|(m, _)| *m == mode)
+            .map(|(_, opcode)| *opcode)
+    };
+    let upper = operand.to_ascii_uppercase();
+    let (mode, expr) = if operand.is_empty() || upper == "A" {
+        if opcode(Mode::Implied).is_some() {
+            (Mode::Implied, "")
+        } else {
+            (Mode::Accumulator, "")
+        }
+    } else if let Some(expr) = operand.strip_prefix('#') {
+        (Mode::Immediate, expr)
+    } else if operand.starts_with('(') && upper.ends_with(",X)") {
+        (Mode::IndirectX, &operand[1..operand.len() - 3])
+    } else if operand.starts_with('(') && upper.ends_with("),Y") {
+        (Mode::IndirectY, &operand[1..operand.len() - 3])
+    } else if operand.starts_with('(') && operand.ends_with(')') {
+        (Mode::Indirect, &operand[1..operand.len() - 1])
+    } else if upper.ends_with(",X") {
+        (Mode::ZeroPageX, &operand[..operand.len() - 2])
+    } else if upper.ends_with(",Y") {
+        (Mode::ZeroPageY, &operand[..operand.len() - 2])
+    } else if opcode(Mode::Relative).is_some() {
+        (Mode::Relative, operand)
+    } else {
+        (Mode::ZeroPage, operand)
+    };
+    // Only numbers that fit in a byte are taken to be in the zero page, since labels may not be
+    // known until later.
+    let mode = match eval(expr, &HashMap::new()) {
+        Ok(value) if value < 0x100 && opcode(mode).is_some() => mode,
+        _ => mode.widened(),
+    };
+    let opcode = opcode(mode).ok_or_else(|| format!("`{mnemonic}` can't take this operand"))?;
+    Ok((opcode, mode, expr))
+}
+
+/// Assemble `source`, which has to start with an `.org` giving the address it is loaded at. Each
+/// line is an optional `label:` followed by an instruction or one of the directives `.byte`,
+/// `.word` and `.org`, and `;` starts a comment.
+// Addresses are checked to stay within the 64 KiB address space, so the casts between them are
+// lossless.
+#[allow(
+    clippy::too_many_lines,
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss
+)]
+pub fn assemble(source: &str) -> Result<Vec<u8>, AsmError> {
+    let error = |line: &str, message| AsmError {
+        line: line.trim().to_owned(),
+        message,
+    };
+    let mut labels = HashMap::new();
+    let mut items = Vec::new();
+    let mut origin = None;
+    let mut addr = 0_u32;
+    for line in source.lines() {
+        let mut text = line.split(';').next().unwrap_or_default().trim();
+        if let Some((label, rest)) = text.split_once(':') {
+            if !label.contains(char::is_whitespace) {
+                if labels.insert(label, addr as u16).is_some() {
+                    return Err(error(line, format!("label `{label}` is already defined")));
+                }
+                text = rest.trim();
+            }
+        }
+        if text.is_empty() {
+            continue;
+        }
+        let (word, operand) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        let operand: &str = operand.trim();
+        let args = || operand.split(',').map(str::trim).collect::<Vec<_>>();
+        let item = match word.to_ascii_lowercase().as_str() {
+            ".org" => {
+                let target = eval(operand, &HashMap::new()).map_err(|e| error(line, e))?;
+                if origin.is_none() {
+                    origin = Some(target);
+                    addr = u32::from(target);
+                    continue;
+                }
+                if u32::from(target) < addr {
+                    return Err(error(
+                        line,
+                        "the program doesn't fit below this address".to_owned(),
+                    ));
+                }
+                Item::Org(target)
+            }
+            ".byte" => Item::Bytes(args()),
+            ".word" => Item::Words(args()),
+            _ => {
+                let operand = operand.split_whitespace().collect::<String>();
+                let (opcode, mode, expr) =
+                    parse_instr(word, &operand).map_err(|e| error(line, e))?;
+                Item::Instr {
+                    opcode,
+                    mode,
+                    operand: expr.to_owned(),
+                }
+            }
+        };
+        if origin.is_none() {
+            return Err(error(
+                line,
+                "the source has to start with `.org`".to_owned(),
+            ));
+        }
+        addr = match &item {
+            Item::Instr { mode, .. } => addr + 1 + u32::from(mode.operand_bytes()),
+            Item::Bytes(bytes) => addr + bytes.len() as u32,
+            Item::Words(words) => addr + 2 * words.len() as u32,
+            Item::Org(target) => u32::from(*target),
+        };
+        if addr > 0x1_0000 {
+            return Err(error(
+                line,
+                "the program runs past the end of memory".to_owned(),
+            ));
+        }
+        items.push((line, item));
+    }
+
+    let origin = u32::from(origin.unwrap_or_default());
+    let mut code = Vec::new();
+    for (line, item) in items {
+        let addr = origin + code.len() as u32;
+        let eval = |expr: &str| eval(expr, &labels).map_err(|e| error(line, e));
+        match item {
+            Item::Instr {
+                opcode,
+                mode,
+                operand,
+            } => {
+                code.push(opcode);
+                match mode {
+                    Mode::Implied | Mode::Accumulator => {}
+                    Mode::Relative => {
+                        let offset = i32::from(eval(&operand)?) - (addr as i32 + 2);
+                        let offset = i8::try_from(offset)
+                            .map_err(|_| error(line, "the branch is out of range".to_owned()))?;
+                        code.push(offset as u8);
+                    }
+                    _ if mode.operand_bytes() == 1 => {
+                        let value = eval(&operand)?;
+                        let byte = u8::try_from(value).map_err(|_| {
+                            error(line, format!("`{operand}` doesn't fit in a byte"))
+                        })?;
+                        code.push(byte);
+                    }
+                    _ => code.extend(eval(&operand)?.to_le_bytes()),
+                }
+            }
+            Item::Bytes(bytes) => {
+                for byte in bytes {
+                    let value = eval(byte)?;
+                    code.push(
+                        u8::try_from(value)
+                            .map_err(|_| error(line, format!("`{byte}` doesn't fit in a byte")))?,
+                    );
+                }
+            }
+            Item::Words(words) => {
+                for word in words {
+                    code.extend(eval(word)?.to_le_bytes());
+                }
+            }
+            Item::Org(target) => code.resize((u32::from(target) - origin) as usize, 0),
+        }
+    }
+    Ok(code)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{interp, parse, Lexer};
+
+    use super::*;
+
+    /// A 6502 with 2 KiB of RAM and PRG ROM, and just enough of the rest of the NES to get
+    /// through reset and read a controller. Writes to the PPU and APU registers are recorded.
+    /// Instructions are decoded from the fields of their opcodes rather than through `OPCODES`.
+    #[allow(clippy::struct_excessive_bools)]
+    struct Cpu {
+        a: u8,
+        x: u8,
+        y: u8,
+        s: u8,
+        pc: u16,
+        carry: bool,
+        zero: bool,
+        interrupt: bool,
+        overflow: bool,
+        negative: bool,
+        ram: [u8; 0x800],
+        prg: Vec<u8>,
+        io_writes: Vec<(u16, u8)>,
+        buttons: u8,
+        shifter: u8,
+    }
+
+    enum Operand {
+        Accumulator,
+        Value(u8),
+        Addr(u16),
+    }
+
+    impl Cpu {
+        fn new(prg: Vec<u8>) -> Self {
+            let mut cpu = Self {
+                a: 0,
+                x: 0,
+                y: 0,
+                s: 0xfd,
+                pc: 0,
+                carry: false,
+                zero: false,
+                interrupt: true,
+                overflow: false,
+                negative: false,
+                ram: [0; 0x800],
+                prg,
+                io_writes: Vec::new(),
+                buttons: 0,
+                shifter: 0,
+            };
+            cpu.pc = cpu.read_word(0xfffc);
+            cpu
+        }
+
+        fn read(&mut self, addr: u16) -> u8 {
+            match addr {
+                0..=0x1fff => self.ram[usize::from(addr) & 0x7ff],
+                // Always in vertical blank.
+                0x2002 => 0x80,
+                0x4016 => {
+                    let bit = self.shifter >> 7;
+                    self.shifter <<= 1;
+                    bit
+                }
+                0x8000.. => self.prg[usize::from(addr - 0x8000)],
+                _ => 0,
+            }
+        }
+
+        fn write(&mut self, addr: u16, value: u8) {
+            match addr {
+                0..=0x1fff => self.ram[usize::from(addr) & 0x7ff] = value,
+                0x2000..=0x401f => {
+                    if addr == 0x4016 && value & 1 == 1 {
+                        self.shifter = self.buttons;
+                    }
+                    self.io_writes.push((addr, value));
+                }
+                _ => panic!("write to ROM at {addr:#06x}"),
+            }
+        }
+
+        fn read_word(&mut self, addr: u16) -> u16 {
+            u16::from_le_bytes([self.read(addr), self.read(addr.wrapping_add(1))])
+        }
+
+        /// Read a pointer from the zero page, wrapping within it.
+        fn read_zp_word(&mut self, addr: u8) -> u16 {
+            u16::from_le_bytes([
+                self.read(addr.into()),
+                self.read(addr.wrapping_add(1).into()),
+            ])
+        }
+
+        fn fetch(&mut self) -> u8 {
+            let byte = self.read(self.pc);
+            self.pc = self.pc.wrapping_add(1);
+            byte
+        }
+
+        fn fetch_word(&mut self) -> u16 {
+            u16::from_le_bytes([self.fetch(), self.fetch()])
+        }
+
+        fn push(&mut self, value: u8) {
+            self.write(0x100 | u16::from(self.s), value);
+            self.s = self.s.wrapping_sub(1);
+        }
+
+        fn pull(&mut self) -> u8 {
+            self.s = self.s.wrapping_add(1);
+            self.read(0x100 | u16::from(self.s))
+        }
+
+        fn status(&self, brk: bool) -> u8 {
+            u8::from(self.carry)
+                | u8::from(self.zero) << 1
+                | u8::from(self.interrupt) << 2
+                | u8::from(brk) << 4
+                | 1 << 5
+                | u8::from(self.overflow) << 6
+                | u8::from(self.negative) << 7
+        }
+
+        fn set_status(&mut self, p: u8) {
+            self.carry = p & 1 != 0;
+            self.zero = p & 2 != 0;
+            self.interrupt = p & 4 != 0;
+            self.overflow = p & 0x40 != 0;
+            self.negative = p & 0x80 != 0;
+        }
+
+        fn set_nz(&mut self, value: u8) -> u8 {
+            self.zero = value == 0;
+            self.negative = value & 0x80 != 0;
+            value
+        }
+
+        fn interrupt(&mut self, vector: u16) {
+            let [lo, hi] = self.pc.to_le_bytes();
+            self.push(hi);
+            self.push(lo);
+            self.push(self.status(false));
+            self.interrupt = true;
+            self.pc = self.read_word(vector);
+        }
+
+        fn nmi(&mut self) {
+            self.interrupt(0xfffa);
+        }
+
+        fn load(&mut self, operand: &Operand) -> u8 {
+            match *operand {
+                Operand::Accumulator => self.a,
+                Operand::Value(value) => value,
+                Operand::Addr(addr) => self.read(addr),
+            }
+        }
+
+        fn store(&mut self, operand: &Operand, value: u8) {
+            match *operand {
+                Operand::Accumulator => self.a = value,
+                Operand::Value(_) => panic!("store to an immediate"),
+                Operand::Addr(addr) => self.write(addr, value),
+            }
+        }
+
+        fn add(&mut self, value: u8) {
+            let sum = u16::from(self.a) + u16::from(value) + u16::from(self.carry);
+            let [result, carry] = sum.to_le_bytes();
+            self.carry = carry != 0;
+            self.overflow = (!(self.a ^ value) & (self.a ^ result)) & 0x80 != 0;
+            self.a = self.set_nz(result);
+        }
+
+        fn compare(&mut self, register: u8, value: u8) {
+            self.carry = register >= value;
+            self.set_nz(register.wrapping_sub(value));
+        }
+
+        /// Work out the operand of an instruction from the `bbb` field of its opcode, in the
+        /// group given by its `cc` field.
+        fn operand(&mut self, bbb: u8, cc: u8, index_y: bool) -> Operand {
+            let index = if index_y { self.y } else { self.x };
+            match (cc, bbb) {
+                (1, 0) => {
+                    let zp = self.fetch().wrapping_add(self.x);
+                    Operand::Addr(self.read_zp_word(zp))
+                }
+                (1, 2) | (0 | 2, 0) => Operand::Value(self.fetch()),
+                (_, 1) => Operand::Addr(self.fetch().into()),
+                (2, 2) => Operand::Accumulator,
+                (_, 3) => Operand::Addr(self.fetch_word()),
+                (1, 4) => {
+                    let zp = self.fetch();
+                    Operand::Addr(self.read_zp_word(zp).wrapping_add(self.y.into()))
+                }
+                (1, 5) => Operand::Addr(self.fetch().wrapping_add(self.x).into()),
+                (_, 5) => Operand::Addr(self.fetch().wrapping_add(index).into()),
+                (1, 6) => Operand::Addr(self.fetch_word().wrapping_add(self.y.into())),
+                (1, 7) => Operand::Addr(self.fetch_word().wrapping_add(self.x.into())),
+                (_, 7) => Operand::Addr(self.fetch_word().wrapping_add(index.into())),
+                _ => panic!("illegal addressing mode {bbb} in group {cc}"),
+            }
+        }
+
+        #[allow(clippy::too_many_lines)]
+        fn step(&mut self) {
+            let opcode = self.fetch();
+            match opcode {
+                0x20 => {
+                    let target = self.fetch_word();
+                    let [lo, hi] = self.pc.wrapping_sub(1).to_le_bytes();
+                    self.push(hi);
+                    self.push(lo);
+                    self.pc = target;
+                }
+                0x40 => {
+                    let p = self.pull();
+                    self.set_status(p);
+                    self.pc = u16::from_le_bytes([self.pull(), self.pull()]);
+                }
+                0x60 => {
+                    self.pc = u16::from_le_bytes([self.pull(), self.pull()]).wrapping_add(1);
+                }
+                0x08 => self.push(self.status(true)),
+                0x28 => {
+                    let p = self.pull();
+                    self.set_status(p);
+                }
+                0x48 => self.push(self.a),
+                0x68 => {
+                    let a = self.pull();
+                    self.a = self.set_nz(a);
+                }
+                0x88 => self.y = self.set_nz(self.y.wrapping_sub(1)),
+                0xa8 => self.y = self.set_nz(self.a),
+                0xc8 => self.y = self.set_nz(self.y.wrapping_add(1)),
+                0xe8 => self.x = self.set_nz(self.x.wrapping_add(1)),
+                0x18 => self.carry = false,
+                0x38 => self.carry = true,
+                0x58 => self.interrupt = false,
+                0x78 => self.interrupt = true,
+                0x98 => self.a = self.set_nz(self.y),
+                0xb8 => self.overflow = false,
+                0xd8 | 0xf8 | 0xea => {}
+                0x8a => self.a = self.set_nz(self.x),
+                0x9a => self.s = self.x,
+                0xaa => self.x = self.set_nz(self.a),
+                0xba => self.x = self.set_nz(self.s),
+                0xca => self.x = self.set_nz(self.x.wrapping_sub(1)),
+                0x4c => self.pc = self.fetch_word(),
+                0x6c => {
+                    let addr = self.fetch_word();
+                    self.pc = self.read_word(addr);
+                }
+                _ if opcode & 0x1f == 0x10 => {
+                    let offset = i8::from_ne_bytes([self.fetch()]);
+                    let flag = match opcode >> 6 {
+                        0 => self.negative,
+                        1 => self.overflow,
+                        2 => self.carry,
+                        _ => self.zero,
+                    };
+                    if flag == (opcode & 0x20 != 0) {
+                        self.pc = self.pc.wrapping_add_signed(offset.into());
+                    }
+                }
+                _ => {
+                    let (aaa, bbb, cc) = (opcode >> 5, opcode >> 2 & 7, opcode & 3);
+                    // `STX` and `LDX` index by `Y` instead.
+                    let operand = self.operand(bbb, cc, cc == 2 && matches!(aaa, 4 | 5));
+                    // Stores don't read their operand, which matters for the controller port.
+                    let value = if aaa == 4 { 0 } else { self.load(&operand) };
+                    match (cc, aaa) {
+                        (1, 0) => self.a = self.set_nz(self.a | value),
+                        (1, 1) => self.a = self.set_nz(self.a & value),
+                        (1, 2) => self.a = self.set_nz(self.a ^ value),
+                        (1, 3) => self.add(value),
+                        (1, 4) => self.store(&operand, self.a),
+                        (1, 5) => self.a = self.set_nz(value),
+                        (1, 6) => self.compare(self.a, value),
+                        (1, 7) => self.add(!value),
+                        (2, 0..=3) => {
+                            let carry_in = u8::from(self.carry);
+                            let (result, carry) = match aaa {
+                                0 => (value << 1, value >> 7),
+                                1 => (value << 1 | carry_in, value >> 7),
+                                2 => (value >> 1, value & 1),
+                                _ => (value >> 1 | carry_in << 7, value & 1),
+                            };
+                            self.carry = carry == 1;
+                            let result = self.set_nz(result);
+                            self.store(&operand, result);
+                        }
+                        (2, 4) => self.store(&operand, self.x),
+                        (2, 5) => self.x = self.set_nz(value),
+                        (2, 6) => {
+                            let result = self.set_nz(value.wrapping_sub(1));
+                            self.store(&operand, result);
+                        }
+                        (2, 7) => {
+                            let result = self.set_nz(value.wrapping_add(1));
+                            self.store(&operand, result);
+                        }
+                        (0, 1) => {
+                            self.zero = self.a & value == 0;
+                            self.overflow = value & 0x40 != 0;
+                            self.negative = value & 0x80 != 0;
+                        }
+                        (0, 4) => self.store(&operand, self.y),
+                        (0, 5) => self.y = self.set_nz(value),
+                        (0, 6) => self.compare(self.y, value),
+                        (0, 7) => self.compare(self.x, value),
+                        _ => panic!("illegal opcode {opcode:#04x}"),
+                    }
+                }
+            }
+        }
+
+        /// Run until the program halts in the loop after `main`, raising an NMI every
+        /// `nmi_period` instructions if given, once they are enabled in `PPUCTRL`.
+        fn run(&mut self, nmi_period: Option<usize>) {
+            for steps in 1..10_000_000 {
+                let pc = self.pc;
+                if self.read(pc) == 0x4c && self.read_word(pc.wrapping_add(1)) == pc {
+                    return;
+                }
+                self.step();
+                let enabled = self
+                    .io_writes
+                    .iter()
+                    .rev()
+                    .find(|(addr, _)| *addr == 0x2000);
+                if nmi_period.is_some_and(|period| steps % period == 0)
+                    && enabled.is_some_and(|(_, ctrl)| ctrl & 0x80 != 0)
+                {
+                    self.nmi();
+                }
+            }
+            panic!("the program didn't halt");
+        }
+
+        /// The cells on the data stack, from the bottom up.
+        fn stack(&self) -> Vec<u64> {
+            (usize::from(self.x)..0x100)
+                .step_by(2)
+                .rev()
+                .map(|addr| u64::from(u16::from_le_bytes([self.ram[addr], self.ram[addr + 1]])))
+                .collect()
+        }
+    }
+
+    /// Compile `source` along with the prelude and run it with `buttons` held on the first
+    /// controller.
+    fn run(source: &str, buttons: u8, nmi_period: Option<usize>) -> Cpu {
+        let mut program = Program::default();
+        program.ctx.cell_width = Nesulator.cell_width();
+        program.ctx.arch = Nesulator.arch();
+        parse::parse_prelude(&mut Lexer::new(PRELUDE, None), &mut program).unwrap();
+        // The lexer needs whitespace after the last token.
+        let source = format!("{source}\n");
+        parse::parse_items(&mut Lexer::new(&source, None), &mut program).unwrap();
+        let mut cpu = Cpu::new(generate_prg(&program).unwrap());
+        cpu.buttons = buttons;
+        cpu.run(nmi_period);
+        cpu
+    }
+
+    /// The data stack that `source` leaves on the evaluator.
+    fn evaluate(source: &str) -> Vec<u64> {
+        let source = format!("{source}\n");
+        let program =
+            parse::parse_tokens_for(&mut Lexer::new(&source, None), Nesulator.cell_width())
+                .unwrap();
+        let mut machine = interp::Machine::new(&program);
+        machine.enter_main(&program).unwrap();
+        machine.run(&mut Vec::new()).unwrap();
+        machine.stack
+    }
+
+    #[test]
+    fn matches_evaluator() {
+        let sources = [
+            "fn main in 40 2 + 7 9 - 0 1 - 1 + 65535 1 + end",
+            "fn main in 17 5 divmod 65535 300 divmod 50000 40000 divmod 7 32769 divmod end",
+            "fn main in 3 5 < 5 3 < 40000 300 > 300 40000 > 2 2 = 2 3 = 2 3 != 258 2 != end",
+            "fn main in 1 0 or 0 0 or 2 1 or 1 1 and 1 2 and 0 not 1 not 5 not true false end",
+            "fn main in 1 2 swap 3 over 4 dup drop end",
+            "fn main in 1 2 3 let a b c in c b a peek x in x a end end end",
+            "fn fib in let n in n 2 < if n ret end n 1 - fib n 2 - fib + end end\n\
+             fn main in 20 fib end",
+            "fn main in 0 0 while dup 100 < do 1 + swap over + swap end drop end",
+            "fn main in \"hi\" swap drop @ \"hi\" swap drop 1 + @ end",
+            "fn main in 0 while dup 300 < do 1 + let i in i 7 divmod + drop i end end end",
+        ];
+        for source in sources {
+            assert_eq!(run(source, 0, None).stack(), evaluate(source), "{source}");
+        }
+    }
+
+    #[test]
+    fn prelude() {
+        let cpu = run(
+            "fn main in
+  0x3f00 ppu_address 0x0f ppu_data poke \"AB\" ppu_copy
+  0 joypad dup button_a pressed swap button_b pressed
+end",
+            0x80,
+            None,
+        );
+        let ppu_writes: Vec<_> = cpu
+            .io_writes
+            .iter()
+            .filter(|(addr, _)| matches!(addr, 0x2006 | 0x2007))
+            .copied()
+            .collect();
+        assert_eq!(
+            ppu_writes,
+            [
+                (0x2006, 0x3f),
+                (0x2006, 0x00),
+                (0x2007, 0x0f),
+                (0x2007, b'A'),
+                (0x2007, b'B')
+            ]
+        );
+        assert_eq!(cpu.stack(), [1, 0]);
+    }
+
+    #[test]
+    fn interrupts() {
+        // The handler uses the scratch bytes and the binding stack while `main` is using them.
+        let sum = "fn sum in
+  0 0 while dup 300 < do
+    1 + let i in i 7 divmod + + i end
+  end
+end";
+        let source = format!(
+            "fn nmi in 0x500 @ 1 + 0x500 poke 12345 100 divmod let q r in r q end drop drop end
+{sum}
+fn main in 0x80 ppu_ctrl poke sum end"
+        );
+        let cpu = run(&source, 0, Some(1000));
+        assert_eq!(cpu.stack(), evaluate(&format!("{sum}\nfn main in sum end")));
+        assert!(cpu.ram[0x500] > 0);
+    }
+
+    #[test]
+    fn assembler() {
+        let code = assemble(
+            "\t.org $8000
+start:
+\tLDA #$10
+\tSTA $2000
+\tLDA (0,X)
+\tLDA ($10), Y
+\tJMP ($1234)
+\tASL A
+\tLDX $10,Y
+\tSTA $0200,X
+\tBNE start ; back to the start
+\t.byte 1, <start, >start
+\t.word start+2
+",
+        )
+        .unwrap();
+        assert_eq!(
+            code,
+            [
+                0xa9, 0x10, 0x8d, 0x00, 0x20, 0xa1, 0x00, 0xb1, 0x10, 0x6c, 0x34, 0x12, 0x0a, 0xb6,
+                0x10, 0x9d, 0x00, 0x02, 0xd0, 0xec, 0x01, 0x00, 0x80, 0x02, 0x80
+            ]
+        );
+        for (source, message) in [
+            ("\t.org $8000\n\tJMP nowhere\n", "unknown label `nowhere`"),
+            ("\t.org $8000\n\tSTA #1\n", "`STA` can't take this operand"),
+            ("\t.org $8000\n\tFOO\n", "unknown instruction `FOO`"),
+            (
+                "\t.org $8000\nstart:\n\t.org $8100\n\tBNE start\n",
+                "the branch is out of range",
+            ),
+        ] {
+            assert_eq!(assemble(source).unwrap_err().message, message);
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use std::env;
+
+use super::*;
+
+/// The number of cells in the data stack, which is 8 MiB like the default native stack.
+const STACK_CELLS: usize = 1 << 20;
+/// The number of cells in the return stack, the same as the 64 KiB of the native targets.
+const RET_STACK_CELLS: usize = 65536 / 8;
+
+/// The runtime. Both stacks are zeroed data, which QBE puts in `.bss`, and `$sp` and `$rsp` hold
+/// the addresses of their tops. The data stack grows up and `$sp` points past its top, while the
+/// return stack grows down and `$rsp` points at its top.
+const RUNTIME: &str = "\
+data $stack = align 8 { z STACK_BYTES }
+data $ret = align 8 { z RET_STACK_BYTES }
+data $sp = { l $stack }
+data $rsp = { l $ret + RET_STACK_BYTES }
+
+# Write the decimal digits of `x` backwards from the end of a buffer.
+function $print(l %x) {
+@start
+\t%buf =l alloc8 24
+\t%end =l add %buf, 20
+\tstoreb 10, %end
+\t%p =l copy %end
+\t%n =l copy %x
+@loop
+\t%p =l sub %p, 1
+\t%r =l urem %n, 10
+\t%c =l add %r, 48
+\tstoreb %c, %p
+\t%n =l udiv %n, 10
+\t%more =w cnel %n, 0
+\tjnz %more, @loop, @done
+@done
+\t%len =l sub %end, %p
+\t%len =l add %len, 1
+\tcall $write(w 1, l %p, l %len)
+\tret
+}
+";
+
+/// Writes the body of a function, numbering the temporaries and labels.
+struct Writer<'a> {
+    buffer: &'a mut String,
+    temps: usize,
+    labels: usize,
+}
+
+impl Writer<'_> {
+    /// A new temporary.
+    fn temp(&mut self) -> String {
+        self.temps += 1;
+        format!("%t{}", self.temps)
+    }
+
+    /// A new label, with a suffix to make the output easier to follow.
+    fn label(&mut self, suffix: &str) -> String {
+        self.labels += 1;
+        format!("@L{}.{suffix}", self.labels)
+    }
+
+    fn push(&mut self, val: &str) -> fmt::Result {
+        let sp = self.temp();
+        let new_sp = self.temp();
+        writeln!(
+            self.buffer,
+            "\t{sp} =l loadl $sp\n\tstorel {val}, {sp}\n\t{new_sp} =l add {sp}, 8\n\tstorel {new_sp}, $sp"
+        )
+    }
+
+    fn pop(&mut self) -> Result<String, fmt::Error> {
+        let sp = self.temp();
+        let new_sp = self.temp();
+        let val = self.temp();
+        writeln!(
+            self.buffer,
+            "\t{sp} =l loadl $sp\n\t{new_sp} =l sub {sp}, 8\n\tstorel {new_sp}, $sp\n\t{val} =l loadl {new_sp}"
+        )?;
+        Ok(val)
+    }
+
+    /// Load the value `depth` cells below the top of the data stack.
+    fn peek(&mut self, depth: usize) -> Result<String, fmt::Error> {
+        let sp = self.temp();
+        let addr = self.temp();
+        let val = self.temp();
+        writeln!(
+            self.buffer,
+            "\t{sp} =l loadl $sp\n\t{addr} =l sub {sp}, {}\n\t{val} =l loadl {addr}",
+            (depth + 1) * 8
+        )?;
+        Ok(val)
+    }
+
+    /// Move the pointer in `global` by `bytes`.
+    fn move_stack(&mut self, global: &str, bytes: i64) -> fmt::Result {
+        let old = self.temp();
+        let new = self.temp();
+        writeln!(
+            self.buffer,
+            "\t{old} =l loadl {global}\n\t{new} =l add {old}, {bytes}\n\tstorel {new}, {global}"
+        )
+    }
+
+    /// Pop the top two values and push the result of `instr` on the second and the top.
+    fn binary(&mut self, instr: &str) -> fmt::Result {
+        let b = self.pop()?;
+        let a = self.pop()?;
+        let result = self.temp();
+        writeln!(self.buffer, "\t{result} =l {instr} {a}, {b}")?;
+        self.push(&result)
+    }
+
+    /// Pop a value and check whether it is true.
+    fn pop_bool(&mut self) -> Result<String, fmt::Error> {
+        let val = self.pop()?;
+        let flag = self.temp();
+        writeln!(self.buffer, "\t{flag} =w ceql {val}, 1")?;
+        Ok(flag)
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn write_op(&mut self, op: &Op) -> fmt::Result {
+        match op {
+            Op::CallFn(index) => writeln!(self.buffer, "\tcall $fn_{index}()")?,
+            Op::Ret(count) => {
+                if *count > 0 {
+                    self.move_stack("$rsp", bytes(*count))?;
+                }
+                // Anything after an early return is unreachable, but it still needs a block.
+                let dead = self.label("dead");
+                writeln!(self.buffer, "\tret\n{dead}")?;
+            }
+            Op::Bind {
+                count,
+                names,
+                peek,
+                body,
+            } => {
+                writeln!(self.buffer, "\t# let {}", names.join(" "))?;
+                self.move_stack("$rsp", -bytes(*count))?;
+                let rsp = self.temp();
+                writeln!(self.buffer, "\t{rsp} =l loadl $rsp")?;
+                for i in 0..*count {
+                    let val = self.peek(i)?;
+                    let addr = self.temp();
+                    writeln!(
+                        self.buffer,
+                        "\t{addr} =l add {rsp}, {}\n\tstorel {val}, {addr}",
+                        i * 8
+                    )?;
+                }
+                if !peek {
+                    self.move_stack("$sp", -bytes(*count))?;
+                }
+                for op in body {
+                    self.write_op(op)?;
+                }
+                // Remove the bindings from the return stack.
+                self.move_stack("$rsp", bytes(*count))?;
+            }
+            Op::PushBind(index) => {
+                let rsp = self.temp();
+                let addr = self.temp();
+                let val = self.temp();
+                writeln!(
+                    self.buffer,
+                    "\t{rsp} =l loadl $rsp\n\t{addr} =l add {rsp}, {}\n\t{val} =l loadl {addr}",
+                    index * 8
+                )?;
+                self.push(&val)?;
+            }
+            Op::PushInt(val) => self.push(&val.to_string())?,
+            Op::PushStrPtr(index) => self.push(&format!("$str_{index}"))?,
+            Op::Plus => self.binary("add")?,
+            Op::Minus => self.binary("sub")?,
+            Op::DivMod => {
+                let b = self.pop()?;
+                let a = self.pop()?;
+                let quotient = self.temp();
+                let remainder = self.temp();
+                writeln!(
+                    self.buffer,
+                    "\t{quotient} =l udiv {a}, {b}\n\t{remainder} =l urem {a}, {b}"
+                )?;
+                self.push(&quotient)?;
+                self.push(&remainder)?;
+            }
+            Op::Dup => {
+                let val = self.peek(0)?;
+                self.push(&val)?;
+            }
+            Op::Drop => self.move_stack("$sp", -8)?,
+            Op::Swap => {
+                let b = self.pop()?;
+                let a = self.pop()?;
+                self.push(&b)?;
+                self.push(&a)?;
+            }
+            Op::Over => {
+                let val = self.peek(1)?;
+                self.push(&val)?;
+            }
+            Op::Equals => self.binary("ceql")?,
+            Op::Neq => self.binary("cnel")?,
+            Op::GreaterThan => self.binary("cugtl")?,
+            Op::LessThan => self.binary("cultl")?,
+            Op::Not => {
+                let val = self.pop()?;
+                let result = self.temp();
+                writeln!(self.buffer, "\t{result} =l sub 1, {val}")?;
+                self.push(&result)?;
+            }
+            Op::Or | Op::And => {
+                let b = self.pop()?;
+                let a = self.pop()?;
+                let b_flag = self.temp();
+                let a_flag = self.temp();
+                let result = self.temp();
+                let instr = if matches!(op, Op::Or) { "or" } else { "and" };
+                writeln!(
+                    self.buffer,
+                    "\t{b_flag} =l ceql {b}, 1\n\t{a_flag} =l ceql {a}, 1\n\t{result} =l {instr} {a_flag}, {b_flag}"
+                )?;
+                self.push(&result)?;
+            }
+            Op::ReadByte => {
+                let addr = self.pop()?;
+                let result = self.temp();
+                writeln!(self.buffer, "\t{result} =l loadub {addr}")?;
+                self.push(&result)?;
+            }
+            Op::If(ops) => {
+                let flag = self.pop_bool()?;
+                let then = self.label("then");
+                let end = self.label("end");
+                writeln!(self.buffer, "\tjnz {flag}, {then}, {end}\n{then}")?;
+                for op in ops {
+                    self.write_op(op)?;
+                }
+                writeln!(self.buffer, "{end}")?;
+            }
+            Op::While { condn, body } => {
+                let condn_label = self.label("while");
+                let body_label = self.label("do");
+                let end = self.label("end");
+                writeln!(self.buffer, "{condn_label}")?;
+                for op in condn {
+                    self.write_op(op)?;
+                }
+                let flag = self.pop_bool()?;
+                writeln!(
+                    self.buffer,
+                    "\tjnz {flag}, {body_label}, {end}\n{body_label}"
+                )?;
+                for op in body {
+                    self.write_op(op)?;
+                }
+                writeln!(self.buffer, "\tjmp {condn_label}\n{end}")?;
+            }
+            Op::Print => {
+                let val = self.pop()?;
+                writeln!(self.buffer, "\tcall $print(l {val})")?;
+            }
+            Op::Puts => {
+                let addr = self.pop()?;
+                let len = self.pop()?;
+                writeln!(self.buffer, "\tcall $write(w 1, l {addr}, l {len})")?;
+            }
+            Op::Asm { .. } => unreachable!("inline assembly isn't parsed for this target"),
+        }
+        Ok(())
+    }
+}
+
+/// The size of `count` cells in bytes, which is never large enough to wrap.
+fn bytes(count: usize) -> i64 {
+    i64::try_from(count * 8).expect("the stacks fit in memory")
+}
+
+pub fn generate(program: Program) -> Result<String, fmt::Error> {
+    let mut outbuf = RUNTIME
+        .replace("RET_STACK_BYTES", &(RET_STACK_CELLS * 8).to_string())
+        .replace("STACK_BYTES", &(STACK_CELLS * 8).to_string());
+
+    for (i, s) in program.ctx.strings.iter().enumerate() {
+        write!(outbuf, "\ndata $str_{i} = {{ b")?;
+        for b in s.as_bytes() {
+            write!(outbuf, " {b}")?;
+        }
+        // The data can't be empty, so end every string with a zero.
+        write!(outbuf, " 0 }}")?;
+    }
+    if !program.ctx.strings.is_empty() {
+        outbuf.push('\n');
+    }
+
+    for (i, func) in program.funcs.iter().enumerate() {
+        writeln!(outbuf, "\n# {}\nfunction $fn_{i}() {{\n@start", func.ident)?;
+        let mut writer = Writer {
+            buffer: &mut outbuf,
+            temps: 0,
+            labels: 0,
+        };
+        for op in &func.body {
+            writer.write_op(op)?;
+        }
+        writeln!(outbuf, "\tret\n}}")?;
+    }
+
+    writeln!(
+        outbuf,
+        "\nexport function w $main() {{\n@start\n\tcall $fn_{}()\n\tret 0\n}}",
+        program.ctx.lookup["main"]
+    )?;
+    Ok(outbuf)
+}
+
+/// QBE IL, compiled to assembly for the host with `$QBE` (or `qbe`) and then assembled and linked
+/// with `$CC` (or `cc`).
+pub struct Qbe;
+
+impl Backend for Qbe {
+    fn name(&self) -> &'static str {
+        "qbe"
+    }
+
+    fn generate(&self, program: Program) -> Result<Artifact, Box<dyn Error>> {
+        Ok(Artifact::Text(generate(program)?))
+    }
+
+    fn intermediate(&self) -> Option<&'static str> {
+        Some("ssa")
+    }
+
+    fn build(&self, artifact: &Path, out: &Path) -> Vec<Command> {
+        let asm = artifact.with_extension("s");
+        let mut qbe = Command::new(env::var("QBE").unwrap_or_else(|_| "qbe".to_owned()));
+        qbe.arg("-o").arg(&asm).arg(artifact);
+        let mut cc = Command::new(env::var("CC").unwrap_or_else(|_| "cc".to_owned()));
+        cc.arg("-o").arg(out).arg(&asm);
+        vec![qbe, cc]
+    }
+
+    fn run(&self, out: &Path) -> Option<Command> {
+        Some(Command::new(Path::new(".").join(out)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::codegen::test::{check_runs, check_snapshots};
+
+    use super::*;
+
+    #[test]
+    fn snapshots() {
+        check_snapshots(&Qbe);
+    }
+
+    #[test]
+    fn run() {
+        check_runs(&Qbe);
+    }
+}
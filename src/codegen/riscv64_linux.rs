@@ -0,0 +1,442 @@
+use super::*;
+
+/// The size of the data stack, which is the same as the default native stack on x86-64.
+const DATA_STACK_BYTES: usize = 8 << 20;
+
+/// The register holding the data stack pointer, which is callee-saved so that it survives the
+/// calls to `print`.
+const DSP: &str = "s1";
+
+/// Apply `op`, `add` or `sub`, to `reg` and `imm`. Only 12-bit signed immediates can be encoded
+/// in `addi`, so larger values go through `t3`.
+fn add_imm(buffer: &mut String, op: &str, reg: &str, imm: usize) -> std::fmt::Result {
+    match (op, imm) {
+        ("add", 0..=2047) => writeln!(buffer, "\taddi\t{reg}, {reg}, {imm}"),
+        ("sub", 0..=2048) => writeln!(buffer, "\taddi\t{reg}, {reg}, -{imm}"),
+        _ => write!(
+            buffer,
+            "\tli\tt3, {imm}
+\t{op}\t{reg}, {reg}, t3
+"
+        ),
+    }
+}
+
+/// Grow (`sub`) or shrink (`add`) the return stack by `bytes`, leaving the new return stack
+/// pointer in `t1`.
+fn move_ret_stack(buffer: &mut String, op: &str, bytes: usize) -> std::fmt::Result {
+    write!(
+        buffer,
+        "\tlla\tt0, ret_stack_rsp
+\tld\tt1, 0(t0)
+"
+    )?;
+    add_imm(buffer, op, "t1", bytes)?;
+    writeln!(buffer, "\tsd\tt1, 0(t0)")
+}
+
+/// Pop the top two values into `a1` (the top) and `a0`, apply `body` and push `a0`.
+fn write_binary(buffer: &mut String, name: &str, body: &str) -> std::fmt::Result {
+    write!(
+        buffer,
+        "\tPOP\ta1\t\t\t# Op::{name}
+\tPOP\ta0
+{body}\tPUSH\ta0
+"
+    )
+}
+
+#[allow(clippy::too_many_lines)]
+fn write_op(
+    op: &Op,
+    count_ops: &mut usize,
+    buffer: &mut String,
+    ctx: &Context,
+) -> std::fmt::Result {
+    match op {
+        Op::CallFn(index) => {
+            writeln!(buffer, "\t\t\t\t\t# Op::CallFn({index})")?;
+            move_ret_stack(buffer, "sub", 8)?;
+            write!(
+                buffer,
+                "\tlla\tt2, RET{count_ops}
+\tsd\tt2, 0(t1)
+\tj\tfn_{func}
+RET{count_ops}:
+",
+                func = ctx
+                    .lookup
+                    .iter()
+                    .find(|(_, v)| *v == index)
+                    .expect("the index was taken from the same context lookup")
+                    .0
+            )?;
+            move_ret_stack(buffer, "add", 8)?;
+        }
+        Op::Ret(count) => {
+            writeln!(buffer, "\t\t\t\t\t# Op::Ret({count})")?;
+            move_ret_stack(buffer, "add", count * 8)?;
+            write!(
+                buffer,
+                "\tld\tt2, 0(t1)
+\tjr\tt2
+"
+            )?;
+        }
+        Op::Bind {
+            count, peek, body, ..
+        } => {
+            writeln!(buffer, "\t\t\t\t\t# Op::Bind({count})")?;
+            move_ret_stack(buffer, "sub", count * 8)?;
+            for i in 0..*count {
+                write!(
+                    buffer,
+                    "\tld\tt2, {0}({DSP})
+\tsd\tt2, {0}(t1)
+",
+                    i * 8
+                )?;
+            }
+            if !peek {
+                add_imm(buffer, "add", DSP, count * 8)?;
+            }
+            for op in body {
+                write_op(op, count_ops, buffer, ctx)?;
+            }
+            // Remove the bindings from the return stack.
+            move_ret_stack(buffer, "add", count * 8)?;
+        }
+        Op::PushBind(index) => write!(
+            buffer,
+            "\tlla\tt0, ret_stack_rsp\t\t# Op::PushBind({index})
+\tld\tt1, 0(t0)
+\tld\tt2, {}(t1)
+\tPUSH\tt2
+",
+            index * 8
+        )?,
+        // `li` takes a signed immediate, but any 64-bit pattern can be loaded.
+        #[allow(clippy::cast_possible_wrap)]
+        Op::PushInt(val) => write!(
+            buffer,
+            "\tli\ta0, {}\t\t# Op::PushInt({val})
+\tPUSH\ta0
+",
+            *val as i64
+        )?,
+        Op::PushStrPtr(index) => write!(
+            buffer,
+            "\tlla\ta0, str_{index}\t\t# Op::PushStrPtr({index})
+\tPUSH\ta0
+"
+        )?,
+        Op::Plus => write_binary(buffer, "Plus", "\tadd\ta0, a0, a1\n")?,
+        Op::Minus => write_binary(buffer, "Minus", "\tsub\ta0, a0, a1\n")?,
+        Op::DivMod => write!(
+            buffer,
+            "\tPOP\ta1\t\t\t# Op::DivMod
+\tPOP\ta0
+\tdivu\ta2, a0, a1
+\tremu\ta3, a0, a1
+\tPUSH\ta2
+\tPUSH\ta3
+",
+        )?,
+        Op::Dup => write!(
+            buffer,
+            "\tld\ta0, 0({DSP})\t\t# Op::Dup
+\tPUSH\ta0
+"
+        )?,
+        Op::Drop => writeln!(buffer, "\taddi\t{DSP}, {DSP}, 8\t\t# Op::Drop")?,
+        Op::Swap => write!(
+            buffer,
+            "\tPOP\ta1\t\t\t# Op::Swap
+\tPOP\ta0
+\tPUSH\ta1
+\tPUSH\ta0
+",
+        )?,
+        Op::Over => write!(
+            buffer,
+            "\tld\ta0, 8({DSP})\t\t# Op::Over
+\tPUSH\ta0
+"
+        )?,
+        // The comparisons don't need labels, but they still count them so that the labels of the
+        // rest of the program are numbered as on x86-64.
+        Op::Equals => {
+            write_binary(buffer, "Equals", "\tsub\ta0, a0, a1\n\tseqz\ta0, a0\n")?;
+            *count_ops += 1;
+        }
+        Op::Neq => {
+            write_binary(buffer, "Neq", "\tsub\ta0, a0, a1\n\tsnez\ta0, a0\n")?;
+            *count_ops += 1;
+        }
+        Op::GreaterThan => {
+            write_binary(buffer, "GreaterThan", "\tsltu\ta0, a1, a0\n")?;
+            *count_ops += 1;
+        }
+        Op::LessThan => {
+            write_binary(buffer, "LessThan", "\tsltu\ta0, a0, a1\n")?;
+            *count_ops += 1;
+        }
+        Op::Not => write!(
+            buffer,
+            "\tPOP\ta0\t\t\t# Op::Not
+\tli\ta1, 1
+\tsub\ta0, a1, a0
+\tPUSH\ta0
+"
+        )?,
+        Op::Or | Op::And => {
+            // Both are found by checking each value for 1.
+            let (name, combine) = match op {
+                Op::Or => ("Or", "or"),
+                _ => ("And", "and"),
+            };
+            write_binary(
+                buffer,
+                name,
+                &format!(
+                    "\taddi\ta0, a0, -1
+\tseqz\ta0, a0
+\taddi\ta1, a1, -1
+\tseqz\ta1, a1
+\t{combine}\ta0, a0, a1
+"
+                ),
+            )?;
+            *count_ops += 1;
+        }
+        Op::ReadByte => write!(
+            buffer,
+            "\tPOP\ta0\t\t\t# Op::ReadByte
+\tlbu\ta0, 0(a0)
+\tPUSH\ta0
+"
+        )?,
+        Op::If(ops) => {
+            let jump_to = *count_ops;
+            *count_ops += 1;
+            // Conditional branches only reach 4 KiB either way, so they skip over a jump instead.
+            write!(
+                buffer,
+                "\tPOP\ta0\t\t\t# Op::If
+\tli\ta1, 1
+\tbeq\ta0, a1, T{jump_to}
+\tj\tF{jump_to}
+T{jump_to}:
+"
+            )?;
+            for op in ops {
+                write_op(op, count_ops, buffer, ctx)?;
+            }
+            writeln!(buffer, "F{jump_to}:")?;
+        }
+        Op::While { condn, body } => {
+            let condn_jump = *count_ops;
+            let end_jump = *count_ops + 1;
+            *count_ops += 2;
+            writeln!(buffer, "F{condn_jump}:\t\t\t\t\t# Op::While")?;
+            for op in condn {
+                write_op(op, count_ops, buffer, ctx)?;
+            }
+            // Check the while condition and jump to end if not met.
+            write!(
+                buffer,
+                "\tPOP\ta0
+\tli\ta1, 1
+\tbeq\ta0, a1, T{end_jump}
+\tj\tF{end_jump}
+T{end_jump}:
+"
+            )?;
+            for op in body {
+                write_op(op, count_ops, buffer, ctx)?;
+            }
+            writeln!(buffer, "\tj\tF{condn_jump}\nF{end_jump}:")?;
+        }
+        Op::Print => write!(buffer, "\tPOP\ta0\t\t\t# Op::Print\n\tcall\tprint\n")?,
+        Op::Puts => write!(
+            buffer,
+            "\tPOP\ta1\t\t\t# Op::Puts
+\tPOP\ta2
+\tli\ta0, 1
+\tli\ta7, 64
+\tecall
+"
+        )?,
+        Op::Asm { signature, lines } => {
+            writeln!(
+                buffer,
+                "\t\t\t\t\t# Op::Asm {} -- {}",
+                signature.inputs, signature.outputs
+            )?;
+            for line in lines {
+                writeln!(buffer, "\t{line}")?;
+            }
+        }
+    }
+    *count_ops += 1;
+    Ok(())
+}
+
+pub fn generate(program: Program) -> Result<String, std::fmt::Error> {
+    let mut outbuf = format!(
+        "\t.macro\tPUSH reg
+\taddi\t{DSP}, {DSP}, -8
+\tsd\t\\reg, 0({DSP})
+\t.endm
+\t.macro\tPOP reg
+\tld\t\\reg, 0({DSP})
+\taddi\t{DSP}, {DSP}, 8
+\t.endm
+
+\t.globl\tmain
+\t.text
+"
+    );
+
+    // Write the decimal digits of a0 backwards from the end of a buffer on the stack.
+    outbuf += "print:
+\taddi\tsp, sp, -32
+\tli\tt0, 10
+\tsb\tt0, 31(sp)
+\taddi\ta1, sp, 31
+.L2:
+\tremu\tt1, a0, t0
+\tdivu\ta0, a0, t0
+\taddi\tt1, t1, 48
+\taddi\ta1, a1, -1
+\tsb\tt1, 0(a1)
+\tbnez\ta0, .L2
+\taddi\ta2, sp, 32
+\tsub\ta2, a2, a1
+\tli\ta0, 1
+\tli\ta7, 64
+\tecall
+\taddi\tsp, sp, 32
+\tret
+";
+
+    let mut count_ops = 0;
+
+    for func in program.funcs.iter() {
+        writeln!(outbuf, "fn_{}:", func.ident)?;
+        for op in &func.body {
+            write_op(op, &mut count_ops, &mut outbuf, &program.ctx)?;
+        }
+    }
+
+    write!(
+        outbuf,
+        "main:
+\tlla\t{DSP}, data_stack_end
+\tlla\tt1, ret_stack_end
+\taddi\tt1, t1, -8
+\tlla\tt0, ret_stack_rsp
+\tsd\tt1, 0(t0)
+\tlla\tt2, RET_MAIN
+\tsd\tt2, 0(t1)
+\tj\tfn_main
+RET_MAIN:
+\tli\ta0, 0
+\tli\ta7, 93
+\tecall
+\t.section\t.rodata
+"
+    )?;
+    for (i, s) in program.ctx.strings.iter().enumerate() {
+        let mut s_bytes = String::new();
+        for b in s.as_bytes() {
+            write!(&mut s_bytes, "{b},").unwrap();
+        }
+        match s_bytes.trim_end_matches(',') {
+            "" => writeln!(outbuf, "str_{i}:")?,
+            bytes => writeln!(outbuf, "str_{i}:\t.byte\t{bytes}")?,
+        }
+    }
+
+    write!(
+        outbuf,
+        "\t.bss
+\t.balign\t8
+ret_stack_rsp:\t.skip\t8
+ret_stack:\t.skip\t65536
+ret_stack_end:
+data_stack:\t.skip\t{DATA_STACK_BYTES}
+data_stack_end:
+"
+    )?;
+    Ok(outbuf)
+}
+
+/// The prefix of the binutils used to build for 64-bit RISC-V, which is only needed when cross
+/// compiling.
+fn binutils_prefix() -> &'static str {
+    if cfg!(target_arch = "riscv64") {
+        ""
+    } else {
+        "riscv64-linux-gnu-"
+    }
+}
+
+/// An ELF executable for RV64IM, assembled with GNU as and linked with `ld`.
+pub struct Executable;
+
+impl Backend for Executable {
+    fn name(&self) -> &'static str {
+        "riscv64-linux"
+    }
+
+    fn arch(&self) -> Option<&'static str> {
+        Some("riscv64")
+    }
+
+    fn generate(&self, program: Program) -> Result<Artifact, Box<dyn Error>> {
+        Ok(Artifact::Text(generate(program)?))
+    }
+
+    fn intermediate(&self) -> Option<&'static str> {
+        Some("s")
+    }
+
+    fn build(&self, artifact: &Path, out: &Path) -> Vec<Command> {
+        let object = out.with_extension("o");
+        let mut as_ = Command::new(format!("{}as", binutils_prefix()));
+        as_.args(["-march=rv64im", "-o"]).arg(&object).arg(artifact);
+        let mut ld = Command::new(format!("{}ld", binutils_prefix()));
+        ld.args(["-e", "main", "-o"]).arg(out).arg(&object);
+        vec![as_, ld]
+    }
+
+    /// Runs the executable natively on 64-bit RISC-V, and under `qemu-riscv64` elsewhere.
+    fn run(&self, out: &Path) -> Option<Command> {
+        let out = Path::new(".").join(out);
+        if cfg!(target_arch = "riscv64") {
+            Some(Command::new(out))
+        } else {
+            let mut qemu = Command::new("qemu-riscv64");
+            qemu.arg(out);
+            Some(qemu)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::codegen::test::{check_runs, check_snapshots};
+
+    use super::*;
+
+    #[test]
+    fn snapshots() {
+        check_snapshots(&Executable);
+    }
+
+    #[test]
+    fn run() {
+        check_runs(&Executable);
+    }
+}
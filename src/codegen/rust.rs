@@ -0,0 +1,264 @@
+use std::env;
+
+use super::*;
+
+/// The runtime. Both stacks are vectors growing up, so the binding at index `i` is `i` cells
+/// below the top of the return stack. Addresses are exposed pointers, which keeps the output
+/// sound under Miri.
+const RUNTIME: &str = "\
+#![allow(dead_code, unreachable_code, unused_variables)]
+
+use std::io::{self, Write};
+
+struct State {
+    stack: Vec<u64>,
+    ret: Vec<u64>,
+    out: io::BufWriter<io::Stdout>,
+}
+
+impl State {
+    fn push(&mut self, x: u64) {
+        self.stack.push(x);
+    }
+
+    fn pop(&mut self) -> u64 {
+        self.stack.pop().expect(\"stack underflow\")
+    }
+
+    /// The value `depth` cells below the top of the data stack.
+    fn peek(&self, depth: usize) -> u64 {
+        self.stack[self.stack.len() - 1 - depth]
+    }
+
+    /// Copy the top `count` values to the return stack, the top one ending up on top.
+    fn bind(&mut self, count: usize, peek: bool) {
+        for i in (0..count).rev() {
+            let x = self.peek(i);
+            self.ret.push(x);
+        }
+        if !peek {
+            self.stack.truncate(self.stack.len() - count);
+        }
+    }
+
+    fn unbind(&mut self, count: usize) {
+        self.ret.truncate(self.ret.len() - count);
+    }
+
+    fn binding(&self, index: usize) -> u64 {
+        self.ret[self.ret.len() - 1 - index]
+    }
+
+    /// Pop the top two values and push `f` of the second and the top.
+    fn binary(&mut self, f: impl FnOnce(u64, u64) -> u64) {
+        let b = self.pop();
+        let a = self.pop();
+        self.push(f(a, b));
+    }
+
+    fn read_byte(&mut self) {
+        let addr = self.pop() as usize;
+        // SAFETY: Rack programs only read from the addresses of their strings.
+        let byte = unsafe { *std::ptr::with_exposed_provenance::<u8>(addr) };
+        self.push(u64::from(byte));
+    }
+
+    fn print(&mut self) {
+        let x = self.pop();
+        writeln!(self.out, \"{x}\").unwrap();
+    }
+
+    fn puts(&mut self) {
+        let addr = self.pop() as usize;
+        let len = self.pop() as usize;
+        // SAFETY: Rack programs only write their strings, with their lengths.
+        let s = unsafe {
+            std::slice::from_raw_parts(std::ptr::with_exposed_provenance::<u8>(addr), len)
+        };
+        self.out.write_all(s).unwrap();
+    }
+}
+";
+
+fn indent(buffer: &mut String, depth: usize) {
+    buffer.extend(std::iter::repeat_n("    ", depth));
+}
+
+fn write_op(op: &Op, depth: usize, buffer: &mut String) -> fmt::Result {
+    indent(buffer, depth);
+    match op {
+        Op::CallFn(index) => writeln!(buffer, "fn_{index}(s);")?,
+        Op::Ret(0) => writeln!(buffer, "return;")?,
+        Op::Ret(count) => {
+            writeln!(buffer, "s.unbind({count});")?;
+            indent(buffer, depth);
+            writeln!(buffer, "return;")?;
+        }
+        Op::Bind {
+            count,
+            names,
+            peek,
+            body,
+        } => {
+            writeln!(buffer, "s.bind({count}, {peek}); // {}", names.join(" "))?;
+            for op in body {
+                write_op(op, depth, buffer)?;
+            }
+            indent(buffer, depth);
+            writeln!(buffer, "s.unbind({count});")?;
+        }
+        Op::PushBind(index) => writeln!(buffer, "s.push(s.binding({index}));")?,
+        Op::PushInt(val) => writeln!(buffer, "s.push({val});")?,
+        Op::PushStrPtr(index) => {
+            writeln!(
+                buffer,
+                "s.push(STR_{index}.as_ptr().expose_provenance() as u64);"
+            )?;
+        }
+        Op::Plus => writeln!(buffer, "s.binary(u64::wrapping_add);")?,
+        Op::Minus => writeln!(buffer, "s.binary(u64::wrapping_sub);")?,
+        Op::DivMod => writeln!(
+            buffer,
+            "{{ let b = s.pop(); let a = s.pop(); s.push(a / b); s.push(a % b); }}"
+        )?,
+        Op::Dup => writeln!(buffer, "s.push(s.peek(0));")?,
+        Op::Drop => writeln!(buffer, "s.pop();")?,
+        Op::Swap => writeln!(
+            buffer,
+            "{{ let b = s.pop(); let a = s.pop(); s.push(b); s.push(a); }}"
+        )?,
+        Op::Over => writeln!(buffer, "s.push(s.peek(1));")?,
+        Op::Equals => writeln!(buffer, "s.binary(|a, b| u64::from(a == b));")?,
+        Op::Neq => writeln!(buffer, "s.binary(|a, b| u64::from(a != b));")?,
+        Op::Not => writeln!(
+            buffer,
+            "{{ let a = s.pop(); s.push(1u64.wrapping_sub(a)); }}"
+        )?,
+        Op::GreaterThan => writeln!(buffer, "s.binary(|a, b| u64::from(a > b));")?,
+        Op::LessThan => writeln!(buffer, "s.binary(|a, b| u64::from(a < b));")?,
+        Op::Or => writeln!(buffer, "s.binary(|a, b| u64::from(a == 1 || b == 1));")?,
+        Op::And => writeln!(buffer, "s.binary(|a, b| u64::from(a == 1 && b == 1));")?,
+        Op::ReadByte => writeln!(buffer, "s.read_byte();")?,
+        Op::If(ops) => {
+            writeln!(buffer, "if s.pop() == 1 {{")?;
+            for op in ops {
+                write_op(op, depth + 1, buffer)?;
+            }
+            indent(buffer, depth);
+            writeln!(buffer, "}}")?;
+        }
+        Op::While { condn, body } => {
+            writeln!(buffer, "loop {{")?;
+            for op in condn {
+                write_op(op, depth + 1, buffer)?;
+            }
+            indent(buffer, depth + 1);
+            writeln!(buffer, "if s.pop() != 1 {{")?;
+            indent(buffer, depth + 2);
+            writeln!(buffer, "break;")?;
+            indent(buffer, depth + 1);
+            writeln!(buffer, "}}")?;
+            for op in body {
+                write_op(op, depth + 1, buffer)?;
+            }
+            indent(buffer, depth);
+            writeln!(buffer, "}}")?;
+        }
+        Op::Print => writeln!(buffer, "s.print();")?,
+        Op::Puts => writeln!(buffer, "s.puts();")?,
+        Op::Asm { .. } => unreachable!("inline assembly isn't parsed for this target"),
+    }
+    Ok(())
+}
+
+pub fn generate(program: Program) -> Result<String, fmt::Error> {
+    let mut outbuf = RUNTIME.to_owned();
+
+    if !program.ctx.strings.is_empty() {
+        outbuf.push('\n');
+    }
+    for (i, s) in program.ctx.strings.iter().enumerate() {
+        writeln!(
+            outbuf,
+            "static STR_{i}: [u8; {}] = {:?};",
+            s.len(),
+            s.as_bytes()
+        )?;
+    }
+
+    for (i, func) in program.funcs.iter().enumerate() {
+        writeln!(
+            outbuf,
+            "\n/// `{}`\nfn fn_{i}(s: &mut State) {{",
+            func.ident
+        )?;
+        for op in &func.body {
+            write_op(op, 1, &mut outbuf)?;
+        }
+        writeln!(outbuf, "}}")?;
+    }
+
+    writeln!(
+        outbuf,
+        "
+fn main() {{
+    let mut s = State {{
+        stack: Vec::new(),
+        ret: Vec::new(),
+        out: io::BufWriter::new(io::stdout()),
+    }};
+    fn_{}(&mut s);
+    s.out.flush().unwrap();
+}}",
+        program.ctx.lookup["main"]
+    )?;
+    Ok(outbuf)
+}
+
+/// Standalone Rust source, compiled with `$RUSTC` (or `rustc`).
+pub struct Rust;
+
+impl Backend for Rust {
+    fn name(&self) -> &'static str {
+        "rust"
+    }
+
+    fn generate(&self, program: Program) -> Result<Artifact, Box<dyn Error>> {
+        Ok(Artifact::Text(generate(program)?))
+    }
+
+    fn intermediate(&self) -> Option<&'static str> {
+        Some("rs")
+    }
+
+    fn build(&self, artifact: &Path, out: &Path) -> Vec<Command> {
+        let mut rustc = Command::new(env::var("RUSTC").unwrap_or_else(|_| "rustc".to_owned()));
+        rustc
+            .args(["--edition", "2021", "-O", "-o"])
+            .arg(out)
+            .arg(artifact);
+        vec![rustc]
+    }
+
+    fn run(&self, out: &Path) -> Option<Command> {
+        Some(Command::new(Path::new(".").join(out)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::codegen::test::{check_runs_with, check_snapshots};
+
+    use super::*;
+
+    #[test]
+    fn snapshots() {
+        check_snapshots(&Rust);
+    }
+
+    /// Functions that don't touch the stacks leave `s` unused, which mustn't be warned about.
+    #[test]
+    fn run() {
+        check_runs_with(&Rust, &["-D", "warnings"]);
+    }
+}
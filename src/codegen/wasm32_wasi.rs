@@ -0,0 +1,854 @@
+use std::env;
+
+use super::*;
+
+/// The size of the data stack, which is the same as the default native stack on x86-64.
+const DATA_STACK_BYTES: u32 = 8 << 20;
+/// The size of the return stack, the same as the native targets.
+const RET_STACK_BYTES: u32 = 65536;
+/// The size of a page of linear memory.
+const PAGE_BYTES: u32 = 65536;
+
+/// The `iovec` passed to `fd_write`, followed by the number of bytes written.
+const IOVEC: u32 = 0;
+const NWRITTEN: u32 = 8;
+/// The end of the buffer that `print` writes digits into, backwards.
+const PRINT_END: u32 = 48;
+/// Where the string literals start.
+const STRINGS: u32 = 64;
+
+// Indices of the globals.
+const SP: u32 = 0;
+const RSP: u32 = 1;
+const GLOBALS: [&str; 2] = ["sp", "rsp"];
+
+// Indices of the functions of the runtime. The imports come first.
+const FD_WRITE: u32 = 0;
+const PUSH: u32 = 1;
+const POP: u32 = 2;
+const WRITE: u32 = 3;
+const PRINT: u32 = 4;
+const PUTS: u32 = 5;
+const START: u32 = 6;
+/// The index of the first Rack function.
+const FUNCS: u32 = 7;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ValType {
+    I32,
+    I64,
+}
+
+impl ValType {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::I32 => "i32",
+            Self::I64 => "i64",
+        }
+    }
+
+    const fn code(self) -> u8 {
+        match self {
+            Self::I32 => 0x7f,
+            Self::I64 => 0x7e,
+        }
+    }
+}
+
+/// The signatures used by the module, indexed by their position.
+const TYPES: [(&[ValType], &[ValType]); 5] = [
+    (&[ValType::I32; 4], &[ValType::I32]),
+    (&[ValType::I64], &[]),
+    (&[], &[ValType::I64]),
+    (&[ValType::I32; 2], &[]),
+    (&[], &[]),
+];
+const TYPE_FD_WRITE: u32 = 0;
+const TYPE_PUSH: u32 = 1;
+const TYPE_POP: u32 = 2;
+const TYPE_WRITE: u32 = 3;
+const TYPE_VOID: u32 = 4;
+
+/// An instruction, which can be written in both the text and the binary format. Memory accesses
+/// carry their offset and always use the natural alignment.
+#[derive(Clone, Copy)]
+enum Instr {
+    Block,
+    Loop,
+    If,
+    End,
+    Br(u32),
+    BrIf(u32),
+    Return,
+    Call(u32),
+    Drop,
+    LocalGet(u32),
+    LocalSet(u32),
+    GlobalGet(u32),
+    GlobalSet(u32),
+    I64Load(u32),
+    I64Load8U(u32),
+    I32Store(u32),
+    I64Store(u32),
+    I64Store8(u32),
+    I32Const(u32),
+    I64Const(u64),
+    I64Eq,
+    I64Ne,
+    I64LtU,
+    I64GtU,
+    I32Add,
+    I32Sub,
+    I32And,
+    I32Or,
+    I64Add,
+    I64Sub,
+    I64DivU,
+    I64RemU,
+    I32WrapI64,
+    I64ExtendI32U,
+}
+
+impl Instr {
+    /// The opcode, and the log2 of the alignment for memory accesses.
+    const fn opcode(self) -> (u8, Option<u32>) {
+        match self {
+            Self::Block => (0x02, None),
+            Self::Loop => (0x03, None),
+            Self::If => (0x04, None),
+            Self::End => (0x0b, None),
+            Self::Br(_) => (0x0c, None),
+            Self::BrIf(_) => (0x0d, None),
+            Self::Return => (0x0f, None),
+            Self::Call(_) => (0x10, None),
+            Self::Drop => (0x1a, None),
+            Self::LocalGet(_) => (0x20, None),
+            Self::LocalSet(_) => (0x21, None),
+            Self::GlobalGet(_) => (0x23, None),
+            Self::GlobalSet(_) => (0x24, None),
+            Self::I64Load(_) => (0x29, Some(3)),
+            Self::I64Load8U(_) => (0x31, Some(0)),
+            Self::I32Store(_) => (0x36, Some(2)),
+            Self::I64Store(_) => (0x37, Some(3)),
+            Self::I64Store8(_) => (0x3c, Some(0)),
+            Self::I32Const(_) => (0x41, None),
+            Self::I64Const(_) => (0x42, None),
+            Self::I64Eq => (0x51, None),
+            Self::I64Ne => (0x52, None),
+            Self::I64LtU => (0x54, None),
+            Self::I64GtU => (0x56, None),
+            Self::I32Add => (0x6a, None),
+            Self::I32Sub => (0x6b, None),
+            Self::I32And => (0x71, None),
+            Self::I32Or => (0x72, None),
+            Self::I64Add => (0x7c, None),
+            Self::I64Sub => (0x7d, None),
+            Self::I64DivU => (0x80, None),
+            Self::I64RemU => (0x82, None),
+            Self::I32WrapI64 => (0xa7, None),
+            Self::I64ExtendI32U => (0xad, None),
+        }
+    }
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Block => "block",
+            Self::Loop => "loop",
+            Self::If => "if",
+            Self::End => "end",
+            Self::Br(_) => "br",
+            Self::BrIf(_) => "br_if",
+            Self::Return => "return",
+            Self::Call(_) => "call",
+            Self::Drop => "drop",
+            Self::LocalGet(_) => "local.get",
+            Self::LocalSet(_) => "local.set",
+            Self::GlobalGet(_) => "global.get",
+            Self::GlobalSet(_) => "global.set",
+            Self::I64Load(_) => "i64.load",
+            Self::I64Load8U(_) => "i64.load8_u",
+            Self::I32Store(_) => "i32.store",
+            Self::I64Store(_) => "i64.store",
+            Self::I64Store8(_) => "i64.store8",
+            Self::I32Const(_) => "i32.const",
+            Self::I64Const(_) => "i64.const",
+            Self::I64Eq => "i64.eq",
+            Self::I64Ne => "i64.ne",
+            Self::I64LtU => "i64.lt_u",
+            Self::I64GtU => "i64.gt_u",
+            Self::I32Add => "i32.add",
+            Self::I32Sub => "i32.sub",
+            Self::I32And => "i32.and",
+            Self::I32Or => "i32.or",
+            Self::I64Add => "i64.add",
+            Self::I64Sub => "i64.sub",
+            Self::I64DivU => "i64.div_u",
+            Self::I64RemU => "i64.rem_u",
+            Self::I32WrapI64 => "i32.wrap_i64",
+            Self::I64ExtendI32U => "i64.extend_i32_u",
+        }
+    }
+
+    fn write_text(self, buffer: &mut String, funcs: &[&str]) -> fmt::Result {
+        buffer.push_str(self.name());
+        match self {
+            Self::Br(depth) | Self::BrIf(depth) => write!(buffer, " {depth}"),
+            Self::Call(index) => write!(buffer, " ${}", funcs[index as usize]),
+            Self::LocalGet(index) | Self::LocalSet(index) => write!(buffer, " {index}"),
+            Self::GlobalGet(index) | Self::GlobalSet(index) => {
+                write!(buffer, " ${}", GLOBALS[index as usize])
+            }
+            Self::I64Load(offset)
+            | Self::I64Load8U(offset)
+            | Self::I32Store(offset)
+            | Self::I64Store(offset)
+            | Self::I64Store8(offset)
+                if offset != 0 =>
+            {
+                write!(buffer, " offset={offset}")
+            }
+            // The text format reads constants as signed or unsigned depending on the sign.
+            Self::I32Const(val) => write!(buffer, " {val}"),
+            Self::I64Const(val) => write!(buffer, " {val}"),
+            _ => Ok(()),
+        }
+    }
+
+    #[allow(clippy::cast_possible_wrap)]
+    fn encode(self, buffer: &mut Vec<u8>) {
+        let (opcode, align) = self.opcode();
+        buffer.push(opcode);
+        match self {
+            // Blocks have no results.
+            Self::Block | Self::Loop | Self::If => buffer.push(0x40),
+            Self::Br(n)
+            | Self::BrIf(n)
+            | Self::Call(n)
+            | Self::LocalGet(n)
+            | Self::LocalSet(n)
+            | Self::GlobalGet(n)
+            | Self::GlobalSet(n) => write_unsigned(buffer, n.into()),
+            Self::I64Load(offset)
+            | Self::I64Load8U(offset)
+            | Self::I32Store(offset)
+            | Self::I64Store(offset)
+            | Self::I64Store8(offset) => {
+                write_unsigned(buffer, align.unwrap_or_default().into());
+                write_unsigned(buffer, offset.into());
+            }
+            // Constants are encoded as signed, but any bit pattern can be given.
+            Self::I32Const(val) => write_signed(buffer, (val as i32).into()),
+            Self::I64Const(val) => write_signed(buffer, val as i64),
+            _ => {}
+        }
+    }
+}
+
+/// Write `val` as unsigned LEB128.
+fn write_unsigned(buffer: &mut Vec<u8>, mut val: u64) {
+    loop {
+        let byte = (val & 0x7f) as u8;
+        val >>= 7;
+        if val == 0 {
+            buffer.push(byte);
+            return;
+        }
+        buffer.push(byte | 0x80);
+    }
+}
+
+/// Write `val` as signed LEB128.
+fn write_signed(buffer: &mut Vec<u8>, mut val: i64) {
+    loop {
+        #[allow(clippy::cast_sign_loss)]
+        let byte = (val & 0x7f) as u8;
+        val >>= 6;
+        if val == 0 || val == -1 {
+            buffer.push(byte);
+            return;
+        }
+        val >>= 1;
+        buffer.push(byte | 0x80);
+    }
+}
+
+fn write_name(buffer: &mut Vec<u8>, name: &str) {
+    write_unsigned(buffer, name.len() as u64);
+    buffer.extend_from_slice(name.as_bytes());
+}
+
+fn write_section(buffer: &mut Vec<u8>, id: u8, contents: &[u8]) {
+    buffer.push(id);
+    write_unsigned(buffer, contents.len() as u64);
+    buffer.extend_from_slice(contents);
+}
+
+/// A function defined by the module.
+struct Func {
+    name: String,
+    /// The name of the Rack function it was lowered from.
+    ident: Option<String>,
+    ty: u32,
+    locals: Vec<ValType>,
+    body: Vec<Instr>,
+}
+
+/// The size of a region of `cells` cells on one of the stacks.
+fn cells(cells: usize) -> u32 {
+    u32::try_from(cells * 8).expect("the stacks fit in memory")
+}
+
+/// The index of the function lowered from the Rack function at `index`.
+fn func_index(index: usize) -> u32 {
+    FUNCS + u32::try_from(index).expect("the functions fit in a module")
+}
+
+/// Adjust the global at `global` by `bytes`, towards the end of memory if `up`.
+fn bump(body: &mut Vec<Instr>, global: u32, bytes: u32, up: bool) {
+    body.extend([
+        Instr::GlobalGet(global),
+        Instr::I32Const(bytes),
+        if up { Instr::I32Add } else { Instr::I32Sub },
+        Instr::GlobalSet(global),
+    ]);
+}
+
+// Scratch locals of the Rack functions.
+const A: u32 = 0;
+const B: u32 = 1;
+
+/// Pop the top value into `B` and the second into `A`.
+fn pop_two(body: &mut Vec<Instr>) {
+    body.extend([
+        Instr::Call(POP),
+        Instr::LocalSet(B),
+        Instr::Call(POP),
+        Instr::LocalSet(A),
+    ]);
+}
+
+/// Pop the top two values, apply `instrs` to them and push the result.
+fn binary(body: &mut Vec<Instr>, instrs: &[Instr]) {
+    pop_two(body);
+    body.extend([Instr::LocalGet(A), Instr::LocalGet(B)]);
+    body.extend_from_slice(instrs);
+    body.push(Instr::Call(PUSH));
+}
+
+/// Pop a value, leaving 1 as an `i32` if it was true.
+fn pop_bool(body: &mut Vec<Instr>) {
+    body.extend([Instr::Call(POP), Instr::I64Const(1), Instr::I64Eq]);
+}
+
+#[allow(clippy::too_many_lines)]
+fn lower_op(op: &Op, body: &mut Vec<Instr>, strings: &[u32]) {
+    match op {
+        Op::CallFn(index) => body.push(Instr::Call(func_index(*index))),
+        Op::Ret(count) => {
+            if *count > 0 {
+                bump(body, RSP, cells(*count), true);
+            }
+            body.push(Instr::Return);
+        }
+        Op::Bind {
+            count,
+            peek,
+            body: ops,
+            ..
+        } => {
+            let bytes = cells(*count);
+            bump(body, RSP, bytes, false);
+            for i in 0..*count {
+                body.extend([
+                    Instr::GlobalGet(RSP),
+                    Instr::GlobalGet(SP),
+                    Instr::I64Load(cells(i)),
+                    Instr::I64Store(cells(i)),
+                ]);
+            }
+            if !peek {
+                bump(body, SP, bytes, true);
+            }
+            for op in ops {
+                lower_op(op, body, strings);
+            }
+            // Remove the bindings from the return stack.
+            bump(body, RSP, bytes, true);
+        }
+        Op::PushBind(index) => body.extend([
+            Instr::GlobalGet(RSP),
+            Instr::I64Load(cells(*index)),
+            Instr::Call(PUSH),
+        ]),
+        Op::PushInt(val) => body.extend([Instr::I64Const(*val), Instr::Call(PUSH)]),
+        Op::PushStrPtr(index) => {
+            body.extend([Instr::I64Const(strings[*index].into()), Instr::Call(PUSH)]);
+        }
+        Op::Plus => binary(body, &[Instr::I64Add]),
+        Op::Minus => binary(body, &[Instr::I64Sub]),
+        Op::DivMod => {
+            pop_two(body);
+            body.extend([
+                Instr::LocalGet(A),
+                Instr::LocalGet(B),
+                Instr::I64DivU,
+                Instr::Call(PUSH),
+                Instr::LocalGet(A),
+                Instr::LocalGet(B),
+                Instr::I64RemU,
+                Instr::Call(PUSH),
+            ]);
+        }
+        Op::Dup => body.extend([Instr::GlobalGet(SP), Instr::I64Load(0), Instr::Call(PUSH)]),
+        Op::Drop => bump(body, SP, 8, true),
+        Op::Swap => {
+            pop_two(body);
+            body.extend([
+                Instr::LocalGet(B),
+                Instr::Call(PUSH),
+                Instr::LocalGet(A),
+                Instr::Call(PUSH),
+            ]);
+        }
+        Op::Over => body.extend([Instr::GlobalGet(SP), Instr::I64Load(8), Instr::Call(PUSH)]),
+        Op::Equals => binary(body, &[Instr::I64Eq, Instr::I64ExtendI32U]),
+        Op::Neq => binary(body, &[Instr::I64Ne, Instr::I64ExtendI32U]),
+        Op::GreaterThan => binary(body, &[Instr::I64GtU, Instr::I64ExtendI32U]),
+        Op::LessThan => binary(body, &[Instr::I64LtU, Instr::I64ExtendI32U]),
+        Op::Not => body.extend([
+            Instr::I64Const(1),
+            Instr::Call(POP),
+            Instr::I64Sub,
+            Instr::Call(PUSH),
+        ]),
+        Op::Or | Op::And => {
+            pop_bool(body);
+            pop_bool(body);
+            body.extend([
+                if matches!(op, Op::Or) {
+                    Instr::I32Or
+                } else {
+                    Instr::I32And
+                },
+                Instr::I64ExtendI32U,
+                Instr::Call(PUSH),
+            ]);
+        }
+        Op::ReadByte => body.extend([
+            Instr::Call(POP),
+            Instr::I32WrapI64,
+            Instr::I64Load8U(0),
+            Instr::Call(PUSH),
+        ]),
+        Op::If(ops) => {
+            pop_bool(body);
+            body.push(Instr::If);
+            for op in ops {
+                lower_op(op, body, strings);
+            }
+            body.push(Instr::End);
+        }
+        Op::While { condn, body: ops } => {
+            body.extend([Instr::Block, Instr::Loop]);
+            for op in condn {
+                lower_op(op, body, strings);
+            }
+            // Check the while condition and break out of the block if not met.
+            body.extend([
+                Instr::Call(POP),
+                Instr::I64Const(1),
+                Instr::I64Ne,
+                Instr::BrIf(1),
+            ]);
+            for op in ops {
+                lower_op(op, body, strings);
+            }
+            body.extend([Instr::Br(0), Instr::End, Instr::End]);
+        }
+        Op::Print => body.extend([Instr::Call(POP), Instr::Call(PRINT)]),
+        Op::Puts => body.push(Instr::Call(PUTS)),
+        Op::Asm { .. } => unreachable!("inline assembly isn't parsed for this target"),
+    }
+}
+
+/// The functions of the runtime, from [`PUSH`] to [`START`].
+fn runtime(main: u32) -> Vec<Func> {
+    let func = |name: &str, ty, locals, body| Func {
+        name: name.to_owned(),
+        ident: None,
+        ty,
+        locals,
+        body,
+    };
+    let mut push = Vec::new();
+    bump(&mut push, SP, 8, false);
+    push.extend([Instr::GlobalGet(SP), Instr::LocalGet(0), Instr::I64Store(0)]);
+
+    let mut pop = vec![Instr::GlobalGet(SP), Instr::I64Load(0)];
+    bump(&mut pop, SP, 8, true);
+
+    // Write the decimal digits of local 0 backwards from `PRINT_END`, with local 1 pointing at
+    // the last digit written.
+    let print = vec![
+        Instr::I32Const(PRINT_END - 1),
+        Instr::LocalSet(1),
+        Instr::LocalGet(1),
+        Instr::I64Const(u64::from(b'\n')),
+        Instr::I64Store8(0),
+        Instr::Loop,
+        Instr::LocalGet(1),
+        Instr::I32Const(1),
+        Instr::I32Sub,
+        Instr::LocalSet(1),
+        Instr::LocalGet(1),
+        Instr::LocalGet(0),
+        Instr::I64Const(10),
+        Instr::I64RemU,
+        Instr::I64Const(u64::from(b'0')),
+        Instr::I64Add,
+        Instr::I64Store8(0),
+        Instr::LocalGet(0),
+        Instr::I64Const(10),
+        Instr::I64DivU,
+        Instr::LocalSet(0),
+        Instr::LocalGet(0),
+        Instr::I64Const(0),
+        Instr::I64Ne,
+        Instr::BrIf(0),
+        Instr::End,
+        Instr::LocalGet(1),
+        Instr::I32Const(PRINT_END),
+        Instr::LocalGet(1),
+        Instr::I32Sub,
+        Instr::Call(WRITE),
+    ];
+
+    vec![
+        func("push", TYPE_PUSH, vec![], push),
+        func("pop", TYPE_POP, vec![], pop),
+        func(
+            "write",
+            TYPE_WRITE,
+            vec![],
+            vec![
+                Instr::I32Const(IOVEC),
+                Instr::LocalGet(0),
+                Instr::I32Store(0),
+                Instr::I32Const(IOVEC),
+                Instr::LocalGet(1),
+                Instr::I32Store(4),
+                Instr::I32Const(1),
+                Instr::I32Const(IOVEC),
+                Instr::I32Const(1),
+                Instr::I32Const(NWRITTEN),
+                Instr::Call(FD_WRITE),
+                Instr::Drop,
+            ],
+        ),
+        func("print", TYPE_PUSH, vec![ValType::I32], print),
+        func(
+            "puts",
+            TYPE_VOID,
+            vec![],
+            vec![
+                Instr::Call(POP),
+                Instr::I32WrapI64,
+                Instr::Call(POP),
+                Instr::I32WrapI64,
+                Instr::Call(WRITE),
+            ],
+        ),
+        func("_start", TYPE_VOID, vec![], vec![Instr::Call(main)]),
+    ]
+}
+
+/// A WebAssembly module that runs a Rack program under WASI.
+pub struct Module {
+    /// The functions, not including the import.
+    funcs: Vec<Func>,
+    /// The string literals and their addresses.
+    strings: Vec<(u32, Vec<u8>)>,
+    ret_stack_end: u32,
+    data_stack_end: u32,
+    pages: u32,
+}
+
+impl Module {
+    pub fn new(program: &Program) -> Self {
+        let mut strings = Vec::new();
+        let mut addr = STRINGS;
+        for s in &program.ctx.strings {
+            strings.push((addr, s.as_bytes().to_vec()));
+            addr += u32::try_from(s.len()).expect("the strings fit in memory");
+        }
+        // The stacks follow the strings, aligned to a cell.
+        let ret_stack_end = addr.div_ceil(8) * 8 + RET_STACK_BYTES;
+        let data_stack_end = ret_stack_end + DATA_STACK_BYTES;
+
+        let addrs = strings.iter().map(|(addr, _)| *addr).collect::<Vec<_>>();
+        let mut funcs = runtime(func_index(program.ctx.lookup["main"]));
+        for (i, func) in program.funcs.iter().enumerate() {
+            let mut body = Vec::new();
+            for op in &func.body {
+                lower_op(op, &mut body, &addrs);
+            }
+            funcs.push(Func {
+                name: format!("fn_{i}"),
+                ident: Some(func.ident.to_owned()),
+                ty: TYPE_VOID,
+                locals: vec![ValType::I64; 2],
+                body,
+            });
+        }
+
+        Self {
+            funcs,
+            strings,
+            ret_stack_end,
+            data_stack_end,
+            pages: data_stack_end.div_ceil(PAGE_BYTES),
+        }
+    }
+
+    /// The names of the functions, including the import.
+    fn func_names(&self) -> Vec<&str> {
+        std::iter::once("fd_write")
+            .chain(self.funcs.iter().map(|func| func.name.as_str()))
+            .collect()
+    }
+
+    /// The module in the text format.
+    pub fn text(&self) -> Result<String, fmt::Error> {
+        let names = self.func_names();
+        let mut outbuf = String::from("(module\n");
+        for (i, (params, results)) in TYPES.iter().enumerate() {
+            write!(outbuf, "  (type (;{i};) (func")?;
+            if !params.is_empty() {
+                write!(outbuf, " (param")?;
+                for param in *params {
+                    write!(outbuf, " {}", param.name())?;
+                }
+                write!(outbuf, ")")?;
+            }
+            if !results.is_empty() {
+                write!(outbuf, " (result")?;
+                for result in *results {
+                    write!(outbuf, " {}", result.name())?;
+                }
+                write!(outbuf, ")")?;
+            }
+            writeln!(outbuf, "))")?;
+        }
+        writeln!(
+            outbuf,
+            "  (import \"wasi_snapshot_preview1\" \"fd_write\" (func $fd_write (type {TYPE_FD_WRITE})))"
+        )?;
+        writeln!(outbuf, "  (memory (export \"memory\") {})", self.pages)?;
+        writeln!(
+            outbuf,
+            "  (global $sp (mut i32) (i32.const {}))",
+            self.data_stack_end
+        )?;
+        writeln!(
+            outbuf,
+            "  (global $rsp (mut i32) (i32.const {}))",
+            self.ret_stack_end
+        )?;
+        writeln!(outbuf, "  (export \"_start\" (func $_start))")?;
+        for (addr, bytes) in &self.strings {
+            write!(outbuf, "  (data (i32.const {addr}) \"")?;
+            for b in bytes {
+                write!(outbuf, "\\{b:02x}")?;
+            }
+            writeln!(outbuf, "\")")?;
+        }
+
+        for func in &self.funcs {
+            if let Some(ident) = &func.ident {
+                writeln!(outbuf, "  ;; {ident}")?;
+            }
+            write!(outbuf, "  (func ${} (type {})", func.name, func.ty)?;
+            for local in &func.locals {
+                write!(outbuf, " (local {})", local.name())?;
+            }
+            outbuf.push('\n');
+            let mut depth = 2;
+            for instr in &func.body {
+                if matches!(instr, Instr::End) {
+                    depth -= 1;
+                }
+                outbuf.extend(std::iter::repeat_n("  ", depth));
+                instr.write_text(&mut outbuf, &names)?;
+                outbuf.push('\n');
+                if matches!(instr, Instr::Block | Instr::Loop | Instr::If) {
+                    depth += 1;
+                }
+            }
+            writeln!(outbuf, "  )")?;
+        }
+        writeln!(outbuf, ")")?;
+        Ok(outbuf)
+    }
+
+    /// The module in the binary format.
+    pub fn binary(&self) -> Vec<u8> {
+        let mut outbuf = b"\0asm\x01\0\0\0".to_vec();
+
+        let mut section = Vec::new();
+        write_unsigned(&mut section, TYPES.len() as u64);
+        for (params, results) in TYPES {
+            section.push(0x60);
+            for types in <[_; 2]>::from((params, results)) {
+                write_unsigned(&mut section, types.len() as u64);
+                section.extend(types.iter().map(|ty| ty.code()));
+            }
+        }
+        write_section(&mut outbuf, 1, &section);
+
+        section.clear();
+        write_unsigned(&mut section, 1);
+        write_name(&mut section, "wasi_snapshot_preview1");
+        write_name(&mut section, "fd_write");
+        section.push(0x00);
+        write_unsigned(&mut section, TYPE_FD_WRITE.into());
+        write_section(&mut outbuf, 2, &section);
+
+        section.clear();
+        write_unsigned(&mut section, self.funcs.len() as u64);
+        for func in &self.funcs {
+            write_unsigned(&mut section, func.ty.into());
+        }
+        write_section(&mut outbuf, 3, &section);
+
+        // One memory with only a minimum size.
+        section = vec![1, 0x00];
+        write_unsigned(&mut section, self.pages.into());
+        write_section(&mut outbuf, 5, &section);
+
+        section.clear();
+        write_unsigned(&mut section, GLOBALS.len() as u64);
+        for init in [self.data_stack_end, self.ret_stack_end] {
+            section.extend([ValType::I32.code(), 0x01]);
+            Instr::I32Const(init).encode(&mut section);
+            Instr::End.encode(&mut section);
+        }
+        write_section(&mut outbuf, 6, &section);
+
+        section.clear();
+        write_unsigned(&mut section, 2);
+        write_name(&mut section, "memory");
+        section.extend([0x02, 0]);
+        write_name(&mut section, "_start");
+        section.push(0x00);
+        write_unsigned(&mut section, START.into());
+        write_section(&mut outbuf, 7, &section);
+
+        section.clear();
+        write_unsigned(&mut section, self.funcs.len() as u64);
+        for func in &self.funcs {
+            let mut code = Vec::new();
+            // Locals are declared in runs of the same type.
+            let runs = func.locals.chunk_by(|a, b| a == b).collect::<Vec<_>>();
+            write_unsigned(&mut code, runs.len() as u64);
+            for run in runs {
+                write_unsigned(&mut code, run.len() as u64);
+                code.push(run[0].code());
+            }
+            for instr in &func.body {
+                instr.encode(&mut code);
+            }
+            Instr::End.encode(&mut code);
+            write_unsigned(&mut section, code.len() as u64);
+            section.extend(code);
+        }
+        write_section(&mut outbuf, 10, &section);
+
+        if !self.strings.is_empty() {
+            section.clear();
+            write_unsigned(&mut section, self.strings.len() as u64);
+            for (addr, bytes) in &self.strings {
+                // An active segment for memory 0.
+                section.push(0x00);
+                Instr::I32Const(*addr).encode(&mut section);
+                Instr::End.encode(&mut section);
+                write_unsigned(&mut section, bytes.len() as u64);
+                section.extend(bytes);
+            }
+            write_section(&mut outbuf, 11, &section);
+        }
+
+        outbuf
+    }
+}
+
+/// A WebAssembly module for WASI, run with `$WASI_RUNTIME` (or `wasmtime`).
+pub struct Wasi;
+
+impl Backend for Wasi {
+    fn name(&self) -> &'static str {
+        "wasm32-wasi"
+    }
+
+    fn extension(&self) -> Option<&'static str> {
+        Some("wasm")
+    }
+
+    fn generate(&self, program: Program) -> Result<Artifact, Box<dyn Error>> {
+        Ok(Artifact::Binary(Module::new(&program).binary()))
+    }
+
+    fn run(&self, out: &Path) -> Option<Command> {
+        let mut runtime =
+            Command::new(env::var("WASI_RUNTIME").unwrap_or_else(|_| "wasmtime".to_owned()));
+        runtime.arg(out);
+        Some(runtime)
+    }
+}
+
+/// The text format of [`Wasi`], for reading or assembling elsewhere.
+pub struct Text;
+
+impl Backend for Text {
+    fn name(&self) -> &'static str {
+        "wasm32-wat"
+    }
+
+    fn extension(&self) -> Option<&'static str> {
+        Some("wat")
+    }
+
+    fn generate(&self, program: Program) -> Result<Artifact, Box<dyn Error>> {
+        Ok(Artifact::Text(Module::new(&program).text()?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::codegen::test::{check_runs, check_snapshots};
+
+    use super::*;
+
+    #[test]
+    fn leb128() {
+        let mut buffer = Vec::new();
+        write_unsigned(&mut buffer, 624_485);
+        write_unsigned(&mut buffer, 127);
+        assert_eq!(buffer, [0xe5, 0x8e, 0x26, 0x7f]);
+
+        buffer.clear();
+        write_signed(&mut buffer, -123_456);
+        write_signed(&mut buffer, 64);
+        write_signed(&mut buffer, -1);
+        assert_eq!(buffer, [0xc0, 0xbb, 0x78, 0xc0, 0x00, 0x7f]);
+    }
+
+    #[test]
+    fn snapshots() {
+        check_snapshots(&Text);
+    }
+
+    #[test]
+    fn run() {
+        check_runs(&Wasi);
+    }
+}
@@ -0,0 +1,872 @@
+use super::*;
+
+/// The size of a cell in bytes. Every offset into the data and return stacks is a multiple of it.
+pub const CELL_SIZE: usize = 8;
+
+#[allow(clippy::too_many_lines)]
+fn write_op(
+    op: &Op,
+    count_ops: &mut usize,
+    buffer: &mut String,
+    ctx: &Context,
+    syntax: Syntax,
+) -> std::fmt::Result {
+    match op {
+        Op::CallFn(index) => {
+            // TODO: this nonsense obviously needs to be refactored. But I don't care right now.
+            let func = ctx
+                .lookup
+                .iter()
+                .find(|(_, v)| *v == index)
+                .expect("the index was taken from the same context lookup")
+                .0;
+            if let Some(signature) = ctx.externs.get(func) {
+                write_c_call(func, *signature, syntax, buffer)?;
+            } else {
+                write!(
+                    buffer,
+                    "\tmov\trax, [ret_stack_rsp]\t; Op::CallFn({index})
+\tsub\trax, 8
+\tmov\t[ret_stack_rsp], rax
+\tmov\tqword [rax], RET{count_ops}
+\tjmp\tfn_{func}
+RET{count_ops}:
+\tmov\trax, [ret_stack_rsp]
+\tadd\trax, 8
+\tmov\t[ret_stack_rsp], rax
+"
+                )?;
+            }
+        }
+        // Small optimisation for the 0 case.
+        Op::Ret(0) => write!(
+            buffer,
+            "\tmov\trax, qword [ret_stack_rsp]\t; Op::Ret(0)
+\tjmp qword [rax]
+"
+        )?,
+        Op::Ret(count) => write!(
+            buffer,
+            "\tmov\trax, [ret_stack_rsp]\t; Op::Ret({count})
+\tadd\trax, {}
+\tmov\tqword [ret_stack_rsp], rax
+\tjmp\tqword [rax]
+",
+            count * CELL_SIZE
+        )?,
+        Op::Bind {
+            count, peek, body, ..
+        } => {
+            write!(
+                buffer,
+                "\tmov\trax, [ret_stack_rsp]\t; Op::Bind({count})
+\tsub\trax, {}
+\tmov\t[ret_stack_rsp], rax
+",
+                count * CELL_SIZE
+            )?;
+            for i in 0..*count {
+                write!(
+                    buffer,
+                    "\tmov\trbx, [rsp + {0}]
+\tmov\t[rax+{0}], rbx
+",
+                    i * CELL_SIZE
+                )?;
+            }
+            if !peek {
+                writeln!(buffer, "\tadd\trsp, {}", count * CELL_SIZE)?;
+            }
+            for op in body {
+                write_op(op, count_ops, buffer, ctx, syntax)?;
+            }
+            // Remove the bindings from the return stack.
+            write!(
+                buffer,
+                "\tmov\trax, [ret_stack_rsp]
+\tadd\trax, {}
+\tmov\tqword [ret_stack_rsp], rax
+",
+                count * CELL_SIZE
+            )?;
+        }
+        Op::PushBind(index) => write!(
+            buffer,
+            "\tmov\trax, [ret_stack_rsp]\t; Op::PushBind({index})
+\tadd\trax, {}
+\tpush\tqword [rax]
+",
+            index * CELL_SIZE
+        )?,
+        // `push` only takes a sign-extended 32-bit immediate.
+        Op::PushInt(val) if *val > i32::MAX as u64 => write!(
+            buffer,
+            "\tmov\trax, {val}\t; Op::PushInt({val})
+\tpush\trax
+"
+        )?,
+        Op::PushInt(val) => writeln!(buffer, "\tpush\t{val}\t\t\t; Op::PushInt({val})")?,
+        Op::PushStrPtr(index) => {
+            writeln!(buffer, "\tpush\tstr_{index}\t\t\t; Op::PushStrPtr({index})")?;
+        }
+        Op::Plus => write!(
+            buffer,
+            "\tpop\trax\t\t\t; Op::Plus
+\tpop\trbx
+\tadd\trax, rbx
+\tpush\trax
+",
+        )?,
+        Op::Minus => write!(
+            buffer,
+            "\tpop\trbx\t\t\t; Op::Minus
+\tpop\trax
+\tsub\trax, rbx
+\tpush\trax
+",
+        )?,
+        Op::DivMod => write!(
+            buffer,
+            "\tpop\trbx\t\t\t; Op::DivMod
+\tpop\trax
+\tmov\trdx, 0
+\tdiv\trbx
+\tpush\trax
+\tpush\trdx
+",
+        )?,
+        Op::Dup => writeln!(buffer, "\tpush\tqword [rsp]\t\t; Op::Dup")?,
+        Op::Drop => writeln!(buffer, "\tadd\trsp, {CELL_SIZE}\t\t\t; Op::Drop")?,
+        Op::Swap => write!(
+            buffer,
+            "\tpop\trax\t\t\t; Op::Swap
+\tpop\trbx
+\tpush\trax
+\tpush\trbx
+",
+        )?,
+        Op::Over => writeln!(buffer, "\tpush\tqword [rsp + {CELL_SIZE}]\t; Op::Over")?,
+        Op::Equals => {
+            write!(
+                buffer,
+                "\tpop\trax\t\t\t; Op::Equals
+\tpop\trbx
+\tcmp\trax, rbx
+\tje\tJ{0}
+\tpush\t0
+\tjmp\tJ{1}
+J{0}:
+\tpush\t1
+J{1}:
+",
+                count_ops,
+                *count_ops + 1,
+            )?;
+            *count_ops += 1;
+        }
+        Op::Neq => {
+            write!(
+                buffer,
+                "\tpop\trax\t\t\t; Op::Neq
+\tpop\trbx
+\tcmp\trax, rbx
+\tjne\tJ{0}
+\tpush\t0
+\tjmp\tJ{1}
+J{0}:
+\tpush\t1
+J{1}:
+",
+                count_ops,
+                *count_ops + 1,
+            )?;
+            *count_ops += 1;
+        }
+        Op::Not => write!(
+            buffer,
+            "\tpop\trax\t\t\t; Op::Not
+\tmov\trbx, 1
+\tsub\trbx, rax
+\tpush\trbx
+"
+        )?,
+        Op::GreaterThan => {
+            write!(
+                buffer,
+                "\tpop\trax\t\t\t; Op::GreaterThan
+\tpop\trbx
+\tcmp\trax, rbx
+\tjb\tJ{0}
+\tpush\t0
+\tjmp\tJ{1}
+J{0}:
+\tpush\t1
+J{1}:
+",
+                count_ops,
+                *count_ops + 1,
+            )?;
+            *count_ops += 1;
+        }
+        Op::LessThan => {
+            write!(
+                buffer,
+                "\tpop\trax\t\t\t; Op::LessThan
+\tpop\trbx
+\tcmp\trbx, rax
+\tjb\tJ{0}
+\tpush\t0
+\tjmp\tJ{1}
+J{0}:
+\tpush\t1
+J{1}:
+",
+                count_ops,
+                *count_ops + 1,
+            )?;
+            *count_ops += 1;
+        }
+        Op::Or => {
+            write!(
+                buffer,
+                "\tpop\trax\t\t\t; Op::Or
+\tpop\trbx
+\tcmp\trax, 1
+\tje\tJ{0}
+\tcmp\trbx, 1
+\tje\tJ{0}
+\tpush\t0
+\tjmp\tJ{1}
+J{0}:
+\tpush\t1
+J{1}:
+",
+                count_ops,
+                *count_ops + 1,
+            )?;
+            *count_ops += 1;
+        }
+        Op::And => {
+            write!(
+                buffer,
+                "\tpop\trax\t\t\t; Op::And
+\tpop\trbx
+\tcmp\trax, rbx
+\tjne\tJ{0}
+\tcmp\trax, 1
+\tjne\tJ{0}
+\tpush\t1
+\tjmp\tJ{1}
+J{0}:
+\tpush\t0
+J{1}:
+",
+                count_ops,
+                *count_ops + 1,
+            )?;
+            *count_ops += 1;
+        }
+        Op::ReadByte => write!(
+            buffer,
+            "\tpop\trbx\t\t\t; Op::ReadByte
+\tmov\trax, 0
+\tmov\tal, byte [rbx]
+\tpush\trax
+"
+        )?,
+        Op::If(ops) => {
+            let jump_to = *count_ops;
+            *count_ops += 1;
+            write!(
+                buffer,
+                "\tpop\trax\t\t\t; Op::If
+\tcmp\trax, 1
+\tjne\tF{jump_to}
+"
+            )?;
+            for op in ops {
+                write_op(op, count_ops, buffer, ctx, syntax)?;
+            }
+            writeln!(buffer, "F{jump_to}:")?;
+        }
+        Op::While { condn, body } => {
+            let condn_jump = *count_ops;
+            let end_jump = *count_ops + 1;
+            *count_ops += 2;
+            writeln!(buffer, "F{condn_jump}:\t\t\t\t\t; Op::While")?;
+            for op in condn {
+                write_op(op, count_ops, buffer, ctx, syntax)?;
+            }
+            // Check the while condition and jump to end if not met.
+            write!(
+                buffer,
+                "\tpop\trax
+\tcmp\trax, 1
+\tjne\tF{end_jump}
+"
+            )?;
+            for op in body {
+                write_op(op, count_ops, buffer, ctx, syntax)?;
+            }
+            writeln!(buffer, "\tjmp F{condn_jump}\nF{end_jump}:")?;
+        }
+        Op::Print => write!(buffer, "\tpop\trdi\t\t\t; Op::Print\n\tcall\tprint\n")?,
+        Op::Puts => write!(
+            buffer,
+            "\tpop\trsi\t\t\t; Op::Puts
+\tpop\trdx
+\tcall\twrite
+"
+        )?,
+        Op::Asm { signature, lines } => {
+            writeln!(
+                buffer,
+                "\t\t\t\t\t; Op::Asm {} -- {}",
+                signature.inputs, signature.outputs
+            )?;
+            for line in lines {
+                writeln!(buffer, "\t{line}")?;
+            }
+        }
+    }
+    *count_ops += 1;
+    Ok(())
+}
+
+/// The assemblers that the Intel syntax output can be written for.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Syntax {
+    /// An executable assembled directly by fasm.
+    Fasm,
+    /// An object assembled by NASM, to be linked with `ld`.
+    Nasm,
+}
+
+impl Syntax {
+    /// The directives that export and import a symbol from an object.
+    const fn linkage(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Fasm => ("public", "extrn"),
+            Self::Nasm => ("global", "extern"),
+        }
+    }
+
+    /// The label for a C function called `name`. NASM reads it as an identifier even if it is a
+    /// mnemonic or a register with a `$` in front. fasm can't escape one, so the function gets a
+    /// label of its own, which `c_linkage` ties to the symbol.
+    fn c_symbol(self, name: &str) -> String {
+        match self {
+            Self::Fasm => format!("c_{name}"),
+            Self::Nasm => format!("${name}"),
+        }
+    }
+
+    /// The directive that exports the C function `name` from an object, or imports it into one.
+    fn c_linkage(self, name: &str, export: bool) -> String {
+        let label = self.c_symbol(name);
+        match (self, export) {
+            (Self::Fasm, true) => format!("public {label} as '{name}'"),
+            (Self::Fasm, false) => format!("extrn '{name}' as {label}"),
+            (Self::Nasm, true) => format!("global {label}"),
+            (Self::Nasm, false) => format!("extern {label}"),
+        }
+    }
+}
+
+/// The registers that the first six integer arguments of a C function are passed in.
+pub const ARG_REGISTERS: [&str; 6] = ["rdi", "rsi", "rdx", "rcx", "r8", "r9"];
+
+/// Whether `name` is a label in the generated code, so that a function can't be exported as it.
+fn is_reserved(name: &str) -> bool {
+    [
+        "print",
+        "write",
+        "ret_stack",
+        "ret_stack_rsp",
+        "ret_stack_end",
+    ]
+    .contains(&name)
+        || ["fn_", "str_", "RET"]
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+        || name
+            .strip_prefix('F')
+            .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Check that none of the C functions that `program` exports or calls clash with the generated
+/// code.
+pub fn check_c_names(program: &Program) -> Result<(), Box<dyn Error>> {
+    match program.funcs.iter().find(|func| {
+        (func.export.is_some() || program.ctx.externs.contains_key(func.ident))
+            && is_reserved(func.ident)
+    }) {
+        Some(func) => Err(format!(
+            "{}: `{}` can't be a C function, since the name is used by the generated code",
+            func.location, func.ident
+        )
+        .into()),
+        None => Ok(()),
+    }
+}
+
+/// Write a function called `name` with the C ABI, which pushes its arguments onto the data stack
+/// and calls `fn_<name>` with an empty return stack, returning the top of the data stack or 0.
+fn write_export(
+    name: &str,
+    signature: Signature,
+    syntax: Syntax,
+    outbuf: &mut String,
+) -> std::fmt::Result {
+    writeln!(
+        outbuf,
+        "{}:
+\tpush\trbx
+\tpush\trbp
+\tmov\trbp, rsp",
+        syntax.c_symbol(name)
+    )?;
+    for register in &ARG_REGISTERS[..signature.inputs] {
+        writeln!(outbuf, "\tpush\t{register}")?;
+    }
+    write!(
+        outbuf,
+        "\tmov\trax, ret_stack_end
+\tsub\trax, 8
+\tmov\tqword [ret_stack_rsp], rax
+\tmov\tqword [rax], RET_EXPORT_{name}
+\tjmp\tfn_{name}
+RET_EXPORT_{name}:
+"
+    )?;
+    if signature.outputs == 1 {
+        writeln!(outbuf, "\tmov\trax, [rsp]")?;
+    } else {
+        writeln!(outbuf, "\txor\teax, eax")?;
+    }
+    write!(
+        outbuf,
+        "\tmov\trsp, rbp
+\tpop\trbp
+\tpop\trbx
+\tret
+"
+    )
+}
+
+/// Write a call to the C function `name`, which takes its arguments from the data stack and
+/// pushes its result. The data stack is aligned for the call, and `rbx` holds the old top across
+/// it since the callee preserves it. `al` is cleared for variadic functions such as `printf`.
+fn write_c_call(
+    name: &str,
+    signature: Signature,
+    syntax: Syntax,
+    buffer: &mut String,
+) -> std::fmt::Result {
+    writeln!(buffer, "\t\t\t\t\t; call {name}")?;
+    for register in ARG_REGISTERS[..signature.inputs].iter().rev() {
+        writeln!(buffer, "\tpop\t{register}")?;
+    }
+    write!(
+        buffer,
+        "\tmov\trbx, rsp
+\tand\trsp, -16
+\txor\teax, eax
+\tcall\t{}
+\tmov\trsp, rbx
+",
+        syntax.c_symbol(name)
+    )?;
+    if signature.outputs == 1 {
+        writeln!(buffer, "\tpush\trax")?;
+    }
+    Ok(())
+}
+
+/// Generate a whole program, or an object that exports its functions and imports the ones it
+/// calls without defining. Only the object defining `main` or exporting functions to C has the
+/// return stack, which the others import.
+#[allow(clippy::too_many_lines)]
+pub fn generate(program: Program, syntax: Syntax, emit: Emit) -> Result<String, std::fmt::Error> {
+    let entry = defines_main(&program);
+    let exports = exports(&program, emit);
+    let ret_stack = entry || !exports.is_empty();
+    let mut outbuf = String::from(match (syntax, emit) {
+        (Syntax::Fasm, Emit::Exe) => {
+            "format ELF64 executable 3
+entry main
+segment readable executable
+"
+        }
+        (Syntax::Nasm, Emit::Exe) => {
+            "global main
+section .text
+"
+        }
+        (Syntax::Fasm, _) => "format ELF64\n",
+        (Syntax::Nasm, _) => "",
+    });
+    if emit != Emit::Exe {
+        let (export, import) = syntax.linkage();
+        if entry {
+            writeln!(outbuf, "{}", syntax.c_linkage("main", true))?;
+        }
+        if ret_stack {
+            writeln!(outbuf, "{export} ret_stack_rsp")?;
+        } else {
+            writeln!(outbuf, "{import} ret_stack_rsp")?;
+        }
+        for func in &program.funcs {
+            if program.ctx.externs.contains_key(func.ident) {
+                writeln!(outbuf, "{}", syntax.c_linkage(func.ident, false))?;
+            } else {
+                let linkage = if func.is_import() { import } else { export };
+                writeln!(outbuf, "{linkage} fn_{}", func.ident)?;
+            }
+        }
+        for (name, _) in &exports {
+            writeln!(outbuf, "{}", syntax.c_linkage(name, true))?;
+        }
+        if !program.ctx.externs.contains_key("fflush") {
+            writeln!(outbuf, "{}", syntax.c_linkage("fflush", false))?;
+        }
+        // Objects may be linked by a C compiler, which expects to be told that the stack need not
+        // be executable.
+        outbuf += match syntax {
+            Syntax::Fasm => "section '.note.GNU-stack'\nsection '.text' executable\n",
+            Syntax::Nasm => {
+                "section .note.GNU-stack noalloc noexec nowrite progbits\nsection .text\n"
+            }
+        };
+    }
+
+    outbuf += "print:
+\tmov\tr9, -3689348814741910323
+\tsub\trsp, 40
+\tmov\tBYTE [rsp+31], 10
+\tlea\trcx, [rsp+30]
+.L2:
+\tmov\trax, rdi
+\tlea\tr8, [rsp+32]
+\tmul\tr9
+\tmov\trax, rdi
+\tsub\tr8, rcx
+\tshr\trdx, 3
+\tlea\trsi, [rdx+rdx*4]
+\tadd\trsi, rsi
+\tsub\trax, rsi
+\tadd\teax, 48
+\tmov\tBYTE [rcx], al
+\tmov\trax, rdi
+\tmov\trdi, rdx
+\tmov\trdx, rcx
+\tsub\trcx, 1
+\tcmp\trax, 9
+\tja\t.L2
+\tlea\trax, [rsp+32]
+\tsub\trdx, rax
+\tlea\trsi, [rsp+32+rdx]
+\tmov\trdx, r8
+\tcall\twrite
+\tadd\trsp, 40
+\tret
+write:
+";
+    // Objects are linked with the C library, whose `stdio` buffers its output, so that is flushed
+    // first to keep the two in order.
+    if emit != Emit::Exe {
+        writeln!(
+            outbuf,
+            "\tmov\trbx, rsp
+\tand\trsp, -16
+\tpush\trsi
+\tpush\trdx
+\txor\tedi, edi
+\tcall\t{}
+\tpop\trdx
+\tpop\trsi
+\tmov\trsp, rbx",
+            syntax.c_symbol("fflush")
+        )?;
+    }
+    outbuf += "\tmov\trax, 1
+\tmov\trdi, 1
+\tsyscall
+\tret
+";
+
+    let mut count_ops = 0;
+
+    for func in program.funcs.iter().filter(|func| !func.is_import()) {
+        writeln!(outbuf, "fn_{}:", func.ident)?;
+        for op in &func.body {
+            write_op(op, &mut count_ops, &mut outbuf, &program.ctx, syntax)?;
+        }
+    }
+    for (name, signature) in &exports {
+        write_export(name, *signature, syntax, &mut outbuf)?;
+    }
+
+    // Objects are linked with the C library, whose startup code calls `main` and flushes its
+    // buffers when it returns.
+    if entry && emit != Emit::Exe {
+        let signature = Signature {
+            inputs: 0,
+            outputs: 0,
+        };
+        write_export("main", signature, syntax, &mut outbuf)?;
+    } else if entry {
+        outbuf += "main:
+\tmov\trax, ret_stack_end
+\tsub\trax, 8
+\tmov\tqword [ret_stack_rsp], rax
+\tmov\tqword [rax], RET_MAIN
+\tcall\tfn_main
+RET_MAIN:
+\tmov\trax, 60
+\tmov\trdi, 0
+\tsyscall
+";
+    }
+    outbuf += match (syntax, emit) {
+        (Syntax::Fasm, Emit::Exe) => "segment readable\n",
+        (Syntax::Fasm, _) => "section '.rodata'\n",
+        (Syntax::Nasm, _) => "section .rodata\n",
+    };
+    for (i, s) in program.ctx.strings.iter().enumerate() {
+        let mut s_bytes = String::new();
+        for b in s.as_bytes() {
+            write!(&mut s_bytes, "{b},").unwrap();
+        }
+        match s_bytes.trim_end_matches(',') {
+            "" => writeln!(outbuf, "str_{i}:")?,
+            bytes => writeln!(outbuf, "str_{i}: db {bytes}")?,
+        }
+    }
+    if !ret_stack {
+        return Ok(outbuf);
+    }
+
+    Ok(outbuf
+        + match (syntax, emit) {
+            (Syntax::Fasm, Emit::Exe) => {
+                "segment readable writable
+ret_stack_rsp: rq 1
+ret_stack: rb 65536
+ret_stack_end:
+"
+            }
+            (Syntax::Fasm, _) => {
+                "section '.bss' writeable
+ret_stack_rsp: rq 1
+ret_stack: rb 65536
+ret_stack_end:
+"
+            }
+            (Syntax::Nasm, _) => {
+                "section .bss
+ret_stack_rsp: resq 1
+ret_stack: resb 65536
+ret_stack_end:
+"
+            }
+        })
+}
+
+/// An ELF executable, assembled with fasm.
+pub struct Executable;
+
+impl Backend for Executable {
+    fn name(&self) -> &'static str {
+        "x86_64-linux"
+    }
+
+    fn generate(&self, program: Program) -> Result<Artifact, Box<dyn Error>> {
+        Ok(Artifact::Text(generate(program, Syntax::Fasm, Emit::Exe)?))
+    }
+
+    fn intermediate(&self) -> Option<&'static str> {
+        Some("asm")
+    }
+
+    fn build(&self, artifact: &Path, out: &Path) -> Vec<Command> {
+        let mut fasm = Command::new("fasm");
+        fasm.arg(artifact).arg(out);
+        let mut chmod = Command::new("chmod");
+        chmod.arg("+x").arg(out);
+        vec![fasm, chmod]
+    }
+
+    fn run(&self, out: &Path) -> Option<Command> {
+        Some(Command::new(Path::new(".").join(out)))
+    }
+
+    fn arch(&self) -> Option<&'static str> {
+        Some("x86_64")
+    }
+
+    fn extern_fns(&self) -> bool {
+        true
+    }
+
+    fn generate_object(&self, program: Program) -> Result<Artifact, Box<dyn Error>> {
+        check_c_names(&program)?;
+        Ok(Artifact::Text(generate(program, Syntax::Fasm, Emit::Obj)?))
+    }
+
+    fn build_object(&self, artifact: &Path, out: &Path) -> Vec<Command> {
+        let mut fasm = Command::new("fasm");
+        fasm.arg(artifact).arg(out);
+        vec![fasm]
+    }
+
+    fn link(&self, objects: &[PathBuf], out: &Path) -> Option<Command> {
+        Some(link_c(objects, out))
+    }
+
+    fn archive(&self, objects: &[PathBuf], out: &Path) -> Option<Command> {
+        Some(archive(objects, out))
+    }
+}
+
+/// The fasm source of [`Executable`], for assembling elsewhere.
+pub struct Source;
+
+impl Backend for Source {
+    fn name(&self) -> &'static str {
+        "x86_64-fasm"
+    }
+
+    fn extension(&self) -> Option<&'static str> {
+        Some("asm")
+    }
+
+    fn generate(&self, program: Program) -> Result<Artifact, Box<dyn Error>> {
+        Ok(Artifact::Text(generate(program, Syntax::Fasm, Emit::Exe)?))
+    }
+
+    fn arch(&self) -> Option<&'static str> {
+        Some("x86_64")
+    }
+
+    fn extern_fns(&self) -> bool {
+        true
+    }
+
+    fn generate_object(&self, program: Program) -> Result<Artifact, Box<dyn Error>> {
+        check_c_names(&program)?;
+        Ok(Artifact::Text(generate(program, Syntax::Fasm, Emit::Obj)?))
+    }
+}
+
+/// An ELF executable, assembled with NASM and linked with `ld`.
+pub struct Nasm;
+
+impl Backend for Nasm {
+    fn name(&self) -> &'static str {
+        "x86_64-nasm"
+    }
+
+    fn generate(&self, program: Program) -> Result<Artifact, Box<dyn Error>> {
+        Ok(Artifact::Text(generate(program, Syntax::Nasm, Emit::Exe)?))
+    }
+
+    fn intermediate(&self) -> Option<&'static str> {
+        Some("asm")
+    }
+
+    fn build(&self, artifact: &Path, out: &Path) -> Vec<Command> {
+        let object = out.with_extension("o");
+        let mut build = self.build_object(artifact, &object);
+        build.push(link(&[object], out));
+        build
+    }
+
+    fn run(&self, out: &Path) -> Option<Command> {
+        Some(Command::new(Path::new(".").join(out)))
+    }
+
+    fn arch(&self) -> Option<&'static str> {
+        Some("x86_64")
+    }
+
+    fn extern_fns(&self) -> bool {
+        true
+    }
+
+    fn generate_object(&self, program: Program) -> Result<Artifact, Box<dyn Error>> {
+        check_c_names(&program)?;
+        Ok(Artifact::Text(generate(program, Syntax::Nasm, Emit::Obj)?))
+    }
+
+    fn build_object(&self, artifact: &Path, out: &Path) -> Vec<Command> {
+        let mut nasm = Command::new("nasm");
+        nasm.args(["-f", "elf64", "-o"]).arg(out).arg(artifact);
+        vec![nasm]
+    }
+
+    fn link(&self, objects: &[PathBuf], out: &Path) -> Option<Command> {
+        Some(link_c(objects, out))
+    }
+
+    fn archive(&self, objects: &[PathBuf], out: &Path) -> Option<Command> {
+        Some(archive(objects, out))
+    }
+}
+
+/// Archive `objects` into a static library at `out`.
+pub fn archive(objects: &[PathBuf], out: &Path) -> Command {
+    let mut ar = Command::new("ar");
+    ar.arg("rcs").arg(out).args(objects);
+    ar
+}
+
+/// Link `objects` and the C library into an executable at `out` with `$CC` (or `cc`), whose
+/// startup code calls `main`. The generated code isn't position independent, so neither is the
+/// executable.
+pub fn link_c(objects: &[PathBuf], out: &Path) -> Command {
+    let mut cc = Command::new(std::env::var("CC").unwrap_or_else(|_| "cc".to_owned()));
+    cc.args(["-no-pie", "-o"]).arg(out).args(objects);
+    cc
+}
+
+/// Link `objects` into a static executable at `out`, entering at `main`.
+pub fn link(objects: &[PathBuf], out: &Path) -> Command {
+    let mut ld = Command::new("ld");
+    ld.args(["-e", "main", "-o"]).arg(out).args(objects);
+    ld
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{codegen::test::check_snapshots, parse, Lexer};
+
+    use super::*;
+
+    #[test]
+    fn snapshots() {
+        check_snapshots(&Executable);
+        check_snapshots(&Nasm);
+    }
+
+    #[test]
+    fn c_symbols() {
+        let source = "extern fn rax 0 -- 0\nexport fn div 0 -- 0 in rax end\n";
+        let mut program = Program::default();
+        program.ctx.object = true;
+        program.ctx.allow_extern = true;
+        parse::parse_items(&mut Lexer::new(source, None), &mut program).unwrap();
+        let fasm = generate(program.clone(), Syntax::Fasm, Emit::Obj).unwrap();
+        for line in [
+            "extrn 'rax' as c_rax",
+            "public c_div as 'div'",
+            "\tcall\tc_rax\n",
+        ] {
+            assert!(fasm.contains(line), "{line:?} in\n{fasm}");
+        }
+        let nasm = generate(program, Syntax::Nasm, Emit::Obj).unwrap();
+        for line in ["extern $rax", "global $div", "\tcall\t$rax\n"] {
+            assert!(nasm.contains(line), "{line:?} in\n{nasm}");
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use super::*;
+
+/// The address the image is linked at and loaded to, the conventional 1 MiB.
+const LOAD_ADDR: u32 = 0x10_0000;
+/// The size of the data stack, which is the native stack like on the other x86-64 targets.
+const STACK_BYTES: usize = 1 << 20;
+/// The status QEMU exits with once the program has finished: the `isa-debug-exit` device exits
+/// with `(value << 1) | 1` for the value written to it, which is `0x10`.
+const QEMU_SUCCESS: i32 = (0x10 << 1) | 1;
+
+/// The headers for both versions of Multiboot. Multiboot 2 is what GRUB boots with `multiboot2`,
+/// while QEMU's `-kernel` only understands the original. Since the image is flat, both give the
+/// addresses to load it at and the end of the `.bss` for the loader to zero.
+const HEADERS: &str = "\
+\t.section\t.text
+\t.code32
+\t.globl\tstart
+multiboot1_header:
+\t.long\t0x1badb002
+\t.long\t0x00010000
+\t.long\t-(0x1badb002 + 0x00010000)
+\t.long\tmultiboot1_header
+\t.long\timage_start
+\t.long\tdata_end
+\t.long\tbss_end
+\t.long\tstart
+\t.balign\t8
+multiboot2_header:
+\t.long\t0xe85250d6
+\t.long\t0
+\t.long\tmultiboot2_header_end - multiboot2_header
+\t.long\t-(0xe85250d6 + (multiboot2_header_end - multiboot2_header))
+\t.word\t2, 0
+\t.long\t24
+\t.long\tmultiboot2_header
+\t.long\timage_start
+\t.long\tdata_end
+\t.long\tbss_end
+\t.word\t3, 0
+\t.long\t12
+\t.long\tstart
+\t.balign\t8
+\t.word\t0, 0
+\t.long\t8
+multiboot2_header_end:
+";
+
+/// Switch from the 32-bit protected mode that the loader leaves us in to long mode, identity
+/// mapping the first GiB with 2 MiB pages, and set up the serial port and the screen.
+const BOOT: &str = "\
+start:
+\tcli
+\tmovl\t$stack_end, %esp
+\tmovl\t$pdpt, %eax
+\torl\t$3, %eax
+\tmovl\t%eax, pml4
+\tmovl\t$pd, %eax
+\torl\t$3, %eax
+\tmovl\t%eax, pdpt
+\txorl\t%ecx, %ecx
+1:
+\tmovl\t%ecx, %eax
+\tshll\t$21, %eax
+\torl\t$0x83, %eax
+\tmovl\t%eax, pd(,%ecx,8)
+\tincl\t%ecx
+\tcmpl\t$512, %ecx
+\tjne\t1b
+\tmovl\t$pml4, %eax
+\tmovl\t%eax, %cr3
+\tmovl\t%cr4, %eax
+\torl\t$0x20, %eax
+\tmovl\t%eax, %cr4
+\tmovl\t$0xc0000080, %ecx
+\trdmsr
+\torl\t$0x100, %eax
+\twrmsr
+\tmovl\t%cr0, %eax
+\torl\t$0x80000001, %eax
+\tmovl\t%eax, %cr0
+\tlgdt\tgdt_ptr
+\tljmp\t$8, $long_mode
+\t.code64
+long_mode:
+\txorl\t%eax, %eax
+\tmovw\t%ax, %ds
+\tmovw\t%ax, %es
+\tmovw\t%ax, %ss
+\tmovw\t%ax, %fs
+\tmovw\t%ax, %gs
+\tmovq\t$stack_end, %rsp
+\tmovw\t$0x3f9, %dx\t\t# Disable the serial port's interrupts.
+\tmovb\t$0x00, %al
+\toutb\t%al, %dx
+\tmovw\t$0x3fb, %dx\t\t# Set the divisor for 38400 baud.
+\tmovb\t$0x80, %al
+\toutb\t%al, %dx
+\tmovw\t$0x3f8, %dx
+\tmovb\t$0x03, %al
+\toutb\t%al, %dx
+\tmovw\t$0x3f9, %dx
+\tmovb\t$0x00, %al
+\toutb\t%al, %dx
+\tmovw\t$0x3fb, %dx\t\t# 8 bits, no parity, one stop bit.
+\tmovb\t$0x03, %al
+\toutb\t%al, %dx
+\tmovw\t$0x3fa, %dx\t\t# Enable and clear the FIFOs.
+\tmovb\t$0xc7, %al
+\toutb\t%al, %dx
+\tmovl\t$0xb8000, %edi\t\t# Clear the screen.
+\tmovq\t$0x0f200f200f200f20, %rax
+\tmovl\t$500, %ecx
+\trep stosq
+\tmovq\t$ret_stack_end, %rax
+\tsubq\t$8, %rax
+\tmovq\t%rax, ret_stack_rsp
+\tmovq\t$RET_MAIN, (%rax)
+\tjmp\tfn_main
+RET_MAIN:
+\tmovw\t$0xf4, %dx\t\t# Exit QEMU if it has an `isa-debug-exit` device.
+\tmovl\t$0x10, %eax\t\t# QEMU then exits with `(0x10 << 1) | 1`.
+\toutl\t%eax, %dx
+halt:
+\tcli
+\thlt
+\tjmp\thalt
+";
+
+/// Write `%rdx` bytes from `%rsi` to the first serial port and to the VGA text buffer, scrolling
+/// the screen once it is full.
+const WRITE: &str = "\
+write:
+\tmovq\t%rdx, %rcx
+\ttestq\t%rcx, %rcx
+\tjz\t.Lwrite_end
+.Lwrite_byte:
+\tmovb\t(%rsi), %r8b
+\tmovw\t$0x3fd, %dx
+.Lserial_wait:
+\tinb\t%dx, %al
+\ttestb\t$0x20, %al
+\tjz\t.Lserial_wait
+\tmovw\t$0x3f8, %dx
+\tmovb\t%r8b, %al
+\toutb\t%al, %dx
+\tmovq\tvga_pos, %rdi
+\tcmpb\t$10, %r8b
+\tjne\t.Lvga_char
+\tmovq\t%rdi, %rax
+\txorl\t%edx, %edx
+\tmovq\t$80, %r9
+\tdivq\t%r9
+\tincq\t%rax
+\timulq\t$80, %rax, %rdi
+\tjmp\t.Lvga_scroll
+.Lvga_char:
+\tmovb\t%r8b, %al
+\tmovb\t$0x0f, %ah
+\tmovw\t%ax, 0xb8000(,%rdi,2)
+\tincq\t%rdi
+.Lvga_scroll:
+\tcmpq\t$2000, %rdi
+\tjb\t.Lvga_done
+\tpushq\t%rsi
+\tpushq\t%rcx
+\tmovl\t$0xb80a0, %esi
+\tmovl\t$0xb8000, %edi
+\tmovl\t$480, %ecx
+\trep movsq
+\tmovq\t$0x0f200f200f200f20, %rax
+\tmovl\t$20, %ecx
+\trep stosq
+\tpopq\t%rcx
+\tpopq\t%rsi
+\tmovq\t$1920, %rdi
+.Lvga_done:
+\tmovq\t%rdi, vga_pos
+\tincq\t%rsi
+\tdecq\t%rcx
+\tjnz\t.Lwrite_byte
+.Lwrite_end:
+\tret
+";
+
+pub fn generate(program: Program) -> Result<String, fmt::Error> {
+    let mut outbuf = format!("image_start:\n{HEADERS}{BOOT}{WRITE}");
+    gas_x86_64_linux::write_funcs(&program, &mut outbuf)?;
+    outbuf += "\
+\t.balign\t8
+gdt:
+\t.quad\t0
+\t.quad\t0x00af9a000000ffff
+\t.quad\t0x00cf92000000ffff
+gdt_ptr:
+\t.word\tgdt_ptr - gdt - 1
+\t.long\tgdt
+";
+    gas_x86_64_linux::write_data(&program, &mut outbuf)?;
+    // The `.bss` comes last, so everything before it is in the image.
+    writeln!(
+        outbuf,
+        "\t.section\t.rodata
+data_end:
+\t.bss
+\t.balign\t4096
+pml4:\t.skip\t4096
+pdpt:\t.skip\t4096
+pd:\t.skip\t4096
+vga_pos:\t.skip\t8
+stack:\t.skip\t{STACK_BYTES}
+stack_end:
+bss_end:"
+    )?;
+    Ok(outbuf)
+}
+
+/// A flat image for x86-64 that can be booted by Multiboot and Multiboot 2 loaders, assembled
+/// with GNU as and linked with `ld`. Output goes to the first serial port and the screen.
+pub struct Image;
+
+impl Backend for Image {
+    fn name(&self) -> &'static str {
+        "x86_64-multiboot"
+    }
+
+    fn extension(&self) -> Option<&'static str> {
+        Some("bin")
+    }
+
+    fn arch(&self) -> Option<&'static str> {
+        Some("x86_64")
+    }
+
+    fn generate(&self, program: Program) -> Result<Artifact, Box<dyn Error>> {
+        Ok(Artifact::Text(generate(program)?))
+    }
+
+    fn intermediate(&self) -> Option<&'static str> {
+        Some("s")
+    }
+
+    fn build(&self, artifact: &Path, out: &Path) -> Vec<Command> {
+        let object = out.with_extension("o");
+        let mut as_ = Command::new("as");
+        as_.arg("-o").arg(&object).arg(artifact);
+        let mut ld = Command::new("ld");
+        ld.arg(format!("-Ttext={LOAD_ADDR:#x}"))
+            .args(["-e", "start", "--oformat", "binary", "-o"])
+            .arg(out)
+            .arg(&object);
+        vec![as_, ld]
+    }
+
+    /// Boots the image in QEMU, with the serial port on stdout.
+    fn run(&self, out: &Path) -> Option<Command> {
+        let mut qemu = Command::new("qemu-system-x86_64");
+        qemu.args(["-display", "none", "-serial", "stdio"])
+            .args(["-device", "isa-debug-exit,iobase=0xf4,iosize=4", "-kernel"])
+            .arg(out);
+        Some(qemu)
+    }
+
+    /// QEMU exits with an odd status even when the program has finished, so that one becomes 0.
+    fn exit_code(&self, code: i32) -> i32 {
+        if code == QEMU_SUCCESS {
+            0
+        } else {
+            code
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::codegen::test::check_snapshots;
+
+    use super::*;
+
+    #[test]
+    fn snapshots() {
+        check_snapshots(&Image);
+    }
+
+    #[test]
+    fn exit_code() {
+        assert_eq!(Image.exit_code(QEMU_SUCCESS), 0);
+        // A write of 0, which the images used to do, or QEMU failing to start.
+        assert_eq!(Image.exit_code(1), 1);
+    }
+}
@@ -0,0 +1,401 @@
+use super::*;
+
+const CELL_WIDTH: CellWidth = CellWidth::new(32);
+/// The size of a cell in bytes. Every offset into the data and return stacks is a multiple of it.
+pub const CELL_SIZE: usize = CELL_WIDTH.bytes();
+
+#[allow(clippy::too_many_lines)]
+fn write_op(op: &Op, count_ops: &mut usize, buffer: &mut String) -> fmt::Result {
+    match op {
+        Op::CallFn(index) => write!(
+            buffer,
+            "\tmovl\tret_stack_rsp, %eax\t# Op::CallFn({index})
+\tsubl\t$4, %eax
+\tmovl\t%eax, ret_stack_rsp
+\tmovl\t$RET{count_ops}, (%eax)
+\tjmp\tfn_{index}
+RET{count_ops}:
+\tmovl\tret_stack_rsp, %eax
+\taddl\t$4, %eax
+\tmovl\t%eax, ret_stack_rsp
+"
+        )?,
+        // Small optimisation for the 0 case.
+        Op::Ret(0) => write!(
+            buffer,
+            "\tmovl\tret_stack_rsp, %eax\t# Op::Ret(0)
+\tjmp\t*(%eax)
+"
+        )?,
+        Op::Ret(count) => write!(
+            buffer,
+            "\tmovl\tret_stack_rsp, %eax\t# Op::Ret({count})
+\taddl\t${}, %eax
+\tmovl\t%eax, ret_stack_rsp
+\tjmp\t*(%eax)
+",
+            count * CELL_SIZE
+        )?,
+        Op::Bind {
+            count, peek, body, ..
+        } => {
+            write!(
+                buffer,
+                "\tmovl\tret_stack_rsp, %eax\t# Op::Bind({count})
+\tsubl\t${}, %eax
+\tmovl\t%eax, ret_stack_rsp
+",
+                count * CELL_SIZE
+            )?;
+            for i in 0..*count {
+                write!(
+                    buffer,
+                    "\tmovl\t{0}(%esp), %ebx
+\tmovl\t%ebx, {0}(%eax)
+",
+                    i * CELL_SIZE
+                )?;
+            }
+            if !peek {
+                writeln!(buffer, "\taddl\t${}, %esp", count * CELL_SIZE)?;
+            }
+            for op in body {
+                write_op(op, count_ops, buffer)?;
+            }
+            // Remove the bindings from the return stack.
+            write!(
+                buffer,
+                "\tmovl\tret_stack_rsp, %eax
+\taddl\t${}, %eax
+\tmovl\t%eax, ret_stack_rsp
+",
+                count * CELL_SIZE
+            )?;
+        }
+        Op::PushBind(index) => write!(
+            buffer,
+            "\tmovl\tret_stack_rsp, %eax\t# Op::PushBind({index})
+\tpushl\t{}(%eax)
+",
+            index * CELL_SIZE
+        )?,
+        Op::PushInt(val) => writeln!(buffer, "\tpushl\t${val}\t\t\t# Op::PushInt({val})")?,
+        Op::PushStrPtr(index) => {
+            writeln!(buffer, "\tpushl\t$str_{index}\t\t# Op::PushStrPtr({index})")?;
+        }
+        Op::Plus => write!(
+            buffer,
+            "\tpopl\t%eax\t\t\t# Op::Plus
+\taddl\t%eax, (%esp)
+",
+        )?,
+        Op::Minus => write!(
+            buffer,
+            "\tpopl\t%eax\t\t\t# Op::Minus
+\tsubl\t%eax, (%esp)
+",
+        )?,
+        Op::DivMod => write!(
+            buffer,
+            "\tpopl\t%ebx\t\t\t# Op::DivMod
+\tpopl\t%eax
+\txorl\t%edx, %edx
+\tdivl\t%ebx
+\tpushl\t%eax
+\tpushl\t%edx
+",
+        )?,
+        Op::Dup => writeln!(buffer, "\tpushl\t(%esp)\t\t\t# Op::Dup")?,
+        Op::Drop => writeln!(buffer, "\taddl\t${CELL_SIZE}, %esp\t\t# Op::Drop")?,
+        Op::Swap => write!(
+            buffer,
+            "\tpopl\t%eax\t\t\t# Op::Swap
+\tpopl\t%ebx
+\tpushl\t%eax
+\tpushl\t%ebx
+",
+        )?,
+        Op::Over => writeln!(buffer, "\tpushl\t{CELL_SIZE}(%esp)\t\t\t# Op::Over")?,
+        Op::Equals | Op::Neq | Op::GreaterThan | Op::LessThan => {
+            // The flags are set by comparing the second value with the top one.
+            let (name, set) = match op {
+                Op::Equals => ("Equals", "sete"),
+                Op::Neq => ("Neq", "setne"),
+                Op::GreaterThan => ("GreaterThan", "seta"),
+                _ => ("LessThan", "setb"),
+            };
+            write!(
+                buffer,
+                "\tpopl\t%eax\t\t\t# Op::{name}
+\tpopl\t%ebx
+\txorl\t%ecx, %ecx
+\tcmpl\t%eax, %ebx
+\t{set}\t%cl
+\tpushl\t%ecx
+"
+            )?;
+        }
+        Op::Not => write!(
+            buffer,
+            "\tmovl\t$1, %eax\t\t# Op::Not
+\tsubl\t(%esp), %eax
+\tmovl\t%eax, (%esp)
+"
+        )?,
+        Op::Or | Op::And => {
+            let (name, instr) = if matches!(op, Op::Or) {
+                ("Or", "orb")
+            } else {
+                ("And", "andb")
+            };
+            write!(
+                buffer,
+                "\tpopl\t%eax\t\t\t# Op::{name}
+\tpopl\t%ebx
+\txorl\t%ecx, %ecx
+\txorl\t%edx, %edx
+\tcmpl\t$1, %eax
+\tsete\t%cl
+\tcmpl\t$1, %ebx
+\tsete\t%dl
+\t{instr}\t%dl, %cl
+\tpushl\t%ecx
+"
+            )?;
+        }
+        Op::ReadByte => write!(
+            buffer,
+            "\tpopl\t%ebx\t\t\t# Op::ReadByte
+\tmovzbl\t(%ebx), %eax
+\tpushl\t%eax
+"
+        )?,
+        Op::If(ops) => {
+            let jump_to = *count_ops;
+            *count_ops += 1;
+            write!(
+                buffer,
+                "\tpopl\t%eax\t\t\t# Op::If
+\tcmpl\t$1, %eax
+\tjne\tF{jump_to}
+"
+            )?;
+            for op in ops {
+                write_op(op, count_ops, buffer)?;
+            }
+            writeln!(buffer, "F{jump_to}:")?;
+        }
+        Op::While { condn, body } => {
+            let condn_jump = *count_ops;
+            let end_jump = *count_ops + 1;
+            *count_ops += 2;
+            writeln!(buffer, "F{condn_jump}:\t\t\t\t\t# Op::While")?;
+            for op in condn {
+                write_op(op, count_ops, buffer)?;
+            }
+            // Check the while condition and jump to end if not met.
+            write!(
+                buffer,
+                "\tpopl\t%eax
+\tcmpl\t$1, %eax
+\tjne\tF{end_jump}
+"
+            )?;
+            for op in body {
+                write_op(op, count_ops, buffer)?;
+            }
+            writeln!(buffer, "\tjmp\tF{condn_jump}\nF{end_jump}:")?;
+        }
+        Op::Print => write!(buffer, "\tpopl\t%eax\t\t\t# Op::Print\n\tcall\tprint\n")?,
+        Op::Puts => write!(
+            buffer,
+            "\tmovl\t$4, %eax\t\t# Op::Puts
+\tmovl\t$1, %ebx
+\tpopl\t%ecx
+\tpopl\t%edx
+\tint\t$0x80
+"
+        )?,
+        // Inline assembly is in Intel syntax on every x86 target.
+        Op::Asm { signature, lines } => {
+            writeln!(
+                buffer,
+                "\t.intel_syntax\tnoprefix\t# Op::Asm {} -- {}",
+                signature.inputs, signature.outputs
+            )?;
+            for line in lines {
+                writeln!(buffer, "\t{line}")?;
+            }
+            writeln!(buffer, "\t.att_syntax\tprefix")?;
+        }
+    }
+    *count_ops += 1;
+    Ok(())
+}
+
+pub fn generate(program: Program) -> Result<String, fmt::Error> {
+    let mut outbuf = String::from(
+        "\t.globl\tmain
+\t.text
+",
+    );
+
+    // Write the decimal digits of %eax backwards from the end of a buffer on the stack.
+    outbuf += "print:
+\tsubl\t$16, %esp
+\tleal\t15(%esp), %edi
+\tmovb\t$10, (%edi)
+\tmovl\t%edi, %ecx
+\tmovl\t$10, %ebx
+.L2:
+\txorl\t%edx, %edx
+\tdivl\t%ebx
+\taddb\t$48, %dl
+\tdecl\t%ecx
+\tmovb\t%dl, (%ecx)
+\ttestl\t%eax, %eax
+\tjnz\t.L2
+\tmovl\t%edi, %edx
+\tsubl\t%ecx, %edx
+\tincl\t%edx
+\tmovl\t$1, %ebx
+\tmovl\t$4, %eax
+\tint\t$0x80
+\taddl\t$16, %esp
+\tret
+";
+
+    let mut count_ops = 0;
+
+    for (i, func) in program.funcs.iter().enumerate() {
+        writeln!(outbuf, "fn_{i}:\t\t\t\t\t# {}", func.ident)?;
+        for op in &func.body {
+            write_op(op, &mut count_ops, &mut outbuf)?;
+        }
+    }
+
+    write!(
+        outbuf,
+        "main:
+\tmovl\t$ret_stack_end, %eax
+\tsubl\t$4, %eax
+\tmovl\t%eax, ret_stack_rsp
+\tmovl\t$RET_MAIN, (%eax)
+\tjmp\tfn_{}
+RET_MAIN:
+\tmovl\t$1, %eax
+\txorl\t%ebx, %ebx
+\tint\t$0x80
+\t.section\t.rodata
+",
+        program.ctx.lookup["main"]
+    )?;
+    for (i, s) in program.ctx.strings.iter().enumerate() {
+        let mut s_bytes = String::new();
+        for b in s.as_bytes() {
+            write!(&mut s_bytes, "{b},")?;
+        }
+        match s_bytes.trim_end_matches(',') {
+            "" => writeln!(outbuf, "str_{i}:")?,
+            bytes => writeln!(outbuf, "str_{i}:\t.byte\t{bytes}")?,
+        }
+    }
+
+    Ok(outbuf
+        + "\t.bss
+\t.balign\t4
+ret_stack_rsp:\t.skip\t4
+ret_stack:\t.skip\t65536
+ret_stack_end:
+")
+}
+
+/// The prefix of the binutils used to build for 32-bit x86, which is only needed when cross
+/// compiling from other architectures.
+fn binutils_prefix() -> &'static str {
+    if cfg!(any(target_arch = "x86", target_arch = "x86_64")) {
+        ""
+    } else {
+        "i686-linux-gnu-"
+    }
+}
+
+/// A 32-bit ELF executable for i386, assembled with GNU as and linked with `ld`. Cells are 32
+/// bits, so programs with larger integer literals are rejected when they are parsed.
+pub struct Executable;
+
+impl Backend for Executable {
+    fn name(&self) -> &'static str {
+        "x86-linux"
+    }
+
+    fn cell_width(&self) -> CellWidth {
+        CELL_WIDTH
+    }
+
+    fn arch(&self) -> Option<&'static str> {
+        Some("x86")
+    }
+
+    fn generate(&self, program: Program) -> Result<Artifact, Box<dyn Error>> {
+        Ok(Artifact::Text(generate(program)?))
+    }
+
+    fn intermediate(&self) -> Option<&'static str> {
+        Some("s")
+    }
+
+    fn build(&self, artifact: &Path, out: &Path) -> Vec<Command> {
+        let object = out.with_extension("o");
+        let mut as_ = Command::new(format!("{}as", binutils_prefix()));
+        as_.args(["--32", "-o"]).arg(&object).arg(artifact);
+        let mut ld = Command::new(format!("{}ld", binutils_prefix()));
+        ld.args(["-m", "elf_i386", "-e", "main", "-o"])
+            .arg(out)
+            .arg(&object);
+        vec![as_, ld]
+    }
+
+    /// Runs the executable natively on x86 and x86-64, and under `qemu-i386` elsewhere.
+    fn run(&self, out: &Path) -> Option<Command> {
+        let out = Path::new(".").join(out);
+        if cfg!(any(target_arch = "x86", target_arch = "x86_64")) {
+            Some(Command::new(out))
+        } else {
+            let mut qemu = Command::new("qemu-i386");
+            qemu.arg(out);
+            Some(qemu)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        codegen::test::{check_runs, check_snapshots},
+        parse, Lexer,
+    };
+
+    use super::*;
+
+    #[test]
+    fn snapshots() {
+        check_snapshots(&Executable);
+    }
+
+    #[test]
+    fn run() {
+        check_runs(&Executable);
+    }
+
+    #[test]
+    fn large_literals() {
+        let source = "fn main in\n  4294967295 print\n  4294967296 print\nend\n";
+        let result =
+            parse::parse_tokens_for(&mut Lexer::new(source, None), Executable.cell_width());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "3:3: `4294967296` does not fit in a 32-bit cell"
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! Code coverage, recording how often each op is executed by the evaluator and reporting it in
+//! the lcov tracefile format.
+
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt::Write as _,
+    fs,
+    io::{self, Write},
+    process,
+};
+
+use crate::{
+    interp::{Code, Instr, Machine, RuntimeError},
+    parse, Lexer, Program,
+};
+
+pub struct Coverage {
+    /// The number of times each instruction was executed.
+    pub hits: Vec<u64>,
+    /// For each conditional jump, the number of times the guarded block was entered and the
+    /// number of times it was skipped.
+    pub branches: Vec<[u64; 2]>,
+}
+
+impl Coverage {
+    fn record(&mut self, machine: &Machine, pc: usize) {
+        self.hits[pc] += 1;
+        if let Instr::JumpUnless(to) = machine.code.instrs[pc] {
+            let skipped = machine.pc == Some(to);
+            self.branches[pc][usize::from(skipped)] += 1;
+        }
+    }
+
+    /// Write an lcov record for the source file of `program`.
+    pub fn write_lcov(&self, program: &Program, code: &Code, w: &mut impl Write) -> io::Result<()> {
+        let mut record = String::new();
+        let file = program.funcs.first().and_then(|f| f.location.file);
+        writeln!(record, "SF:{}", file.unwrap_or("-")).unwrap();
+
+        let mut hit_funcs = 0;
+        for (func, entry) in program.funcs.iter().zip(&code.entries) {
+            writeln!(record, "FN:{},{}", func.location.pos.0, func.ident).unwrap();
+            writeln!(record, "FNDA:{},{}", self.hits[*entry], func.ident).unwrap();
+            hit_funcs += usize::from(self.hits[*entry] > 0);
+        }
+        writeln!(record, "FNF:{}", program.funcs.len()).unwrap();
+        writeln!(record, "FNH:{hit_funcs}").unwrap();
+
+        // Only the code of the program's own functions is considered.
+        let end = code
+            .entries
+            .get(program.funcs.len())
+            .copied()
+            .unwrap_or(code.instrs.len());
+
+        let (mut found, mut hit) = (0, 0);
+        for (block, pc) in (0..end)
+            .filter(|pc| matches!(code.instrs[*pc], Instr::JumpUnless(_)))
+            .enumerate()
+        {
+            let line = code.debug[pc].location.pos.0;
+            for (branch, taken) in self.branches[pc].iter().enumerate() {
+                if self.hits[pc] == 0 {
+                    writeln!(record, "BRDA:{line},{block},{branch},-").unwrap();
+                } else {
+                    writeln!(record, "BRDA:{line},{block},{branch},{taken}").unwrap();
+                }
+                found += 1;
+                hit += usize::from(*taken > 0);
+            }
+        }
+        writeln!(record, "BRF:{found}").unwrap();
+        writeln!(record, "BRH:{hit}").unwrap();
+
+        // A line counts as executed as many times as its most executed op.
+        let mut lines = BTreeMap::new();
+        for pc in 0..end {
+            let count = lines.entry(code.debug[pc].location.pos.0).or_insert(0);
+            *count = self.hits[pc].max(*count);
+        }
+        for (line, count) in &lines {
+            writeln!(record, "DA:{line},{count}").unwrap();
+        }
+        writeln!(record, "LF:{}", lines.len()).unwrap();
+        writeln!(record, "LH:{}", lines.values().filter(|c| **c > 0).count()).unwrap();
+        writeln!(record, "end_of_record").unwrap();
+
+        w.write_all(record.as_bytes())
+    }
+}
+
+/// Run `program` to completion, writing its output to `out`, and record its coverage. If the
+/// program fails, the coverage up to the failure is returned alongside the error.
+pub fn cover<'src>(
+    program: &Program<'src>,
+    out: &mut impl Write,
+) -> (Machine<'src>, Coverage, Result<(), RuntimeError>) {
+    let mut machine = Machine::new(program);
+    let len = machine.code.instrs.len();
+    let mut coverage = Coverage {
+        hits: vec![0; len],
+        branches: vec![[0; 2]; len],
+    };
+    let result = machine.enter_main(program).and_then(|()| {
+        machine.run_observed(out, |machine, pc| {
+            coverage.record(machine, pc);
+            Ok::<_, RuntimeError>(())
+        })
+    });
+    (machine, coverage, result)
+}
+
+/// Run each of `files`, writing the coverage of all of them to `lcov_path`.
+pub fn run(files: &[String], lcov_path: &str, quiet: bool) -> Result<(), Box<dyn Error>> {
+    let mut lcov = Vec::new();
+    let mut failed = false;
+    for file in files {
+        let Ok(source) = fs::read_to_string(file) else {
+            eprintln!("Couldn't read file `{file}`");
+            process::exit(1);
+        };
+        let program = match parse::parse_tokens(&mut Lexer::new(&source, Some(file))) {
+            Ok(program) => program,
+            Err(e) => {
+                eprintln!("{e}");
+                failed = true;
+                continue;
+            }
+        };
+        if !program.funcs.iter().any(|f| f.ident == "main") {
+            eprintln!("[ERROR] No entry point `main` found in `{file}`.");
+            failed = true;
+            continue;
+        }
+
+        if !quiet {
+            println!("[INFO] Running `{file}`");
+        }
+        let (machine, coverage, result) = cover(&program, &mut io::stdout());
+        if let Err(e) = result {
+            eprintln!("[ERROR] {file}: {e}");
+            failed = true;
+        }
+        coverage.write_lcov(&program, &machine.code, &mut lcov)?;
+    }
+
+    fs::write(lcov_path, lcov).unwrap_or_else(|_| panic!("failed to write to {lcov_path}"));
+    if !quiet {
+        println!("[INFO] Wrote coverage to `{lcov_path}`");
+    }
+    if failed {
+        process::exit(1);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lcov() {
+        let source = "fn unused in end\n\nfn main in\n  1 if\n    2 print\n  end\nend\n";
+        let program = parse::parse_tokens(&mut Lexer::new(source, Some("t.rk"))).unwrap();
+        let (machine, coverage, result) = cover(&program, &mut io::sink());
+        result.unwrap();
+
+        let mut lcov = Vec::new();
+        coverage
+            .write_lcov(&program, &machine.code, &mut lcov)
+            .unwrap();
+        let lcov = String::from_utf8(lcov).unwrap();
+        for line in [
+            "SF:t.rk",
+            "FNDA:0,unused",
+            "FNDA:1,main",
+            "BRDA:4,0,0,1",
+            "BRDA:4,0,1,0",
+            "DA:1,0",
+            "DA:5,1",
+            "LF:4",
+            "LH:3",
+        ] {
+            assert!(
+                lcov.lines().any(|l| l == line),
+                "missing `{line}` in\n{lcov}"
+            );
+        }
+    }
+}
@@ -0,0 +1,464 @@
+//! A step debugger running programs on the in-process evaluator.
+
+use std::{
+    error::Error,
+    io::{self, Write},
+};
+
+use rustyline::{error::ReadlineError, Editor};
+
+use crate::{
+    interp::{Instr, Machine, RetEntry, State},
+    Program,
+};
+
+const HELP: &str = "\
+Commands:
+  r, run                   Restart the program from the beginning
+  b, break <FN|[FILE:]LINE>
+                           Set a breakpoint on a function or a line
+  d, delete <N>            Delete breakpoint N
+  breakpoints              List the breakpoints
+  s, step                  Execute one op
+  n, next                  Execute one op, stepping over function calls
+  f, finish                Run until the current function returns
+  c, continue              Run until a breakpoint is hit or the program exits
+  stack                    Show the data stack
+  rstack                   Show the return stack
+  bindings                 Show the `let` and `peek` bindings in scope
+  p, print <NAME>          Show the value of a binding
+  bt, backtrace            Show the active function calls
+  l, list                  Show the source around the current op
+  h, help                  Show this message
+  q, quit                  Exit the debugger
+
+An empty line repeats the previous command.
+";
+
+struct Breakpoint {
+    id: usize,
+    spec: String,
+    addrs: Vec<usize>,
+}
+
+struct Debugger<'a, 'src> {
+    program: &'a Program<'src>,
+    source: &'a str,
+    machine: Machine<'src>,
+    breakpoints: Vec<Breakpoint>,
+    next_id: usize,
+}
+
+pub fn run(program: &Program, source: &str) -> Result<(), Box<dyn Error>> {
+    let mut editor = Editor::<()>::new()?;
+    let mut debugger = Debugger {
+        program,
+        source,
+        machine: Machine::default(),
+        breakpoints: Vec::new(),
+        next_id: 1,
+    };
+    debugger.restart();
+
+    let mut last = String::new();
+    loop {
+        let line = match editor.readline("(rdb) ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e.into()),
+        };
+        let line = if line.trim().is_empty() {
+            last.clone()
+        } else {
+            editor.add_history_entry(line.trim());
+            line.trim().to_owned()
+        };
+        if line.is_empty() {
+            continue;
+        }
+
+        let (cmd, arg) = line
+            .split_once(char::is_whitespace)
+            .map_or((line.as_str(), ""), |(cmd, arg)| (cmd, arg.trim()));
+        match cmd {
+            "r" | "run" => debugger.restart(),
+            "b" | "break" => debugger.add_breakpoint(arg),
+            "d" | "delete" => debugger.delete_breakpoint(arg),
+            "breakpoints" => debugger.list_breakpoints(),
+            "s" | "step" => debugger.step(),
+            "n" | "next" => debugger.next(),
+            "f" | "finish" => debugger.finish(),
+            "c" | "continue" => debugger.resume(|_| false),
+            "stack" => println!("{:?}", debugger.machine.stack),
+            "rstack" => debugger.show_ret_stack(),
+            "bindings" => debugger.show_bindings(),
+            "p" | "print" => debugger.show_binding(arg),
+            "bt" | "backtrace" => debugger.show_backtrace(),
+            "l" | "list" => debugger.list(),
+            "h" | "help" => print!("{HELP}"),
+            "q" | "quit" => break,
+            _ => eprintln!("[ERROR] Unknown command `{cmd}`. Type `help` for more information."),
+        }
+        last = line;
+    }
+    Ok(())
+}
+
+impl Debugger<'_, '_> {
+    fn restart(&mut self) {
+        self.machine = Machine::new(self.program);
+        self.machine
+            .enter_main(self.program)
+            .expect("the return stack is empty");
+        self.show_location();
+    }
+
+    fn is_running(&self) -> bool {
+        if self.machine.pc.is_none() {
+            eprintln!("[ERROR] The program is not running. Use `run` to start it again.");
+        }
+        self.machine.pc.is_some()
+    }
+
+    fn step(&mut self) {
+        if self.is_running() {
+            self.resume(|_| true);
+        }
+    }
+
+    fn next(&mut self) {
+        let Some(pc) = self.machine.pc else {
+            self.is_running();
+            return;
+        };
+        if let Instr::CallFn(_) = self.machine.code.instrs[pc] {
+            // The call pushes its return address at `base`, so the call has returned once the
+            // return stack shrinks back to this size.
+            let base = self.machine.ret_stack.len();
+            self.resume(|m| m.ret_stack.len() <= base);
+        } else {
+            self.resume(|_| true);
+        }
+    }
+
+    fn finish(&mut self) {
+        if !self.is_running() {
+            return;
+        }
+        let base = self
+            .machine
+            .ret_stack
+            .iter()
+            .rposition(|entry| matches!(entry, RetEntry::Return(_)))
+            .expect("a running function has a return address");
+        self.resume(|m| m.ret_stack.len() <= base);
+    }
+
+    /// Execute instructions until `done` returns true, a breakpoint is reached, or the program
+    /// stops. At least one instruction is always executed, so that resuming from a breakpoint
+    /// doesn't stop at it again straight away.
+    fn resume(&mut self, mut done: impl FnMut(&Machine) -> bool) {
+        if !self.is_running() {
+            return;
+        }
+        let mut stdout = io::stdout();
+        loop {
+            let state = self.machine.step(&mut stdout);
+            stdout.flush().ok();
+            match state {
+                Ok(State::Running) => {}
+                Ok(State::Halted) => {
+                    println!("[INFO] Program exited");
+                    return;
+                }
+                Err(e) => {
+                    eprintln!("[ERROR] {e}");
+                    break;
+                }
+            }
+            if done(&self.machine) {
+                break;
+            }
+            let pc = self.machine.pc.expect("the machine is running");
+            if let Some(bp) = self.breakpoints.iter().find(|bp| bp.addrs.contains(&pc)) {
+                println!("Breakpoint {} ({})", bp.id, bp.spec);
+                break;
+            }
+        }
+        self.show_location();
+    }
+
+    fn add_breakpoint(&mut self, spec: &str) {
+        let code = &self.machine.code;
+        let addrs = if let Some(index) = self.program.ctx.lookup.get(spec) {
+            vec![code.entries[*index]]
+        } else {
+            let (file, line) = match spec.rsplit_once(':') {
+                Some((file, line)) => (Some(file), line),
+                None => (None, spec),
+            };
+            let Ok(line) = line.parse::<usize>() else {
+                eprintln!("[ERROR] `{spec}` is neither a function nor a line");
+                return;
+            };
+            // Only stop at the first instruction of each run of instructions from the line, so
+            // that stepping through a line isn't interrupted by its own breakpoint.
+            let on_line = |pc: usize| {
+                let location = &code.debug[pc].location;
+                location.pos.0 == line
+                    && file.is_none_or(|file| location.file.is_some_and(|f| f.ends_with(file)))
+            };
+            let funcs_end = code.entries.get(self.program.funcs.len()).copied();
+            (0..funcs_end.unwrap_or(code.instrs.len()))
+                .filter(|pc| {
+                    on_line(*pc)
+                        && (*pc == 0
+                            || !on_line(pc - 1)
+                            || code.func_at(pc - 1) != code.func_at(*pc))
+                })
+                .collect()
+        };
+        if addrs.is_empty() {
+            eprintln!("[ERROR] There is no code at `{spec}`");
+            return;
+        }
+        println!("Breakpoint {} at {spec}", self.next_id);
+        self.breakpoints.push(Breakpoint {
+            id: self.next_id,
+            spec: spec.to_owned(),
+            addrs,
+        });
+        self.next_id += 1;
+    }
+
+    fn delete_breakpoint(&mut self, id: &str) {
+        let count = self.breakpoints.len();
+        self.breakpoints
+            .retain(|bp| id.parse::<usize>() != Ok(bp.id));
+        if self.breakpoints.len() == count {
+            eprintln!("[ERROR] No breakpoint `{id}`");
+        }
+    }
+
+    fn list_breakpoints(&self) {
+        if self.breakpoints.is_empty() {
+            println!("No breakpoints");
+        }
+        for bp in &self.breakpoints {
+            println!("{:>3}  {}", bp.id, bp.spec);
+        }
+    }
+
+    /// Describe the instruction at `pc` and where it came from.
+    fn describe(&self, pc: usize) -> String {
+        let code = &self.machine.code;
+        let instr = match code.instrs[pc] {
+            Instr::CallFn(index) => format!("CallFn({})", code.idents[index]),
+            instr => format!("{instr:?}"),
+        };
+        format!(
+            "{} ({}): {instr}",
+            code.idents[code.func_at(pc)],
+            code.debug[pc].location
+        )
+    }
+
+    fn show_location(&self) {
+        let Some(pc) = self.machine.pc else {
+            return;
+        };
+        println!("{}", self.describe(pc));
+        let row = self.machine.code.debug[pc].location.pos.0;
+        self.show_lines(row..=row);
+    }
+
+    fn list(&self) {
+        let Some(pc) = self.machine.pc else {
+            self.is_running();
+            return;
+        };
+        let row = self.machine.code.debug[pc].location.pos.0;
+        self.show_lines(row.saturating_sub(5).max(1)..=row + 5);
+    }
+
+    fn show_lines(&self, rows: std::ops::RangeInclusive<usize>) {
+        let current = self
+            .machine
+            .pc
+            .map(|pc| self.machine.code.debug[pc].location.pos.0);
+        for (row, line) in self
+            .source
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line))
+            .filter(|(row, _)| rows.contains(row))
+        {
+            let marker = if Some(row) == current { '>' } else { ' ' };
+            println!("{marker}{row:>4} | {line}");
+        }
+    }
+
+    /// Describe each entry of the return stack, from the top.
+    fn ret_stack(&self) -> Vec<String> {
+        let code = &self.machine.code;
+        self.machine
+            .ret_stack
+            .iter()
+            .rev()
+            .map(|entry| match entry {
+                RetEntry::Binding(val) => val.to_string(),
+                RetEntry::Return(to) => match code.instrs.get(to - 1) {
+                    Some(_) => format!("<return to {}>", self.describe(to - 1)),
+                    None => "<exit>".to_owned(),
+                },
+            })
+            .collect()
+    }
+
+    fn show_ret_stack(&self) {
+        for entry in self.ret_stack() {
+            println!("  {entry}");
+        }
+    }
+
+    fn show_bindings(&self) {
+        let bindings = self.machine.bindings();
+        if bindings.is_empty() {
+            println!("No bindings in scope");
+        }
+        for (name, val) in bindings {
+            println!("  {name} = {val}");
+        }
+    }
+
+    fn show_binding(&self, name: &str) {
+        match self
+            .machine
+            .bindings()
+            .into_iter()
+            .rev()
+            .find(|(ident, _)| *ident == name)
+        {
+            Some((_, val)) => println!("{name} = {val}"),
+            None => eprintln!("[ERROR] `{name}` is not bound here"),
+        }
+    }
+
+    fn show_backtrace(&self) {
+        for (i, pc) in self.machine.backtrace().into_iter().enumerate() {
+            println!("#{i} {}", self.describe(pc));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parse, Lexer};
+
+    const SOURCE: &str = "\
+fn double in
+  dup +
+end
+
+fn main in
+  1 double
+  let x in
+    x double print
+  end
+  2 double print
+end
+";
+
+    fn debugger<'a, 'src>(program: &'a Program<'src>) -> Debugger<'a, 'src> {
+        let mut debugger = Debugger {
+            program,
+            source: SOURCE,
+            machine: Machine::default(),
+            breakpoints: Vec::new(),
+            next_id: 1,
+        };
+        debugger.restart();
+        debugger
+    }
+
+    /// The function and line that the debugger stopped at, if the program is still running.
+    fn stopped_at<'src>(debugger: &Debugger<'_, 'src>) -> Option<(&'src str, usize)> {
+        let code = &debugger.machine.code;
+        debugger
+            .machine
+            .pc
+            .map(|pc| (code.idents[code.func_at(pc)], code.debug[pc].location.pos.0))
+    }
+
+    #[test]
+    fn step_next_finish() {
+        let program = parse::parse_tokens(&mut Lexer::new(SOURCE, None)).unwrap();
+        let mut debugger = debugger(&program);
+        assert_eq!(stopped_at(&debugger), Some(("main", 6)));
+
+        // `step` goes into calls, and `finish` comes back out of them.
+        debugger.step();
+        assert!(matches!(
+            debugger.machine.code.instrs[debugger.machine.pc.unwrap()],
+            Instr::CallFn(_)
+        ));
+        debugger.step();
+        assert_eq!(stopped_at(&debugger), Some(("double", 2)));
+        debugger.finish();
+        assert_eq!(stopped_at(&debugger), Some(("main", 7)));
+        assert_eq!(debugger.machine.stack, [2]);
+
+        // `next` steps over calls.
+        debugger.restart();
+        debugger.step();
+        debugger.next();
+        assert_eq!(stopped_at(&debugger), Some(("main", 7)));
+        assert_eq!(debugger.machine.stack, [2]);
+    }
+
+    #[test]
+    fn breakpoints() {
+        let program = parse::parse_tokens(&mut Lexer::new(SOURCE, None)).unwrap();
+        let mut debugger = debugger(&program);
+        debugger.add_breakpoint("double");
+        debugger.add_breakpoint("10");
+        assert_eq!(debugger.breakpoints.len(), 2);
+
+        // A breakpoint on a line that has no code isn't added.
+        debugger.add_breakpoint("4");
+        assert_eq!(debugger.breakpoints.len(), 2);
+
+        debugger.resume(|_| false);
+        assert_eq!(stopped_at(&debugger), Some(("double", 2)));
+        assert_eq!(debugger.machine.stack, [1]);
+        debugger.resume(|_| false);
+        assert_eq!(stopped_at(&debugger), Some(("double", 2)));
+        assert_eq!(debugger.machine.stack, [2]);
+        debugger.resume(|_| false);
+        assert_eq!(stopped_at(&debugger), Some(("main", 10)));
+
+        // Without the breakpoint on `double`, the program runs to the end.
+        debugger.delete_breakpoint("1");
+        debugger.resume(|_| false);
+        assert_eq!(stopped_at(&debugger), None);
+    }
+
+    #[test]
+    fn ret_stack() {
+        let program = parse::parse_tokens(&mut Lexer::new(SOURCE, None)).unwrap();
+        let mut debugger = debugger(&program);
+        debugger.add_breakpoint("double");
+        debugger.resume(|_| false);
+        debugger.resume(|_| false);
+        let entries = debugger.ret_stack();
+        assert_eq!(entries.len(), 3, "{entries:?}");
+        assert!(
+            entries[0].starts_with("<return to main (8:"),
+            "{}",
+            entries[0]
+        );
+        assert_eq!(entries[1..], ["2", "<exit>"]);
+    }
+}
@@ -0,0 +1,856 @@
+//! Differential fuzzing of the backends against the evaluator.
+//!
+//! Random programs are generated from a small grammar that only produces well-formed, terminating
+//! code: every expression leaves exactly one value on the stack, every statement leaves the stack
+//! as it found it, loops have constant trip counts and functions can only call the functions
+//! defined before them. Each program is built for the target and run on the evaluator, and if the
+//! outputs differ the program is shrunk to a minimal example before being reported.
+
+use std::{
+    env,
+    error::Error,
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+    process::{self, Stdio},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use clap::Args;
+
+use crate::{
+    codegen::{Backend, Target},
+    interp::Machine,
+    parse::{self, CellWidth},
+    Lexer,
+};
+
+/// The comparison ops, which push a boolean.
+const COMPARISONS: [&str; 4] = ["=", "!=", "<", ">"];
+/// The ops that combine two arbitrary values.
+const ARITHMETIC: [&str; 2] = ["+", "-"];
+const LOGIC: [&str; 2] = ["and", "or"];
+const STRINGS: [&str; 4] = ["a", "rack", "hello, world", "0123456789"];
+
+/// A xorshift64* generator, which is plenty for picking grammar rules.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// A number in `0..n`.
+    fn below(&mut self, n: usize) -> usize {
+        usize::try_from(self.next() % n as u64).expect("the result is below `n`")
+    }
+
+    fn pick<T: Copy>(&mut self, choices: &[T]) -> T {
+        choices[self.below(choices.len())]
+    }
+
+    fn chance(&mut self, percent: usize) -> bool {
+        self.below(100) < percent
+    }
+}
+
+/// An expression leaves exactly one value on the stack.
+#[derive(Debug, Clone)]
+enum Expr {
+    Int(u64),
+    /// `a b op`
+    Binary(&'static str, Box<Self>, Box<Self>),
+    /// `a not`
+    Not(Box<Self>),
+    /// `a n op` where `op` is `/` or `%` and `n` is never zero.
+    DivBy(&'static str, Box<Self>, u64),
+    /// `a dup +`
+    Dup(Box<Self>),
+    /// `a b swap -`
+    Swap(Box<Self>, Box<Self>),
+    /// `a b over - -`
+    Over(Box<Self>, Box<Self>),
+    /// A binding, by its depth in the current scope.
+    Var(usize),
+    Call(usize, Vec<Self>),
+    /// `a.. let names.. in e end`
+    Let(Vec<Self>, Box<Self>),
+    /// `a peek name in e end +`
+    Peek(Box<Self>, Box<Self>),
+    /// The byte at `index` of a string literal.
+    Byte(&'static str, usize),
+}
+
+/// A statement leaves the stack as it found it.
+#[derive(Debug, Clone)]
+enum Stmt {
+    Print(Expr),
+    Puts(&'static str),
+    If(Expr, Vec<Self>),
+    /// Run the body a fixed number of times, with the counter on the stack.
+    While(u64, Vec<Self>),
+    Let(Vec<Expr>, Vec<Self>),
+}
+
+/// A function taking `arity` values and returning one.
+#[derive(Debug, Clone)]
+struct FuncDef {
+    arity: usize,
+    body: Vec<Stmt>,
+    /// A condition and a value to return early with.
+    early: Option<(Expr, Expr)>,
+    result: Expr,
+}
+
+#[derive(Debug, Clone)]
+struct Case {
+    funcs: Vec<FuncDef>,
+    main: Vec<Stmt>,
+}
+
+struct Generator {
+    rng: Rng,
+    /// The arities of the functions that may be called.
+    arities: Vec<usize>,
+    /// The width of a cell on the target, which every literal has to fit in.
+    cell_width: CellWidth,
+}
+
+impl Generator {
+    fn int(&mut self) -> u64 {
+        match self.rng.below(10) {
+            0 => self.cell_width.max() - self.rng.below(3) as u64,
+            1 => self.cell_width.wrap(self.rng.next()),
+            2 => 0,
+            _ => self.rng.below(20) as u64,
+        }
+    }
+
+    fn exprs(&mut self, count: usize, depth: usize, scope: usize) -> Vec<Expr> {
+        (0..count).map(|_| self.expr(depth, scope)).collect()
+    }
+
+    fn boxed(&mut self, depth: usize, scope: usize) -> Box<Expr> {
+        Box::new(self.expr(depth, scope))
+    }
+
+    fn expr(&mut self, depth: usize, scope: usize) -> Expr {
+        if depth == 0 || self.rng.chance(20) {
+            return match self.rng.below(6) {
+                0 | 1 if scope > 0 => Expr::Var(self.rng.below(scope)),
+                2 => {
+                    let s = self.rng.pick(&STRINGS);
+                    Expr::Byte(s, self.rng.below(s.len()))
+                }
+                _ => Expr::Int(self.int()),
+            };
+        }
+        let depth = depth - 1;
+        match self.rng.below(11) {
+            0 => Expr::Binary(
+                self.rng.pick(&ARITHMETIC),
+                self.boxed(depth, scope),
+                self.boxed(depth, scope),
+            ),
+            1 => Expr::Binary(
+                self.rng.pick(&COMPARISONS),
+                self.boxed(depth, scope),
+                self.boxed(depth, scope),
+            ),
+            2 => Expr::Binary(
+                self.rng.pick(&LOGIC),
+                self.boxed(depth, scope),
+                self.boxed(depth, scope),
+            ),
+            3 => Expr::Not(self.boxed(depth, scope)),
+            4 => Expr::DivBy(
+                self.rng.pick(&["/", "%"]),
+                self.boxed(depth, scope),
+                self.int().max(1),
+            ),
+            5 => Expr::Dup(self.boxed(depth, scope)),
+            6 => Expr::Swap(self.boxed(depth, scope), self.boxed(depth, scope)),
+            7 => Expr::Over(self.boxed(depth, scope), self.boxed(depth, scope)),
+            8 if !self.arities.is_empty() => {
+                let f = self.rng.below(self.arities.len());
+                Expr::Call(f, self.exprs(self.arities[f], depth, scope))
+            }
+            9 => {
+                let count = 1 + self.rng.below(3);
+                let values = self.exprs(count, depth, scope);
+                Expr::Let(values, self.boxed(depth, scope + count))
+            }
+            _ => Expr::Peek(self.boxed(depth, scope), self.boxed(depth, scope + 1)),
+        }
+    }
+
+    fn stmts(&mut self, depth: usize, scope: usize) -> Vec<Stmt> {
+        let count = 1 + self.rng.below(if depth == 0 { 2 } else { 4 });
+        (0..count).map(|_| self.stmt(depth, scope)).collect()
+    }
+
+    fn stmt(&mut self, depth: usize, scope: usize) -> Stmt {
+        if depth == 0 {
+            return Stmt::Print(self.expr(2, scope));
+        }
+        let depth = depth - 1;
+        match self.rng.below(8) {
+            0 => Stmt::Puts(self.rng.pick(&STRINGS)),
+            1 | 2 => Stmt::If(self.expr(2, scope), self.stmts(depth, scope)),
+            3 => Stmt::While(self.rng.below(4) as u64, self.stmts(depth, scope)),
+            4 => {
+                let count = 1 + self.rng.below(3);
+                let values = self.exprs(count, 2, scope);
+                Stmt::Let(values, self.stmts(depth, scope + count))
+            }
+            _ => Stmt::Print(self.expr(3, scope)),
+        }
+    }
+
+    fn case(&mut self) -> Case {
+        let mut funcs = Vec::new();
+        for _ in 0..self.rng.below(4) {
+            let arity = self.rng.below(4);
+            let func = FuncDef {
+                arity,
+                body: if self.rng.chance(50) {
+                    self.stmts(1, arity)
+                } else {
+                    Vec::new()
+                },
+                early: self
+                    .rng
+                    .chance(30)
+                    .then(|| (self.expr(2, arity), self.expr(2, arity))),
+                result: self.expr(3, arity),
+            };
+            funcs.push(func);
+            self.arities.push(arity);
+        }
+        let main = self.stmts(3, 0);
+        self.arities.clear();
+        Case { funcs, main }
+    }
+}
+
+/// Renders a case as Rack source. Rendering fails if a binding or function is referenced that
+/// is not in scope, which can happen to cases produced while shrinking.
+struct Renderer {
+    out: String,
+    indent: usize,
+    funcs: usize,
+}
+
+impl Renderer {
+    fn line(&mut self, s: &str) {
+        writeln!(self.out, "{:1$}{s}", "", self.indent * 2).unwrap();
+    }
+
+    fn expr(&self, e: &Expr, scope: usize) -> Option<String> {
+        Some(match e {
+            Expr::Int(n) => n.to_string(),
+            Expr::Binary(op, a, b) => {
+                format!("{} {} {op}", self.expr(a, scope)?, self.expr(b, scope)?)
+            }
+            Expr::Not(a) => format!("{} not", self.expr(a, scope)?),
+            Expr::DivBy(op, a, n) => format!("{} {n} {op}", self.expr(a, scope)?),
+            Expr::Dup(a) => format!("{} dup +", self.expr(a, scope)?),
+            Expr::Swap(a, b) => format!("{} {} swap -", self.expr(a, scope)?, self.expr(b, scope)?),
+            Expr::Over(a, b) => {
+                format!("{} {} over - -", self.expr(a, scope)?, self.expr(b, scope)?)
+            }
+            Expr::Var(i) if *i < scope => format!("b{i}"),
+            Expr::Call(f, args) if *f < self.funcs => {
+                let mut s = String::new();
+                for arg in args {
+                    write!(s, "{} ", self.expr(arg, scope)?).unwrap();
+                }
+                format!("{s}f{f}")
+            }
+            Expr::Var(_) | Expr::Call(..) => return None,
+            Expr::Let(values, body) => {
+                let mut s = String::new();
+                for value in values {
+                    write!(s, "{} ", self.expr(value, scope)?).unwrap();
+                }
+                let names = names(scope, values.len());
+                let body = self.expr(body, scope + values.len())?;
+                format!("{s}let {names} in {body} end")
+            }
+            Expr::Peek(a, body) => format!(
+                "{} peek b{scope} in {} end +",
+                self.expr(a, scope)?,
+                self.expr(body, scope + 1)?
+            ),
+            Expr::Byte(s, i) => format!("\"{s}\" swap drop {i} + @"),
+        })
+    }
+
+    fn stmts(&mut self, stmts: &[Stmt], scope: usize) -> Option<()> {
+        for stmt in stmts {
+            self.stmt(stmt, scope)?;
+        }
+        Some(())
+    }
+
+    fn stmt(&mut self, stmt: &Stmt, scope: usize) -> Option<()> {
+        match stmt {
+            Stmt::Print(e) => {
+                let e = self.expr(e, scope)?;
+                self.line(&format!("{e} print"));
+            }
+            Stmt::Puts(s) => self.line(&format!("\"{s}\\n\" puts")),
+            Stmt::If(condn, body) => {
+                let condn = self.expr(condn, scope)?;
+                self.line(&format!("{condn} if"));
+                self.block(body, scope)?;
+            }
+            Stmt::While(count, body) => {
+                self.line(&format!("{count} while dup 0 > do"));
+                self.indent += 1;
+                self.stmts(body, scope)?;
+                self.line("1 -");
+                self.indent -= 1;
+                self.line("end drop");
+            }
+            Stmt::Let(values, body) => {
+                let mut s = String::new();
+                for value in values {
+                    write!(s, "{} ", self.expr(value, scope)?).unwrap();
+                }
+                self.line(&format!("{s}let {} in", names(scope, values.len())));
+                self.block(body, scope + values.len())?;
+            }
+        }
+        Some(())
+    }
+
+    /// Render an indented block followed by `end`.
+    fn block(&mut self, body: &[Stmt], scope: usize) -> Option<()> {
+        self.indent += 1;
+        self.stmts(body, scope)?;
+        self.indent -= 1;
+        self.line("end");
+        Some(())
+    }
+
+    fn render(case: &Case) -> Option<String> {
+        let mut r = Self {
+            out: String::new(),
+            indent: 0,
+            funcs: 0,
+        };
+        for (i, func) in case.funcs.iter().enumerate() {
+            r.line(&format!("fn f{i} in"));
+            r.indent += 1;
+            if func.arity > 0 {
+                r.line(&format!("let {} in", names(0, func.arity)));
+                r.indent += 1;
+            }
+            r.stmts(&func.body, func.arity)?;
+            if let Some((condn, value)) = &func.early {
+                let condn = r.expr(condn, func.arity)?;
+                let value = r.expr(value, func.arity)?;
+                r.line(&format!("{condn} if {value} ret end"));
+            }
+            let result = r.expr(&func.result, func.arity)?;
+            r.line(&result);
+            if func.arity > 0 {
+                r.indent -= 1;
+                r.line("end");
+            }
+            r.indent -= 1;
+            r.line("end");
+            r.out.push('\n');
+            r.funcs += 1;
+        }
+        r.line("fn main in");
+        r.block(&case.main, 0)?;
+        Some(r.out)
+    }
+}
+
+fn names(from: usize, count: usize) -> String {
+    (from..from + count)
+        .map(|i| format!("b{i}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The ways of making an expression smaller, without looking inside its children.
+fn shrink_expr_here(e: &Expr) -> Vec<Expr> {
+    let mut out = Vec::new();
+    match e {
+        Expr::Int(0) => return out,
+        Expr::Int(n) => {
+            out.push(Expr::Int(0));
+            out.push(Expr::Int(n / 2));
+            return out;
+        }
+        Expr::Binary(_, a, b) | Expr::Swap(a, b) | Expr::Over(a, b) => {
+            out.push(a.as_ref().clone());
+            out.push(b.as_ref().clone());
+        }
+        Expr::Not(a) | Expr::DivBy(_, a, _) | Expr::Dup(a) | Expr::Let(_, a) | Expr::Peek(_, a) => {
+            out.push(a.as_ref().clone());
+        }
+        Expr::Call(_, args) => out.extend(args.iter().cloned()),
+        Expr::Var(_) | Expr::Byte(..) => {}
+    }
+    out.insert(0, Expr::Int(0));
+    out
+}
+
+/// Every expression that differs from `e` by a single shrinking step.
+fn shrink_expr(e: &Expr) -> Vec<Expr> {
+    let mut out = shrink_expr_here(e);
+    let boxed = |e: Expr| Box::new(e);
+    match e {
+        Expr::Int(_) | Expr::Var(_) | Expr::Byte(..) => {}
+        Expr::Binary(op, a, b) => {
+            out.extend(
+                shrink_expr(a)
+                    .into_iter()
+                    .map(|a| Expr::Binary(op, boxed(a), b.clone())),
+            );
+            out.extend(
+                shrink_expr(b)
+                    .into_iter()
+                    .map(|b| Expr::Binary(op, a.clone(), boxed(b))),
+            );
+        }
+        Expr::Swap(a, b) => {
+            out.extend(
+                shrink_expr(a)
+                    .into_iter()
+                    .map(|a| Expr::Swap(boxed(a), b.clone())),
+            );
+            out.extend(
+                shrink_expr(b)
+                    .into_iter()
+                    .map(|b| Expr::Swap(a.clone(), boxed(b))),
+            );
+        }
+        Expr::Over(a, b) => {
+            out.extend(
+                shrink_expr(a)
+                    .into_iter()
+                    .map(|a| Expr::Over(boxed(a), b.clone())),
+            );
+            out.extend(
+                shrink_expr(b)
+                    .into_iter()
+                    .map(|b| Expr::Over(a.clone(), boxed(b))),
+            );
+        }
+        Expr::Not(a) => out.extend(shrink_expr(a).into_iter().map(|a| Expr::Not(boxed(a)))),
+        Expr::DivBy(op, a, n) => {
+            out.extend(
+                shrink_expr(a)
+                    .into_iter()
+                    .map(|a| Expr::DivBy(op, boxed(a), *n)),
+            );
+        }
+        Expr::Dup(a) => out.extend(shrink_expr(a).into_iter().map(|a| Expr::Dup(boxed(a)))),
+        Expr::Call(f, args) => {
+            for (i, arg) in args.iter().enumerate() {
+                out.extend(shrink_expr(arg).into_iter().map(|arg| {
+                    let mut args = args.clone();
+                    args[i] = arg;
+                    Expr::Call(*f, args)
+                }));
+            }
+        }
+        Expr::Let(values, body) => {
+            for (i, value) in values.iter().enumerate() {
+                out.extend(shrink_expr(value).into_iter().map(|value| {
+                    let mut values = values.clone();
+                    values[i] = value;
+                    Expr::Let(values, body.clone())
+                }));
+            }
+            out.extend(
+                shrink_expr(body)
+                    .into_iter()
+                    .map(|body| Expr::Let(values.clone(), boxed(body))),
+            );
+        }
+        Expr::Peek(a, body) => {
+            out.extend(
+                shrink_expr(a)
+                    .into_iter()
+                    .map(|a| Expr::Peek(boxed(a), body.clone())),
+            );
+            out.extend(
+                shrink_expr(body)
+                    .into_iter()
+                    .map(|body| Expr::Peek(a.clone(), boxed(body))),
+            );
+        }
+    }
+    out
+}
+
+/// Every statement list that differs from `stmts` by a single shrinking step.
+fn shrink_stmts(stmts: &[Stmt]) -> Vec<Vec<Stmt>> {
+    let mut out = Vec::new();
+    for i in 0..stmts.len() {
+        let replace = |with: &[Stmt]| {
+            let mut stmts = stmts.to_vec();
+            stmts.splice(i..=i, with.iter().cloned());
+            stmts
+        };
+        out.push(replace(&[]));
+        match &stmts[i] {
+            Stmt::Print(e) => {
+                out.extend(
+                    shrink_expr(e)
+                        .into_iter()
+                        .map(|e| replace(&[Stmt::Print(e)])),
+                );
+            }
+            Stmt::Puts(_) => {}
+            Stmt::If(condn, body) => {
+                out.push(replace(body));
+                out.extend(
+                    shrink_expr(condn)
+                        .into_iter()
+                        .map(|condn| replace(&[Stmt::If(condn, body.clone())])),
+                );
+                out.extend(
+                    shrink_stmts(body)
+                        .into_iter()
+                        .map(|body| replace(&[Stmt::If(condn.clone(), body)])),
+                );
+            }
+            Stmt::While(count, body) => {
+                out.push(replace(body));
+                if *count > 0 {
+                    out.push(replace(&[Stmt::While(count - 1, body.clone())]));
+                }
+                out.extend(
+                    shrink_stmts(body)
+                        .into_iter()
+                        .map(|body| replace(&[Stmt::While(*count, body)])),
+                );
+            }
+            Stmt::Let(values, body) => {
+                for (j, value) in values.iter().enumerate() {
+                    out.extend(shrink_expr(value).into_iter().map(|value| {
+                        let mut values = values.clone();
+                        values[j] = value;
+                        replace(&[Stmt::Let(values, body.clone())])
+                    }));
+                }
+                out.extend(
+                    shrink_stmts(body)
+                        .into_iter()
+                        .map(|body| replace(&[Stmt::Let(values.clone(), body)])),
+                );
+            }
+        }
+    }
+    out
+}
+
+/// Every case that differs from `case` by a single shrinking step.
+fn shrink_case(case: &Case) -> Vec<Case> {
+    let mut out = Vec::new();
+    let with_func = |i: usize, func: FuncDef| {
+        let mut case = case.clone();
+        case.funcs[i] = func;
+        case
+    };
+    for (i, func) in case.funcs.iter().enumerate() {
+        let mut case = case.clone();
+        case.funcs.remove(i);
+        out.push(case);
+
+        out.extend(shrink_stmts(&func.body).into_iter().map(|body| {
+            with_func(
+                i,
+                FuncDef {
+                    body,
+                    ..func.clone()
+                },
+            )
+        }));
+        if let Some((condn, value)) = &func.early {
+            out.push(with_func(
+                i,
+                FuncDef {
+                    early: None,
+                    ..func.clone()
+                },
+            ));
+            out.extend(shrink_expr(condn).into_iter().map(|condn| {
+                with_func(
+                    i,
+                    FuncDef {
+                        early: Some((condn, value.clone())),
+                        ..func.clone()
+                    },
+                )
+            }));
+            out.extend(shrink_expr(value).into_iter().map(|value| {
+                with_func(
+                    i,
+                    FuncDef {
+                        early: Some((condn.clone(), value)),
+                        ..func.clone()
+                    },
+                )
+            }));
+        }
+        out.extend(shrink_expr(&func.result).into_iter().map(|result| {
+            with_func(
+                i,
+                FuncDef {
+                    result,
+                    ..func.clone()
+                },
+            )
+        }));
+    }
+    out.extend(shrink_stmts(&case.main).into_iter().map(|main| Case {
+        funcs: case.funcs.clone(),
+        main,
+    }));
+    out
+}
+
+/// The outputs of a program on both backends, if they differ.
+struct Mismatch {
+    native: Vec<u8>,
+    reference: Vec<u8>,
+}
+
+struct Harness {
+    backend: &'static dyn Backend,
+    dir: PathBuf,
+}
+
+impl Harness {
+    /// Run `source` on the evaluator.
+    fn reference(&self, source: &str) -> Result<Vec<u8>, String> {
+        let program =
+            parse::parse_tokens_for(&mut Lexer::new(source, None), self.backend.cell_width())
+                .map_err(|e| e.to_string())?;
+        let mut out = Vec::new();
+        let mut machine = Machine::new(&program);
+        machine
+            .enter_main(&program)
+            .and_then(|()| machine.run(&mut out))
+            .map_err(|e| e.to_string())?;
+        Ok(out)
+    }
+
+    /// Build `source` with the backend and run it. Failures of the program itself are reported
+    /// in the inner result, so that they can be compared with the evaluator's output, while
+    /// failures to run the tools at all are fatal.
+    fn native(&self, source: &str) -> Result<Result<Vec<u8>, String>, String> {
+        let program =
+            parse::parse_tokens_for(&mut Lexer::new(source, None), self.backend.cell_width())
+                .map_err(|e| e.to_string())?;
+        let artifact = match self.backend.generate(program) {
+            Ok(artifact) => artifact,
+            Err(e) => return Ok(Err(e.to_string())),
+        };
+        let out = self.dir.join("case");
+        let artifact_path = match self.backend.intermediate() {
+            Some(extension) => out.with_extension(extension),
+            None => out.clone(),
+        };
+        fs::write(&artifact_path, artifact.as_bytes()).map_err(|e| e.to_string())?;
+
+        for mut cmd in self.backend.build(&artifact_path, &out) {
+            let output = cmd
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped())
+                .output()
+                .map_err(|e| {
+                    format!(
+                        "failed to run `{}`: {e}",
+                        cmd.get_program().to_string_lossy()
+                    )
+                })?;
+            if !output.status.success() {
+                return Ok(Err(String::from_utf8_lossy(&output.stderr).into_owned()));
+            }
+        }
+        let mut cmd = self
+            .backend
+            .run(&out)
+            .expect("the backend was checked to be runnable");
+        let run = cmd
+            .stderr(Stdio::null())
+            .output()
+            .map_err(|e| e.to_string())?;
+        if !run.status.success() {
+            return Ok(Err(format!("program exited with {}", run.status)));
+        }
+        Ok(Ok(run.stdout))
+    }
+
+    /// Check a case, returning `None` if it can't be rendered or both backends agree.
+    fn check(&self, case: &Case) -> Result<Option<Mismatch>, String> {
+        let Some(source) = Renderer::render(case) else {
+            return Ok(None);
+        };
+        let reference = self.reference(&source)?;
+        let native = self.native(&source)?.unwrap_or_else(String::into_bytes);
+        Ok((native != reference).then_some(Mismatch { native, reference }))
+    }
+
+    /// Generate and check the case for `seed`, minimising it if it fails. Every case gets its own
+    /// seed, so that a failure can be reproduced on its own.
+    fn fuzz(&self, seed: u64) -> Result<Option<(u64, Case, Mismatch)>, String> {
+        let mut gen = Generator {
+            rng: Rng(seed.max(1)),
+            arities: Vec::new(),
+            cell_width: self.backend.cell_width(),
+        };
+        let case = gen.case();
+        if self.check(&case)?.is_none() {
+            return Ok(None);
+        }
+        println!("[INFO] Case {seed} failed, minimising");
+        let (case, mismatch) = self.minimise(case)?;
+        Ok(Some((seed, case, mismatch)))
+    }
+
+    /// Greedily apply shrinking steps for as long as the case keeps failing.
+    fn minimise(&self, mut case: Case) -> Result<(Case, Mismatch), String> {
+        let mut mismatch = self.check(&case)?.expect("the case fails");
+        'outer: loop {
+            for smaller in shrink_case(&case) {
+                if let Some(m) = self.check(&smaller)? {
+                    case = smaller;
+                    mismatch = m;
+                    continue 'outer;
+                }
+            }
+            return Ok((case, mismatch));
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct Options {
+    /// Number of programs to generate
+    #[clap(short = 'n', long, default_value_t = 1000)]
+    iterations: u64,
+    /// Seed for the first program, defaulting to the current time
+    #[clap(long)]
+    seed: Option<u64>,
+    /// Directory to write the minimised failing program to
+    #[clap(short, long, value_name = "DIR", default_value = ".")]
+    out: String,
+}
+
+/// Generate and check programs for `target`, given by the global `--target`, until one fails,
+/// which is then minimised and written to the output directory.
+pub fn run(options: &Options, Target(backend): Target) -> Result<(), Box<dyn Error>> {
+    let Options {
+        iterations,
+        seed,
+        ref out,
+    } = *options;
+    let seed = seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(1, |d| d.as_secs())
+    });
+    if backend.run(Path::new("")).is_none() {
+        eprintln!(
+            "[ERROR] Programs for `{}` can't be run here",
+            backend.name()
+        );
+        process::exit(1);
+    }
+    let dir = env::temp_dir().join(format!("rack-fuzz-{}", process::id()));
+    fs::create_dir_all(&dir)?;
+    let harness = Harness { backend, dir };
+
+    println!("[INFO] Fuzzing with seed {seed}");
+    let result = (0..iterations)
+        .map(|i| seed.wrapping_add(i))
+        .find_map(|case_seed| harness.fuzz(case_seed).transpose());
+    fs::remove_dir_all(&harness.dir).ok();
+
+    let (case_seed, case, mismatch) = match result {
+        None => {
+            println!("[INFO] {iterations} cases passed");
+            return Ok(());
+        }
+        Some(Ok(failure)) => failure,
+        Some(Err(e)) => {
+            eprintln!("[ERROR] {e}");
+            process::exit(1);
+        }
+    };
+    let source = Renderer::render(&case).expect("failing cases render");
+    let path = Path::new(out).join(format!("fuzz-{case_seed}.rk"));
+    fs::write(&path, &source)?;
+    println!("{source}");
+    println!(
+        "----TARGET----\n{}",
+        String::from_utf8_lossy(&mismatch.native)
+    );
+    println!(
+        "----EVALUATOR----\n{}",
+        String::from_utf8_lossy(&mismatch.reference)
+    );
+    println!(
+        "[ERROR] Outputs differ, wrote minimised case to `{}`",
+        path.display()
+    );
+    process::exit(1);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Every generated program should run on the evaluator without error and leave the stack
+    /// empty, which is a good sign that the grammar is stack-balanced.
+    #[test]
+    fn well_formed() {
+        for seed in 1..100 {
+            let mut gen = Generator {
+                rng: Rng(seed),
+                arities: Vec::new(),
+                cell_width: CellWidth::default(),
+            };
+            let source = Renderer::render(&gen.case()).unwrap();
+            let program = parse::parse_tokens(&mut Lexer::new(&source, None))
+                .unwrap_or_else(|e| panic!("{e}\n{source}"));
+            let mut machine = Machine::new(&program);
+            machine.enter_main(&program).unwrap();
+            machine
+                .run(&mut Vec::new())
+                .unwrap_or_else(|e| panic!("{e}\n{source}"));
+            assert!(machine.stack.is_empty(), "{source}");
+            assert!(machine.ret_stack.is_empty(), "{source}");
+        }
+    }
+
+    #[test]
+    fn shrinking() {
+        let mut gen = Generator {
+            rng: Rng(42),
+            arities: Vec::new(),
+            cell_width: CellWidth::default(),
+        };
+        let case = gen.case();
+        let smaller = shrink_case(&case);
+        assert!(!smaller.is_empty());
+        // Removing everything from `main` must always be among the candidates.
+        assert!(smaller
+            .iter()
+            .any(|c| c.main.len() < case.main.len() && c.funcs.len() == case.funcs.len()));
+    }
+}
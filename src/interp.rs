@@ -0,0 +1,598 @@
+//! An in-process evaluator for Rack programs.
+//!
+//! Rather than walking the op tree directly, every function is first lowered into a flat list of
+//! [`Instr`]s. This way the machine state is entirely described by the data stack, the return
+//! stack and a program counter, so execution can be paused and resumed between any two
+//! instructions.
+
+use std::{borrow::Cow, io::Write};
+
+use thiserror::Error;
+
+use crate::{
+    lex::Location,
+    parse::{CellWidth, LocatedOp},
+    Func, Op, Program,
+};
+
+/// The number of cells that fit on the return stack. This matches the 64KiB `ret_stack` reserved
+/// by the x86-64 backend.
+pub const RET_STACK_CELLS: usize = 65536 / 8;
+
+/// The address at which string literals are laid out in the evaluator's memory.
+pub const STR_BASE: u64 = 0x1000;
+
+/// Return address used for the frame of the entry point; returning to it halts the machine.
+const HALT: usize = usize::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instr {
+    PushInt(u64),
+    PushStrPtr(usize),
+    Plus,
+    Minus,
+    DivMod,
+    Dup,
+    Drop,
+    Swap,
+    Over,
+    Equals,
+    Neq,
+    Not,
+    GreaterThan,
+    LessThan,
+    Or,
+    And,
+    ReadByte,
+    Print,
+    Puts,
+    PushBind(usize),
+    /// Copy the top `count` cells of the data stack to the return stack, popping them unless
+    /// `peek` is set.
+    Bind {
+        count: usize,
+        peek: bool,
+    },
+    /// Drop `count` bindings from the return stack at the end of a `let`/`peek` block.
+    Unbind(usize),
+    Jump(usize),
+    /// Pop from the data stack and jump if the value is not `true`.
+    JumpUnless(usize),
+    CallFn(usize),
+    Ret(usize),
+}
+
+/// Where an instruction came from.
+#[derive(Debug, Clone)]
+pub struct DebugInfo<'src> {
+    pub location: Location<'src>,
+    /// Index into `Code::scopes` of the bindings that are in scope at the instruction.
+    pub scope: usize,
+}
+
+/// The lowered form of a program.
+#[derive(Debug, Clone)]
+pub struct Code<'src> {
+    pub instrs: Vec<Instr>,
+    /// Debugging information for each instruction, indexed like `instrs`.
+    pub debug: Vec<DebugInfo<'src>>,
+    /// The address of the first instruction of each function, indexed like `Program::funcs`.
+    /// Functions added with `push_func` come after those of the program.
+    pub entries: Vec<usize>,
+    pub idents: Vec<&'src str>,
+    /// The names bound by `let` and `peek` at each point in the program, the innermost last. The
+    /// first scope is always empty.
+    pub scopes: Vec<Vec<&'src str>>,
+}
+
+impl Default for Code<'_> {
+    fn default() -> Self {
+        Self {
+            instrs: Vec::new(),
+            debug: Vec::new(),
+            entries: Vec::new(),
+            idents: Vec::new(),
+            scopes: vec![Vec::new()],
+        }
+    }
+}
+
+impl<'src> Code<'src> {
+    pub fn new(funcs: &[Func<'src>]) -> Self {
+        let mut code = Self::default();
+        for func in funcs {
+            code.push_func(func);
+        }
+        code
+    }
+
+    /// Lower a function, returning its address. Functions that are not part of the program the
+    /// code was created from can't be called by it, but can still be entered directly.
+    pub fn push_func(&mut self, func: &Func<'src>) -> usize {
+        let entry = self.instrs.len();
+        self.entries.push(entry);
+        self.idents.push(func.ident);
+        self.lower_block(&func.body, 0);
+        entry
+    }
+
+    /// The index of the function that the instruction at `pc` belongs to.
+    pub fn func_at(&self, pc: usize) -> usize {
+        self.entries.partition_point(|entry| *entry <= pc) - 1
+    }
+
+    fn lower_block(&mut self, ops: &[LocatedOp<'src>], scope: usize) {
+        for op in ops {
+            let location = op.location.clone();
+            self.lower_op(op, DebugInfo { location, scope });
+        }
+    }
+
+    fn emit(&mut self, instr: Instr, debug: &DebugInfo<'src>) -> usize {
+        self.instrs.push(instr);
+        self.debug.push(debug.clone());
+        self.instrs.len() - 1
+    }
+
+    /// Point the jump at `at` to the next instruction to be emitted.
+    fn patch(&mut self, at: usize) {
+        let target = self.instrs.len();
+        match &mut self.instrs[at] {
+            Instr::Jump(to) | Instr::JumpUnless(to) => *to = target,
+            instr => unreachable!("tried to patch non-jump {instr:?}"),
+        }
+    }
+
+    fn lower_op(&mut self, op: &Op<'src>, debug: DebugInfo<'src>) {
+        let instr = match op {
+            Op::PushInt(val) => Instr::PushInt(*val),
+            Op::PushStrPtr(index) => Instr::PushStrPtr(*index),
+            Op::Plus => Instr::Plus,
+            Op::Minus => Instr::Minus,
+            Op::DivMod => Instr::DivMod,
+            Op::Dup => Instr::Dup,
+            Op::Drop => Instr::Drop,
+            Op::Swap => Instr::Swap,
+            Op::Over => Instr::Over,
+            Op::Equals => Instr::Equals,
+            Op::Neq => Instr::Neq,
+            Op::Not => Instr::Not,
+            Op::GreaterThan => Instr::GreaterThan,
+            Op::LessThan => Instr::LessThan,
+            Op::Or => Instr::Or,
+            Op::And => Instr::And,
+            Op::ReadByte => Instr::ReadByte,
+            Op::Print => Instr::Print,
+            Op::Puts => Instr::Puts,
+            Op::PushBind(index) => Instr::PushBind(*index),
+            Op::CallFn(index) => Instr::CallFn(*index),
+            Op::Ret(count) => Instr::Ret(*count),
+            Op::Asm { .. } => unreachable!("inline assembly isn't parsed for the evaluator"),
+            Op::If(body) => {
+                let skip = self.emit(Instr::JumpUnless(0), &debug);
+                self.lower_block(body, debug.scope);
+                self.patch(skip);
+                return;
+            }
+            Op::While { condn, body } => {
+                let start = self.instrs.len();
+                self.lower_block(condn, debug.scope);
+                let exit = self.emit(Instr::JumpUnless(0), &debug);
+                self.lower_block(body, debug.scope);
+                self.emit(Instr::Jump(start), &debug);
+                self.patch(exit);
+                return;
+            }
+            Op::Bind {
+                count,
+                names,
+                peek,
+                body,
+            } => {
+                self.emit(
+                    Instr::Bind {
+                        count: *count,
+                        peek: *peek,
+                    },
+                    &debug,
+                );
+                let mut inner = self.scopes[debug.scope].clone();
+                inner.extend(names);
+                self.scopes.push(inner);
+                let inner = self.scopes.len() - 1;
+                self.lower_block(body, inner);
+                self.emit(
+                    Instr::Unbind(*count),
+                    &DebugInfo {
+                        scope: inner,
+                        ..debug
+                    },
+                );
+                return;
+            }
+        };
+        self.emit(instr, &debug);
+    }
+}
+
+/// An entry on the return stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetEntry {
+    /// A value bound by `let` or `peek`.
+    Binding(u64),
+    /// The address to continue at once the current function returns.
+    Return(usize),
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum RuntimeError {
+    #[error("stack underflow")]
+    StackUnderflow,
+    #[error("return stack overflow")]
+    RetStackOverflow,
+    #[error("division by zero")]
+    DivisionByZero,
+    #[error("invalid memory access at {addr:#x} ({len} bytes)")]
+    InvalidAccess { addr: u64, len: u64 },
+    #[error("return stack is corrupted")]
+    CorruptRetStack,
+    #[error("failed to write output")]
+    Output,
+}
+
+/// Whether the machine can keep executing after a step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Running,
+    Halted,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Machine<'src> {
+    pub stack: Vec<u64>,
+    pub ret_stack: Vec<RetEntry>,
+    pub code: Code<'src>,
+    /// The address of the next instruction to execute, or `None` if the machine has halted.
+    pub pc: Option<usize>,
+    /// Backing memory for string literals, starting at `STR_BASE`.
+    memory: Vec<u8>,
+    /// The address of each string literal, indexed like `Context::strings`.
+    str_addrs: Vec<u64>,
+    /// The width of a cell on the target of the loaded program, which arithmetic wraps around at.
+    cell_width: CellWidth,
+}
+
+impl<'src> Machine<'src> {
+    pub fn new(program: &Program<'src>) -> Self {
+        let mut machine = Self::default();
+        machine.load(program);
+        machine
+    }
+
+    /// Replace the code of the machine with that of `program`, keeping its stacks intact. String
+    /// literals that the machine hasn't seen yet are appended to its memory.
+    pub fn load(&mut self, program: &Program<'src>) {
+        self.code = Code::new(&program.funcs);
+        self.cell_width = program.ctx.cell_width;
+        self.load_strings(&program.ctx.strings);
+    }
+
+    fn load_strings(&mut self, strings: &[Cow<str>]) {
+        for s in strings.iter().skip(self.str_addrs.len()) {
+            self.str_addrs.push(STR_BASE + self.memory.len() as u64);
+            self.memory.extend_from_slice(s.as_bytes());
+        }
+    }
+
+    /// Prepare to call the code at `entry`, such that returning from it halts the machine.
+    pub fn enter(&mut self, entry: usize) -> Result<(), RuntimeError> {
+        self.push_ret(RetEntry::Return(HALT))?;
+        self.pc = Some(entry);
+        Ok(())
+    }
+
+    /// Prepare to call the function `main` of the loaded program.
+    pub fn enter_main(&mut self, program: &Program) -> Result<(), RuntimeError> {
+        let main = program
+            .ctx
+            .lookup
+            .get("main")
+            .expect("program has an entry point");
+        self.enter(self.code.entries[*main])
+    }
+
+    pub fn run(&mut self, out: &mut impl Write) -> Result<(), RuntimeError> {
+        while self.step(out)? == State::Running {}
+        Ok(())
+    }
+
+    /// Run to completion, calling `observe` with the address of each instruction once it has
+    /// been executed.
+    pub fn run_observed<E: From<RuntimeError>>(
+        &mut self,
+        out: &mut impl Write,
+        mut observe: impl FnMut(&Self, usize) -> Result<(), E>,
+    ) -> Result<(), E> {
+        while let Some(pc) = self.pc {
+            self.step(out)?;
+            observe(self, pc)?;
+        }
+        Ok(())
+    }
+
+    /// Abandon the current execution, clearing the return stack but leaving the data stack as is.
+    pub fn reset(&mut self) {
+        self.ret_stack.clear();
+        self.pc = None;
+    }
+
+    /// The address of the current instruction in each active function, innermost first. For the
+    /// callers, this is the address of the call.
+    pub fn backtrace(&self) -> Vec<usize> {
+        self.pc
+            .into_iter()
+            .chain(self.ret_stack.iter().rev().filter_map(|entry| match entry {
+                RetEntry::Return(to) if *to != HALT => Some(to - 1),
+                _ => None,
+            }))
+            .collect()
+    }
+
+    /// The bindings that are in scope at the current instruction, outermost first.
+    pub fn bindings(&self) -> Vec<(&'src str, u64)> {
+        let Some(pc) = self.pc else {
+            return Vec::new();
+        };
+        let names = &self.code.scopes[self.code.debug[pc].scope];
+        // The innermost binding is on top of the return stack.
+        let values = self.ret_stack.iter().rev().map_while(|entry| match entry {
+            RetEntry::Binding(val) => Some(*val),
+            RetEntry::Return(_) => None,
+        });
+        let mut bindings = names.iter().rev().copied().zip(values).collect::<Vec<_>>();
+        bindings.reverse();
+        bindings
+    }
+
+    pub fn pop(&mut self) -> Result<u64, RuntimeError> {
+        self.stack.pop().ok_or(RuntimeError::StackUnderflow)
+    }
+
+    fn peek(&self, depth: usize) -> Result<u64, RuntimeError> {
+        self.stack
+            .len()
+            .checked_sub(depth + 1)
+            .map(|i| self.stack[i])
+            .ok_or(RuntimeError::StackUnderflow)
+    }
+
+    fn push_ret(&mut self, entry: RetEntry) -> Result<(), RuntimeError> {
+        if self.ret_stack.len() >= RET_STACK_CELLS {
+            return Err(RuntimeError::RetStackOverflow);
+        }
+        self.ret_stack.push(entry);
+        Ok(())
+    }
+
+    /// Drop `count` bindings from the top of the return stack.
+    fn drop_bindings(&mut self, count: usize) -> Result<(), RuntimeError> {
+        let len = self
+            .ret_stack
+            .len()
+            .checked_sub(count)
+            .ok_or(RuntimeError::CorruptRetStack)?;
+        if self.ret_stack[len..]
+            .iter()
+            .any(|e| matches!(e, RetEntry::Return(_)))
+        {
+            return Err(RuntimeError::CorruptRetStack);
+        }
+        self.ret_stack.truncate(len);
+        Ok(())
+    }
+
+    /// Get `len` bytes of memory starting at `addr`.
+    pub fn memory(&self, addr: u64, len: u64) -> Result<&[u8], RuntimeError> {
+        addr.checked_sub(STR_BASE)
+            .and_then(|start| {
+                Some(usize::try_from(start).ok()?..usize::try_from(start.checked_add(len)?).ok()?)
+            })
+            .and_then(|range| self.memory.get(range))
+            .ok_or(RuntimeError::InvalidAccess { addr, len })
+    }
+
+    /// Execute a single instruction.
+    #[allow(clippy::too_many_lines)]
+    pub fn step(&mut self, out: &mut impl Write) -> Result<State, RuntimeError> {
+        let Some(pc) = self.pc else {
+            return Ok(State::Halted);
+        };
+        let mut next = pc + 1;
+        match self.code.instrs[pc] {
+            Instr::PushInt(val) => self.stack.push(val),
+            Instr::PushStrPtr(index) => self.stack.push(self.str_addrs[index]),
+            Instr::Plus => {
+                let (a, b) = (self.pop()?, self.pop()?);
+                self.stack.push(self.cell_width.wrap(b.wrapping_add(a)));
+            }
+            Instr::Minus => {
+                let (a, b) = (self.pop()?, self.pop()?);
+                self.stack.push(self.cell_width.wrap(b.wrapping_sub(a)));
+            }
+            Instr::DivMod => {
+                let (a, b) = (self.pop()?, self.pop()?);
+                if a == 0 {
+                    return Err(RuntimeError::DivisionByZero);
+                }
+                self.stack.push(b / a);
+                self.stack.push(b % a);
+            }
+            Instr::Dup => self.stack.push(self.peek(0)?),
+            Instr::Drop => {
+                self.pop()?;
+            }
+            Instr::Swap => {
+                let (a, b) = (self.pop()?, self.pop()?);
+                self.stack.push(a);
+                self.stack.push(b);
+            }
+            Instr::Over => self.stack.push(self.peek(1)?),
+            Instr::Equals => {
+                let (a, b) = (self.pop()?, self.pop()?);
+                self.stack.push(u64::from(b == a));
+            }
+            Instr::Neq => {
+                let (a, b) = (self.pop()?, self.pop()?);
+                self.stack.push(u64::from(b != a));
+            }
+            Instr::Not => {
+                let a = self.pop()?;
+                self.stack.push(self.cell_width.wrap(1u64.wrapping_sub(a)));
+            }
+            Instr::GreaterThan => {
+                let (a, b) = (self.pop()?, self.pop()?);
+                self.stack.push(u64::from(b > a));
+            }
+            Instr::LessThan => {
+                let (a, b) = (self.pop()?, self.pop()?);
+                self.stack.push(u64::from(b < a));
+            }
+            Instr::Or => {
+                let (a, b) = (self.pop()?, self.pop()?);
+                self.stack.push(u64::from(a == 1 || b == 1));
+            }
+            Instr::And => {
+                let (a, b) = (self.pop()?, self.pop()?);
+                self.stack.push(u64::from(a == 1 && b == 1));
+            }
+            Instr::ReadByte => {
+                let addr = self.pop()?;
+                let byte = self.memory(addr, 1)?[0];
+                self.stack.push(u64::from(byte));
+            }
+            Instr::Print => {
+                let val = self.pop()?;
+                writeln!(out, "{val}").map_err(|_| RuntimeError::Output)?;
+            }
+            Instr::Puts => {
+                let (ptr, len) = (self.pop()?, self.pop()?);
+                let s = self.memory(ptr, len)?;
+                out.write_all(s).map_err(|_| RuntimeError::Output)?;
+            }
+            Instr::PushBind(index) => {
+                let entry = self
+                    .ret_stack
+                    .len()
+                    .checked_sub(index + 1)
+                    .map(|i| self.ret_stack[i]);
+                let Some(RetEntry::Binding(val)) = entry else {
+                    return Err(RuntimeError::CorruptRetStack);
+                };
+                self.stack.push(val);
+            }
+            Instr::Bind { count, peek } => {
+                let base = self
+                    .stack
+                    .len()
+                    .checked_sub(count)
+                    .ok_or(RuntimeError::StackUnderflow)?;
+                for i in base..self.stack.len() {
+                    self.push_ret(RetEntry::Binding(self.stack[i]))?;
+                }
+                if !peek {
+                    self.stack.truncate(base);
+                }
+            }
+            Instr::Unbind(count) => self.drop_bindings(count)?,
+            Instr::Jump(to) => next = to,
+            Instr::JumpUnless(to) => {
+                if self.pop()? != 1 {
+                    next = to;
+                }
+            }
+            Instr::CallFn(index) => {
+                self.push_ret(RetEntry::Return(next))?;
+                next = self.code.entries[index];
+            }
+            Instr::Ret(count) => {
+                self.drop_bindings(count)?;
+                let Some(RetEntry::Return(to)) = self.ret_stack.pop() else {
+                    return Err(RuntimeError::CorruptRetStack);
+                };
+                if to == HALT {
+                    self.pc = None;
+                    return Ok(State::Halted);
+                }
+                next = to;
+            }
+        }
+        self.pc = Some(next);
+        Ok(State::Running)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parse, Lexer};
+    use std::io;
+
+    fn run(source: &str) -> (Machine<'_>, String) {
+        let program = parse::parse_tokens(&mut Lexer::new(source, None)).unwrap();
+        let mut machine = Machine::new(&program);
+        machine.enter_main(&program).unwrap();
+        let mut out = Vec::new();
+        machine.run(&mut out).unwrap();
+        (machine, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn bindings() {
+        let (_, out) = run(include_str!("../tests/src/let.rk"));
+        assert_eq!(out, "1\n2\n1\n3\n2\n");
+    }
+
+    #[test]
+    fn early_ret() {
+        let (machine, out) = run(include_str!("../tests/src/early_ret.rk"));
+        assert_eq!(out, "1\n0\n");
+        assert!(machine.ret_stack.is_empty());
+    }
+
+    #[test]
+    fn strings() {
+        let (_, out) = run(include_str!("../examples/streq.rk"));
+        assert_eq!(out, "2nd EQUAL\n3rd EQUAL\n");
+    }
+
+    #[test]
+    fn over() {
+        let (machine, _) = run("fn main in 1 2 over end\n");
+        assert_eq!(machine.stack, [1, 2, 1]);
+    }
+
+    #[test]
+    fn narrow_cells() {
+        let source = "fn main in 0 1 - print 65535 1 + print 2 not print end\n";
+        let program =
+            parse::parse_tokens_for(&mut Lexer::new(source, None), CellWidth::new(16)).unwrap();
+        let mut machine = Machine::new(&program);
+        machine.enter_main(&program).unwrap();
+        let mut out = Vec::new();
+        machine.run(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "65535\n0\n65535\n");
+    }
+
+    #[test]
+    fn binding_names() {
+        let source = "fn main in 1 2 let a b in 3 peek c in a end end end\n";
+        let program = parse::parse_tokens(&mut Lexer::new(source, None)).unwrap();
+        let mut machine = Machine::new(&program);
+        machine.enter_main(&program).unwrap();
+        while machine.code.instrs[machine.pc.unwrap()] != Instr::PushBind(2) {
+            machine.step(&mut io::sink()).unwrap();
+        }
+        assert_eq!(machine.bindings(), [("a", 1), ("b", 2), ("c", 3)]);
+        assert_eq!(machine.backtrace().len(), 1);
+    }
+}
@@ -0,0 +1,612 @@
+//! Compile a program to machine code in memory with Cranelift and run it in process.
+
+use std::{error::Error, io::Write, mem, ptr};
+
+use cranelift_codegen::{
+    ir::{
+        condcodes::IntCC, types::I64, AbiParam, Block, FuncRef, InstBuilder, MemFlags, Type,
+        UserFuncName, Value,
+    },
+    settings::{self, Configurable},
+    Context,
+};
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext, Variable};
+use cranelift_jit::{JITBuilder, JITModule};
+use cranelift_module::{default_libcall_names, FuncId, Linkage, Module};
+
+use thiserror::Error;
+
+use crate::{Op, Program};
+
+/// The number of cells in the data stack, which is 8 MiB like the default native stack.
+const STACK_CELLS: usize = 1 << 20;
+/// The number of cells in the return stack, the same as the 64 KiB of the native targets.
+const RET_STACK_CELLS: usize = 65536 / 8;
+
+/// The errors that the compiled code checks for, which stop the program.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trap {
+    #[error("stack overflow")]
+    StackOverflow,
+    #[error("stack underflow")]
+    StackUnderflow,
+    #[error("return stack overflow")]
+    RetStackOverflow,
+    /// The same message as the evaluator's `RuntimeError::DivisionByZero`.
+    #[error("division by zero")]
+    DivisionByZero,
+}
+
+/// Every trap, indexed by the code that the compiled code passes to `rack_trap`.
+const TRAPS: [Trap; 4] = [
+    Trap::StackOverflow,
+    Trap::StackUnderflow,
+    Trap::RetStackOverflow,
+    Trap::DivisionByZero,
+];
+
+/// Where the output of the program goes, passed to the host functions by the compiled code.
+struct Output<'a> {
+    writer: &'a mut dyn Write,
+    /// The first error writing the output, which stops any more from being written.
+    error: Option<std::io::Error>,
+    /// The trap that stopped the program, if any.
+    trap: Option<Trap>,
+}
+
+impl Output<'_> {
+    fn write(&mut self, bytes: &[u8]) {
+        if self.error.is_none() {
+            self.error = self.writer.write_all(bytes).err();
+        }
+    }
+}
+
+extern "C" fn host_print(out: &mut Output, val: u64) {
+    out.write(format!("{val}\n").as_bytes());
+}
+
+#[allow(clippy::cast_possible_truncation)]
+extern "C" fn host_trap(out: &mut Output, code: u64) {
+    out.trap = Some(TRAPS[code as usize]);
+}
+
+/// # Safety
+///
+/// `ptr` must point to `len` readable bytes, which the program guarantees for the strings it
+/// pushes.
+#[allow(clippy::cast_possible_truncation)]
+unsafe extern "C" fn host_puts(out: &mut Output, ptr: *const u8, len: u64) {
+    out.write(std::slice::from_raw_parts(ptr, len as usize));
+}
+
+/// The type of every compiled Rack function. They take the data stack pointer, the return stack
+/// pointer and the output, and return the new data stack pointer. The return stack pointer
+/// doesn't need to be returned, since each function removes the bindings it made. A null data
+/// stack pointer is returned instead if the program was stopped by a trap, and every caller
+/// passes it on.
+type RackFn = unsafe extern "C" fn(*mut u64, *mut u64, *mut Output) -> *mut u64;
+
+// The variables holding the pointers passed to every function.
+const SP: u32 = 0;
+const RSP: u32 = 1;
+const OUT: u32 = 2;
+
+fn var(index: u32) -> Variable {
+    Variable::from_u32(index)
+}
+
+/// The addresses that the stacks span, which the compiled code checks every access against.
+/// Both stacks grow down from their end.
+#[derive(Clone, Copy)]
+struct Bounds {
+    stack_start: i64,
+    stack_end: i64,
+    ret_stack_start: i64,
+}
+
+/// Lowers the ops of one function, with the data stack growing down from `SP` and the return
+/// stack from `RSP`.
+struct Lowering<'a, 'b> {
+    builder: &'a mut FunctionBuilder<'b>,
+    funcs: &'a [FuncRef],
+    print: FuncRef,
+    puts: FuncRef,
+    trap: FuncRef,
+    /// The address of each string.
+    strings: &'a [i64],
+    bounds: Bounds,
+    /// The blocks that stop the program with each trap, once they are jumped to.
+    traps: [Option<Block>; TRAPS.len()],
+    /// The block that returns a null data stack pointer after a callee was stopped by a trap.
+    stopped: Option<Block>,
+}
+
+impl Lowering<'_, '_> {
+    /// Carry on if `ok` is true, or stop the program with `trap` otherwise.
+    fn check(&mut self, ok: Value, trap: Trap) {
+        let block = *self.traps[trap as usize].get_or_insert_with(|| self.builder.create_block());
+        self.carry_on_unless(ok, block);
+    }
+
+    fn carry_on_unless(&mut self, ok: Value, block: Block) {
+        let next = self.builder.create_block();
+        self.builder.ins().brif(ok, next, &[], block, &[]);
+        self.builder.switch_to_block(next);
+    }
+
+    /// Check that there are at least `cells` cells on the data stack.
+    fn require(&mut self, cells: i64) {
+        let sp = self.builder.use_var(var(SP));
+        let ok = self.builder.ins().icmp_imm(
+            IntCC::UnsignedLessThanOrEqual,
+            sp,
+            self.bounds.stack_end - cells * 8,
+        );
+        self.check(ok, Trap::StackUnderflow);
+    }
+
+    fn push(&mut self, val: Value) {
+        let sp = self.builder.use_var(var(SP));
+        let sp = self.builder.ins().iadd_imm(sp, -8);
+        let ok = self.builder.ins().icmp_imm(
+            IntCC::UnsignedGreaterThanOrEqual,
+            sp,
+            self.bounds.stack_start,
+        );
+        self.check(ok, Trap::StackOverflow);
+        self.builder.ins().store(MemFlags::trusted(), val, sp, 0);
+        self.builder.def_var(var(SP), sp);
+    }
+
+    fn pop(&mut self) -> Value {
+        self.require(1);
+        let sp = self.builder.use_var(var(SP));
+        let val = self.builder.ins().load(I64, MemFlags::trusted(), sp, 0);
+        let sp = self.builder.ins().iadd_imm(sp, 8);
+        self.builder.def_var(var(SP), sp);
+        val
+    }
+
+    /// The value `depth` cells from the top of the data stack.
+    fn peek(&mut self, depth: i32) -> Value {
+        self.require(i64::from(depth) + 1);
+        let sp = self.builder.use_var(var(SP));
+        self.builder
+            .ins()
+            .load(I64, MemFlags::trusted(), sp, depth * 8)
+    }
+
+    /// Take `bytes` from the return stack, returning the new return stack pointer.
+    fn reserve(&mut self, bytes: i64) -> Value {
+        let rsp = self.builder.use_var(var(RSP));
+        let rsp = self.builder.ins().iadd_imm(rsp, -bytes);
+        let ok = self.builder.ins().icmp_imm(
+            IntCC::UnsignedGreaterThanOrEqual,
+            rsp,
+            self.bounds.ret_stack_start,
+        );
+        self.check(ok, Trap::RetStackOverflow);
+        rsp
+    }
+
+    fn move_stack(&mut self, index: u32, bytes: i64) {
+        let ptr = self.builder.use_var(var(index));
+        let ptr = self.builder.ins().iadd_imm(ptr, bytes);
+        self.builder.def_var(var(index), ptr);
+    }
+
+    /// Pop a value, checking whether it is true.
+    fn pop_bool(&mut self) -> Value {
+        let val = self.pop();
+        self.builder.ins().icmp_imm(IntCC::Equal, val, 1)
+    }
+
+    /// Pop the top two values and push 1 if `cond` holds for the second and the top.
+    fn comparison(&mut self, cond: IntCC) {
+        let b = self.pop();
+        let a = self.pop();
+        let flag = self.builder.ins().icmp(cond, a, b);
+        let val = self.builder.ins().uextend(I64, flag);
+        self.push(val);
+    }
+
+    fn ret(&mut self) {
+        let sp = self.builder.use_var(var(SP));
+        self.builder.ins().return_(&[sp]);
+    }
+
+    /// Fill in the blocks that stop the program, once the body has been lowered.
+    fn finish(&mut self) {
+        for (code, block) in (0..).zip(self.traps) {
+            let Some(block) = block else { continue };
+            self.builder.switch_to_block(block);
+            let out = self.builder.use_var(var(OUT));
+            let code = self.builder.ins().iconst(I64, code);
+            self.builder.ins().call(self.trap, &[out, code]);
+            let null = self.builder.ins().iconst(I64, 0);
+            self.builder.ins().return_(&[null]);
+        }
+        if let Some(block) = self.stopped {
+            self.builder.switch_to_block(block);
+            let null = self.builder.ins().iconst(I64, 0);
+            self.builder.ins().return_(&[null]);
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn lower_op(&mut self, op: &Op) {
+        match op {
+            Op::CallFn(index) => {
+                // Each call takes a cell of the return stack, as the return address does on the
+                // native targets, which bounds the depth of recursion.
+                let rsp = self.reserve(8);
+                let args = [
+                    self.builder.use_var(var(SP)),
+                    rsp,
+                    self.builder.use_var(var(OUT)),
+                ];
+                let call = self.builder.ins().call(self.funcs[*index], &args);
+                let sp = self.builder.inst_results(call)[0];
+                let ok = self.builder.ins().icmp_imm(IntCC::NotEqual, sp, 0);
+                let stopped = *self
+                    .stopped
+                    .get_or_insert_with(|| self.builder.create_block());
+                self.carry_on_unless(ok, stopped);
+                self.builder.def_var(var(SP), sp);
+            }
+            Op::Ret(_) => {
+                self.ret();
+                // Anything after an early return is unreachable, but it still needs a block.
+                let block = self.builder.create_block();
+                self.builder.switch_to_block(block);
+            }
+            Op::Bind {
+                count, peek, body, ..
+            } => {
+                let bytes = i64::try_from(count * 8).expect("the bindings fit in memory");
+                let rsp = self.reserve(bytes);
+                self.builder.def_var(var(RSP), rsp);
+                for i in 0..*count {
+                    let depth = i32::try_from(i).expect("the bindings fit in memory");
+                    let val = self.peek(depth);
+                    let rsp = self.builder.use_var(var(RSP));
+                    self.builder
+                        .ins()
+                        .store(MemFlags::trusted(), val, rsp, depth * 8);
+                }
+                if !peek {
+                    self.move_stack(SP, bytes);
+                }
+                for op in body {
+                    self.lower_op(op);
+                }
+                // Remove the bindings from the return stack.
+                self.move_stack(RSP, bytes);
+            }
+            Op::PushBind(index) => {
+                let offset = i32::try_from(index * 8).expect("the bindings fit in memory");
+                let rsp = self.builder.use_var(var(RSP));
+                let val = self
+                    .builder
+                    .ins()
+                    .load(I64, MemFlags::trusted(), rsp, offset);
+                self.push(val);
+            }
+            // Any bit pattern can be given as an immediate.
+            #[allow(clippy::cast_possible_wrap)]
+            Op::PushInt(val) => {
+                let val = self.builder.ins().iconst(I64, *val as i64);
+                self.push(val);
+            }
+            Op::PushStrPtr(index) => {
+                let val = self.builder.ins().iconst(I64, self.strings[*index]);
+                self.push(val);
+            }
+            Op::Plus | Op::Minus => {
+                let b = self.pop();
+                let a = self.pop();
+                let val = if matches!(op, Op::Plus) {
+                    self.builder.ins().iadd(a, b)
+                } else {
+                    self.builder.ins().isub(a, b)
+                };
+                self.push(val);
+            }
+            Op::DivMod => {
+                let b = self.pop();
+                let a = self.pop();
+                // Dividing by zero would raise `SIGFPE` in the compiler itself.
+                let ok = self.builder.ins().icmp_imm(IntCC::NotEqual, b, 0);
+                self.check(ok, Trap::DivisionByZero);
+                let quotient = self.builder.ins().udiv(a, b);
+                let remainder = self.builder.ins().urem(a, b);
+                self.push(quotient);
+                self.push(remainder);
+            }
+            Op::Dup => {
+                let val = self.peek(0);
+                self.push(val);
+            }
+            Op::Drop => {
+                self.require(1);
+                self.move_stack(SP, 8);
+            }
+            Op::Swap => {
+                let b = self.pop();
+                let a = self.pop();
+                self.push(b);
+                self.push(a);
+            }
+            Op::Over => {
+                let val = self.peek(1);
+                self.push(val);
+            }
+            Op::Equals => self.comparison(IntCC::Equal),
+            Op::Neq => self.comparison(IntCC::NotEqual),
+            Op::GreaterThan => self.comparison(IntCC::UnsignedGreaterThan),
+            Op::LessThan => self.comparison(IntCC::UnsignedLessThan),
+            Op::Not => {
+                let val = self.pop();
+                let val = self.builder.ins().irsub_imm(val, 1);
+                self.push(val);
+            }
+            Op::Or | Op::And => {
+                let b = self.pop_bool();
+                let a = self.pop_bool();
+                let flag = if matches!(op, Op::Or) {
+                    self.builder.ins().bor(a, b)
+                } else {
+                    self.builder.ins().band(a, b)
+                };
+                let val = self.builder.ins().uextend(I64, flag);
+                self.push(val);
+            }
+            Op::ReadByte => {
+                let addr = self.pop();
+                let val = self.builder.ins().uload8(I64, MemFlags::new(), addr, 0);
+                self.push(val);
+            }
+            Op::If(ops) => {
+                let then = self.builder.create_block();
+                let end = self.builder.create_block();
+                let flag = self.pop_bool();
+                self.builder.ins().brif(flag, then, &[], end, &[]);
+                self.builder.switch_to_block(then);
+                for op in ops {
+                    self.lower_op(op);
+                }
+                self.builder.ins().jump(end, &[]);
+                self.builder.switch_to_block(end);
+            }
+            Op::While { condn, body } => {
+                let header = self.builder.create_block();
+                let body_block = self.builder.create_block();
+                let end = self.builder.create_block();
+                self.builder.ins().jump(header, &[]);
+                self.builder.switch_to_block(header);
+                for op in condn {
+                    self.lower_op(op);
+                }
+                let flag = self.pop_bool();
+                self.builder.ins().brif(flag, body_block, &[], end, &[]);
+                self.builder.switch_to_block(body_block);
+                for op in body {
+                    self.lower_op(op);
+                }
+                self.builder.ins().jump(header, &[]);
+                self.builder.switch_to_block(end);
+            }
+            Op::Print => {
+                let val = self.pop();
+                let out = self.builder.use_var(var(OUT));
+                self.builder.ins().call(self.print, &[out, val]);
+            }
+            Op::Puts => {
+                let ptr = self.pop();
+                let len = self.pop();
+                let out = self.builder.use_var(var(OUT));
+                self.builder.ins().call(self.puts, &[out, ptr, len]);
+            }
+            Op::Asm { .. } => unreachable!("programs with inline assembly aren't run in memory"),
+        }
+    }
+}
+
+/// Compile every function of `program` to run on the stacks spanning `bounds`, returning the
+/// module holding them and the entry point.
+fn compile(program: &Program, bounds: Bounds) -> Result<(JITModule, FuncId), Box<dyn Error>> {
+    let mut flags = settings::builder();
+    flags.set("use_colocated_libcalls", "false")?;
+    flags.set("is_pic", "false")?;
+    let isa = cranelift_native::builder()
+        .map_err(|e| format!("this machine isn't supported by the JIT: {e}"))?
+        .finish(settings::Flags::new(flags))?;
+    let mut builder = JITBuilder::with_isa(isa, default_libcall_names());
+    builder.symbol("rack_print", host_print as *const u8);
+    builder.symbol("rack_puts", host_puts as *const u8);
+    builder.symbol("rack_trap", host_trap as *const u8);
+    let mut module = JITModule::new(builder);
+
+    let ptr = module.target_config().pointer_type();
+    let mut signature = module.make_signature();
+    signature.params.extend([AbiParam::new(ptr); 3]);
+    signature.returns.push(AbiParam::new(ptr));
+    let mut print = module.make_signature();
+    print
+        .params
+        .extend([AbiParam::new(ptr), AbiParam::new(I64)]);
+    let mut puts = module.make_signature();
+    puts.params
+        .extend([AbiParam::new(ptr), AbiParam::new(ptr), AbiParam::new(I64)]);
+    // Traps are reported with a code in place of the value printed.
+    let trap = module.declare_function("rack_trap", Linkage::Import, &print)?;
+    let print = module.declare_function("rack_print", Linkage::Import, &print)?;
+    let puts = module.declare_function("rack_puts", Linkage::Import, &puts)?;
+
+    let mut ids = Vec::new();
+    for i in 0..program.funcs.len() {
+        ids.push(module.declare_function(&format!("fn_{i}"), Linkage::Local, &signature)?);
+    }
+    let strings = program
+        .ctx
+        .strings
+        .iter()
+        .map(|s| s.as_ptr() as i64)
+        .collect::<Vec<_>>();
+
+    let mut ctx = module.make_context();
+    let mut func_ctx = FunctionBuilderContext::new();
+    for (func, id) in program.funcs.iter().zip(&ids) {
+        ctx.func.signature = signature.clone();
+        ctx.func.name = UserFuncName::user(0, id.as_u32());
+        define(
+            &mut module,
+            &mut ctx,
+            &mut func_ctx,
+            ptr,
+            |builder, module| {
+                let funcs = ids
+                    .iter()
+                    .map(|id| module.declare_func_in_func(*id, builder.func))
+                    .collect::<Vec<_>>();
+                let mut lowering = Lowering {
+                    print: module.declare_func_in_func(print, builder.func),
+                    puts: module.declare_func_in_func(puts, builder.func),
+                    trap: module.declare_func_in_func(trap, builder.func),
+                    builder,
+                    funcs: &funcs,
+                    strings: &strings,
+                    bounds,
+                    traps: [None; TRAPS.len()],
+                    stopped: None,
+                };
+                for op in &func.body {
+                    lowering.lower_op(op);
+                }
+                lowering.ret();
+                lowering.finish();
+            },
+        );
+        module.define_function(*id, &mut ctx)?;
+        module.clear_context(&mut ctx);
+    }
+    module.finalize_definitions()?;
+    Ok((module, ids[program.ctx.lookup["main"]]))
+}
+
+/// Build the body of the function in `ctx` with `lower`, starting with the pointers from the
+/// parameters in variables.
+fn define(
+    module: &mut JITModule,
+    ctx: &mut Context,
+    func_ctx: &mut FunctionBuilderContext,
+    ptr: Type,
+    lower: impl FnOnce(&mut FunctionBuilder, &mut JITModule),
+) {
+    let mut builder = FunctionBuilder::new(&mut ctx.func, func_ctx);
+    let entry = builder.create_block();
+    builder.append_block_params_for_function_params(entry);
+    builder.switch_to_block(entry);
+    for index in [SP, RSP, OUT] {
+        builder.declare_var(var(index), ptr);
+        let param = builder.block_params(entry)[index as usize];
+        builder.def_var(var(index), param);
+    }
+    lower(&mut builder, module);
+    builder.seal_all_blocks();
+    builder.finalize();
+}
+
+/// Compile `program` and run it, writing its output to `out`.
+pub fn run(program: &Program, out: &mut dyn Write) -> Result<(), Box<dyn Error>> {
+    let mut stack = vec![0_u64; STACK_CELLS];
+    let mut ret_stack = vec![0_u64; RET_STACK_CELLS];
+    let stack = stack.as_mut_ptr_range();
+    let ret_stack = ret_stack.as_mut_ptr_range();
+    let bounds = Bounds {
+        stack_start: stack.start as i64,
+        stack_end: stack.end as i64,
+        ret_stack_start: ret_stack.start as i64,
+    };
+    let (module, main) = compile(program, bounds)?;
+    let mut output = Output {
+        writer: out,
+        error: None,
+        trap: None,
+    };
+
+    // SAFETY: the function was compiled with the signature of `RackFn`, and the stacks and
+    // output outlive the call. Every access to the stacks is checked against `bounds`, which
+    // they span, and the program is stopped before it would go outside them.
+    unsafe {
+        let main = mem::transmute::<*const u8, RackFn>(module.get_finalized_function(main));
+        main(stack.end, ret_stack.end, ptr::addr_of_mut!(output));
+        module.free_memory();
+    }
+    if let Some(trap) = output.trap {
+        return Err(trap.into());
+    }
+    output.error.map_or(Ok(()), |e| Err(e.into()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        codegen::test::{expected_stdout, programs, TARGET_DEPENDENT},
+        parse, Lexer,
+    };
+
+    #[test]
+    fn programs_match_expected() {
+        for (stem, path, source) in programs() {
+            if TARGET_DEPENDENT.contains(&stem.as_str()) {
+                continue;
+            }
+            let program = parse::parse_tokens(&mut Lexer::new(&source, path.to_str())).unwrap();
+            let mut out = Vec::new();
+            run(&program, &mut out).unwrap();
+            assert_eq!(
+                String::from_utf8_lossy(&out),
+                expected_stdout(&stem),
+                "`{stem}`"
+            );
+        }
+    }
+
+    #[test]
+    fn division_by_zero() {
+        let source = "fn main in 7 print 1 0 divmod print print end\n";
+        let program = parse::parse_tokens(&mut Lexer::new(source, None)).unwrap();
+        let mut out = Vec::new();
+        let error = run(&program, &mut out).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            crate::interp::RuntimeError::DivisionByZero.to_string()
+        );
+        assert_eq!(String::from_utf8_lossy(&out), "7\n");
+    }
+
+    #[test]
+    fn stack_bounds() {
+        for (source, error) in [
+            ("fn main in drop end\n", Trap::StackUnderflow),
+            ("fn main in 1 + end\n", Trap::StackUnderflow),
+            ("fn main in while true do 1 end end\n", Trap::StackOverflow),
+            (
+                "fn f in let x in x 1 + dup 20000 < if f end end end\nfn main in 0 f end\n",
+                Trap::RetStackOverflow,
+            ),
+            ("fn f in f end\nfn main in f end\n", Trap::RetStackOverflow),
+        ] {
+            let program = parse::parse_tokens(&mut Lexer::new(source, None)).unwrap();
+            let result = run(&program, &mut Vec::new());
+            assert_eq!(
+                result.unwrap_err().downcast_ref::<Trap>(),
+                Some(&error),
+                "{source}"
+            );
+        }
+    }
+}
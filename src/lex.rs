@@ -46,13 +46,13 @@ impl fmt::Display for TokenKind {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Location<'f> {
     pub file: Option<&'f str>,
     pub pos: (usize, usize),
 }
 
-impl<'f> fmt::Display for Location<'f> {
+impl fmt::Display for Location<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let (row, col) = self.pos;
         if let Some(file) = self.file {
@@ -66,6 +66,8 @@ impl<'f> fmt::Display for Location<'f> {
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub enum Keyword {
     Fn,
+    Export,
+    Extern,
     In,
     End,
     Plus,
@@ -95,6 +97,10 @@ pub enum Keyword {
     Ret,
     True,
     False,
+    Asm,
+    CfgIf,
+    CfgElse,
+    CfgEnd,
 }
 
 #[derive(Debug)]
@@ -131,6 +137,8 @@ macro_rules! keyword_str {
 
 keyword_str! {
     "fn" => Fn,
+    "export" => Export,
+    "extern" => Extern,
     "in" => In,
     "end" => End,
     "+" => Plus,
@@ -160,9 +168,13 @@ keyword_str! {
     "ret" => Ret,
     "true" => True,
     "false" => False,
+    "asm" => Asm,
+    "#if" => CfgIf,
+    "#else" => CfgElse,
+    "#end" => CfgEnd,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Lexer<'src> {
     cursor: usize,
     content: &'src str,
@@ -191,7 +203,7 @@ impl<'src> Lexer<'src> {
                     .content
                     .as_bytes()
                     .get(self.cursor)
-                    .map_or(false, |c| *c != b'\n')
+                    .is_some_and(|c| *c != b'\n')
                 {
                     self.cursor += 1;
                 }
@@ -9,206 +9,357 @@
     clippy::explicit_iter_loop
 )]
 
-use clap::Parser;
+use clap::{CommandFactory, ErrorKind, Parser, Subcommand};
 use std::{
+    convert::identity,
     error::Error,
-    fmt, fs,
-    path::Path,
+    fs, io,
+    path::{Path, PathBuf},
     process::{self, Stdio},
-    str::FromStr,
 };
 
 pub use crate::{
     lex::Lexer,
-    parse::{parse_tokens, Context, Func, Op, Program},
+    parse::{parse_tokens, CfgOption, Context, Func, Op, Program},
 };
+use codegen::{Artifact, Emit, Target};
+use trace::TraceFormat;
 
 mod codegen;
+mod coverage;
+mod debug;
+mod fuzz;
+mod interp;
+#[cfg(feature = "jit")]
+mod jit;
 mod lex;
 mod parse;
-
-#[allow(non_camel_case_types)]
-enum Target {
-    X86_64_Linux,
-    X86_64_FASM,
-    Mos6502_Nesulator,
-}
-
-impl Default for Target {
-    fn default() -> Self {
-        Self::X86_64_Linux
-    }
-}
-
-macro_rules! target_as_str {
-    {$($var:tt => $string:literal),*,} => {
-        impl fmt::Display for Target {
-            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-                match self {
-                    $(Self::$var => write!(f, $string)),*,
-                }
-            }
-        }
-
-        impl FromStr for Target {
-            type Err = TargetNotFoundError;
-
-            fn from_str(s: &str) -> Result<Self, Self::Err> {
-                match s.to_lowercase().as_str() {
-                    $($string => Ok(Self::$var)),*,
-                    _ => Err(TargetNotFoundError),
-                }
-            }
-        }
-    }
-}
-
-target_as_str! {
-    X86_64_Linux => "x86_64-linux",
-    X86_64_FASM => "x86_64-fasm",
-    Mos6502_Nesulator => "mos_6502-nesulator",
-}
-
-#[derive(Debug)]
-struct TargetNotFoundError;
-
-impl Error for TargetNotFoundError {}
-
-impl fmt::Display for TargetNotFoundError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        write!(f, "target not supported")
-    }
-}
+mod profile;
+mod repl;
+mod trace;
 
 #[derive(Parser)]
+#[clap(subcommand_negates_reqs = true)]
 struct Config {
-    /// Run the program after successful compilation
-    #[clap(short, long)]
+    #[clap(subcommand)]
+    command: Option<Command>,
+    /// Run the program after successful compilation, in memory if no target or output is given
+    #[clap(short, long, conflicts_with = "emit")]
     run: bool,
-    /// Target architecture
-    #[clap(short, long, default_value_t)]
-    target: Target,
+    /// Target architecture [default: x86_64-linux]
+    #[clap(short, long, global = true)]
+    target: Option<Target>,
+    /// Set an option for `#if` to test, given as `NAME` or `KEY=VALUE`
+    #[clap(long, value_name = "OPTION", global = true)]
+    cfg: Vec<CfgOption>,
+    /// What to generate: a whole program (`exe`), an object to link with others (`obj`) or a
+    /// static library (`lib`)
+    #[clap(
+        long,
+        value_name = "KIND",
+        default_value = "exe",
+        conflicts_with = "trace"
+    )]
+    emit: Emit,
     /// Input file
-    #[clap()]
-    file: String,
+    #[clap(required = true)]
+    file: Option<String>,
     /// Output file
     #[clap(short, long, value_name = "FILE")]
     out: Option<String>,
     /// Don't print log information
-    #[clap(short, long)]
+    #[clap(short, long, global = true)]
     quiet: bool,
+    /// Run the program on the evaluator instead, logging each op to stderr (`text` or `json`)
+    #[clap(
+        long,
+        value_name = "FORMAT",
+        min_values = 0,
+        require_equals = true,
+        default_missing_value = "text"
+    )]
+    trace: Option<TraceFormat>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Start an interactive session
+    Repl,
+    /// Run a program under the debugger
+    Debug {
+        /// Input file
+        file: String,
+    },
+    /// Run a program and report where it spends its time
+    Profile {
+        /// Input file
+        file: String,
+        /// Output file for the folded call stacks
+        #[clap(short, long, value_name = "FILE", default_value = "./out.folded")]
+        out: String,
+    },
+    /// Run programs and report which of their ops were executed
+    Coverage {
+        /// Input files
+        #[clap(required = true)]
+        files: Vec<String>,
+        /// Output file for the lcov report
+        #[clap(short, long, value_name = "FILE", default_value = "./lcov.info")]
+        out: String,
+    },
+    /// Compare the output of random programs on a target and the evaluator
+    Fuzz(fuzz::Options),
+    /// Link objects compiled with `--emit obj` into an executable
+    Link {
+        /// Input objects
+        #[clap(required = true)]
+        files: Vec<PathBuf>,
+        /// Output file
+        #[clap(short, long, value_name = "FILE", default_value = "./out")]
+        out: PathBuf,
+    },
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
     let config = Config::parse();
+    // Global options may come before a subcommand, but the others only apply to an input file.
+    if config.command.is_some()
+        && (config.run
+            || config.out.is_some()
+            || config.trace.is_some()
+            || config.emit != Emit::Exe)
+    {
+        Config::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "Only `--target`, `--cfg` and `--quiet` can be given before a subcommand",
+            )
+            .exit();
+    }
 
-    let source_f = &config.file;
+    let source_f = match &config.command {
+        Some(Command::Repl) => return repl::run(),
+        Some(Command::Coverage { files, out }) => return coverage::run(files, out, config.quiet),
+        Some(Command::Fuzz(options)) => {
+            return fuzz::run(options, config.target.unwrap_or_default())
+        }
+        Some(Command::Link { files, out }) => {
+            link(files, out, &config);
+            return Ok(());
+        }
+        Some(Command::Debug { file } | Command::Profile { file, .. }) => file,
+        None => config
+            .file
+            .as_ref()
+            .expect("file is required without a subcommand"),
+    };
     let Ok(source) = fs::read_to_string(source_f) else {
         eprintln!("Couldn't read file `{source_f}`");
         process::exit(1);
     };
 
     let mut lexer = Lexer::new(&source, Some(source_f));
-    let program = parse::parse_tokens(&mut lexer).unwrap_or_else(|e| {
+    let backend = config.target.unwrap_or_default().0;
+    let mut program = Program::default();
+    program.ctx.cell_width = backend.cell_width();
+    program.ctx.object = config.emit != Emit::Exe;
+    // C functions and inline assembly can only be used in the target's output, not by the
+    // evaluator.
+    let compiled = config.command.is_none() && config.trace.is_none();
+    program.ctx.allow_extern = backend.extern_fns() && compiled;
+    program.ctx.arch = backend.arch().filter(|_| compiled);
+    program.ctx.cfg.extend(config.cfg.iter().cloned());
+    program
+        .ctx
+        .cfg
+        .insert(CfgOption::new("target", Some(backend.name())));
+    if let Some(arch) = backend.arch() {
+        program.ctx.cfg.insert(CfgOption::new("arch", Some(arch)));
+    }
+    if let (Some(prelude), true) = (backend.prelude(), compiled) {
+        parse::parse_prelude(&mut Lexer::new(prelude, Some("<prelude>")), &mut program)
+            .expect("the prelude parses");
+    }
+    parse::parse_items(&mut lexer, &mut program).unwrap_or_else(|e| {
         eprintln!("{e}");
         process::exit(1);
     });
     // TODO: do this properly.
-    if !program.funcs.iter().any(|f| f.ident == "main") {
+    if config.emit == Emit::Exe && !program.funcs.iter().any(|f| f.ident == "main") {
         eprintln!("[ERROR] No entry point `main` found.");
         process::exit(1);
     }
 
-    // Determine output path of compiled program
-    let default_path = &config.out.clone().unwrap_or_else(|| {
-        match config.target {
-            Target::X86_64_Linux | Target::Mos6502_Nesulator => "./out",
-            Target::X86_64_FASM => "./out.asm",
-        }
-        .to_string()
-    });
-    let output_path = Path::new(&default_path);
-
-    match config.target {
-        Target::X86_64_Linux => {
-            let asm_path = output_path
-                .with_extension("asm")
-                .to_str()
-                .expect("path name is invalid unicode")
-                .to_owned();
-
-            if !config.quiet {
-                println!("[INFO] Generating `{asm_path}`");
-            }
+    match &config.command {
+        Some(Command::Debug { .. }) => return debug::run(&program, &source),
+        Some(Command::Profile { out, .. }) => return profile::run(&program, out, config.quiet),
+        _ => {}
+    }
 
-            let outbuf = codegen::fasm_x86_64_linux::generate(program)?;
-            fs::write(&asm_path, outbuf)
-                .unwrap_or_else(|_| panic!("failed to write to {asm_path}"));
+    if let Some(format) = config.trace {
+        let mut trace = io::BufWriter::new(io::stderr());
+        if let Err(e) = trace::run(&program, format, &mut io::stdout(), &mut trace) {
+            drop(trace);
+            eprintln!("[ERROR] {e}");
+            process::exit(1);
+        }
+        return Ok(());
+    }
 
-            run_command(&format!("fasm {asm_path}"), &config, !config.quiet);
+    // Without a target or an output file there is nothing to keep, so `--run` can skip the
+    // target's tools and compile straight to memory.
+    #[cfg(feature = "jit")]
+    if config.run
+        && config.target.is_none()
+        && config.out.is_none()
+        && program.ctx.externs.is_empty()
+        && !program.ctx.inline_asm
+    {
+        if let Err(e) = jit::run(&program, &mut io::stdout()) {
+            eprintln!("[ERROR] {e}");
+            process::exit(1);
+        }
+        return Ok(());
+    }
 
-            let output_path = output_path
-                .to_str()
-                .expect("path name is invalid unicode")
-                .to_owned();
-            run_command(&format!("chmod +x {output_path}"), &config, !config.quiet);
+    build(program, &config);
+    Ok(())
+}
 
-            if config.run {
-                run_command(&output_path, &config, true);
-            }
+/// Generate the output for `program` and build it, running it afterwards if asked to.
+fn build(program: Program, config: &Config) {
+    let backend = config.target.unwrap_or_default().0;
+    // An object that is assembled gets the usual extension, rather than that of the executable.
+    let extension = match (config.emit, backend.intermediate()) {
+        (Emit::Obj, Some(_)) => Some("o"),
+        (Emit::Lib, _) => Some("a"),
+        _ => backend.extension(),
+    };
+    let output_path = match &config.out {
+        Some(out) => PathBuf::from(out),
+        None => Path::new("./out").with_extension(extension.unwrap_or_default()),
+    };
+    let artifact_path = match backend.intermediate() {
+        Some(extension) => output_path.with_extension(extension),
+        None => output_path.clone(),
+    };
 
-            Ok(())
+    // A program that calls C functions is built as an object and linked with the C library.
+    let hosted = config.emit == Emit::Exe && !program.ctx.externs.is_empty();
+    let commands = match config.emit {
+        Emit::Exe if hosted => {
+            let object = output_path.with_extension("o");
+            let mut commands = backend.build_object(&artifact_path, &object);
+            commands.extend(backend.link(&[object], &output_path));
+            commands
         }
-        Target::X86_64_FASM => {
-            let output_path = output_path
-                .to_str()
-                .expect("path name is invalid unicode")
-                .to_owned();
-            if !config.quiet {
-                println!("[INFO] Generating `{output_path}`");
-            }
-            let outbuf = codegen::fasm_x86_64_linux::generate(program)?;
-            fs::write(&output_path, outbuf)
-                .unwrap_or_else(|_| panic!("failed to write to {output_path}"));
-            Ok(())
+        Emit::Exe => backend.build(&artifact_path, &output_path),
+        Emit::Obj => backend.build_object(&artifact_path, &output_path),
+        Emit::Lib => {
+            let object = output_path.with_extension("o");
+            let mut commands = backend.build_object(&artifact_path, &object);
+            let Some(archive) = backend.archive(&[object], &output_path) else {
+                eprintln!(
+                    "[ERROR] Static libraries can't be built for `{}`",
+                    backend.name()
+                );
+                process::exit(1);
+            };
+            commands.push(archive);
+            commands
         }
-        Target::Mos6502_Nesulator => {
-            let output_path = output_path
-                .to_str()
-                .expect("path name is invalid unicode")
-                .to_owned();
-            if !config.quiet {
-                println!("[INFO] Generating `{output_path}`");
-            }
+    };
 
-            let outbuf = codegen::mos_6502_nesulator::generate(program);
-            fs::write(&output_path, outbuf)
-                .unwrap_or_else(|_| panic!("Unable to write to {output_path}"));
+    // Objects that export functions come with a header declaring them for C.
+    let header = (config.emit != Emit::Exe && program.funcs.iter().any(|f| f.export.is_some()))
+        .then(|| {
+            let path = output_path.with_extension("h");
+            let guard = path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() {
+                        c.to_ascii_uppercase()
+                    } else {
+                        '_'
+                    }
+                })
+                .collect::<String>();
+            let header = codegen::c_header(&program, &guard).expect("writing to a string succeeds");
+            (path, header)
+        });
 
-            if !config.quiet {
-                println!("[INFO] Wrote {} bytes", outbuf.len());
-            }
-
-            Ok(())
+    if !config.quiet {
+        println!("[INFO] Generating `{}`", artifact_path.display());
+    }
+    let artifact = if config.emit == Emit::Exe && !hosted {
+        backend.generate(program)
+    } else {
+        backend.generate_object(program)
+    }
+    .unwrap_or_else(|e| {
+        eprintln!("[ERROR] {e}");
+        process::exit(1);
+    });
+    fs::write(&artifact_path, artifact.as_bytes())
+        .unwrap_or_else(|_| panic!("failed to write to {}", artifact_path.display()));
+    if let (Artifact::Binary(bytes), false) = (&artifact, config.quiet) {
+        println!("[INFO] Wrote {} bytes", bytes.len());
+    }
+    if let Some((path, header)) = header {
+        if !config.quiet {
+            println!("[INFO] Generating `{}`", path.display());
         }
+        fs::write(&path, header)
+            .unwrap_or_else(|_| panic!("failed to write to {}", path.display()));
+    }
+
+    for mut cmd in commands {
+        run_command(&mut cmd, config, !config.quiet, identity);
     }
+    if config.run {
+        let Some(mut cmd) = backend.run(&output_path) else {
+            eprintln!(
+                "[ERROR] Programs for `{}` can't be run here",
+                backend.name()
+            );
+            process::exit(1);
+        };
+        run_command(&mut cmd, config, true, |code| backend.exit_code(code));
+    }
+}
+
+/// Link the objects in `files` into an executable at `out`.
+fn link(files: &[PathBuf], out: &Path, config: &Config) {
+    let backend = config.target.unwrap_or_default().0;
+    let Some(mut cmd) = backend.link(files, out) else {
+        eprintln!("[ERROR] Objects for `{}` can't be linked", backend.name());
+        process::exit(1);
+    };
+    run_command(&mut cmd, config, !config.quiet, identity);
 }
 
-fn run_command(cmd: &str, config: &Config, echo: bool) {
+/// Run `cmd`, exiting with its status, as converted by `exit_code`, if that isn't 0.
+fn run_command(
+    cmd: &mut process::Command,
+    config: &Config,
+    echo: bool,
+    exit_code: impl Fn(i32) -> i32,
+) {
     if !config.quiet {
-        println!("[INFO] Running `{cmd}`");
+        let mut line = cmd.get_program().to_string_lossy().into_owned();
+        for arg in cmd.get_args() {
+            line = line + " " + &arg.to_string_lossy();
+        }
+        println!("[INFO] Running `{line}`");
     }
-    let mut cmd = cmd.split_whitespace();
 
     let out_pipe = if echo { Stdio::inherit } else { Stdio::null };
-    match process::Command::new(cmd.next().expect("No command provided"))
-        .args(cmd)
-        .stdout(out_pipe())
-        .stderr(out_pipe())
-        .output()
-    {
+    match cmd.stdout(out_pipe()).stderr(out_pipe()).output() {
         Ok(output) => {
-            if let Some(code) = output.status.code() {
+            if let Some(code) = output.status.code().map(exit_code) {
                 if code != 0 {
                     process::exit(code)
                 }
@@ -1,11 +1,18 @@
-use std::{borrow::Cow, collections::HashMap};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt,
+    ops::Deref,
+    str::FromStr,
+};
 
-use crate::lex::{Keyword, Lexer, Location, TokenKind};
+use crate::lex::{Keyword, Lexer, Location, Token, TokenKind};
 
 use thiserror::Error;
 
-#[derive(Debug, PartialEq, Eq)]
-pub enum Op {
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Op<'src> {
     PushInt(u64),
     PushStrPtr(usize),
     Plus,
@@ -23,19 +30,20 @@ pub enum Op {
     Or,
     And,
     ReadByte,
-    If(Vec<Op>),
+    If(Vec<LocatedOp<'src>>),
     While {
-        condn: Vec<Op>,
-        body: Vec<Op>,
+        condn: Vec<LocatedOp<'src>>,
+        body: Vec<LocatedOp<'src>>,
     },
     Print,
     CallFn(usize),
     // We just copy the bound values to the return stack, so the only info needed by codegen is
-    // actually just the number of bindings (`count`).
+    // actually just the number of bindings (`count`). The `names` are kept for debugging.
     Bind {
         count: usize,
+        names: Vec<&'src str>,
         peek: bool,
-        body: Vec<Op>,
+        body: Vec<LocatedOp<'src>>,
     },
     // As per the previous comment, we can just use the index from the top of the stack of the
     // binding we want.
@@ -43,6 +51,73 @@ pub enum Op {
     // We hold the number of additional stack frames to drop (due to let/peek bindings).
     Ret(usize),
     Puts,
+    /// Inline assembly, passed through into the output line by line. It takes and leaves cells on
+    /// the data stack as given by `signature`, in whatever form the target keeps it.
+    Asm {
+        signature: Signature,
+        lines: Vec<Cow<'src, str>>,
+    },
+}
+
+/// An op along with the location in the source that it was parsed from. It dereferences to the
+/// op, so that code generation can ignore the location.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LocatedOp<'src> {
+    pub op: Op<'src>,
+    pub location: Location<'src>,
+}
+
+impl<'src> Deref for LocatedOp<'src> {
+    type Target = Op<'src>;
+
+    fn deref(&self) -> &Op<'src> {
+        &self.op
+    }
+}
+
+impl<'src> PartialEq<Op<'src>> for LocatedOp<'src> {
+    fn eq(&self, other: &Op<'src>) -> bool {
+        self.op == *other
+    }
+}
+
+/// The width of a cell on the stacks, which is a property of the target. Arithmetic wraps around
+/// at the width of a cell, and integer literals have to fit in one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellWidth {
+    bits: u32,
+}
+
+impl CellWidth {
+    pub const fn new(bits: u32) -> Self {
+        assert!(matches!(bits, 8 | 16 | 32 | 64), "unsupported cell width");
+        Self { bits }
+    }
+
+    pub const fn bits(self) -> u32 {
+        self.bits
+    }
+
+    /// The size of a cell in bytes.
+    pub const fn bytes(self) -> usize {
+        self.bits as usize / 8
+    }
+
+    /// The largest value that fits in a cell.
+    pub const fn max(self) -> u64 {
+        u64::MAX >> (u64::BITS - self.bits)
+    }
+
+    /// Wrap `val` around to the width of a cell.
+    pub const fn wrap(self, val: u64) -> u64 {
+        val & self.max()
+    }
+}
+
+impl Default for CellWidth {
+    fn default() -> Self {
+        Self::new(64)
+    }
 }
 
 #[derive(Error, Debug, Clone)]
@@ -65,11 +140,22 @@ pub enum SyntaxError<'src> {
         identifier: &'src str,
         location: Location<'src>,
     },
+    #[error("{location}: `{identifier}` is already defined")]
+    Redefinition {
+        identifier: &'src str,
+        location: Location<'src>,
+    },
     #[error("{location}: `{kw}` does not make sense in the current context")]
     UnexpectedKeyword {
         kw: Keyword,
         location: Location<'src>,
     },
+    #[error("{location}: `{value}` does not fit in a {bits}-bit cell")]
+    LiteralOutOfRange {
+        value: u64,
+        bits: u32,
+        location: Location<'src>,
+    },
     #[error("{location}: {message}")]
     Generic {
         location: Location<'src>,
@@ -77,19 +163,84 @@ pub enum SyntaxError<'src> {
     },
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default, Clone)]
 pub struct Program<'src> {
     pub funcs: Vec<Func<'src>>,
     pub ctx: Context<'src>,
 }
 
+/// An option that `#if` tests for: a flag such as `debug`, or a key with a value such as
+/// `target = "x86_64-linux"`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CfgOption {
+    pub key: String,
+    pub value: Option<String>,
+}
+
+impl CfgOption {
+    pub fn new(key: &str, value: Option<&str>) -> Self {
+        Self {
+            key: key.to_owned(),
+            value: value.map(ToOwned::to_owned),
+        }
+    }
+}
+
 #[derive(Debug)]
+pub struct InvalidCfgError;
+
+impl Error for InvalidCfgError {}
+
+impl fmt::Display for InvalidCfgError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid option (expected `NAME` or `KEY=VALUE`)")
+    }
+}
+
+impl FromStr for CfgOption {
+    type Err = InvalidCfgError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = match s.split_once('=') {
+            Some((key, value)) => (key, Some(value)),
+            None => (s, None),
+        };
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return Err(InvalidCfgError);
+        }
+        Ok(Self::new(key, value))
+    }
+}
+
+/// The number of cells that a function takes from the stack and leaves on it, as declared for
+/// calls across the C ABI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Signature {
+    pub inputs: usize,
+    pub outputs: usize,
+}
+
+#[derive(Debug, Clone)]
 pub struct Func<'src> {
     pub ident: &'src str,
-    pub body: Vec<Op>,
+    /// The signature of the C function that calls this one, if it is exported.
+    pub export: Option<Signature>,
+    /// Empty for a function that is imported from another object, since every function that is
+    /// defined at least returns.
+    pub body: Vec<LocatedOp<'src>>,
+    pub location: Location<'src>,
+}
+
+impl Func<'_> {
+    /// Whether the function is only called here and defined in another object.
+    pub fn is_import(&self) -> bool {
+        self.body.is_empty()
+    }
 }
 
-#[derive(Debug, Default)]
+// The flags are independent options set by the driver.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Default, Clone)]
 pub struct Context<'src> {
     /// Nametable.
     pub lookup: HashMap<&'src str, usize>,
@@ -98,23 +249,159 @@ pub struct Context<'src> {
     /// String literals referencing directly into the source, or in a heap allocation for where
     /// escapes were used.
     pub strings: Vec<Cow<'src, str>>,
+    /// The width of a cell on the target, which every integer literal is checked against.
+    pub cell_width: CellWidth,
+    /// Whether the program is compiled on its own into an object, in which case calls to unknown
+    /// functions are imported from the other objects it is linked with.
+    pub object: bool,
+    /// Whether a function may be defined again, replacing the earlier definition, as it can in
+    /// the REPL.
+    pub redefine: bool,
+    /// Whether the target can call C functions, so that they may be declared with `extern fn`.
+    pub allow_extern: bool,
+    /// The signatures of the C functions declared with `extern fn`, which are in the program as
+    /// imports.
+    pub externs: HashMap<&'src str, Signature>,
+    /// The architecture of the target's assembly, if inline assembly can be passed through into
+    /// it.
+    pub arch: Option<&'static str>,
+    /// Whether the program has inline assembly, so that it can only be compiled for `arch`.
+    pub inline_asm: bool,
+    /// The options that `#if` tests for, which describe the target and come from `--cfg`.
+    pub cfg: HashSet<CfgOption>,
+    /// The functions from the target's prelude that the program hasn't defined its own versions
+    /// of yet, which replace them.
+    prelude: HashSet<&'src str>,
+    /// The location of each `#if` that hasn't been closed by `#end` yet, and whether its `#else`
+    /// branch is the one being parsed. A block can only close the ones opened in it.
+    conditionals: Vec<(Location<'src>, bool)>,
+    /// The locations of the first calls to the functions imported so far that haven't been added
+    /// to the program yet.
+    imports: Vec<Location<'src>>,
     bindings: Vec<&'src str>,
 }
 
 impl<'src> Context<'src> {
-    fn insert_func_ident(&mut self, ident: &'src str) {
-        self.lookup.insert(ident, self.lookup.len());
+    /// Returns the index of the function in the nametable. An identifier that is already known
+    /// keeps its index, so that a definition replaces an import or, in the REPL, the old function.
+    fn insert_func_ident(&mut self, ident: &'src str) -> usize {
+        if let Some(index) = self.lookup.get(ident) {
+            return *index;
+        }
+        let index = self.lookup.len();
+        self.lookup.insert(ident, index);
         self.func_idents.push(ident);
+        index
+    }
+
+    /// An op pushing `value`, if it fits in a cell.
+    fn push_int(
+        &self,
+        value: u64,
+        location: &Location<'src>,
+    ) -> Result<Op<'src>, SyntaxError<'src>> {
+        if value > self.cell_width.max() {
+            return Err(SyntaxError::LiteralOutOfRange {
+                value,
+                bits: self.cell_width.bits(),
+                location: location.clone(),
+            });
+        }
+        Ok(Op::PushInt(value))
+    }
+
+    /// Close the innermost conditional, unless it was opened outside of the block that was
+    /// entered with `depth` conditionals open.
+    fn close_conditional(&mut self, depth: usize) -> Option<(Location<'src>, bool)> {
+        if self.conditionals.len() > depth {
+            self.conditionals.pop()
+        } else {
+            None
+        }
     }
 }
 
 pub fn parse_tokens<'src>(lexer: &mut Lexer<'src>) -> Result<Program<'src>, SyntaxError<'src>> {
-    let mut funcs = Vec::new();
-    let mut ctx = Context::default();
-    loop {
-        let Some(t) = lexer.next() else { break };
+    parse_tokens_for(lexer, CellWidth::default())
+}
+
+/// Parse a program for a target whose cells are `cell_width` wide.
+pub fn parse_tokens_for<'src>(
+    lexer: &mut Lexer<'src>,
+    cell_width: CellWidth,
+) -> Result<Program<'src>, SyntaxError<'src>> {
+    let mut program = Program::default();
+    program.ctx.cell_width = cell_width;
+    parse_items(lexer, &mut program)?;
+    Ok(program)
+}
+
+/// Parse top-level items into an existing program, so that they may refer to the functions that
+/// were already defined in it.
+pub fn parse_items<'src>(
+    lexer: &mut Lexer<'src>,
+    program: &mut Program<'src>,
+) -> Result<(), SyntaxError<'src>> {
+    while let Some(t) = lexer.next() {
         match t.kind {
-            TokenKind::Keyword(Keyword::Fn) => funcs.push(parse_fn(lexer, &mut ctx)?),
+            TokenKind::Keyword(kw @ (Keyword::Fn | Keyword::Export)) => {
+                if kw == Keyword::Export {
+                    lexer.expect_next(TokenKind::Keyword(Keyword::Fn))?;
+                }
+                let (index, func) = parse_fn(lexer, &mut program.ctx, kw == Keyword::Export)?;
+                if index < program.funcs.len() {
+                    if !(program.ctx.redefine
+                        || program.funcs[index].is_import()
+                        || program.ctx.prelude.remove(func.ident))
+                    {
+                        return Err(SyntaxError::Redefinition {
+                            identifier: func.ident,
+                            location: func.location,
+                        });
+                    }
+                    program.funcs[index] = func;
+                } else {
+                    program.funcs.push(func);
+                }
+                // The functions imported by the body come after it in the nametable.
+                for location in program.ctx.imports.drain(..) {
+                    program.funcs.push(Func {
+                        ident: program.ctx.func_idents[program.funcs.len()],
+                        export: None,
+                        body: Vec::new(),
+                        location,
+                    });
+                }
+            }
+            TokenKind::Keyword(Keyword::Extern) => {
+                lexer.expect_next(TokenKind::Keyword(Keyword::Fn))?;
+                let t = lexer.expect_next(TokenKind::Identifier)?;
+                let ctx = &mut program.ctx;
+                if !ctx.allow_extern {
+                    return Err(SyntaxError::Generic {
+                        location: t.location,
+                        message: "C functions can only be called when compiling for a target that links with C",
+                    });
+                }
+                if ctx.lookup.contains_key(t.value) {
+                    return Err(SyntaxError::Generic {
+                        location: t.location,
+                        message: "C functions have to be declared before the name is used",
+                    });
+                }
+                let signature = parse_c_signature(lexer, &t)?;
+                ctx.insert_func_ident(t.value);
+                ctx.externs.insert(t.value, signature);
+                program.funcs.push(Func {
+                    ident: t.value,
+                    export: None,
+                    body: Vec::new(),
+                    location: t.location,
+                });
+            }
+            TokenKind::Keyword(kw @ (Keyword::CfgIf | Keyword::CfgElse | Keyword::CfgEnd)) => {
+                parse_cfg(lexer, &mut program.ctx, kw, t.location, 0)?;
+            }
             _ => {
                 return Err(SyntaxError::UnexpectedTopLevel {
                     found: t.kind,
@@ -123,57 +410,102 @@ pub fn parse_tokens<'src>(lexer: &mut Lexer<'src>) -> Result<Program<'src>, Synt
             }
         }
     }
-    Ok(Program { funcs, ctx })
+    // Like any other block, a conditional that isn't closed runs into the end of the input.
+    if !program.ctx.conditionals.is_empty() {
+        return Err(SyntaxError::Eof(lexer.location()));
+    }
+    Ok(())
+}
+
+/// Parse the target's prelude into `program`, whose own definitions may then replace the
+/// functions in it.
+pub fn parse_prelude<'src>(
+    lexer: &mut Lexer<'src>,
+    program: &mut Program<'src>,
+) -> Result<(), SyntaxError<'src>> {
+    parse_items(lexer, program)?;
+    program.ctx.prelude = program.funcs.iter().map(|func| func.ident).collect();
+    Ok(())
+}
+
+/// Parse a sequence of ops running until the end of the input, as if it were the body of a
+/// function called `ident`.
+pub fn parse_body<'src>(
+    lexer: &mut Lexer<'src>,
+    ctx: &mut Context<'src>,
+    ident: &'src str,
+) -> Result<Func<'src>, SyntaxError<'src>> {
+    let location = lexer.location();
+    finish_fn(lexer, ctx, ident, location, None)
 }
 
 #[allow(clippy::too_many_lines)]
 fn parse_block<'src>(
     lexer: &mut Lexer<'src>,
     ctx: &mut Context<'src>,
-    terminator: Keyword,
-) -> Result<Vec<Op>, SyntaxError<'src>> {
+    terminator: Option<Keyword>,
+) -> Result<Vec<LocatedOp<'src>>, SyntaxError<'src>> {
     let mut body = Vec::new();
+    // The conditionals opened outside of the block, which have to be closed outside of it too.
+    let depth = ctx.conditionals.len();
     loop {
-        let t = lexer
-            .next()
-            .ok_or_else(|| SyntaxError::Eof(lexer.location()))?;
+        let t = match lexer.next() {
+            Some(t) => t,
+            None if terminator.is_none() && ctx.conditionals.len() == depth => break,
+            None => return Err(SyntaxError::Eof(lexer.location())),
+        };
+        macro_rules! emit {
+            ($op:expr) => {{
+                let op = $op;
+                body.push(LocatedOp {
+                    op,
+                    location: t.location.clone(),
+                });
+            }};
+        }
 
         match t.kind {
-            TokenKind::Int(num) => body.push(Op::PushInt(num)),
+            TokenKind::Int(num) => emit!(ctx.push_int(num, &t.location)?),
             TokenKind::Keyword(kw) => {
-                if kw == terminator {
+                if Some(kw) == terminator {
+                    if let Some((location, _)) = ctx.conditionals.get(depth) {
+                        return Err(SyntaxError::Generic {
+                            location: location.clone(),
+                            message: "`#if` has to be closed by `#end` in the same block",
+                        });
+                    }
                     break;
                 }
                 match kw {
-                    Keyword::Plus => body.push(Op::Plus),
-                    Keyword::Minus => body.push(Op::Minus),
-                    Keyword::Print => body.push(Op::Print),
-                    Keyword::Dup => body.push(Op::Dup),
-                    Keyword::Drop => body.push(Op::Drop),
-                    Keyword::Swap => body.push(Op::Swap),
-                    Keyword::Over => body.push(Op::Over),
-                    Keyword::True => body.push(Op::PushInt(1)),
-                    Keyword::False => body.push(Op::PushInt(0)),
-                    Keyword::Equals => body.push(Op::Equals),
-                    Keyword::Neq => body.push(Op::Neq),
-                    Keyword::Not => body.push(Op::Not),
-                    Keyword::GreaterThan => body.push(Op::GreaterThan),
-                    Keyword::LessThan => body.push(Op::LessThan),
-                    Keyword::Or => body.push(Op::Or),
-                    Keyword::And => body.push(Op::And),
-                    Keyword::ReadByte => body.push(Op::ReadByte),
-                    Keyword::Puts => body.push(Op::Puts),
-                    Keyword::DivMod => body.push(Op::DivMod),
+                    Keyword::Plus => emit!(Op::Plus),
+                    Keyword::Minus => emit!(Op::Minus),
+                    Keyword::Print => emit!(Op::Print),
+                    Keyword::Dup => emit!(Op::Dup),
+                    Keyword::Drop => emit!(Op::Drop),
+                    Keyword::Swap => emit!(Op::Swap),
+                    Keyword::Over => emit!(Op::Over),
+                    Keyword::True => emit!(Op::PushInt(1)),
+                    Keyword::False => emit!(Op::PushInt(0)),
+                    Keyword::Equals => emit!(Op::Equals),
+                    Keyword::Neq => emit!(Op::Neq),
+                    Keyword::Not => emit!(Op::Not),
+                    Keyword::GreaterThan => emit!(Op::GreaterThan),
+                    Keyword::LessThan => emit!(Op::LessThan),
+                    Keyword::Or => emit!(Op::Or),
+                    Keyword::And => emit!(Op::And),
+                    Keyword::ReadByte => emit!(Op::ReadByte),
+                    Keyword::Puts => emit!(Op::Puts),
+                    Keyword::DivMod => emit!(Op::DivMod),
                     Keyword::Div => {
-                        body.push(Op::DivMod);
-                        body.push(Op::Drop);
+                        emit!(Op::DivMod);
+                        emit!(Op::Drop);
                     }
                     Keyword::Mod => {
-                        body.push(Op::DivMod);
-                        body.push(Op::Swap);
-                        body.push(Op::Drop);
+                        emit!(Op::DivMod);
+                        emit!(Op::Swap);
+                        emit!(Op::Drop);
                     }
-                    Keyword::Fn => {
+                    Keyword::Fn | Keyword::Export | Keyword::Extern => {
                         return Err(SyntaxError::Generic {
                             location: t.location,
                             message: "no function definitions outside of top-level",
@@ -186,28 +518,20 @@ fn parse_block<'src>(
                         })
                     }
                     Keyword::If => {
-                        body.push(Op::If(parse_block(lexer, ctx, Keyword::End)?));
-                    }
-                    Keyword::While => {
-                        let condn = parse_block(lexer, ctx, Keyword::Do)?;
-                        let loop_body = parse_block(lexer, ctx, Keyword::End)?;
-                        body.push(Op::While {
-                            condn,
-                            body: loop_body,
-                        });
+                        emit!(Op::If(parse_block(lexer, ctx, Some(Keyword::End))?));
                     }
+                    Keyword::While => emit!(Op::While {
+                        condn: parse_block(lexer, ctx, Some(Keyword::Do))?,
+                        body: parse_block(lexer, ctx, Some(Keyword::End))?,
+                    }),
                     Keyword::Let | Keyword::Peek => {
                         let bindings_count = ctx.bindings.len();
-                        let mut count = 0;
                         loop {
                             let next_t = lexer
                                 .next()
                                 .ok_or_else(|| SyntaxError::Eof(lexer.location()))?;
                             match next_t.kind {
-                                TokenKind::Identifier => {
-                                    ctx.bindings.push(next_t.value);
-                                    count += 1;
-                                }
+                                TokenKind::Identifier => ctx.bindings.push(next_t.value),
                                 TokenKind::Keyword(Keyword::In) => break,
                                 found => {
                                     return Err(SyntaxError::UnexpectedToken {
@@ -218,16 +542,22 @@ fn parse_block<'src>(
                                 }
                             }
                         }
-                        body.push(Op::Bind {
-                            count,
+                        let names = ctx.bindings[bindings_count..].to_vec();
+                        emit!(Op::Bind {
+                            count: names.len(),
+                            names,
                             peek: matches!(t.kind, TokenKind::Keyword(Keyword::Peek)),
-                            body: parse_block(lexer, ctx, Keyword::End)?,
+                            body: parse_block(lexer, ctx, Some(Keyword::End))?,
                         });
                         // We can safely remove all the new bindings from ctx as the scope has
                         // ended.
                         ctx.bindings.drain(bindings_count..);
                     }
-                    Keyword::Ret => body.push(Op::Ret(ctx.bindings.len())),
+                    Keyword::Ret => emit!(Op::Ret(ctx.bindings.len())),
+                    Keyword::Asm => emit!(parse_asm(lexer, ctx, &t)?),
+                    Keyword::CfgIf | Keyword::CfgElse | Keyword::CfgEnd => {
+                        parse_cfg(lexer, ctx, kw, t.location, depth)?;
+                    }
                     Keyword::Do | Keyword::End => {
                         return Err(SyntaxError::UnexpectedKeyword {
                             kw,
@@ -244,9 +574,12 @@ fn parse_block<'src>(
                     let symbol = ctx.lookup.get(&t.value).unwrap_or_else(|| {
                         panic!("`{0}` is in scope => `{0}` is in nametable", t.value)
                     });
-                    body.push(Op::CallFn(*symbol));
+                    emit!(Op::CallFn(*symbol));
                 } else if let Some(index) = ctx.bindings.iter().rev().position(|b| *b == t.value) {
-                    body.push(Op::PushBind(index));
+                    emit!(Op::PushBind(index));
+                } else if ctx.object {
+                    ctx.imports.push(t.location.clone());
+                    emit!(Op::CallFn(ctx.insert_func_ident(t.value)));
                 } else {
                     return Err(SyntaxError::UnknownIdentifier {
                         identifier: t.value,
@@ -257,24 +590,7 @@ fn parse_block<'src>(
             TokenKind::String => {
                 // Clean the strings - involves stripping the delimiting " and escaping \s.
                 // Must be done now so that we have an accurate length for `Op::PushInt`.
-                let value = t
-                    .value
-                    .strip_prefix('"')
-                    .expect("string literal only lexed with opening `\"`")
-                    .strip_suffix('"')
-                    .expect("string literal only lexed with closed `\"`");
-
-                let value = if t.value.contains('\\') {
-                    Cow::Owned(
-                        value
-                            .replace("\\n", "\n")
-                            .replace("\\n", "\n")
-                            .replace("\\t", "\t")
-                            .replace("\\0", "\0"),
-                    )
-                } else {
-                    Cow::Borrowed(value)
-                };
+                let value = string_value(&t);
                 let len = value.len();
 
                 // Small optimisation: if an equal string already exists as a literal then we don't
@@ -288,8 +604,8 @@ fn parse_block<'src>(
                 if index == ctx.strings.len() {
                     ctx.strings.push(value);
                 }
-                body.push(Op::PushInt(len as u64));
-                body.push(Op::PushStrPtr(index));
+                emit!(ctx.push_int(len as u64, &t.location)?);
+                emit!(Op::PushStrPtr(index));
             }
             TokenKind::Char => {
                 // For now, we are not supporting escapes in chars. This is a priority to support
@@ -307,23 +623,402 @@ fn parse_block<'src>(
                     });
                 }
                 let value = value.chars().next().expect("we just asserted count == 1") as u64;
-                body.push(Op::PushInt(value));
+                emit!(ctx.push_int(value, &t.location)?);
             }
         }
     }
     Ok(body)
 }
 
+/// Parse a signature such as `2 -- 1`.
+fn parse_signature<'src>(lexer: &mut Lexer<'src>) -> Result<Signature, SyntaxError<'src>> {
+    let inputs = parse_count(lexer)?;
+    lexer.expect_next(TokenKind::Keyword(Keyword::Minus))?;
+    lexer.expect_next(TokenKind::Keyword(Keyword::Minus))?;
+    let outputs = parse_count(lexer)?;
+    Ok(Signature { inputs, outputs })
+}
+
+/// Parse the number of cells in a signature.
+fn parse_count<'src>(lexer: &mut Lexer<'src>) -> Result<usize, SyntaxError<'src>> {
+    let t = lexer
+        .next()
+        .ok_or_else(|| SyntaxError::Eof(lexer.location()))?;
+    match t.kind {
+        TokenKind::Int(count) => Ok(usize::try_from(count).unwrap_or(usize::MAX)),
+        found => Err(SyntaxError::UnexpectedToken {
+            expected: TokenKind::Int(0),
+            found,
+            location: t.location,
+        }),
+    }
+}
+
+/// The contents of a string literal, with the delimiting `"` stripped and the escapes replaced.
+fn string_value<'src>(t: &Token<'src>) -> Cow<'src, str> {
+    let value = t
+        .value
+        .strip_prefix('"')
+        .expect("string literal only lexed with opening `\"`")
+        .strip_suffix('"')
+        .expect("string literal only lexed with closed `\"`");
+
+    if t.value.contains('\\') {
+        Cow::Owned(
+            value
+                .replace("\\n", "\n")
+                .replace("\\n", "\n")
+                .replace("\\t", "\t")
+                .replace("\\0", "\0"),
+        )
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// Handle the conditional compilation directive `kw` at `location`, in a block that was entered
+/// with `depth` conditionals open. Parsing carries on into the branch whose condition holds, and
+/// the tokens of the other branch are skipped.
+fn parse_cfg<'src>(
+    lexer: &mut Lexer<'src>,
+    ctx: &mut Context<'src>,
+    kw: Keyword,
+    location: Location<'src>,
+    depth: usize,
+) -> Result<(), SyntaxError<'src>> {
+    match kw {
+        Keyword::CfgIf => {
+            if parse_cfg_condition(lexer, ctx)? {
+                ctx.conditionals.push((location, false));
+            } else if skip_cfg_branch(lexer)? == Keyword::CfgElse {
+                ctx.conditionals.push((location, true));
+            }
+        }
+        Keyword::CfgElse => match ctx.close_conditional(depth) {
+            Some((_, false)) => {
+                if skip_cfg_branch(lexer)? == Keyword::CfgElse {
+                    return Err(SyntaxError::Generic {
+                        location,
+                        message: "`#if` can only have one `#else`",
+                    });
+                }
+            }
+            Some((_, true)) => {
+                return Err(SyntaxError::Generic {
+                    location,
+                    message: "`#if` can only have one `#else`",
+                })
+            }
+            None => {
+                return Err(SyntaxError::Generic {
+                    location,
+                    message: "`#else` without `#if`",
+                })
+            }
+        },
+        _ => {
+            if ctx.close_conditional(depth).is_none() {
+                return Err(SyntaxError::Generic {
+                    location,
+                    message: "`#end` without `#if`",
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parse the condition after `#if`: a flag `NAME` or an option `KEY = "VALUE"`, optionally
+/// preceded by `not`, which holds if it is in `ctx.cfg`.
+fn parse_cfg_condition<'src>(
+    lexer: &mut Lexer<'src>,
+    ctx: &Context<'src>,
+) -> Result<bool, SyntaxError<'src>> {
+    let mut t = lexer
+        .next()
+        .ok_or_else(|| SyntaxError::Eof(lexer.location()))?;
+    let negated = t.kind == TokenKind::Keyword(Keyword::Not);
+    if negated {
+        t = lexer
+            .next()
+            .ok_or_else(|| SyntaxError::Eof(lexer.location()))?;
+    }
+    let key = t.expect_kind(TokenKind::Identifier)?.value;
+
+    // A flag may be followed by anything, including an `=` op in the branch, so only take the
+    // `=` if a string literal comes after it.
+    let mut ahead = lexer.clone();
+    let value = match (ahead.next(), ahead.next()) {
+        (Some(eq), Some(value))
+            if eq.kind == TokenKind::Keyword(Keyword::Equals)
+                && value.kind == TokenKind::String =>
+        {
+            *lexer = ahead;
+            Some(string_value(&value).into_owned())
+        }
+        _ => None,
+    };
+    let holds = ctx.cfg.contains(&CfgOption {
+        key: key.to_owned(),
+        value,
+    });
+    Ok(holds != negated)
+}
+
+/// Skip the tokens of a branch that isn't taken, including any conditionals nested in it, up to
+/// the `#else` or `#end` that ends it.
+fn skip_cfg_branch<'src>(lexer: &mut Lexer<'src>) -> Result<Keyword, SyntaxError<'src>> {
+    let mut depth = 0_usize;
+    loop {
+        let t = lexer
+            .next()
+            .ok_or_else(|| SyntaxError::Eof(lexer.location()))?;
+        match t.kind {
+            TokenKind::Keyword(Keyword::CfgIf) => depth += 1,
+            TokenKind::Keyword(kw @ (Keyword::CfgElse | Keyword::CfgEnd)) if depth == 0 => {
+                return Ok(kw)
+            }
+            TokenKind::Keyword(Keyword::CfgEnd) => depth -= 1,
+            _ => {}
+        }
+    }
+}
+
+/// Parse the rest of an inline assembly block started by `t`: the architecture it is written for,
+/// if given, then the cells it takes and leaves, then its lines as string literals up to `end`.
+fn parse_asm<'src>(
+    lexer: &mut Lexer<'src>,
+    ctx: &mut Context<'src>,
+    t: &Token<'src>,
+) -> Result<Op<'src>, SyntaxError<'src>> {
+    let Some(arch) = ctx.arch else {
+        return Err(SyntaxError::Generic {
+            location: t.location.clone(),
+            message: "inline assembly can't be compiled for this target",
+        });
+    };
+    let next_t = lexer
+        .next()
+        .ok_or_else(|| SyntaxError::Eof(lexer.location()))?;
+    let inputs = match next_t.kind {
+        TokenKind::Identifier if next_t.value != arch => {
+            return Err(SyntaxError::Generic {
+                location: next_t.location,
+                message:
+                    "inline assembly for another architecture can't be compiled for this target",
+            })
+        }
+        TokenKind::Identifier => parse_count(lexer)?,
+        TokenKind::Int(count) => usize::try_from(count).unwrap_or(usize::MAX),
+        found => {
+            return Err(SyntaxError::UnexpectedToken {
+                expected: TokenKind::Int(0),
+                found,
+                location: next_t.location,
+            })
+        }
+    };
+    lexer.expect_next(TokenKind::Keyword(Keyword::Minus))?;
+    lexer.expect_next(TokenKind::Keyword(Keyword::Minus))?;
+    let outputs = parse_count(lexer)?;
+
+    let mut lines = Vec::new();
+    loop {
+        let next_t = lexer
+            .next()
+            .ok_or_else(|| SyntaxError::Eof(lexer.location()))?;
+        match next_t.kind {
+            TokenKind::String => lines.push(string_value(&next_t)),
+            TokenKind::Keyword(Keyword::End) => break,
+            found => {
+                return Err(SyntaxError::UnexpectedToken {
+                    expected: TokenKind::String,
+                    found,
+                    location: next_t.location,
+                })
+            }
+        }
+    }
+    ctx.inline_asm = true;
+    Ok(Op::Asm {
+        signature: Signature { inputs, outputs },
+        lines,
+    })
+}
+
 fn parse_fn<'src>(
     lexer: &mut Lexer<'src>,
     ctx: &mut Context<'src>,
-) -> Result<Func<'src>, SyntaxError<'src>> {
+    export: bool,
+) -> Result<(usize, Func<'src>), SyntaxError<'src>> {
     let t = lexer.expect_next(TokenKind::Identifier)?;
     let ident = t.value;
-    ctx.insert_func_ident(ident);
+    if ctx.externs.contains_key(ident) {
+        return Err(SyntaxError::Generic {
+            location: t.location,
+            message: "C functions declared with `extern fn` can't be defined",
+        });
+    }
+    let index = ctx.insert_func_ident(ident);
+    let export = if export {
+        Some(parse_c_signature(lexer, &t)?)
+    } else {
+        None
+    };
     lexer.expect_next(TokenKind::Keyword(Keyword::In))?;
 
-    let mut body = parse_block(lexer, ctx, Keyword::End)?;
-    body.push(Op::Ret(0));
-    Ok(Func { ident, body })
+    let mut func = finish_fn(lexer, ctx, ident, t.location, Some(Keyword::End))?;
+    func.export = export;
+    Ok((index, func))
+}
+
+/// Parse the signature of the function named by `t`, which is called across the C ABI.
+fn parse_c_signature<'src>(
+    lexer: &mut Lexer<'src>,
+    t: &Token<'src>,
+) -> Result<Signature, SyntaxError<'src>> {
+    let signature = parse_signature(lexer)?;
+    // The arguments are passed in registers, and the result is returned in one.
+    if signature.inputs > 6 || signature.outputs > 1 {
+        return Err(SyntaxError::Generic {
+            location: t.location.clone(),
+            message: "functions called across the C ABI take at most 6 cells and leave at most 1",
+        });
+    }
+    if t.value == "main" || !is_c_identifier(t.value) {
+        return Err(SyntaxError::Generic {
+            location: t.location.clone(),
+            message: "functions called across the C ABI need a C identifier other than `main`",
+        });
+    }
+    Ok(signature)
+}
+
+fn is_c_identifier(ident: &str) -> bool {
+    ident.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && ident.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Parse the body of a function up to `terminator`, adding the implicit return at the end.
+fn finish_fn<'src>(
+    lexer: &mut Lexer<'src>,
+    ctx: &mut Context<'src>,
+    ident: &'src str,
+    location: Location<'src>,
+    terminator: Option<Keyword>,
+) -> Result<Func<'src>, SyntaxError<'src>> {
+    let mut body = parse_block(lexer, ctx, terminator)?;
+    body.push(LocatedOp {
+        op: Op::Ret(0),
+        location: lexer.location(),
+    });
+    Ok(Func {
+        ident,
+        export: None,
+        body,
+        location,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Parse `source` with the options in `cfg`, returning the body of `main`.
+    fn parse_with<'src>(source: &'src str, cfg: &[&str]) -> Vec<Op<'src>> {
+        let mut program = Program::default();
+        program.ctx.cfg = cfg.iter().map(|option| option.parse().unwrap()).collect();
+        parse_items(&mut Lexer::new(source, None), &mut program).unwrap();
+        let main = program.funcs.remove(program.ctx.lookup["main"]);
+        main.body.into_iter().map(|op| op.op).collect()
+    }
+
+    #[test]
+    fn conditionals() {
+        let source = "\
+#if target = \"x86_64-linux\"
+fn main in
+  1
+  #if debug 2 #if not trace 3 #end #else 4 #end
+  5 5 #if eq = #end
+end
+#else
+fn main in end
+#end
+";
+        let push = |values: &[u64]| -> Vec<Op> {
+            let mut ops: Vec<_> = values.iter().map(|value| Op::PushInt(*value)).collect();
+            ops.push(Op::Ret(0));
+            ops
+        };
+        assert_eq!(parse_with(source, &[]), push(&[]));
+        assert_eq!(
+            parse_with(source, &["target=x86_64-linux"]),
+            push(&[1, 4, 5, 5])
+        );
+        assert_eq!(
+            parse_with(source, &["target=x86_64-linux", "debug", "trace"]),
+            push(&[1, 2, 5, 5])
+        );
+        let mut ops = push(&[1, 2, 3, 5, 5]);
+        ops.insert(5, Op::Equals);
+        assert_eq!(
+            parse_with(source, &["target=x86_64-linux", "debug", "eq"]),
+            ops
+        );
+    }
+
+    #[test]
+    fn conditionals_stay_in_their_block() {
+        for source in [
+            "#if foo\nfn main in\n  1\n#end\n  2\nend\n",
+            "fn main in\n  1\n#if foo\nend\n#end\n",
+            "fn main in\n  #if foo 1 if 2 #else 3 end #end\nend\n",
+        ] {
+            for cfg in [&["foo"][..], &[]] {
+                let mut program = Program::default();
+                program.ctx.cfg = cfg.iter().map(|option| option.parse().unwrap()).collect();
+                assert!(
+                    parse_items(&mut Lexer::new(source, None), &mut program).is_err(),
+                    "{source:?} with {cfg:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn redefinition() {
+        let source = "fn main in 1 end fn main in 2 end\n";
+        let mut program = Program::default();
+        assert!(matches!(
+            parse_items(&mut Lexer::new(source, None), &mut program),
+            Err(SyntaxError::Redefinition {
+                identifier: "main",
+                ..
+            })
+        ));
+
+        let mut program = Program::default();
+        program.ctx.redefine = true;
+        parse_items(&mut Lexer::new(source, None), &mut program).unwrap();
+        assert_eq!(program.funcs.len(), 1);
+        assert_eq!(program.funcs[0].body, [Op::PushInt(2), Op::Ret(0)]);
+
+        // A function from the prelude can be replaced once, even after it has been called.
+        let mut program = Program::default();
+        let prelude = "fn answer in 1 end fn other in 2 end\n";
+        parse_prelude(&mut Lexer::new(prelude, None), &mut program).unwrap();
+        let source = "fn main in answer end fn answer in 42 end\n";
+        parse_items(&mut Lexer::new(source, None), &mut program).unwrap();
+        assert_eq!(program.funcs.len(), 3);
+        assert_eq!(program.funcs[0].body, [Op::PushInt(42), Op::Ret(0)]);
+        assert_eq!(program.funcs[2].body, [Op::CallFn(0), Op::Ret(0)]);
+        assert!(matches!(
+            parse_items(&mut Lexer::new("fn answer in 43 end\n", None), &mut program),
+            Err(SyntaxError::Redefinition {
+                identifier: "answer",
+                ..
+            })
+        ));
+    }
 }
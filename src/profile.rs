@@ -0,0 +1,286 @@
+//! A profiler counting the ops that the evaluator executes in each function.
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::Write as _,
+    fs,
+    io::{self, Write},
+};
+
+use crate::{
+    interp::{Code, Instr, Machine, RuntimeError},
+    Program,
+};
+
+/// The number of loops listed in the report.
+const HOT_LOOPS: usize = 10;
+
+/// A node in the tree of call stacks seen while profiling.
+struct Node {
+    func: usize,
+    parent: Option<usize>,
+    children: HashMap<usize, usize>,
+    /// Ops executed with exactly this call stack.
+    ops: u64,
+}
+
+struct Frame {
+    func: usize,
+    /// The total number of ops executed when the function was entered.
+    entered_at: u64,
+}
+
+#[derive(Default)]
+pub struct Profile {
+    pub calls: Vec<u64>,
+    /// Ops executed while a function was active, including those of the functions it called.
+    pub inclusive: Vec<u64>,
+    /// Ops executed in the body of a function itself.
+    pub exclusive: Vec<u64>,
+    /// The number of times each instruction was executed.
+    pub hits: Vec<u64>,
+    total: u64,
+    nodes: Vec<Node>,
+    node: usize,
+    frames: Vec<Frame>,
+    /// The number of frames each function currently has, so that recursive calls are only
+    /// counted once towards the inclusive total.
+    active: Vec<usize>,
+}
+
+impl Profile {
+    fn new(code: &Code, main: usize) -> Self {
+        let funcs = code.entries.len();
+        let mut profile = Self {
+            calls: vec![0; funcs],
+            inclusive: vec![0; funcs],
+            exclusive: vec![0; funcs],
+            hits: vec![0; code.instrs.len()],
+            active: vec![0; funcs],
+            ..Self::default()
+        };
+        profile.nodes.push(Node {
+            func: main,
+            parent: None,
+            children: HashMap::new(),
+            ops: 0,
+        });
+        profile.enter(main);
+        profile
+    }
+
+    fn enter(&mut self, func: usize) {
+        self.calls[func] += 1;
+        self.active[func] += 1;
+        self.frames.push(Frame {
+            func,
+            entered_at: self.total,
+        });
+    }
+
+    fn leave(&mut self) {
+        let frame = self
+            .frames
+            .pop()
+            .expect("returned from a function that was entered");
+        self.active[frame.func] -= 1;
+        if self.active[frame.func] == 0 {
+            self.inclusive[frame.func] += self.total - frame.entered_at;
+        }
+    }
+
+    /// Account for the instruction at `pc` having been executed.
+    fn record(&mut self, code: &Code, pc: usize) {
+        let func = self.frames.last().expect("a function is running").func;
+        self.total += 1;
+        self.hits[pc] += 1;
+        self.exclusive[func] += 1;
+        self.nodes[self.node].ops += 1;
+        match code.instrs[pc] {
+            Instr::CallFn(callee) => {
+                let parent = self.node;
+                let next = self.nodes.len();
+                self.node = *self.nodes[parent].children.entry(callee).or_insert(next);
+                if self.node == next {
+                    self.nodes.push(Node {
+                        func: callee,
+                        parent: Some(parent),
+                        children: HashMap::new(),
+                        ops: 0,
+                    });
+                }
+                self.enter(callee);
+            }
+            Instr::Ret(_) => {
+                self.leave();
+                if let Some(parent) = self.nodes[self.node].parent {
+                    self.node = parent;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Close the frames of functions that never returned, because the program failed.
+    fn finish(&mut self) {
+        while !self.frames.is_empty() {
+            self.leave();
+        }
+    }
+
+    /// Write the call stacks in the folded format understood by flamegraph tools, with one line
+    /// of semicolon-separated function names followed by the ops spent in it.
+    pub fn write_folded(&self, code: &Code, w: &mut impl Write) -> io::Result<()> {
+        for node in &self.nodes {
+            if node.ops == 0 {
+                continue;
+            }
+            let mut stack = vec![code.idents[node.func]];
+            let mut parent = node.parent;
+            while let Some(p) = parent {
+                stack.push(code.idents[self.nodes[p].func]);
+                parent = self.nodes[p].parent;
+            }
+            stack.reverse();
+            writeln!(w, "{} {}", stack.join(";"), node.ops)?;
+        }
+        Ok(())
+    }
+
+    /// A human-readable summary of the profile.
+    pub fn report(&self, code: &Code, funcs: usize) -> String {
+        let mut report = String::new();
+        let mut order = (0..funcs)
+            .filter(|f| self.calls[*f] > 0)
+            .collect::<Vec<_>>();
+        order.sort_by_key(|f| std::cmp::Reverse(self.exclusive[*f]));
+        let width = order
+            .iter()
+            .map(|f| code.idents[*f].len())
+            .chain(["function".len()])
+            .max()
+            .unwrap_or_default();
+
+        writeln!(
+            report,
+            "{:width$}  {:>10}  {:>12}  {:>12}",
+            "function", "calls", "inclusive", "exclusive"
+        )
+        .unwrap();
+        for f in order {
+            writeln!(
+                report,
+                "{:width$}  {:>10}  {:>12}  {:>12}",
+                code.idents[f], self.calls[f], self.inclusive[f], self.exclusive[f]
+            )
+            .unwrap();
+        }
+
+        // The back edge of a loop is the only backwards jump, and it is executed once for every
+        // iteration.
+        let mut loops = code
+            .instrs
+            .iter()
+            .enumerate()
+            .filter_map(|(pc, instr)| match instr {
+                Instr::Jump(start) if *start <= pc && self.hits[pc] > 0 => {
+                    let ops = self.hits[*start..=pc].iter().sum::<u64>();
+                    Some((pc, self.hits[pc], ops))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        loops.sort_by_key(|(_, _, ops)| std::cmp::Reverse(*ops));
+        if !loops.is_empty() {
+            let locations = loops
+                .iter()
+                .take(HOT_LOOPS)
+                .map(|(pc, ..)| code.debug[*pc].location.to_string())
+                .collect::<Vec<_>>();
+            let width = locations
+                .iter()
+                .map(String::len)
+                .chain(["loop".len()])
+                .max()
+                .unwrap_or_default();
+            writeln!(report).unwrap();
+            writeln!(
+                report,
+                "{:width$}  {:>10}  {:>12}",
+                "loop", "iterations", "ops"
+            )
+            .unwrap();
+            for (location, (_, iterations, ops)) in locations.iter().zip(&loops) {
+                writeln!(report, "{location:width$}  {iterations:>10}  {ops:>12}").unwrap();
+            }
+        }
+        report
+    }
+}
+
+/// Run `program` to completion, writing its output to `out`, and profile it. If the program
+/// fails, the profile of everything up to the failure is returned alongside the error.
+pub fn profile<'src>(
+    program: &Program<'src>,
+    out: &mut impl Write,
+) -> (Machine<'src>, Profile, Result<(), RuntimeError>) {
+    let mut machine = Machine::new(program);
+    let main = program.ctx.lookup["main"];
+    let mut profile = Profile::new(&machine.code, main);
+    let result = machine.enter_main(program).and_then(|()| {
+        machine.run_observed(out, |machine, pc| {
+            profile.record(&machine.code, pc);
+            Ok::<_, RuntimeError>(())
+        })
+    });
+    profile.finish();
+    (machine, profile, result)
+}
+
+/// Profile `program`, printing the report after its output and writing the folded call stacks to
+/// `folded_path`.
+pub fn run(program: &Program, folded_path: &str, quiet: bool) -> Result<(), Box<dyn Error>> {
+    let (machine, profile, result) = profile(program, &mut io::stdout());
+    if let Err(e) = result {
+        eprintln!("[ERROR] {e}");
+    }
+    print!("\n{}", profile.report(&machine.code, program.funcs.len()));
+
+    let mut folded = Vec::new();
+    profile.write_folded(&machine.code, &mut folded)?;
+    fs::write(folded_path, folded).unwrap_or_else(|_| panic!("failed to write to {folded_path}"));
+    if !quiet {
+        println!("[INFO] Wrote folded stacks to `{folded_path}`");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parse, Lexer};
+
+    #[test]
+    fn recursion() {
+        let source = include_str!("../examples/fib.rk");
+        let program = parse::parse_tokens(&mut Lexer::new(source, None)).unwrap();
+        let (machine, profile, result) = profile(&program, &mut io::sink());
+        result.unwrap();
+
+        let [fib, main] = ["fib", "main"].map(|f| program.ctx.lookup[f]);
+        // Computing fib(n) takes 2 * fib(n + 1) - 1 calls.
+        assert_eq!(profile.calls[fib], (2 * 8 - 1) + (2 * 55 - 1));
+        assert_eq!(profile.calls[main], 1);
+        assert_eq!(profile.inclusive[main], profile.hits.iter().sum::<u64>());
+        assert_eq!(
+            profile.inclusive[fib] + profile.exclusive[main],
+            profile.inclusive[main]
+        );
+
+        let mut folded = Vec::new();
+        profile.write_folded(&machine.code, &mut folded).unwrap();
+        let folded = String::from_utf8(folded).unwrap();
+        assert!(folded.starts_with("main 7\nmain;fib "), "{folded}");
+    }
+}
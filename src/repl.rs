@@ -0,0 +1,217 @@
+//! An interactive session evaluating Rack on the in-process evaluator.
+
+use std::{
+    error::Error,
+    fs,
+    io::{self, Write},
+};
+
+use rustyline::{error::ReadlineError, Editor};
+
+use crate::{
+    interp::Machine,
+    lex::{Keyword, TokenKind},
+    parse::{self, SyntaxError},
+    Lexer, Program,
+};
+
+const HELP: &str = "\
+Enter ops to run them as the body of `fn main`, or `fn` definitions to add them to the session.
+
+Commands:
+  :stack         Show the data stack
+  :clear         Clear the data stack
+  :load <FILE>   Load the functions defined in a file
+  :history       Show the lines entered so far
+  :help          Show this message
+  :quit          Exit the session
+";
+
+struct Session {
+    program: Program<'static>,
+    machine: Machine<'static>,
+}
+
+/// The outcome of handling some input.
+enum Input {
+    Done,
+    /// The input ended in the middle of a construct, so we need another line.
+    Incomplete,
+    Quit,
+}
+
+pub fn run() -> Result<(), Box<dyn Error>> {
+    let mut editor = Editor::<()>::new()?;
+    let mut program = Program::default();
+    program.ctx.redefine = true;
+    let mut session = Session {
+        program,
+        machine: Machine::default(),
+    };
+    let mut buffer = String::new();
+
+    println!("Rack REPL. Type `:help` for more information.");
+    loop {
+        let prompt = if buffer.is_empty() { "rack> " } else { "... " };
+        let line = match editor.readline(prompt) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => {
+                buffer.clear();
+                continue;
+            }
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e.into()),
+        };
+
+        if buffer.is_empty() {
+            if let Some(cmd) = line.trim().strip_prefix(':') {
+                editor.add_history_entry(line.trim());
+                if matches!(session.command(cmd, &editor), Input::Quit) {
+                    break;
+                }
+                continue;
+            }
+        }
+
+        buffer.push_str(&line);
+        buffer.push('\n');
+        // We only record complete inputs, so that a multi-line definition can be recalled at once.
+        if !matches!(session.eval(&buffer), Input::Incomplete) {
+            editor.add_history_entry(buffer.trim_end());
+            buffer.clear();
+        }
+    }
+    Ok(())
+}
+
+impl Session {
+    fn command(&mut self, cmd: &str, editor: &Editor<()>) -> Input {
+        let (cmd, arg) = cmd
+            .split_once(char::is_whitespace)
+            .map_or((cmd, ""), |(cmd, arg)| (cmd, arg.trim()));
+        match cmd {
+            "stack" | "s" => self.show_stack(),
+            "clear" | "c" => self.machine.stack.clear(),
+            "load" | "l" => {
+                if arg.is_empty() {
+                    eprintln!("[ERROR] Usage: `:load <FILE>`");
+                } else {
+                    self.load(arg);
+                }
+            }
+            "history" => {
+                for (i, line) in editor.history().iter().enumerate() {
+                    println!("{i:>4}  {line}");
+                }
+            }
+            "help" | "h" => print!("{HELP}"),
+            "quit" | "q" => return Input::Quit,
+            _ => eprintln!("[ERROR] Unknown command `:{cmd}`. Type `:help` for more information."),
+        }
+        Input::Done
+    }
+
+    fn load(&mut self, path: &str) {
+        let Ok(source) = fs::read_to_string(path) else {
+            eprintln!("[ERROR] Couldn't read file `{path}`");
+            return;
+        };
+        let (source, path) = (leak(source), leak(path.to_owned()));
+        let mut program = self.program.clone();
+        match parse::parse_items(&mut Lexer::new(source, Some(path)), &mut program) {
+            Ok(()) => {
+                let new = program.funcs.len() - self.program.funcs.len();
+                self.program = program;
+                self.machine.load(&self.program);
+                println!("[INFO] Loaded `{path}` ({new} new functions)");
+            }
+            Err(e) => eprintln!("{e}"),
+        }
+    }
+
+    fn eval(&mut self, source: &str) -> Input {
+        let source = leak(source.to_owned());
+        // Anything that can only appear at the top level of a file is parsed as items.
+        let is_item = matches!(
+            Lexer::new(source, None).next().map(|t| t.kind),
+            Some(TokenKind::Keyword(
+                Keyword::Fn | Keyword::Export | Keyword::Extern | Keyword::CfgIf
+            ))
+        );
+
+        // Work on a copy so that the session is untouched by input that fails to parse.
+        let mut program = self.program.clone();
+        let mut lexer = Lexer::new(source, None);
+        let body = if is_item {
+            parse::parse_items(&mut lexer, &mut program).map(|()| None)
+        } else {
+            parse::parse_body(&mut lexer, &mut program.ctx, "<input>").map(Some)
+        };
+        let body = match body {
+            Ok(body) => body,
+            Err(SyntaxError::Eof(_)) => return Input::Incomplete,
+            Err(e) => {
+                eprintln!("{e}");
+                return Input::Done;
+            }
+        };
+        self.program = program;
+        self.machine.load(&self.program);
+
+        if let Some(body) = body {
+            let entry = self.machine.code.push_func(&body);
+            let mut stdout = io::stdout();
+            let result = self
+                .machine
+                .enter(entry)
+                .and_then(|()| self.machine.run(&mut stdout));
+            stdout.flush().ok();
+            if let Err(e) = result {
+                eprintln!("[ERROR] {e}");
+                self.machine.reset();
+            }
+            self.show_stack();
+        }
+        Input::Done
+    }
+
+    fn show_stack(&self) {
+        println!("{:?}", self.machine.stack);
+    }
+}
+
+/// Everything parsed in a session borrows from its source for as long as the session lives, so
+/// the sources are simply leaked.
+fn leak(s: String) -> &'static str {
+    Box::leak(s.into_boxed_str())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn items() {
+        let mut program = Program::default();
+        program.ctx.redefine = true;
+        program.ctx.cfg.insert("debug".parse().unwrap());
+        let mut session = Session {
+            program,
+            machine: Machine::default(),
+        };
+        for source in [
+            "export fn double 1 -- 1 in dup + end\n",
+            "#if debug\nfn triple in dup dup + + end\n#end\n",
+            "21 double 2 triple\n",
+        ] {
+            assert!(matches!(session.eval(source), Input::Done), "{source}");
+        }
+        assert_eq!(session.machine.stack, [42, 6]);
+
+        // A definition that isn't finished yet asks for more input.
+        assert!(matches!(
+            session.eval("export fn half 1 -- 1 in\n"),
+            Input::Incomplete
+        ));
+    }
+}
@@ -0,0 +1,152 @@
+//! Execution traces, logging every instruction run by the evaluator along with the data stack
+//! that it leaves behind.
+
+use std::{
+    io::{self, Write},
+    str::FromStr,
+};
+
+use thiserror::Error;
+
+use crate::{
+    interp::{Instr, Machine, RuntimeError},
+    Program,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// One tab-separated line per instruction.
+    Text,
+    /// One JSON object per line.
+    Json,
+}
+
+#[derive(Error, Debug)]
+#[error("trace format not supported (expected `text` or `json`)")]
+pub struct UnknownTraceFormatError;
+
+impl FromStr for TraceFormat {
+    type Err = UnknownTraceFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(UnknownTraceFormatError),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum TraceError {
+    #[error(transparent)]
+    Runtime(#[from] RuntimeError),
+    #[error("failed to write trace: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// Run `program` to completion, writing its output to `out` and the trace to `trace`.
+pub fn run(
+    program: &Program,
+    format: TraceFormat,
+    out: &mut impl Write,
+    trace: &mut impl Write,
+) -> Result<(), TraceError> {
+    let mut machine = Machine::new(program);
+    machine.enter_main(program)?;
+    machine.run_observed(out, |machine, pc| {
+        write_record(machine, pc, format, trace).map_err(TraceError::from)
+    })?;
+    trace.flush()?;
+    Ok(())
+}
+
+fn write_record(
+    machine: &Machine,
+    pc: usize,
+    format: TraceFormat,
+    trace: &mut impl Write,
+) -> io::Result<()> {
+    let code = &machine.code;
+    let func = code.idents[code.func_at(pc)];
+    let location = &code.debug[pc].location;
+    let op = match code.instrs[pc] {
+        Instr::CallFn(index) => format!("CallFn({})", code.idents[index]),
+        instr => format!("{instr:?}"),
+    };
+    match format {
+        TraceFormat::Text => {
+            write!(trace, "{location}\t{func}\t{op}\t[")?;
+            for (i, val) in machine.stack.iter().enumerate() {
+                let sep = if i == 0 { "" } else { " " };
+                write!(trace, "{sep}{val}")?;
+            }
+            writeln!(trace, "]")
+        }
+        TraceFormat::Json => {
+            let (line, col) = location.pos;
+            write!(trace, "{{\"file\":")?;
+            match location.file {
+                Some(file) => write_json_str(trace, file)?,
+                None => write!(trace, "null")?,
+            }
+            write!(trace, ",\"line\":{line},\"col\":{col},\"func\":")?;
+            write_json_str(trace, func)?;
+            write!(trace, ",\"op\":")?;
+            write_json_str(trace, &op)?;
+            write!(trace, ",\"stack\":[")?;
+            for (i, val) in machine.stack.iter().enumerate() {
+                let sep = if i == 0 { "" } else { "," };
+                write!(trace, "{sep}{val}")?;
+            }
+            writeln!(trace, "]}}")
+        }
+    }
+}
+
+fn write_json_str(w: &mut impl Write, s: &str) -> io::Result<()> {
+    write!(w, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(w, "\\\"")?,
+            '\\' => write!(w, "\\\\")?,
+            c if c.is_control() => write!(w, "\\u{:04x}", c as u32)?,
+            c => write!(w, "{c}")?,
+        }
+    }
+    write!(w, "\"")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parse, Lexer};
+
+    fn trace(source: &str, format: TraceFormat) -> String {
+        let program = parse::parse_tokens(&mut Lexer::new(source, Some("t.rk"))).unwrap();
+        let mut trace = Vec::new();
+        run(&program, format, &mut io::sink(), &mut trace).unwrap();
+        String::from_utf8(trace).unwrap()
+    }
+
+    #[test]
+    fn text() {
+        let trace = trace("fn main in\n  1 2 swap\nend\n", TraceFormat::Text);
+        assert_eq!(
+            trace.lines().nth(2),
+            Some("t.rk:2:7\tmain\tSwap\t[2 1]"),
+            "{trace}"
+        );
+    }
+
+    #[test]
+    fn json() {
+        let trace = trace("fn main in 1 drop end\n", TraceFormat::Json);
+        assert_eq!(
+            trace.lines().next(),
+            Some(
+                r#"{"file":"t.rk","line":1,"col":12,"func":"main","op":"PushInt(1)","stack":[1]}"#
+            )
+        );
+    }
+}
@@ -17,7 +17,7 @@ pub fn integration_tests(_: TokenStream) -> TokenStream {
                 }
             }
         })
-        .map(|ts| TokenStream::from(ts));
+        .map(TokenStream::from);
 
     TokenStream::from_iter(tests)
 }
@@ -43,7 +43,7 @@ pub fn run_test(file: &str, mode: Mode) {
         .chain(child.stdout.iter())
         .chain(b"\n----STDERR----\n".iter())
         .chain(child.stderr.iter())
-        .map(|b| *b)
+        .copied()
         .collect();
 
     match mode {
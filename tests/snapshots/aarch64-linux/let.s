@@ -0,0 +1,130 @@
+	.macro	PUSH reg
+	str	\reg, [x19, #-8]!
+	.endm
+	.macro	POP reg
+	ldr	\reg, [x19], #8
+	.endm
+
+	.globl	main
+	.text
+print:
+	sub	sp, sp, #32
+	mov	x2, #10
+	strb	w2, [sp, #31]
+	add	x1, sp, #31
+	mov	x3, #10
+.L2:
+	udiv	x4, x0, x3
+	msub	x5, x4, x3, x0
+	add	x5, x5, #48
+	strb	w5, [x1, #-1]!
+	mov	x0, x4
+	cbnz	x0, .L2
+	add	x2, sp, #32
+	sub	x2, x2, x1
+	mov	x0, #1
+	mov	x8, #64
+	svc	#0
+	add	sp, sp, #32
+	ret
+fn_main:
+					// Op::PushInt(1)
+	movz	x0, #1
+	PUSH	x0
+					// Op::PushInt(2)
+	movz	x0, #2
+	PUSH	x0
+					// Op::Bind(2)
+	adrp	x9, ret_stack_rsp
+	ldr	x10, [x9, :lo12:ret_stack_rsp]
+	sub	x10, x10, #16
+	str	x10, [x9, :lo12:ret_stack_rsp]
+	ldr	x11, [x19, #0]
+	str	x11, [x10, #0]
+	ldr	x11, [x19, #8]
+	str	x11, [x10, #8]
+	add	x19, x19, #16
+	adrp	x9, ret_stack_rsp		// Op::PushBind(1)
+	ldr	x10, [x9, :lo12:ret_stack_rsp]
+	ldr	x11, [x10, #8]
+	PUSH	x11
+	POP	x0			// Op::Print
+	bl	print
+					// Op::PushInt(3)
+	movz	x0, #3
+	PUSH	x0
+					// Op::PushInt(4)
+	movz	x0, #4
+	PUSH	x0
+					// Op::Bind(2)
+	adrp	x9, ret_stack_rsp
+	ldr	x10, [x9, :lo12:ret_stack_rsp]
+	sub	x10, x10, #16
+	str	x10, [x9, :lo12:ret_stack_rsp]
+	ldr	x11, [x19, #0]
+	str	x11, [x10, #0]
+	ldr	x11, [x19, #8]
+	str	x11, [x10, #8]
+	add	x19, x19, #16
+	adrp	x9, ret_stack_rsp		// Op::PushBind(2)
+	ldr	x10, [x9, :lo12:ret_stack_rsp]
+	ldr	x11, [x10, #16]
+	PUSH	x11
+	POP	x0			// Op::Print
+	bl	print
+	adrp	x9, ret_stack_rsp		// Op::PushBind(3)
+	ldr	x10, [x9, :lo12:ret_stack_rsp]
+	ldr	x11, [x10, #24]
+	PUSH	x11
+	POP	x0			// Op::Print
+	bl	print
+	adrp	x9, ret_stack_rsp		// Op::PushBind(1)
+	ldr	x10, [x9, :lo12:ret_stack_rsp]
+	ldr	x11, [x10, #8]
+	PUSH	x11
+	POP	x0			// Op::Print
+	bl	print
+	adrp	x9, ret_stack_rsp
+	ldr	x10, [x9, :lo12:ret_stack_rsp]
+	add	x10, x10, #16
+	str	x10, [x9, :lo12:ret_stack_rsp]
+	adrp	x9, ret_stack_rsp		// Op::PushBind(0)
+	ldr	x10, [x9, :lo12:ret_stack_rsp]
+	ldr	x11, [x10, #0]
+	PUSH	x11
+	POP	x0			// Op::Print
+	bl	print
+	adrp	x9, ret_stack_rsp
+	ldr	x10, [x9, :lo12:ret_stack_rsp]
+	add	x10, x10, #16
+	str	x10, [x9, :lo12:ret_stack_rsp]
+					// Op::Ret(0)
+	adrp	x9, ret_stack_rsp
+	ldr	x10, [x9, :lo12:ret_stack_rsp]
+	add	x10, x10, #0
+	str	x10, [x9, :lo12:ret_stack_rsp]
+	ldr	x11, [x10]
+	br	x11
+main:
+	adrp	x19, data_stack_end
+	add	x19, x19, :lo12:data_stack_end
+	adrp	x10, ret_stack_end
+	add	x10, x10, :lo12:ret_stack_end
+	sub	x10, x10, #8
+	adrp	x9, ret_stack_rsp
+	str	x10, [x9, :lo12:ret_stack_rsp]
+	adr	x11, RET_MAIN
+	str	x11, [x10]
+	b	fn_main
+RET_MAIN:
+	mov	x0, #0
+	mov	x8, #93
+	svc	#0
+	.section	.rodata
+	.bss
+	.balign	8
+ret_stack_rsp:	.skip	8
+ret_stack:	.skip	65536
+ret_stack_end:
+data_stack:	.skip	8388608
+data_stack_end:
@@ -0,0 +1,44 @@
+#define STACK_CELLS 1048576
+#define RET_STACK_CELLS 8192
+#include <inttypes.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+
+static uint64_t stack[STACK_CELLS];
+static size_t sp;
+static uint64_t ret_stack[RET_STACK_CELLS];
+static size_t rsp = RET_STACK_CELLS;
+
+static void push(uint64_t x) { stack[sp++] = x; }
+
+static uint64_t pop(void) { return stack[--sp]; }
+
+/* Copy the top `count` values to the return stack, the top one ending up at `ret_stack[rsp]`. */
+static void bind(size_t count, int peek) {
+	size_t i;
+	rsp -= count;
+	for (i = 0; i < count; i++) ret_stack[rsp + i] = stack[sp - 1 - i];
+	if (!peek) sp -= count;
+}
+
+
+static void fn_0(void); /* main */
+
+/* main */
+static void fn_0(void) {
+	push(UINT64_C(10));
+	push(stack[sp - 1]);
+	printf("%" PRIu64 "\n", pop());
+	printf("%" PRIu64 "\n", pop());
+	push(UINT64_C(18446744073709551615));
+	push(stack[sp - 1]);
+	printf("%" PRIu64 "\n", pop());
+	printf("%" PRIu64 "\n", pop());
+	return;
+}
+
+int main(void) {
+	fn_0();
+	return 0;
+}
@@ -0,0 +1,59 @@
+#define STACK_CELLS 1048576
+#define RET_STACK_CELLS 8192
+#include <inttypes.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+
+static uint64_t stack[STACK_CELLS];
+static size_t sp;
+static uint64_t ret_stack[RET_STACK_CELLS];
+static size_t rsp = RET_STACK_CELLS;
+
+static void push(uint64_t x) { stack[sp++] = x; }
+
+static uint64_t pop(void) { return stack[--sp]; }
+
+/* Copy the top `count` values to the return stack, the top one ending up at `ret_stack[rsp]`. */
+static void bind(size_t count, int peek) {
+	size_t i;
+	rsp -= count;
+	for (i = 0; i < count; i++) ret_stack[rsp + i] = stack[sp - 1 - i];
+	if (!peek) sp -= count;
+}
+
+static const unsigned char str_0[] = {115,117,99,99,101,115,115,33,10,0};
+
+static void fn_0(void); /* main */
+
+/* main */
+static void fn_0(void) {
+	push(UINT64_C(1));
+	push(UINT64_C(2));
+	{ uint64_t b = pop(), a = pop(); push(a < b); }
+	if (pop() == 1) {
+		push(UINT64_C(9));
+		push((uint64_t)(uintptr_t)str_0);
+		{ const void *s = (const void *)(uintptr_t)pop(); fwrite(s, 1, (size_t)pop(), stdout); }
+	}
+	push(UINT64_C(2));
+	push(UINT64_C(1));
+	{ uint64_t b = pop(), a = pop(); push(a < b); }
+	if (pop() == 1) {
+		push(UINT64_C(0));
+		printf("%" PRIu64 "\n", pop());
+	}
+	push(UINT64_C(2));
+	push(UINT64_C(2));
+	{ uint64_t b = pop(), a = pop(); push(a < b); }
+	if (pop() == 1) {
+		push(UINT64_C(0));
+		printf("%" PRIu64 "\n", pop());
+	}
+	return;
+}
+
+int main(void) {
+	fn_0();
+	return 0;
+}
@@ -0,0 +1,220 @@
+
+declare i64 @write(i32, ptr, i64)
+declare void @exit(i32)
+
+define private void @push(ptr %stack, ptr %sp, i64 %x) #0 {
+  %n = load i64, ptr %sp
+  %p = getelementptr i64, ptr %stack, i64 %n
+  store i64 %x, ptr %p
+  %m = add i64 %n, 1
+  store i64 %m, ptr %sp
+  ret void
+}
+
+define private i64 @pop(ptr %stack, ptr %sp) #0 {
+  %n = load i64, ptr %sp
+  %m = sub i64 %n, 1
+  store i64 %m, ptr %sp
+  %p = getelementptr i64, ptr %stack, i64 %m
+  %x = load i64, ptr %p
+  ret i64 %x
+}
+
+; The value `depth` cells below the top of the data stack.
+define private i64 @peek(ptr %stack, ptr %sp, i64 %depth) #0 {
+  %n = load i64, ptr %sp
+  %m = sub i64 %n, 1
+  %i = sub i64 %m, %depth
+  %p = getelementptr i64, ptr %stack, i64 %i
+  %x = load i64, ptr %p
+  ret i64 %x
+}
+
+; Move the return stack by `delta` cells.
+define private void @rsp_add(ptr %rsp, i64 %delta) #0 {
+  %n = load i64, ptr %rsp
+  %m = add i64 %n, %delta
+  store i64 %m, ptr %rsp
+  ret void
+}
+
+define private i64 @ret_get(ptr %ret, ptr %rsp, i64 %index) #0 {
+  %n = load i64, ptr %rsp
+  %i = add i64 %n, %index
+  %p = getelementptr i64, ptr %ret, i64 %i
+  %x = load i64, ptr %p
+  ret i64 %x
+}
+
+define private void @ret_set(ptr %ret, ptr %rsp, i64 %index, i64 %x) #0 {
+  %n = load i64, ptr %rsp
+  %i = add i64 %n, %index
+  %p = getelementptr i64, ptr %ret, i64 %i
+  store i64 %x, ptr %p
+  ret void
+}
+
+; Exit with status 1 if `x` is 0, since dividing by it is undefined behaviour that the optimiser
+; would be free to assume away.
+define private void @check_divisor(i64 %x) #0 {
+  %zero = icmp eq i64 %x, 0
+  br i1 %zero, label %fail, label %ok
+fail:
+  call void @exit(i32 1)
+  unreachable
+ok:
+  ret void
+}
+
+; Write the decimal digits of `x` backwards from the end of a buffer.
+define private void @print(i64 %x) {
+entry:
+  %buf = alloca [21 x i8]
+  %end = getelementptr [21 x i8], ptr %buf, i64 0, i64 20
+  store i8 10, ptr %end
+  br label %loop
+loop:
+  %n = phi i64 [ %x, %entry ], [ %q, %loop ]
+  %p = phi ptr [ %end, %entry ], [ %d, %loop ]
+  %q = udiv i64 %n, 10
+  %r = urem i64 %n, 10
+  %c = trunc i64 %r to i8
+  %digit = add i8 %c, 48
+  %d = getelementptr i8, ptr %p, i64 -1
+  store i8 %digit, ptr %d
+  %more = icmp ne i64 %q, 0
+  br i1 %more, label %loop, label %done
+done:
+  %start = ptrtoint ptr %d to i64
+  %stop = ptrtoint ptr %end to i64
+  %len = sub i64 %stop, %start
+  %len1 = add i64 %len, 1
+  %w = call i64 @write(i32 1, ptr %d, i64 %len1)
+  ret void
+}
+
+attributes #0 = { alwaysinline }
+
+; main
+define private void @fn_0(ptr %stack, ptr %sp, ptr %ret, ptr %rsp) {
+entry:
+  call void @push(ptr %stack, ptr %sp, i64 100)
+  br label %L1.while
+L1.while:
+  call void @push(ptr %stack, ptr %sp, i64 1)
+  %t1 = call i64 @pop(ptr %stack, ptr %sp)
+  %t2 = call i64 @pop(ptr %stack, ptr %sp)
+  %t3 = sub i64 %t2, %t1
+  call void @push(ptr %stack, ptr %sp, i64 %t3)
+  %t4 = call i64 @peek(ptr %stack, ptr %sp, i64 0)
+  call void @push(ptr %stack, ptr %sp, i64 %t4)
+  call void @push(ptr %stack, ptr %sp, i64 0)
+  %t5 = call i64 @pop(ptr %stack, ptr %sp)
+  %t6 = call i64 @pop(ptr %stack, ptr %sp)
+  %t7 = icmp ugt i64 %t6, %t5
+  %t8 = zext i1 %t7 to i64
+  call void @push(ptr %stack, ptr %sp, i64 %t8)
+  %t9 = call i64 @pop(ptr %stack, ptr %sp)
+  %t10 = icmp eq i64 %t9, 1
+  br i1 %t10, label %L2.do, label %L3.end
+L2.do:
+  call void @push(ptr %stack, ptr %sp, i64 0)
+  br label %L4.while
+L4.while:
+  call void @push(ptr %stack, ptr %sp, i64 1)
+  %t11 = call i64 @pop(ptr %stack, ptr %sp)
+  %t12 = call i64 @pop(ptr %stack, ptr %sp)
+  %t13 = add i64 %t12, %t11
+  call void @push(ptr %stack, ptr %sp, i64 %t13)
+  %t14 = call i64 @peek(ptr %stack, ptr %sp, i64 0)
+  call void @push(ptr %stack, ptr %sp, i64 %t14)
+  call void @push(ptr %stack, ptr %sp, i64 5)
+  %t15 = call i64 @pop(ptr %stack, ptr %sp)
+  %t16 = call i64 @pop(ptr %stack, ptr %sp)
+  %t17 = icmp ult i64 %t16, %t15
+  %t18 = zext i1 %t17 to i64
+  call void @push(ptr %stack, ptr %sp, i64 %t18)
+  %t19 = call i64 @pop(ptr %stack, ptr %sp)
+  %t20 = icmp eq i64 %t19, 1
+  br i1 %t20, label %L5.do, label %L6.end
+L5.do:
+  call void @push(ptr %stack, ptr %sp, i64 8)
+  %t21 = call i64 @pop(ptr %stack, ptr %sp)
+  call void @print(i64 %t21)
+  br label %L4.while
+L6.end:
+  %t22 = call i64 @pop(ptr %stack, ptr %sp)
+  call void @push(ptr %stack, ptr %sp, i64 50)
+  call void @push(ptr %stack, ptr %sp, i64 1)
+  %t23 = call i64 @pop(ptr %stack, ptr %sp)
+  %t24 = call i64 @pop(ptr %stack, ptr %sp)
+  %t25 = add i64 %t24, %t23
+  call void @push(ptr %stack, ptr %sp, i64 %t25)
+  call void @push(ptr %stack, ptr %sp, i64 78)
+  call void @push(ptr %stack, ptr %sp, i64 27)
+  %t26 = call i64 @pop(ptr %stack, ptr %sp)
+  %t27 = call i64 @pop(ptr %stack, ptr %sp)
+  %t28 = sub i64 %t27, %t26
+  call void @push(ptr %stack, ptr %sp, i64 %t28)
+  %t29 = call i64 @pop(ptr %stack, ptr %sp)
+  %t30 = call i64 @pop(ptr %stack, ptr %sp)
+  %t31 = icmp eq i64 %t30, %t29
+  %t32 = zext i1 %t31 to i64
+  call void @push(ptr %stack, ptr %sp, i64 %t32)
+  call void @push(ptr %stack, ptr %sp, i64 1)
+  %t33 = call i64 @pop(ptr %stack, ptr %sp)
+  %t34 = icmp eq i64 %t33, 1
+  %t35 = call i64 @pop(ptr %stack, ptr %sp)
+  %t36 = icmp eq i64 %t35, 1
+  %t37 = and i1 %t36, %t34
+  %t38 = zext i1 %t37 to i64
+  call void @push(ptr %stack, ptr %sp, i64 %t38)
+  %t39 = call i64 @pop(ptr %stack, ptr %sp)
+  %t40 = icmp eq i64 %t39, 1
+  br i1 %t40, label %L7.then, label %L8.end
+L7.then:
+  call void @push(ptr %stack, ptr %sp, i64 0)
+  call void @push(ptr %stack, ptr %sp, i64 8)
+  call void @push(ptr %stack, ptr %sp, i64 3)
+  %t41 = call i64 @pop(ptr %stack, ptr %sp)
+  %t42 = call i64 @pop(ptr %stack, ptr %sp)
+  %t43 = icmp ugt i64 %t42, %t41
+  %t44 = zext i1 %t43 to i64
+  call void @push(ptr %stack, ptr %sp, i64 %t44)
+  %t45 = call i64 @pop(ptr %stack, ptr %sp)
+  %t46 = icmp eq i64 %t45, 1
+  %t47 = call i64 @pop(ptr %stack, ptr %sp)
+  %t48 = icmp eq i64 %t47, 1
+  %t49 = or i1 %t48, %t46
+  %t50 = zext i1 %t49 to i64
+  call void @push(ptr %stack, ptr %sp, i64 %t50)
+  %t51 = call i64 @pop(ptr %stack, ptr %sp)
+  %t52 = icmp eq i64 %t51, 1
+  br i1 %t52, label %L9.then, label %L10.end
+L9.then:
+  call void @push(ptr %stack, ptr %sp, i64 42)
+  %t53 = call i64 @pop(ptr %stack, ptr %sp)
+  call void @print(i64 %t53)
+  br label %L10.end
+L10.end:
+  br label %L8.end
+L8.end:
+  br label %L1.while
+L3.end:
+  ret void
+L11.dead:
+  ret void
+}
+
+define i32 @main() {
+entry:
+  %stack = alloca [65536 x i64]
+  %sp = alloca i64
+  store i64 0, ptr %sp
+  %ret = alloca [8192 x i64]
+  %rsp = alloca i64
+  store i64 8192, ptr %rsp
+  call void @fn_0(ptr %stack, ptr %sp, ptr %ret, ptr %rsp)
+  call void @exit(i32 0)
+  unreachable
+}
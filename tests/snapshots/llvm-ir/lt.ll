@@ -0,0 +1,169 @@
+@str.0 = private unnamed_addr constant [9 x i8] c"success!\0A"
+
+declare i64 @write(i32, ptr, i64)
+declare void @exit(i32)
+
+define private void @push(ptr %stack, ptr %sp, i64 %x) #0 {
+  %n = load i64, ptr %sp
+  %p = getelementptr i64, ptr %stack, i64 %n
+  store i64 %x, ptr %p
+  %m = add i64 %n, 1
+  store i64 %m, ptr %sp
+  ret void
+}
+
+define private i64 @pop(ptr %stack, ptr %sp) #0 {
+  %n = load i64, ptr %sp
+  %m = sub i64 %n, 1
+  store i64 %m, ptr %sp
+  %p = getelementptr i64, ptr %stack, i64 %m
+  %x = load i64, ptr %p
+  ret i64 %x
+}
+
+; The value `depth` cells below the top of the data stack.
+define private i64 @peek(ptr %stack, ptr %sp, i64 %depth) #0 {
+  %n = load i64, ptr %sp
+  %m = sub i64 %n, 1
+  %i = sub i64 %m, %depth
+  %p = getelementptr i64, ptr %stack, i64 %i
+  %x = load i64, ptr %p
+  ret i64 %x
+}
+
+; Move the return stack by `delta` cells.
+define private void @rsp_add(ptr %rsp, i64 %delta) #0 {
+  %n = load i64, ptr %rsp
+  %m = add i64 %n, %delta
+  store i64 %m, ptr %rsp
+  ret void
+}
+
+define private i64 @ret_get(ptr %ret, ptr %rsp, i64 %index) #0 {
+  %n = load i64, ptr %rsp
+  %i = add i64 %n, %index
+  %p = getelementptr i64, ptr %ret, i64 %i
+  %x = load i64, ptr %p
+  ret i64 %x
+}
+
+define private void @ret_set(ptr %ret, ptr %rsp, i64 %index, i64 %x) #0 {
+  %n = load i64, ptr %rsp
+  %i = add i64 %n, %index
+  %p = getelementptr i64, ptr %ret, i64 %i
+  store i64 %x, ptr %p
+  ret void
+}
+
+; Exit with status 1 if `x` is 0, since dividing by it is undefined behaviour that the optimiser
+; would be free to assume away.
+define private void @check_divisor(i64 %x) #0 {
+  %zero = icmp eq i64 %x, 0
+  br i1 %zero, label %fail, label %ok
+fail:
+  call void @exit(i32 1)
+  unreachable
+ok:
+  ret void
+}
+
+; Write the decimal digits of `x` backwards from the end of a buffer.
+define private void @print(i64 %x) {
+entry:
+  %buf = alloca [21 x i8]
+  %end = getelementptr [21 x i8], ptr %buf, i64 0, i64 20
+  store i8 10, ptr %end
+  br label %loop
+loop:
+  %n = phi i64 [ %x, %entry ], [ %q, %loop ]
+  %p = phi ptr [ %end, %entry ], [ %d, %loop ]
+  %q = udiv i64 %n, 10
+  %r = urem i64 %n, 10
+  %c = trunc i64 %r to i8
+  %digit = add i8 %c, 48
+  %d = getelementptr i8, ptr %p, i64 -1
+  store i8 %digit, ptr %d
+  %more = icmp ne i64 %q, 0
+  br i1 %more, label %loop, label %done
+done:
+  %start = ptrtoint ptr %d to i64
+  %stop = ptrtoint ptr %end to i64
+  %len = sub i64 %stop, %start
+  %len1 = add i64 %len, 1
+  %w = call i64 @write(i32 1, ptr %d, i64 %len1)
+  ret void
+}
+
+attributes #0 = { alwaysinline }
+
+; main
+define private void @fn_0(ptr %stack, ptr %sp, ptr %ret, ptr %rsp) {
+entry:
+  call void @push(ptr %stack, ptr %sp, i64 1)
+  call void @push(ptr %stack, ptr %sp, i64 2)
+  %t1 = call i64 @pop(ptr %stack, ptr %sp)
+  %t2 = call i64 @pop(ptr %stack, ptr %sp)
+  %t3 = icmp ult i64 %t2, %t1
+  %t4 = zext i1 %t3 to i64
+  call void @push(ptr %stack, ptr %sp, i64 %t4)
+  %t5 = call i64 @pop(ptr %stack, ptr %sp)
+  %t6 = icmp eq i64 %t5, 1
+  br i1 %t6, label %L1.then, label %L2.end
+L1.then:
+  call void @push(ptr %stack, ptr %sp, i64 9)
+  call void @push(ptr %stack, ptr %sp, i64 ptrtoint (ptr @str.0 to i64))
+  %t7 = call i64 @pop(ptr %stack, ptr %sp)
+  %t8 = call i64 @pop(ptr %stack, ptr %sp)
+  %t9 = inttoptr i64 %t7 to ptr
+  %t10 = call i64 @write(i32 1, ptr %t9, i64 %t8)
+  br label %L2.end
+L2.end:
+  call void @push(ptr %stack, ptr %sp, i64 2)
+  call void @push(ptr %stack, ptr %sp, i64 1)
+  %t11 = call i64 @pop(ptr %stack, ptr %sp)
+  %t12 = call i64 @pop(ptr %stack, ptr %sp)
+  %t13 = icmp ult i64 %t12, %t11
+  %t14 = zext i1 %t13 to i64
+  call void @push(ptr %stack, ptr %sp, i64 %t14)
+  %t15 = call i64 @pop(ptr %stack, ptr %sp)
+  %t16 = icmp eq i64 %t15, 1
+  br i1 %t16, label %L3.then, label %L4.end
+L3.then:
+  call void @push(ptr %stack, ptr %sp, i64 0)
+  %t17 = call i64 @pop(ptr %stack, ptr %sp)
+  call void @print(i64 %t17)
+  br label %L4.end
+L4.end:
+  call void @push(ptr %stack, ptr %sp, i64 2)
+  call void @push(ptr %stack, ptr %sp, i64 2)
+  %t18 = call i64 @pop(ptr %stack, ptr %sp)
+  %t19 = call i64 @pop(ptr %stack, ptr %sp)
+  %t20 = icmp ult i64 %t19, %t18
+  %t21 = zext i1 %t20 to i64
+  call void @push(ptr %stack, ptr %sp, i64 %t21)
+  %t22 = call i64 @pop(ptr %stack, ptr %sp)
+  %t23 = icmp eq i64 %t22, 1
+  br i1 %t23, label %L5.then, label %L6.end
+L5.then:
+  call void @push(ptr %stack, ptr %sp, i64 0)
+  %t24 = call i64 @pop(ptr %stack, ptr %sp)
+  call void @print(i64 %t24)
+  br label %L6.end
+L6.end:
+  ret void
+L7.dead:
+  ret void
+}
+
+define i32 @main() {
+entry:
+  %stack = alloca [65536 x i64]
+  %sp = alloca i64
+  store i64 0, ptr %sp
+  %ret = alloca [8192 x i64]
+  %rsp = alloca i64
+  store i64 8192, ptr %rsp
+  call void @fn_0(ptr %stack, ptr %sp, ptr %ret, ptr %rsp)
+  call void @exit(i32 0)
+  unreachable
+}
@@ -0,0 +1,201 @@
+
+declare i64 @write(i32, ptr, i64)
+declare void @exit(i32)
+
+define private void @push(ptr %stack, ptr %sp, i64 %x) #0 {
+  %n = load i64, ptr %sp
+  %p = getelementptr i64, ptr %stack, i64 %n
+  store i64 %x, ptr %p
+  %m = add i64 %n, 1
+  store i64 %m, ptr %sp
+  ret void
+}
+
+define private i64 @pop(ptr %stack, ptr %sp) #0 {
+  %n = load i64, ptr %sp
+  %m = sub i64 %n, 1
+  store i64 %m, ptr %sp
+  %p = getelementptr i64, ptr %stack, i64 %m
+  %x = load i64, ptr %p
+  ret i64 %x
+}
+
+; The value `depth` cells below the top of the data stack.
+define private i64 @peek(ptr %stack, ptr %sp, i64 %depth) #0 {
+  %n = load i64, ptr %sp
+  %m = sub i64 %n, 1
+  %i = sub i64 %m, %depth
+  %p = getelementptr i64, ptr %stack, i64 %i
+  %x = load i64, ptr %p
+  ret i64 %x
+}
+
+; Move the return stack by `delta` cells.
+define private void @rsp_add(ptr %rsp, i64 %delta) #0 {
+  %n = load i64, ptr %rsp
+  %m = add i64 %n, %delta
+  store i64 %m, ptr %rsp
+  ret void
+}
+
+define private i64 @ret_get(ptr %ret, ptr %rsp, i64 %index) #0 {
+  %n = load i64, ptr %rsp
+  %i = add i64 %n, %index
+  %p = getelementptr i64, ptr %ret, i64 %i
+  %x = load i64, ptr %p
+  ret i64 %x
+}
+
+define private void @ret_set(ptr %ret, ptr %rsp, i64 %index, i64 %x) #0 {
+  %n = load i64, ptr %rsp
+  %i = add i64 %n, %index
+  %p = getelementptr i64, ptr %ret, i64 %i
+  store i64 %x, ptr %p
+  ret void
+}
+
+; Exit with status 1 if `x` is 0, since dividing by it is undefined behaviour that the optimiser
+; would be free to assume away.
+define private void @check_divisor(i64 %x) #0 {
+  %zero = icmp eq i64 %x, 0
+  br i1 %zero, label %fail, label %ok
+fail:
+  call void @exit(i32 1)
+  unreachable
+ok:
+  ret void
+}
+
+; Write the decimal digits of `x` backwards from the end of a buffer.
+define private void @print(i64 %x) {
+entry:
+  %buf = alloca [21 x i8]
+  %end = getelementptr [21 x i8], ptr %buf, i64 0, i64 20
+  store i8 10, ptr %end
+  br label %loop
+loop:
+  %n = phi i64 [ %x, %entry ], [ %q, %loop ]
+  %p = phi ptr [ %end, %entry ], [ %d, %loop ]
+  %q = udiv i64 %n, 10
+  %r = urem i64 %n, 10
+  %c = trunc i64 %r to i8
+  %digit = add i8 %c, 48
+  %d = getelementptr i8, ptr %p, i64 -1
+  store i8 %digit, ptr %d
+  %more = icmp ne i64 %q, 0
+  br i1 %more, label %loop, label %done
+done:
+  %start = ptrtoint ptr %d to i64
+  %stop = ptrtoint ptr %end to i64
+  %len = sub i64 %stop, %start
+  %len1 = add i64 %len, 1
+  %w = call i64 @write(i32 1, ptr %d, i64 %len1)
+  ret void
+}
+
+attributes #0 = { alwaysinline }
+
+; main
+define private void @fn_0(ptr %stack, ptr %sp, ptr %ret, ptr %rsp) {
+entry:
+  call void @push(ptr %stack, ptr %sp, i64 1)
+  call void @push(ptr %stack, ptr %sp, i64 2)
+  ; let a b
+  call void @rsp_add(ptr %rsp, i64 -2)
+  %t1 = call i64 @peek(ptr %stack, ptr %sp, i64 0)
+  call void @ret_set(ptr %ret, ptr %rsp, i64 0, i64 %t1)
+  %t2 = call i64 @peek(ptr %stack, ptr %sp, i64 1)
+  call void @ret_set(ptr %ret, ptr %rsp, i64 1, i64 %t2)
+  %t3 = load i64, ptr %sp
+  %t4 = sub i64 %t3, 2
+  store i64 %t4, ptr %sp
+  %t5 = call i64 @ret_get(ptr %ret, ptr %rsp, i64 1)
+  call void @push(ptr %stack, ptr %sp, i64 %t5)
+  %t6 = call i64 @pop(ptr %stack, ptr %sp)
+  call void @print(i64 %t6)
+  %t7 = call i64 @ret_get(ptr %ret, ptr %rsp, i64 0)
+  call void @push(ptr %stack, ptr %sp, i64 %t7)
+  %t8 = call i64 @pop(ptr %stack, ptr %sp)
+  call void @print(i64 %t8)
+  call void @push(ptr %stack, ptr %sp, i64 3)
+  call void @push(ptr %stack, ptr %sp, i64 4)
+  ; let c d
+  call void @rsp_add(ptr %rsp, i64 -2)
+  %t9 = call i64 @peek(ptr %stack, ptr %sp, i64 0)
+  call void @ret_set(ptr %ret, ptr %rsp, i64 0, i64 %t9)
+  %t10 = call i64 @peek(ptr %stack, ptr %sp, i64 1)
+  call void @ret_set(ptr %ret, ptr %rsp, i64 1, i64 %t10)
+  %t11 = load i64, ptr %sp
+  %t12 = sub i64 %t11, 2
+  store i64 %t12, ptr %sp
+  %t13 = call i64 @ret_get(ptr %ret, ptr %rsp, i64 1)
+  call void @push(ptr %stack, ptr %sp, i64 %t13)
+  %t14 = call i64 @pop(ptr %stack, ptr %sp)
+  call void @print(i64 %t14)
+  %t15 = call i64 @ret_get(ptr %ret, ptr %rsp, i64 0)
+  call void @push(ptr %stack, ptr %sp, i64 %t15)
+  %t16 = call i64 @pop(ptr %stack, ptr %sp)
+  call void @print(i64 %t16)
+  call void @rsp_add(ptr %rsp, i64 2)
+  %t17 = call i64 @ret_get(ptr %ret, ptr %rsp, i64 1)
+  call void @push(ptr %stack, ptr %sp, i64 %t17)
+  %t18 = call i64 @pop(ptr %stack, ptr %sp)
+  call void @print(i64 %t18)
+  call void @rsp_add(ptr %rsp, i64 2)
+  call void @push(ptr %stack, ptr %sp, i64 5)
+  call void @push(ptr %stack, ptr %sp, i64 6)
+  ; let f g
+  call void @rsp_add(ptr %rsp, i64 -2)
+  %t19 = call i64 @peek(ptr %stack, ptr %sp, i64 0)
+  call void @ret_set(ptr %ret, ptr %rsp, i64 0, i64 %t19)
+  %t20 = call i64 @peek(ptr %stack, ptr %sp, i64 1)
+  call void @ret_set(ptr %ret, ptr %rsp, i64 1, i64 %t20)
+  %t21 = load i64, ptr %sp
+  %t22 = sub i64 %t21, 2
+  store i64 %t22, ptr %sp
+  %t23 = call i64 @ret_get(ptr %ret, ptr %rsp, i64 1)
+  call void @push(ptr %stack, ptr %sp, i64 %t23)
+  %t24 = call i64 @pop(ptr %stack, ptr %sp)
+  call void @print(i64 %t24)
+  %t25 = call i64 @ret_get(ptr %ret, ptr %rsp, i64 0)
+  call void @push(ptr %stack, ptr %sp, i64 %t25)
+  %t26 = call i64 @pop(ptr %stack, ptr %sp)
+  call void @print(i64 %t26)
+  call void @rsp_add(ptr %rsp, i64 2)
+  call void @push(ptr %stack, ptr %sp, i64 7)
+  call void @push(ptr %stack, ptr %sp, i64 8)
+  ; let a b
+  call void @rsp_add(ptr %rsp, i64 -2)
+  %t27 = call i64 @peek(ptr %stack, ptr %sp, i64 0)
+  call void @ret_set(ptr %ret, ptr %rsp, i64 0, i64 %t27)
+  %t28 = call i64 @peek(ptr %stack, ptr %sp, i64 1)
+  call void @ret_set(ptr %ret, ptr %rsp, i64 1, i64 %t28)
+  %t29 = load i64, ptr %sp
+  %t30 = sub i64 %t29, 2
+  store i64 %t30, ptr %sp
+  %t31 = call i64 @ret_get(ptr %ret, ptr %rsp, i64 1)
+  call void @push(ptr %stack, ptr %sp, i64 %t31)
+  %t32 = call i64 @pop(ptr %stack, ptr %sp)
+  call void @print(i64 %t32)
+  %t33 = call i64 @ret_get(ptr %ret, ptr %rsp, i64 0)
+  call void @push(ptr %stack, ptr %sp, i64 %t33)
+  %t34 = call i64 @pop(ptr %stack, ptr %sp)
+  call void @print(i64 %t34)
+  call void @rsp_add(ptr %rsp, i64 2)
+  ret void
+L1.dead:
+  ret void
+}
+
+define i32 @main() {
+entry:
+  %stack = alloca [65536 x i64]
+  %sp = alloca i64
+  store i64 0, ptr %sp
+  %ret = alloca [8192 x i64]
+  %rsp = alloca i64
+  store i64 8192, ptr %rsp
+  call void @fn_0(ptr %stack, ptr %sp, ptr %ret, ptr %rsp)
+  call void @exit(i32 0)
+  unreachable
+}
@@ -0,0 +1,100 @@
+	.macro	PUSH reg
+	addi	s1, s1, -8
+	sd	\reg, 0(s1)
+	.endm
+	.macro	POP reg
+	ld	\reg, 0(s1)
+	addi	s1, s1, 8
+	.endm
+
+	.globl	main
+	.text
+print:
+	addi	sp, sp, -32
+	li	t0, 10
+	sb	t0, 31(sp)
+	addi	a1, sp, 31
+.L2:
+	remu	t1, a0, t0
+	divu	a0, a0, t0
+	addi	t1, t1, 48
+	addi	a1, a1, -1
+	sb	t1, 0(a1)
+	bnez	a0, .L2
+	addi	a2, sp, 32
+	sub	a2, a2, a1
+	li	a0, 1
+	li	a7, 64
+	ecall
+	addi	sp, sp, 32
+	ret
+fn_test_fn:
+					# Op::Ret(0)
+	lla	t0, ret_stack_rsp
+	ld	t1, 0(t0)
+	addi	t1, t1, 0
+	sd	t1, 0(t0)
+	ld	t2, 0(t1)
+	jr	t2
+fn_main:
+	li	a0, 12		# Op::PushInt(12)
+	PUSH	a0
+	lla	a0, str_0		# Op::PushStrPtr(0)
+	PUSH	a0
+	POP	a1			# Op::Puts
+	POP	a2
+	li	a0, 1
+	li	a7, 64
+	ecall
+					# Op::CallFn(0)
+	lla	t0, ret_stack_rsp
+	ld	t1, 0(t0)
+	addi	t1, t1, -8
+	sd	t1, 0(t0)
+	lla	t2, RET4
+	sd	t2, 0(t1)
+	j	fn_test_fn
+RET4:
+	lla	t0, ret_stack_rsp
+	ld	t1, 0(t0)
+	addi	t1, t1, 8
+	sd	t1, 0(t0)
+	li	a0, 9		# Op::PushInt(9)
+	PUSH	a0
+	lla	a0, str_1		# Op::PushStrPtr(1)
+	PUSH	a0
+	POP	a1			# Op::Puts
+	POP	a2
+	li	a0, 1
+	li	a7, 64
+	ecall
+					# Op::Ret(0)
+	lla	t0, ret_stack_rsp
+	ld	t1, 0(t0)
+	addi	t1, t1, 0
+	sd	t1, 0(t0)
+	ld	t2, 0(t1)
+	jr	t2
+main:
+	lla	s1, data_stack_end
+	lla	t1, ret_stack_end
+	addi	t1, t1, -8
+	lla	t0, ret_stack_rsp
+	sd	t1, 0(t0)
+	lla	t2, RET_MAIN
+	sd	t2, 0(t1)
+	j	fn_main
+RET_MAIN:
+	li	a0, 0
+	li	a7, 93
+	ecall
+	.section	.rodata
+str_0:	.byte	104,101,108,108,111,32,119,111,114,108,100,10
+str_1:	.byte	115,117,99,99,101,115,115,33,10
+	.bss
+	.balign	8
+ret_stack_rsp:	.skip	8
+ret_stack:	.skip	65536
+ret_stack_end:
+data_stack:	.skip	8388608
+data_stack_end:
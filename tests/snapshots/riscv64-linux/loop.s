@@ -0,0 +1,156 @@
+	.macro	PUSH reg
+	addi	s1, s1, -8
+	sd	\reg, 0(s1)
+	.endm
+	.macro	POP reg
+	ld	\reg, 0(s1)
+	addi	s1, s1, 8
+	.endm
+
+	.globl	main
+	.text
+print:
+	addi	sp, sp, -32
+	li	t0, 10
+	sb	t0, 31(sp)
+	addi	a1, sp, 31
+.L2:
+	remu	t1, a0, t0
+	divu	a0, a0, t0
+	addi	t1, t1, 48
+	addi	a1, a1, -1
+	sb	t1, 0(a1)
+	bnez	a0, .L2
+	addi	a2, sp, 32
+	sub	a2, a2, a1
+	li	a0, 1
+	li	a7, 64
+	ecall
+	addi	sp, sp, 32
+	ret
+fn_main:
+	li	a0, 0		# Op::PushInt(0)
+	PUSH	a0
+F1:					# Op::While
+	li	a0, 1		# Op::PushInt(1)
+	PUSH	a0
+	POP	a1			# Op::Plus
+	POP	a0
+	add	a0, a0, a1
+	PUSH	a0
+	ld	a0, 0(s1)		# Op::Dup
+	PUSH	a0
+	li	a0, 101		# Op::PushInt(101)
+	PUSH	a0
+	POP	a1			# Op::LessThan
+	POP	a0
+	sltu	a0, a0, a1
+	PUSH	a0
+	POP	a0
+	li	a1, 1
+	beq	a0, a1, T2
+	j	F2
+T2:
+	ld	a0, 0(s1)		# Op::Dup
+	PUSH	a0
+	POP	a0			# Op::Print
+	call	print
+	ld	a0, 0(s1)		# Op::Dup
+	PUSH	a0
+	li	a0, 69		# Op::PushInt(69)
+	PUSH	a0
+	POP	a1			# Op::Equals
+	POP	a0
+	sub	a0, a0, a1
+	seqz	a0, a0
+	PUSH	a0
+	POP	a0			# Op::If
+	li	a1, 1
+	beq	a0, a1, T15
+	j	F15
+T15:
+	li	a0, 420		# Op::PushInt(420)
+	PUSH	a0
+	POP	a0			# Op::Print
+	call	print
+F15:
+	j	F1
+F2:
+	li	a0, 0		# Op::PushInt(0)
+	PUSH	a0
+F21:					# Op::While
+	li	a0, 1		# Op::PushInt(1)
+	PUSH	a0
+	li	a0, 1		# Op::PushInt(1)
+	PUSH	a0
+	POP	a1			# Op::And
+	POP	a0
+	addi	a0, a0, -1
+	seqz	a0, a0
+	addi	a1, a1, -1
+	seqz	a1, a1
+	and	a0, a0, a1
+	PUSH	a0
+	POP	a0			# Op::If
+	li	a1, 1
+	beq	a0, a1, T27
+	j	F27
+T27:
+	li	a0, 0		# Op::PushInt(0)
+	PUSH	a0
+	POP	a0			# Op::Print
+	call	print
+F27:
+	li	a0, 1		# Op::PushInt(1)
+	PUSH	a0
+	POP	a1			# Op::Plus
+	POP	a0
+	add	a0, a0, a1
+	PUSH	a0
+	ld	a0, 0(s1)		# Op::Dup
+	PUSH	a0
+	li	a0, 101		# Op::PushInt(101)
+	PUSH	a0
+	POP	a1			# Op::LessThan
+	POP	a0
+	sltu	a0, a0, a1
+	PUSH	a0
+	POP	a0
+	li	a1, 1
+	beq	a0, a1, T22
+	j	F22
+T22:
+	li	a0, 1		# Op::PushInt(1)
+	PUSH	a0
+	POP	a0			# Op::Print
+	call	print
+	j	F21
+F22:
+					# Op::Ret(0)
+	lla	t0, ret_stack_rsp
+	ld	t1, 0(t0)
+	addi	t1, t1, 0
+	sd	t1, 0(t0)
+	ld	t2, 0(t1)
+	jr	t2
+main:
+	lla	s1, data_stack_end
+	lla	t1, ret_stack_end
+	addi	t1, t1, -8
+	lla	t0, ret_stack_rsp
+	sd	t1, 0(t0)
+	lla	t2, RET_MAIN
+	sd	t2, 0(t1)
+	j	fn_main
+RET_MAIN:
+	li	a0, 0
+	li	a7, 93
+	ecall
+	.section	.rodata
+	.bss
+	.balign	8
+ret_stack_rsp:	.skip	8
+ret_stack:	.skip	65536
+ret_stack_end:
+data_stack:	.skip	8388608
+data_stack_end:
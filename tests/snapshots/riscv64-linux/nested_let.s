@@ -0,0 +1,185 @@
+	.macro	PUSH reg
+	addi	s1, s1, -8
+	sd	\reg, 0(s1)
+	.endm
+	.macro	POP reg
+	ld	\reg, 0(s1)
+	addi	s1, s1, 8
+	.endm
+
+	.globl	main
+	.text
+print:
+	addi	sp, sp, -32
+	li	t0, 10
+	sb	t0, 31(sp)
+	addi	a1, sp, 31
+.L2:
+	remu	t1, a0, t0
+	divu	a0, a0, t0
+	addi	t1, t1, 48
+	addi	a1, a1, -1
+	sb	t1, 0(a1)
+	bnez	a0, .L2
+	addi	a2, sp, 32
+	sub	a2, a2, a1
+	li	a0, 1
+	li	a7, 64
+	ecall
+	addi	sp, sp, 32
+	ret
+fn_main:
+	li	a0, 1		# Op::PushInt(1)
+	PUSH	a0
+	li	a0, 2		# Op::PushInt(2)
+	PUSH	a0
+					# Op::Bind(2)
+	lla	t0, ret_stack_rsp
+	ld	t1, 0(t0)
+	addi	t1, t1, -16
+	sd	t1, 0(t0)
+	ld	t2, 0(s1)
+	sd	t2, 0(t1)
+	ld	t2, 8(s1)
+	sd	t2, 8(t1)
+	addi	s1, s1, 16
+	lla	t0, ret_stack_rsp		# Op::PushBind(1)
+	ld	t1, 0(t0)
+	ld	t2, 8(t1)
+	PUSH	t2
+	POP	a0			# Op::Print
+	call	print
+	lla	t0, ret_stack_rsp		# Op::PushBind(0)
+	ld	t1, 0(t0)
+	ld	t2, 0(t1)
+	PUSH	t2
+	POP	a0			# Op::Print
+	call	print
+	li	a0, 3		# Op::PushInt(3)
+	PUSH	a0
+	li	a0, 4		# Op::PushInt(4)
+	PUSH	a0
+					# Op::Bind(2)
+	lla	t0, ret_stack_rsp
+	ld	t1, 0(t0)
+	addi	t1, t1, -16
+	sd	t1, 0(t0)
+	ld	t2, 0(s1)
+	sd	t2, 0(t1)
+	ld	t2, 8(s1)
+	sd	t2, 8(t1)
+	addi	s1, s1, 16
+	lla	t0, ret_stack_rsp		# Op::PushBind(1)
+	ld	t1, 0(t0)
+	ld	t2, 8(t1)
+	PUSH	t2
+	POP	a0			# Op::Print
+	call	print
+	lla	t0, ret_stack_rsp		# Op::PushBind(0)
+	ld	t1, 0(t0)
+	ld	t2, 0(t1)
+	PUSH	t2
+	POP	a0			# Op::Print
+	call	print
+	lla	t0, ret_stack_rsp
+	ld	t1, 0(t0)
+	addi	t1, t1, 16
+	sd	t1, 0(t0)
+	lla	t0, ret_stack_rsp		# Op::PushBind(1)
+	ld	t1, 0(t0)
+	ld	t2, 8(t1)
+	PUSH	t2
+	POP	a0			# Op::Print
+	call	print
+	lla	t0, ret_stack_rsp
+	ld	t1, 0(t0)
+	addi	t1, t1, 16
+	sd	t1, 0(t0)
+	li	a0, 5		# Op::PushInt(5)
+	PUSH	a0
+	li	a0, 6		# Op::PushInt(6)
+	PUSH	a0
+					# Op::Bind(2)
+	lla	t0, ret_stack_rsp
+	ld	t1, 0(t0)
+	addi	t1, t1, -16
+	sd	t1, 0(t0)
+	ld	t2, 0(s1)
+	sd	t2, 0(t1)
+	ld	t2, 8(s1)
+	sd	t2, 8(t1)
+	addi	s1, s1, 16
+	lla	t0, ret_stack_rsp		# Op::PushBind(1)
+	ld	t1, 0(t0)
+	ld	t2, 8(t1)
+	PUSH	t2
+	POP	a0			# Op::Print
+	call	print
+	lla	t0, ret_stack_rsp		# Op::PushBind(0)
+	ld	t1, 0(t0)
+	ld	t2, 0(t1)
+	PUSH	t2
+	POP	a0			# Op::Print
+	call	print
+	lla	t0, ret_stack_rsp
+	ld	t1, 0(t0)
+	addi	t1, t1, 16
+	sd	t1, 0(t0)
+	li	a0, 7		# Op::PushInt(7)
+	PUSH	a0
+	li	a0, 8		# Op::PushInt(8)
+	PUSH	a0
+					# Op::Bind(2)
+	lla	t0, ret_stack_rsp
+	ld	t1, 0(t0)
+	addi	t1, t1, -16
+	sd	t1, 0(t0)
+	ld	t2, 0(s1)
+	sd	t2, 0(t1)
+	ld	t2, 8(s1)
+	sd	t2, 8(t1)
+	addi	s1, s1, 16
+	lla	t0, ret_stack_rsp		# Op::PushBind(1)
+	ld	t1, 0(t0)
+	ld	t2, 8(t1)
+	PUSH	t2
+	POP	a0			# Op::Print
+	call	print
+	lla	t0, ret_stack_rsp		# Op::PushBind(0)
+	ld	t1, 0(t0)
+	ld	t2, 0(t1)
+	PUSH	t2
+	POP	a0			# Op::Print
+	call	print
+	lla	t0, ret_stack_rsp
+	ld	t1, 0(t0)
+	addi	t1, t1, 16
+	sd	t1, 0(t0)
+					# Op::Ret(0)
+	lla	t0, ret_stack_rsp
+	ld	t1, 0(t0)
+	addi	t1, t1, 0
+	sd	t1, 0(t0)
+	ld	t2, 0(t1)
+	jr	t2
+main:
+	lla	s1, data_stack_end
+	lla	t1, ret_stack_end
+	addi	t1, t1, -8
+	lla	t0, ret_stack_rsp
+	sd	t1, 0(t0)
+	lla	t2, RET_MAIN
+	sd	t2, 0(t1)
+	j	fn_main
+RET_MAIN:
+	li	a0, 0
+	li	a7, 93
+	ecall
+	.section	.rodata
+	.bss
+	.balign	8
+ret_stack_rsp:	.skip	8
+ret_stack:	.skip	65536
+ret_stack_end:
+data_stack:	.skip	8388608
+data_stack_end:
@@ -0,0 +1,121 @@
+#![allow(dead_code, unreachable_code, unused_variables)]
+
+use std::io::{self, Write};
+
+struct State {
+    stack: Vec<u64>,
+    ret: Vec<u64>,
+    out: io::BufWriter<io::Stdout>,
+}
+
+impl State {
+    fn push(&mut self, x: u64) {
+        self.stack.push(x);
+    }
+
+    fn pop(&mut self) -> u64 {
+        self.stack.pop().expect("stack underflow")
+    }
+
+    /// The value `depth` cells below the top of the data stack.
+    fn peek(&self, depth: usize) -> u64 {
+        self.stack[self.stack.len() - 1 - depth]
+    }
+
+    /// Copy the top `count` values to the return stack, the top one ending up on top.
+    fn bind(&mut self, count: usize, peek: bool) {
+        for i in (0..count).rev() {
+            let x = self.peek(i);
+            self.ret.push(x);
+        }
+        if !peek {
+            self.stack.truncate(self.stack.len() - count);
+        }
+    }
+
+    fn unbind(&mut self, count: usize) {
+        self.ret.truncate(self.ret.len() - count);
+    }
+
+    fn binding(&self, index: usize) -> u64 {
+        self.ret[self.ret.len() - 1 - index]
+    }
+
+    /// Pop the top two values and push `f` of the second and the top.
+    fn binary(&mut self, f: impl FnOnce(u64, u64) -> u64) {
+        let b = self.pop();
+        let a = self.pop();
+        self.push(f(a, b));
+    }
+
+    fn read_byte(&mut self) {
+        let addr = self.pop() as usize;
+        // SAFETY: Rack programs only read from the addresses of their strings.
+        let byte = unsafe { *std::ptr::with_exposed_provenance::<u8>(addr) };
+        self.push(u64::from(byte));
+    }
+
+    fn print(&mut self) {
+        let x = self.pop();
+        writeln!(self.out, "{x}").unwrap();
+    }
+
+    fn puts(&mut self) {
+        let addr = self.pop() as usize;
+        let len = self.pop() as usize;
+        // SAFETY: Rack programs only write their strings, with their lengths.
+        let s = unsafe {
+            std::slice::from_raw_parts(std::ptr::with_exposed_provenance::<u8>(addr), len)
+        };
+        self.out.write_all(s).unwrap();
+    }
+}
+
+static STR_0: [u8; 5] = [116, 114, 117, 101, 10];
+static STR_1: [u8; 6] = [102, 97, 108, 115, 101, 10];
+static STR_2: [u8; 10] = [97, 108, 115, 111, 32, 116, 114, 117, 101, 10];
+
+/// `main`
+fn fn_0(s: &mut State) {
+    s.push(50);
+    s.push(8);
+    s.binary(u64::wrapping_sub);
+    s.push(42);
+    s.binary(|a, b| u64::from(a == b));
+    if s.pop() == 1 {
+        s.push(5);
+        s.push(STR_0.as_ptr().expose_provenance() as u64);
+        s.puts();
+        s.push(1);
+        if s.pop() == 1 {
+            s.push(0);
+            if s.pop() == 1 {
+                s.push(6);
+                s.push(STR_1.as_ptr().expose_provenance() as u64);
+                s.puts();
+            }
+            s.push(10);
+            s.push(STR_2.as_ptr().expose_provenance() as u64);
+            s.puts();
+        }
+        s.push(8);
+        s.push(2);
+        s.binary(|a, b| u64::from(a == b));
+        if s.pop() == 1 {
+            s.push(6);
+            s.push(STR_1.as_ptr().expose_provenance() as u64);
+            s.puts();
+        }
+    }
+    return;
+}
+
+fn main() {
+    let mut s = State {
+        stack: Vec::new(),
+        ret: Vec::new(),
+        out: io::BufWriter::new(io::stdout()),
+    };
+    fn_0(&mut s);
+    s.out.flush().unwrap();
+}
@@ -0,0 +1,142 @@
+(module
+  (type (;0;) (func (param i32 i32 i32 i32) (result i32)))
+  (type (;1;) (func (param i64)))
+  (type (;2;) (func (result i64)))
+  (type (;3;) (func (param i32 i32)))
+  (type (;4;) (func))
+  (import "wasi_snapshot_preview1" "fd_write" (func $fd_write (type 0)))
+  (memory (export "memory") 130)
+  (global $sp (mut i32) (i32.const 8454224))
+  (global $rsp (mut i32) (i32.const 65616))
+  (export "_start" (func $_start))
+  (data (i32.const 64) "\48\65\6c\6c\6f\2c\20")
+  (data (i32.const 71) "\77\6f\72\6c\64\21\0a")
+  (func $push (type 1)
+    global.get $sp
+    i32.const 8
+    i32.sub
+    global.set $sp
+    global.get $sp
+    local.get 0
+    i64.store
+  )
+  (func $pop (type 2)
+    global.get $sp
+    i64.load
+    global.get $sp
+    i32.const 8
+    i32.add
+    global.set $sp
+  )
+  (func $write (type 3)
+    i32.const 0
+    local.get 0
+    i32.store
+    i32.const 0
+    local.get 1
+    i32.store offset=4
+    i32.const 1
+    i32.const 0
+    i32.const 1
+    i32.const 8
+    call $fd_write
+    drop
+  )
+  (func $print (type 1) (local i32)
+    i32.const 47
+    local.set 1
+    local.get 1
+    i64.const 10
+    i64.store8
+    loop
+      local.get 1
+      i32.const 1
+      i32.sub
+      local.set 1
+      local.get 1
+      local.get 0
+      i64.const 10
+      i64.rem_u
+      i64.const 48
+      i64.add
+      i64.store8
+      local.get 0
+      i64.const 10
+      i64.div_u
+      local.set 0
+      local.get 0
+      i64.const 0
+      i64.ne
+      br_if 0
+    end
+    local.get 1
+    i32.const 48
+    local.get 1
+    i32.sub
+    call $write
+  )
+  (func $puts (type 4)
+    call $pop
+    i32.wrap_i64
+    call $pop
+    i32.wrap_i64
+    call $write
+  )
+  (func $_start (type 4)
+    call $fn_0
+  )
+  ;; main
+  (func $fn_0 (type 4) (local i64) (local i64)
+    i64.const 7
+    call $push
+    i64.const 64
+    call $push
+    i64.const 7
+    call $push
+    i64.const 71
+    call $push
+    global.get $rsp
+    i32.const 32
+    i32.sub
+    global.set $rsp
+    global.get $rsp
+    global.get $sp
+    i64.load
+    i64.store
+    global.get $rsp
+    global.get $sp
+    i64.load offset=8
+    i64.store offset=8
+    global.get $rsp
+    global.get $sp
+    i64.load offset=16
+    i64.store offset=16
+    global.get $rsp
+    global.get $sp
+    i64.load offset=24
+    i64.store offset=24
+    global.get $sp
+    i32.const 32
+    i32.add
+    global.set $sp
+    global.get $rsp
+    i64.load offset=24
+    call $push
+    global.get $rsp
+    i64.load offset=16
+    call $push
+    call $puts
+    global.get $rsp
+    i64.load offset=8
+    call $push
+    global.get $rsp
+    i64.load
+    call $push
+    call $puts
+    global.get $rsp
+    i32.const 32
+    i32.add
+    global.set $rsp
+    return
+  )
+)
@@ -0,0 +1,161 @@
+(module
+  (type (;0;) (func (param i32 i32 i32 i32) (result i32)))
+  (type (;1;) (func (param i64)))
+  (type (;2;) (func (result i64)))
+  (type (;3;) (func (param i32 i32)))
+  (type (;4;) (func))
+  (import "wasi_snapshot_preview1" "fd_write" (func $fd_write (type 0)))
+  (memory (export "memory") 130)
+  (global $sp (mut i32) (i32.const 8454216))
+  (global $rsp (mut i32) (i32.const 65608))
+  (export "_start" (func $_start))
+  (data (i32.const 64) "\74\65\73\74")
+  (func $push (type 1)
+    global.get $sp
+    i32.const 8
+    i32.sub
+    global.set $sp
+    global.get $sp
+    local.get 0
+    i64.store
+  )
+  (func $pop (type 2)
+    global.get $sp
+    i64.load
+    global.get $sp
+    i32.const 8
+    i32.add
+    global.set $sp
+  )
+  (func $write (type 3)
+    i32.const 0
+    local.get 0
+    i32.store
+    i32.const 0
+    local.get 1
+    i32.store offset=4
+    i32.const 1
+    i32.const 0
+    i32.const 1
+    i32.const 8
+    call $fd_write
+    drop
+  )
+  (func $print (type 1) (local i32)
+    i32.const 47
+    local.set 1
+    local.get 1
+    i64.const 10
+    i64.store8
+    loop
+      local.get 1
+      i32.const 1
+      i32.sub
+      local.set 1
+      local.get 1
+      local.get 0
+      i64.const 10
+      i64.rem_u
+      i64.const 48
+      i64.add
+      i64.store8
+      local.get 0
+      i64.const 10
+      i64.div_u
+      local.set 0
+      local.get 0
+      i64.const 0
+      i64.ne
+      br_if 0
+    end
+    local.get 1
+    i32.const 48
+    local.get 1
+    i32.sub
+    call $write
+  )
+  (func $puts (type 4)
+    call $pop
+    i32.wrap_i64
+    call $pop
+    i32.wrap_i64
+    call $write
+  )
+  (func $_start (type 4)
+    call $fn_0
+  )
+  ;; main
+  (func $fn_0 (type 4) (local i64) (local i64)
+    i64.const 4
+    call $push
+    i64.const 64
+    call $push
+    global.get $sp
+    i64.load
+    call $push
+    call $pop
+    i32.wrap_i64
+    i64.load8_u
+    call $push
+    call $pop
+    call $print
+    global.get $sp
+    i64.load
+    call $push
+    i64.const 1
+    call $push
+    call $pop
+    local.set 1
+    call $pop
+    local.set 0
+    local.get 0
+    local.get 1
+    i64.add
+    call $push
+    call $pop
+    i32.wrap_i64
+    i64.load8_u
+    call $push
+    call $pop
+    call $print
+    global.get $sp
+    i64.load
+    call $push
+    i64.const 2
+    call $push
+    call $pop
+    local.set 1
+    call $pop
+    local.set 0
+    local.get 0
+    local.get 1
+    i64.add
+    call $push
+    call $pop
+    i32.wrap_i64
+    i64.load8_u
+    call $push
+    call $pop
+    call $print
+    global.get $sp
+    i64.load
+    call $push
+    i64.const 3
+    call $push
+    call $pop
+    local.set 1
+    call $pop
+    local.set 0
+    local.get 0
+    local.get 1
+    i64.add
+    call $push
+    call $pop
+    i32.wrap_i64
+    i64.load8_u
+    call $push
+    call $pop
+    call $print
+    return
+  )
+)
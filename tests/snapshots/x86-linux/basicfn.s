@@ -0,0 +1,71 @@
+	.globl	main
+	.text
+print:
+	subl	$16, %esp
+	leal	15(%esp), %edi
+	movb	$10, (%edi)
+	movl	%edi, %ecx
+	movl	$10, %ebx
+.L2:
+	xorl	%edx, %edx
+	divl	%ebx
+	addb	$48, %dl
+	decl	%ecx
+	movb	%dl, (%ecx)
+	testl	%eax, %eax
+	jnz	.L2
+	movl	%edi, %edx
+	subl	%ecx, %edx
+	incl	%edx
+	movl	$1, %ebx
+	movl	$4, %eax
+	int	$0x80
+	addl	$16, %esp
+	ret
+fn_0:					# test_fn
+	movl	ret_stack_rsp, %eax	# Op::Ret(0)
+	jmp	*(%eax)
+fn_1:					# main
+	pushl	$12			# Op::PushInt(12)
+	pushl	$str_0		# Op::PushStrPtr(0)
+	movl	$4, %eax		# Op::Puts
+	movl	$1, %ebx
+	popl	%ecx
+	popl	%edx
+	int	$0x80
+	movl	ret_stack_rsp, %eax	# Op::CallFn(0)
+	subl	$4, %eax
+	movl	%eax, ret_stack_rsp
+	movl	$RET4, (%eax)
+	jmp	fn_0
+RET4:
+	movl	ret_stack_rsp, %eax
+	addl	$4, %eax
+	movl	%eax, ret_stack_rsp
+	pushl	$9			# Op::PushInt(9)
+	pushl	$str_1		# Op::PushStrPtr(1)
+	movl	$4, %eax		# Op::Puts
+	movl	$1, %ebx
+	popl	%ecx
+	popl	%edx
+	int	$0x80
+	movl	ret_stack_rsp, %eax	# Op::Ret(0)
+	jmp	*(%eax)
+main:
+	movl	$ret_stack_end, %eax
+	subl	$4, %eax
+	movl	%eax, ret_stack_rsp
+	movl	$RET_MAIN, (%eax)
+	jmp	fn_1
+RET_MAIN:
+	movl	$1, %eax
+	xorl	%ebx, %ebx
+	int	$0x80
+	.section	.rodata
+str_0:	.byte	104,101,108,108,111,32,119,111,114,108,100,10
+str_1:	.byte	115,117,99,99,101,115,115,33,10
+	.bss
+	.balign	4
+ret_stack_rsp:	.skip	4
+ret_stack:	.skip	65536
+ret_stack_end:
@@ -0,0 +1,81 @@
+	.globl	main
+	.text
+print:
+	subl	$16, %esp
+	leal	15(%esp), %edi
+	movb	$10, (%edi)
+	movl	%edi, %ecx
+	movl	$10, %ebx
+.L2:
+	xorl	%edx, %edx
+	divl	%ebx
+	addb	$48, %dl
+	decl	%ecx
+	movb	%dl, (%ecx)
+	testl	%eax, %eax
+	jnz	.L2
+	movl	%edi, %edx
+	subl	%ecx, %edx
+	incl	%edx
+	movl	$1, %ebx
+	movl	$4, %eax
+	int	$0x80
+	addl	$16, %esp
+	ret
+fn_0:					# main
+	pushl	$10			# Op::PushInt(10)
+	pushl	$5			# Op::PushInt(5)
+	popl	%ebx			# Op::DivMod
+	popl	%eax
+	xorl	%edx, %edx
+	divl	%ebx
+	pushl	%eax
+	pushl	%edx
+	addl	$4, %esp		# Op::Drop
+	popl	%eax			# Op::Print
+	call	print
+	pushl	$10			# Op::PushInt(10)
+	pushl	$5			# Op::PushInt(5)
+	popl	%ebx			# Op::DivMod
+	popl	%eax
+	xorl	%edx, %edx
+	divl	%ebx
+	pushl	%eax
+	pushl	%edx
+	popl	%eax			# Op::Swap
+	popl	%ebx
+	pushl	%eax
+	pushl	%ebx
+	addl	$4, %esp		# Op::Drop
+	popl	%eax			# Op::Print
+	call	print
+	pushl	$10			# Op::PushInt(10)
+	pushl	$4			# Op::PushInt(4)
+	popl	%ebx			# Op::DivMod
+	popl	%eax
+	xorl	%edx, %edx
+	divl	%ebx
+	pushl	%eax
+	pushl	%edx
+	popl	%eax			# Op::Print
+	call	print
+	popl	%eax			# Op::Print
+	call	print
+	movl	ret_stack_rsp, %eax	# Op::Ret(0)
+	jmp	*(%eax)
+main:
+	movl	$ret_stack_end, %eax
+	subl	$4, %eax
+	movl	%eax, ret_stack_rsp
+	movl	$RET_MAIN, (%eax)
+	jmp	fn_0
+RET_MAIN:
+	movl	$1, %eax
+	xorl	%ebx, %ebx
+	int	$0x80
+	.section	.rodata
+	.bss
+	.balign	4
+ret_stack_rsp:	.skip	4
+ret_stack:	.skip	65536
+ret_stack_end:
@@ -0,0 +1,138 @@
+	.globl	main
+	.text
+print:
+	subl	$16, %esp
+	leal	15(%esp), %edi
+	movb	$10, (%edi)
+	movl	%edi, %ecx
+	movl	$10, %ebx
+.L2:
+	xorl	%edx, %edx
+	divl	%ebx
+	addb	$48, %dl
+	decl	%ecx
+	movb	%dl, (%ecx)
+	testl	%eax, %eax
+	jnz	.L2
+	movl	%edi, %edx
+	subl	%ecx, %edx
+	incl	%edx
+	movl	$1, %ebx
+	movl	$4, %eax
+	int	$0x80
+	addl	$16, %esp
+	ret
+fn_0:					# main
+	pushl	$100			# Op::PushInt(100)
+F1:					# Op::While
+	pushl	$1			# Op::PushInt(1)
+	popl	%eax			# Op::Minus
+	subl	%eax, (%esp)
+	pushl	(%esp)			# Op::Dup
+	pushl	$0			# Op::PushInt(0)
+	popl	%eax			# Op::GreaterThan
+	popl	%ebx
+	xorl	%ecx, %ecx
+	cmpl	%eax, %ebx
+	seta	%cl
+	pushl	%ecx
+	popl	%eax
+	cmpl	$1, %eax
+	jne	F2
+	pushl	$0			# Op::PushInt(0)
+F9:					# Op::While
+	pushl	$1			# Op::PushInt(1)
+	popl	%eax			# Op::Plus
+	addl	%eax, (%esp)
+	pushl	(%esp)			# Op::Dup
+	pushl	$5			# Op::PushInt(5)
+	popl	%eax			# Op::LessThan
+	popl	%ebx
+	xorl	%ecx, %ecx
+	cmpl	%eax, %ebx
+	setb	%cl
+	pushl	%ecx
+	popl	%eax
+	cmpl	$1, %eax
+	jne	F10
+	pushl	$8			# Op::PushInt(8)
+	popl	%eax			# Op::Print
+	call	print
+	jmp	F9
+F10:
+	addl	$4, %esp		# Op::Drop
+	pushl	$50			# Op::PushInt(50)
+	pushl	$1			# Op::PushInt(1)
+	popl	%eax			# Op::Plus
+	addl	%eax, (%esp)
+	pushl	$78			# Op::PushInt(78)
+	pushl	$27			# Op::PushInt(27)
+	popl	%eax			# Op::Minus
+	subl	%eax, (%esp)
+	popl	%eax			# Op::Equals
+	popl	%ebx
+	xorl	%ecx, %ecx
+	cmpl	%eax, %ebx
+	sete	%cl
+	pushl	%ecx
+	pushl	$1			# Op::PushInt(1)
+	popl	%eax			# Op::And
+	popl	%ebx
+	xorl	%ecx, %ecx
+	xorl	%edx, %edx
+	cmpl	$1, %eax
+	sete	%cl
+	cmpl	$1, %ebx
+	sete	%dl
+	andb	%dl, %cl
+	pushl	%ecx
+	popl	%eax			# Op::If
+	cmpl	$1, %eax
+	jne	F29
+	pushl	$0			# Op::PushInt(0)
+	pushl	$8			# Op::PushInt(8)
+	pushl	$3			# Op::PushInt(3)
+	popl	%eax			# Op::GreaterThan
+	popl	%ebx
+	xorl	%ecx, %ecx
+	cmpl	%eax, %ebx
+	seta	%cl
+	pushl	%ecx
+	popl	%eax			# Op::Or
+	popl	%ebx
+	xorl	%ecx, %ecx
+	xorl	%edx, %edx
+	cmpl	$1, %eax
+	sete	%cl
+	cmpl	$1, %ebx
+	sete	%dl
+	orb	%dl, %cl
+	pushl	%ecx
+	popl	%eax			# Op::If
+	cmpl	$1, %eax
+	jne	F35
+	pushl	$42			# Op::PushInt(42)
+	popl	%eax			# Op::Print
+	call	print
+F35:
+F29:
+	jmp	F1
+F2:
+	movl	ret_stack_rsp, %eax	# Op::Ret(0)
+	jmp	*(%eax)
+main:
+	movl	$ret_stack_end, %eax
+	subl	$4, %eax
+	movl	%eax, ret_stack_rsp
+	movl	$RET_MAIN, (%eax)
+	jmp	fn_0
+RET_MAIN:
+	movl	$1, %eax
+	xorl	%ebx, %ebx
+	int	$0x80
+	.section	.rodata
+	.bss
+	.balign	4
+ret_stack_rsp:	.skip	4
+ret_stack:	.skip	65536
+ret_stack_end:
@@ -0,0 +1,85 @@
+	.globl	main
+	.text
+print:
+	subl	$16, %esp
+	leal	15(%esp), %edi
+	movb	$10, (%edi)
+	movl	%edi, %ecx
+	movl	$10, %ebx
+.L2:
+	xorl	%edx, %edx
+	divl	%ebx
+	addb	$48, %dl
+	decl	%ecx
+	movb	%dl, (%ecx)
+	testl	%eax, %eax
+	jnz	.L2
+	movl	%edi, %edx
+	subl	%ecx, %edx
+	incl	%edx
+	movl	$1, %ebx
+	movl	$4, %eax
+	int	$0x80
+	addl	$16, %esp
+	ret
+fn_0:					# foo
+	pushl	$18			# Op::PushInt(18)
+	popl	%eax			# Op::Equals
+	popl	%ebx
+	xorl	%ecx, %ecx
+	cmpl	%eax, %ebx
+	sete	%cl
+	pushl	%ecx
+	popl	%eax			# Op::If
+	cmpl	$1, %eax
+	jne	F2
+	pushl	$0			# Op::PushInt(0)
+	movl	ret_stack_rsp, %eax	# Op::Ret(0)
+	jmp	*(%eax)
+F2:
+	pushl	$1			# Op::PushInt(1)
+	movl	ret_stack_rsp, %eax	# Op::Ret(0)
+	jmp	*(%eax)
+fn_1:					# main
+	pushl	$1			# Op::PushInt(1)
+	movl	ret_stack_rsp, %eax	# Op::CallFn(0)
+	subl	$4, %eax
+	movl	%eax, ret_stack_rsp
+	movl	$RET9, (%eax)
+	jmp	fn_0
+RET9:
+	movl	ret_stack_rsp, %eax
+	addl	$4, %eax
+	movl	%eax, ret_stack_rsp
+	popl	%eax			# Op::Print
+	call	print
+	pushl	$18			# Op::PushInt(18)
+	movl	ret_stack_rsp, %eax	# Op::CallFn(0)
+	subl	$4, %eax
+	movl	%eax, ret_stack_rsp
+	movl	$RET12, (%eax)
+	jmp	fn_0
+RET12:
+	movl	ret_stack_rsp, %eax
+	addl	$4, %eax
+	movl	%eax, ret_stack_rsp
+	popl	%eax			# Op::Print
+	call	print
+	movl	ret_stack_rsp, %eax	# Op::Ret(0)
+	jmp	*(%eax)
+main:
+	movl	$ret_stack_end, %eax
+	subl	$4, %eax
+	movl	%eax, ret_stack_rsp
+	movl	$RET_MAIN, (%eax)
+	jmp	fn_1
+RET_MAIN:
+	movl	$1, %eax
+	xorl	%ebx, %ebx
+	int	$0x80
+	.section	.rodata
+	.bss
+	.balign	4
+ret_stack_rsp:	.skip	4
+ret_stack:	.skip	65536
+ret_stack_end:
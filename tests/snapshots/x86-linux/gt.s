@@ -0,0 +1,93 @@
+	.globl	main
+	.text
+print:
+	subl	$16, %esp
+	leal	15(%esp), %edi
+	movb	$10, (%edi)
+	movl	%edi, %ecx
+	movl	$10, %ebx
+.L2:
+	xorl	%edx, %edx
+	divl	%ebx
+	addb	$48, %dl
+	decl	%ecx
+	movb	%dl, (%ecx)
+	testl	%eax, %eax
+	jnz	.L2
+	movl	%edi, %edx
+	subl	%ecx, %edx
+	incl	%edx
+	movl	$1, %ebx
+	movl	$4, %eax
+	int	$0x80
+	addl	$16, %esp
+	ret
+fn_0:					# main
+	pushl	$1			# Op::PushInt(1)
+	pushl	$2			# Op::PushInt(2)
+	popl	%eax			# Op::GreaterThan
+	popl	%ebx
+	xorl	%ecx, %ecx
+	cmpl	%eax, %ebx
+	seta	%cl
+	pushl	%ecx
+	popl	%eax			# Op::If
+	cmpl	$1, %eax
+	jne	F3
+	pushl	$0			# Op::PushInt(0)
+	popl	%eax			# Op::Print
+	call	print
+F3:
+	pushl	$2			# Op::PushInt(2)
+	pushl	$1			# Op::PushInt(1)
+	popl	%eax			# Op::GreaterThan
+	popl	%ebx
+	xorl	%ecx, %ecx
+	cmpl	%eax, %ebx
+	seta	%cl
+	pushl	%ecx
+	popl	%eax			# Op::If
+	cmpl	$1, %eax
+	jne	F10
+	pushl	$9			# Op::PushInt(9)
+	pushl	$str_0		# Op::PushStrPtr(0)
+	movl	$4, %eax		# Op::Puts
+	movl	$1, %ebx
+	popl	%ecx
+	popl	%edx
+	int	$0x80
+F10:
+	pushl	$2			# Op::PushInt(2)
+	pushl	$2			# Op::PushInt(2)
+	popl	%eax			# Op::GreaterThan
+	popl	%ebx
+	xorl	%ecx, %ecx
+	cmpl	%eax, %ebx
+	seta	%cl
+	pushl	%ecx
+	popl	%eax			# Op::If
+	cmpl	$1, %eax
+	jne	F18
+	pushl	$0			# Op::PushInt(0)
+	popl	%eax			# Op::Print
+	call	print
+F18:
+	movl	ret_stack_rsp, %eax	# Op::Ret(0)
+	jmp	*(%eax)
+main:
+	movl	$ret_stack_end, %eax
+	subl	$4, %eax
+	movl	%eax, ret_stack_rsp
+	movl	$RET_MAIN, (%eax)
+	jmp	fn_0
+RET_MAIN:
+	movl	$1, %eax
+	xorl	%ebx, %ebx
+	int	$0x80
+	.section	.rodata
+str_0:	.byte	115,117,99,99,101,115,115,33,10
+	.bss
+	.balign	4
+ret_stack_rsp:	.skip	4
+ret_stack:	.skip	65536
+ret_stack_end:
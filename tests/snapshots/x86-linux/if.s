@@ -0,0 +1,111 @@
+	.globl	main
+	.text
+print:
+	subl	$16, %esp
+	leal	15(%esp), %edi
+	movb	$10, (%edi)
+	movl	%edi, %ecx
+	movl	$10, %ebx
+.L2:
+	xorl	%edx, %edx
+	divl	%ebx
+	addb	$48, %dl
+	decl	%ecx
+	movb	%dl, (%ecx)
+	testl	%eax, %eax
+	jnz	.L2
+	movl	%edi, %edx
+	subl	%ecx, %edx
+	incl	%edx
+	movl	$1, %ebx
+	movl	$4, %eax
+	int	$0x80
+	addl	$16, %esp
+	ret
+fn_0:					# main
+	pushl	$50			# Op::PushInt(50)
+	pushl	$8			# Op::PushInt(8)
+	popl	%eax			# Op::Minus
+	subl	%eax, (%esp)
+	pushl	$42			# Op::PushInt(42)
+	popl	%eax			# Op::Equals
+	popl	%ebx
+	xorl	%ecx, %ecx
+	cmpl	%eax, %ebx
+	sete	%cl
+	pushl	%ecx
+	popl	%eax			# Op::If
+	cmpl	$1, %eax
+	jne	F5
+	pushl	$5			# Op::PushInt(5)
+	pushl	$str_0		# Op::PushStrPtr(0)
+	movl	$4, %eax		# Op::Puts
+	movl	$1, %ebx
+	popl	%ecx
+	popl	%edx
+	int	$0x80
+	pushl	$1			# Op::PushInt(1)
+	popl	%eax			# Op::If
+	cmpl	$1, %eax
+	jne	F10
+	pushl	$0			# Op::PushInt(0)
+	popl	%eax			# Op::If
+	cmpl	$1, %eax
+	jne	F12
+	pushl	$6			# Op::PushInt(6)
+	pushl	$str_1		# Op::PushStrPtr(1)
+	movl	$4, %eax		# Op::Puts
+	movl	$1, %ebx
+	popl	%ecx
+	popl	%edx
+	int	$0x80
+F12:
+	pushl	$10			# Op::PushInt(10)
+	pushl	$str_2		# Op::PushStrPtr(2)
+	movl	$4, %eax		# Op::Puts
+	movl	$1, %ebx
+	popl	%ecx
+	popl	%edx
+	int	$0x80
+F10:
+	pushl	$8			# Op::PushInt(8)
+	pushl	$2			# Op::PushInt(2)
+	popl	%eax			# Op::Equals
+	popl	%ebx
+	xorl	%ecx, %ecx
+	cmpl	%eax, %ebx
+	sete	%cl
+	pushl	%ecx
+	popl	%eax			# Op::If
+	cmpl	$1, %eax
+	jne	F24
+	pushl	$6			# Op::PushInt(6)
+	pushl	$str_1		# Op::PushStrPtr(1)
+	movl	$4, %eax		# Op::Puts
+	movl	$1, %ebx
+	popl	%ecx
+	popl	%edx
+	int	$0x80
+F24:
+F5:
+	movl	ret_stack_rsp, %eax	# Op::Ret(0)
+	jmp	*(%eax)
+main:
+	movl	$ret_stack_end, %eax
+	subl	$4, %eax
+	movl	%eax, ret_stack_rsp
+	movl	$RET_MAIN, (%eax)
+	jmp	fn_0
+RET_MAIN:
+	movl	$1, %eax
+	xorl	%ebx, %ebx
+	int	$0x80
+	.section	.rodata
+str_0:	.byte	116,114,117,101,10
+str_1:	.byte	102,97,108,115,101,10
+str_2:	.byte	97,108,115,111,32,116,114,117,101,10
+	.bss
+	.balign	4
+ret_stack_rsp:	.skip	4
+ret_stack:	.skip	65536
+ret_stack_end:
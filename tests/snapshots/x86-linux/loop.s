@@ -0,0 +1,119 @@
+	.globl	main
+	.text
+print:
+	subl	$16, %esp
+	leal	15(%esp), %edi
+	movb	$10, (%edi)
+	movl	%edi, %ecx
+	movl	$10, %ebx
+.L2:
+	xorl	%edx, %edx
+	divl	%ebx
+	addb	$48, %dl
+	decl	%ecx
+	movb	%dl, (%ecx)
+	testl	%eax, %eax
+	jnz	.L2
+	movl	%edi, %edx
+	subl	%ecx, %edx
+	incl	%edx
+	movl	$1, %ebx
+	movl	$4, %eax
+	int	$0x80
+	addl	$16, %esp
+	ret
+fn_0:					# main
+	pushl	$0			# Op::PushInt(0)
+F1:					# Op::While
+	pushl	$1			# Op::PushInt(1)
+	popl	%eax			# Op::Plus
+	addl	%eax, (%esp)
+	pushl	(%esp)			# Op::Dup
+	pushl	$101			# Op::PushInt(101)
+	popl	%eax			# Op::LessThan
+	popl	%ebx
+	xorl	%ecx, %ecx
+	cmpl	%eax, %ebx
+	setb	%cl
+	pushl	%ecx
+	popl	%eax
+	cmpl	$1, %eax
+	jne	F2
+	pushl	(%esp)			# Op::Dup
+	popl	%eax			# Op::Print
+	call	print
+	pushl	(%esp)			# Op::Dup
+	pushl	$69			# Op::PushInt(69)
+	popl	%eax			# Op::Equals
+	popl	%ebx
+	xorl	%ecx, %ecx
+	cmpl	%eax, %ebx
+	sete	%cl
+	pushl	%ecx
+	popl	%eax			# Op::If
+	cmpl	$1, %eax
+	jne	F13
+	pushl	$420			# Op::PushInt(420)
+	popl	%eax			# Op::Print
+	call	print
+F13:
+	jmp	F1
+F2:
+	pushl	$0			# Op::PushInt(0)
+F19:					# Op::While
+	pushl	$1			# Op::PushInt(1)
+	pushl	$1			# Op::PushInt(1)
+	popl	%eax			# Op::And
+	popl	%ebx
+	xorl	%ecx, %ecx
+	xorl	%edx, %edx
+	cmpl	$1, %eax
+	sete	%cl
+	cmpl	$1, %ebx
+	sete	%dl
+	andb	%dl, %cl
+	pushl	%ecx
+	popl	%eax			# Op::If
+	cmpl	$1, %eax
+	jne	F24
+	pushl	$0			# Op::PushInt(0)
+	popl	%eax			# Op::Print
+	call	print
+F24:
+	pushl	$1			# Op::PushInt(1)
+	popl	%eax			# Op::Plus
+	addl	%eax, (%esp)
+	pushl	(%esp)			# Op::Dup
+	pushl	$101			# Op::PushInt(101)
+	popl	%eax			# Op::LessThan
+	popl	%ebx
+	xorl	%ecx, %ecx
+	cmpl	%eax, %ebx
+	setb	%cl
+	pushl	%ecx
+	popl	%eax
+	cmpl	$1, %eax
+	jne	F20
+	pushl	$1			# Op::PushInt(1)
+	popl	%eax			# Op::Print
+	call	print
+	jmp	F19
+F20:
+	movl	ret_stack_rsp, %eax	# Op::Ret(0)
+	jmp	*(%eax)
+main:
+	movl	$ret_stack_end, %eax
+	subl	$4, %eax
+	movl	%eax, ret_stack_rsp
+	movl	$RET_MAIN, (%eax)
+	jmp	fn_0
+RET_MAIN:
+	movl	$1, %eax
+	xorl	%ebx, %ebx
+	int	$0x80
+	.section	.rodata
+	.bss
+	.balign	4
+ret_stack_rsp:	.skip	4
+ret_stack:	.skip	65536
+ret_stack_end:
@@ -0,0 +1,113 @@
+	.globl	main
+	.text
+print:
+	subl	$16, %esp
+	leal	15(%esp), %edi
+	movb	$10, (%edi)
+	movl	%edi, %ecx
+	movl	$10, %ebx
+.L2:
+	xorl	%edx, %edx
+	divl	%ebx
+	addb	$48, %dl
+	decl	%ecx
+	movb	%dl, (%ecx)
+	testl	%eax, %eax
+	jnz	.L2
+	movl	%edi, %edx
+	subl	%ecx, %edx
+	incl	%edx
+	movl	$1, %ebx
+	movl	$4, %eax
+	int	$0x80
+	addl	$16, %esp
+	ret
+fn_0:					# main
+	pushl	$0			# Op::PushInt(0)
+	pushl	$0			# Op::PushInt(0)
+	popl	%eax			# Op::Neq
+	popl	%ebx
+	xorl	%ecx, %ecx
+	cmpl	%eax, %ebx
+	setne	%cl
+	pushl	%ecx
+	popl	%eax			# Op::Print
+	call	print
+	pushl	$0			# Op::PushInt(0)
+	pushl	$1			# Op::PushInt(1)
+	popl	%eax			# Op::Neq
+	popl	%ebx
+	xorl	%ecx, %ecx
+	cmpl	%eax, %ebx
+	setne	%cl
+	pushl	%ecx
+	popl	%eax			# Op::Print
+	call	print
+	pushl	$1			# Op::PushInt(1)
+	pushl	$0			# Op::PushInt(0)
+	popl	%eax			# Op::Neq
+	popl	%ebx
+	xorl	%ecx, %ecx
+	cmpl	%eax, %ebx
+	setne	%cl
+	pushl	%ecx
+	popl	%eax			# Op::Print
+	call	print
+	pushl	$1			# Op::PushInt(1)
+	pushl	$1			# Op::PushInt(1)
+	popl	%eax			# Op::Neq
+	popl	%ebx
+	xorl	%ecx, %ecx
+	cmpl	%eax, %ebx
+	setne	%cl
+	pushl	%ecx
+	popl	%eax			# Op::Print
+	call	print
+	pushl	$5			# Op::PushInt(5)
+	pushl	$5			# Op::PushInt(5)
+	popl	%eax			# Op::Neq
+	popl	%ebx
+	xorl	%ecx, %ecx
+	cmpl	%eax, %ebx
+	setne	%cl
+	pushl	%ecx
+	popl	%eax			# Op::If
+	cmpl	$1, %eax
+	jne	F19
+	pushl	$13			# Op::PushInt(13)
+	popl	%eax			# Op::Print
+	call	print
+F19:
+	pushl	$5			# Op::PushInt(5)
+	pushl	$4			# Op::PushInt(4)
+	popl	%eax			# Op::Neq
+	popl	%ebx
+	xorl	%ecx, %ecx
+	cmpl	%eax, %ebx
+	setne	%cl
+	pushl	%ecx
+	popl	%eax			# Op::If
+	cmpl	$1, %eax
+	jne	F26
+	pushl	$100			# Op::PushInt(100)
+	popl	%eax			# Op::Print
+	call	print
+F26:
+	movl	ret_stack_rsp, %eax	# Op::Ret(0)
+	jmp	*(%eax)
+main:
+	movl	$ret_stack_end, %eax
+	subl	$4, %eax
+	movl	%eax, ret_stack_rsp
+	movl	$RET_MAIN, (%eax)
+	jmp	fn_0
+RET_MAIN:
+	movl	$1, %eax
+	xorl	%ebx, %ebx
+	int	$0x80
+	.section	.rodata
+	.bss
+	.balign	4
+ret_stack_rsp:	.skip	4
+ret_stack:	.skip	65536
+ret_stack_end:
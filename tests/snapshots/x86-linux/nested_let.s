@@ -0,0 +1,131 @@
+	.globl	main
+	.text
+print:
+	subl	$16, %esp
+	leal	15(%esp), %edi
+	movb	$10, (%edi)
+	movl	%edi, %ecx
+	movl	$10, %ebx
+.L2:
+	xorl	%edx, %edx
+	divl	%ebx
+	addb	$48, %dl
+	decl	%ecx
+	movb	%dl, (%ecx)
+	testl	%eax, %eax
+	jnz	.L2
+	movl	%edi, %edx
+	subl	%ecx, %edx
+	incl	%edx
+	movl	$1, %ebx
+	movl	$4, %eax
+	int	$0x80
+	addl	$16, %esp
+	ret
+fn_0:					# main
+	pushl	$1			# Op::PushInt(1)
+	pushl	$2			# Op::PushInt(2)
+	movl	ret_stack_rsp, %eax	# Op::Bind(2)
+	subl	$8, %eax
+	movl	%eax, ret_stack_rsp
+	movl	0(%esp), %ebx
+	movl	%ebx, 0(%eax)
+	movl	4(%esp), %ebx
+	movl	%ebx, 4(%eax)
+	addl	$8, %esp
+	movl	ret_stack_rsp, %eax	# Op::PushBind(1)
+	pushl	4(%eax)
+	popl	%eax			# Op::Print
+	call	print
+	movl	ret_stack_rsp, %eax	# Op::PushBind(0)
+	pushl	0(%eax)
+	popl	%eax			# Op::Print
+	call	print
+	pushl	$3			# Op::PushInt(3)
+	pushl	$4			# Op::PushInt(4)
+	movl	ret_stack_rsp, %eax	# Op::Bind(2)
+	subl	$8, %eax
+	movl	%eax, ret_stack_rsp
+	movl	0(%esp), %ebx
+	movl	%ebx, 0(%eax)
+	movl	4(%esp), %ebx
+	movl	%ebx, 4(%eax)
+	addl	$8, %esp
+	movl	ret_stack_rsp, %eax	# Op::PushBind(1)
+	pushl	4(%eax)
+	popl	%eax			# Op::Print
+	call	print
+	movl	ret_stack_rsp, %eax	# Op::PushBind(0)
+	pushl	0(%eax)
+	popl	%eax			# Op::Print
+	call	print
+	movl	ret_stack_rsp, %eax
+	addl	$8, %eax
+	movl	%eax, ret_stack_rsp
+	movl	ret_stack_rsp, %eax	# Op::PushBind(1)
+	pushl	4(%eax)
+	popl	%eax			# Op::Print
+	call	print
+	movl	ret_stack_rsp, %eax
+	addl	$8, %eax
+	movl	%eax, ret_stack_rsp
+	pushl	$5			# Op::PushInt(5)
+	pushl	$6			# Op::PushInt(6)
+	movl	ret_stack_rsp, %eax	# Op::Bind(2)
+	subl	$8, %eax
+	movl	%eax, ret_stack_rsp
+	movl	0(%esp), %ebx
+	movl	%ebx, 0(%eax)
+	movl	4(%esp), %ebx
+	movl	%ebx, 4(%eax)
+	addl	$8, %esp
+	movl	ret_stack_rsp, %eax	# Op::PushBind(1)
+	pushl	4(%eax)
+	popl	%eax			# Op::Print
+	call	print
+	movl	ret_stack_rsp, %eax	# Op::PushBind(0)
+	pushl	0(%eax)
+	popl	%eax			# Op::Print
+	call	print
+	movl	ret_stack_rsp, %eax
+	addl	$8, %eax
+	movl	%eax, ret_stack_rsp
+	pushl	$7			# Op::PushInt(7)
+	pushl	$8			# Op::PushInt(8)
+	movl	ret_stack_rsp, %eax	# Op::Bind(2)
+	subl	$8, %eax
+	movl	%eax, ret_stack_rsp
+	movl	0(%esp), %ebx
+	movl	%ebx, 0(%eax)
+	movl	4(%esp), %ebx
+	movl	%ebx, 4(%eax)
+	addl	$8, %esp
+	movl	ret_stack_rsp, %eax	# Op::PushBind(1)
+	pushl	4(%eax)
+	popl	%eax			# Op::Print
+	call	print
+	movl	ret_stack_rsp, %eax	# Op::PushBind(0)
+	pushl	0(%eax)
+	popl	%eax			# Op::Print
+	call	print
+	movl	ret_stack_rsp, %eax
+	addl	$8, %eax
+	movl	%eax, ret_stack_rsp
+	movl	ret_stack_rsp, %eax	# Op::Ret(0)
+	jmp	*(%eax)
+main:
+	movl	$ret_stack_end, %eax
+	subl	$4, %eax
+	movl	%eax, ret_stack_rsp
+	movl	$RET_MAIN, (%eax)
+	jmp	fn_0
+RET_MAIN:
+	movl	$1, %eax
+	xorl	%ebx, %ebx
+	int	$0x80
+	.section	.rodata
+	.bss
+	.balign	4
+ret_stack_rsp:	.skip	4
+ret_stack:	.skip	65536
+ret_stack_end:
@@ -0,0 +1,79 @@
+	.globl	main
+	.text
+print:
+	subl	$16, %esp
+	leal	15(%esp), %edi
+	movb	$10, (%edi)
+	movl	%edi, %ecx
+	movl	$10, %ebx
+.L2:
+	xorl	%edx, %edx
+	divl	%ebx
+	addb	$48, %dl
+	decl	%ecx
+	movb	%dl, (%ecx)
+	testl	%eax, %eax
+	jnz	.L2
+	movl	%edi, %edx
+	subl	%ecx, %edx
+	incl	%edx
+	movl	$1, %ebx
+	movl	$4, %eax
+	int	$0x80
+	addl	$16, %esp
+	ret
+fn_0:					# main
+	pushl	$4			# Op::PushInt(4)
+	pushl	$str_0		# Op::PushStrPtr(0)
+	pushl	(%esp)			# Op::Dup
+	popl	%ebx			# Op::ReadByte
+	movzbl	(%ebx), %eax
+	pushl	%eax
+	popl	%eax			# Op::Print
+	call	print
+	pushl	(%esp)			# Op::Dup
+	pushl	$1			# Op::PushInt(1)
+	popl	%eax			# Op::Plus
+	addl	%eax, (%esp)
+	popl	%ebx			# Op::ReadByte
+	movzbl	(%ebx), %eax
+	pushl	%eax
+	popl	%eax			# Op::Print
+	call	print
+	pushl	(%esp)			# Op::Dup
+	pushl	$2			# Op::PushInt(2)
+	popl	%eax			# Op::Plus
+	addl	%eax, (%esp)
+	popl	%ebx			# Op::ReadByte
+	movzbl	(%ebx), %eax
+	pushl	%eax
+	popl	%eax			# Op::Print
+	call	print
+	pushl	(%esp)			# Op::Dup
+	pushl	$3			# Op::PushInt(3)
+	popl	%eax			# Op::Plus
+	addl	%eax, (%esp)
+	popl	%ebx			# Op::ReadByte
+	movzbl	(%ebx), %eax
+	pushl	%eax
+	popl	%eax			# Op::Print
+	call	print
+	movl	ret_stack_rsp, %eax	# Op::Ret(0)
+	jmp	*(%eax)
+main:
+	movl	$ret_stack_end, %eax
+	subl	$4, %eax
+	movl	%eax, ret_stack_rsp
+	movl	$RET_MAIN, (%eax)
+	jmp	fn_0
+RET_MAIN:
+	movl	$1, %eax
+	xorl	%ebx, %ebx
+	int	$0x80
+	.section	.rodata
+str_0:	.byte	116,101,115,116
+	.bss
+	.balign	4
+ret_stack_rsp:	.skip	4
+ret_stack:	.skip	65536
+ret_stack_end:
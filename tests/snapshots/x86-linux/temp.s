@@ -0,0 +1,95 @@
+	.globl	main
+	.text
+print:
+	subl	$16, %esp
+	leal	15(%esp), %edi
+	movb	$10, (%edi)
+	movl	%edi, %ecx
+	movl	$10, %ebx
+.L2:
+	xorl	%edx, %edx
+	divl	%ebx
+	addb	$48, %dl
+	decl	%ecx
+	movb	%dl, (%ecx)
+	testl	%eax, %eax
+	jnz	.L2
+	movl	%edi, %edx
+	subl	%ecx, %edx
+	incl	%edx
+	movl	$1, %ebx
+	movl	$4, %eax
+	int	$0x80
+	addl	$16, %esp
+	ret
+fn_0:					# streq
+	movl	ret_stack_rsp, %eax	# Op::Bind(4)
+	subl	$16, %eax
+	movl	%eax, ret_stack_rsp
+	movl	0(%esp), %ebx
+	movl	%ebx, 0(%eax)
+	movl	4(%esp), %ebx
+	movl	%ebx, 4(%eax)
+	movl	8(%esp), %ebx
+	movl	%ebx, 8(%eax)
+	movl	12(%esp), %ebx
+	movl	%ebx, 12(%eax)
+	addl	$16, %esp
+	pushl	$0			# Op::PushInt(0)
+	pushl	$1			# Op::PushInt(1)
+	popl	%eax			# Op::Minus
+	subl	%eax, (%esp)
+	popl	%eax			# Op::Print
+	call	print
+	movl	ret_stack_rsp, %eax
+	addl	$16, %eax
+	movl	%eax, ret_stack_rsp
+	movl	ret_stack_rsp, %eax	# Op::Ret(0)
+	jmp	*(%eax)
+fn_1:					# putlns
+	movl	$4, %eax		# Op::Puts
+	movl	$1, %ebx
+	popl	%ecx
+	popl	%edx
+	int	$0x80
+	pushl	$1			# Op::PushInt(1)
+	pushl	$str_0		# Op::PushStrPtr(0)
+	movl	$4, %eax		# Op::Puts
+	movl	$1, %ebx
+	popl	%ecx
+	popl	%edx
+	int	$0x80
+	movl	ret_stack_rsp, %eax	# Op::Ret(0)
+	jmp	*(%eax)
+fn_2:					# main
+	pushl	$12			# Op::PushInt(12)
+	pushl	$str_1		# Op::PushStrPtr(1)
+	movl	ret_stack_rsp, %eax	# Op::CallFn(1)
+	subl	$4, %eax
+	movl	%eax, ret_stack_rsp
+	movl	$RET13, (%eax)
+	jmp	fn_1
+RET13:
+	movl	ret_stack_rsp, %eax
+	addl	$4, %eax
+	movl	%eax, ret_stack_rsp
+	movl	ret_stack_rsp, %eax	# Op::Ret(0)
+	jmp	*(%eax)
+main:
+	movl	$ret_stack_end, %eax
+	subl	$4, %eax
+	movl	%eax, ret_stack_rsp
+	movl	$RET_MAIN, (%eax)
+	jmp	fn_2
+RET_MAIN:
+	movl	$1, %eax
+	xorl	%ebx, %ebx
+	int	$0x80
+	.section	.rodata
+str_0:	.byte	10
+str_1:	.byte	104,101,108,108,111,44,32,119,111,114,108,100
+	.bss
+	.balign	4
+ret_stack_rsp:	.skip	4
+ret_stack:	.skip	65536
+ret_stack_end:
@@ -0,0 +1,172 @@
+	.globl	main
+	.text
+write:
+	movq	$1, %rax
+	movq	$1, %rdi
+	syscall
+	ret
+print:
+	movabsq	$-3689348814741910323, %r9
+	subq	$40, %rsp
+	movb	$10, 31(%rsp)
+	leaq	30(%rsp), %rcx
+.L2:
+	movq	%rdi, %rax
+	leaq	32(%rsp), %r8
+	mulq	%r9
+	movq	%rdi, %rax
+	subq	%rcx, %r8
+	shrq	$3, %rdx
+	leaq	(%rdx,%rdx,4), %rsi
+	addq	%rsi, %rsi
+	subq	%rsi, %rax
+	addl	$48, %eax
+	movb	%al, (%rcx)
+	movq	%rdi, %rax
+	movq	%rdx, %rdi
+	movq	%rcx, %rdx
+	subq	$1, %rcx
+	cmpq	$9, %rax
+	ja	.L2
+	leaq	32(%rsp), %rax
+	subq	%rax, %rdx
+	leaq	32(%rsp,%rdx), %rsi
+	movq	%r8, %rdx
+	call	write
+	addq	$40, %rsp
+	ret
+fn_main:
+	pushq	$100			# Op::PushInt(100)
+F1:					# Op::While
+	pushq	$1			# Op::PushInt(1)
+	popq	%rbx			# Op::Minus
+	popq	%rax
+	subq	%rbx, %rax
+	pushq	%rax
+	pushq	(%rsp)			# Op::Dup
+	pushq	$0			# Op::PushInt(0)
+	popq	%rax			# Op::GreaterThan
+	popq	%rbx
+	cmpq	%rax, %rbx
+	ja	J7
+	pushq	$0
+	jmp	J8
+J7:
+	pushq	$1
+J8:
+	popq	%rax
+	cmpq	$1, %rax
+	jne	F2
+	pushq	$0			# Op::PushInt(0)
+F10:					# Op::While
+	pushq	$1			# Op::PushInt(1)
+	popq	%rax			# Op::Plus
+	popq	%rbx
+	addq	%rbx, %rax
+	pushq	%rax
+	pushq	(%rsp)			# Op::Dup
+	pushq	$5			# Op::PushInt(5)
+	popq	%rax			# Op::LessThan
+	popq	%rbx
+	cmpq	%rax, %rbx
+	jb	J16
+	pushq	$0
+	jmp	J17
+J16:
+	pushq	$1
+J17:
+	popq	%rax
+	cmpq	$1, %rax
+	jne	F11
+	pushq	$8			# Op::PushInt(8)
+	popq	%rdi			# Op::Print
+	call	print
+	jmp	F10
+F11:
+	addq	$8, %rsp		# Op::Drop
+	pushq	$50			# Op::PushInt(50)
+	pushq	$1			# Op::PushInt(1)
+	popq	%rax			# Op::Plus
+	popq	%rbx
+	addq	%rbx, %rax
+	pushq	%rax
+	pushq	$78			# Op::PushInt(78)
+	pushq	$27			# Op::PushInt(27)
+	popq	%rbx			# Op::Minus
+	popq	%rax
+	subq	%rbx, %rax
+	pushq	%rax
+	popq	%rax			# Op::Equals
+	popq	%rbx
+	cmpq	%rax, %rbx
+	je	J28
+	pushq	$0
+	jmp	J29
+J28:
+	pushq	$1
+J29:
+	pushq	$1			# Op::PushInt(1)
+	popq	%rax			# Op::And
+	popq	%rbx
+	cmpq	%rbx, %rax
+	jne	J31
+	cmpq	$1, %rax
+	jne	J31
+	pushq	$1
+	jmp	J32
+J31:
+	pushq	$0
+J32:
+	popq	%rax			# Op::If
+	cmpq	$1, %rax
+	jne	F33
+	pushq	$0			# Op::PushInt(0)
+	pushq	$8			# Op::PushInt(8)
+	pushq	$3			# Op::PushInt(3)
+	popq	%rax			# Op::GreaterThan
+	popq	%rbx
+	cmpq	%rax, %rbx
+	ja	J37
+	pushq	$0
+	jmp	J38
+J37:
+	pushq	$1
+J38:
+	popq	%rax			# Op::Or
+	popq	%rbx
+	cmpq	$1, %rax
+	je	J39
+	cmpq	$1, %rbx
+	je	J39
+	pushq	$0
+	jmp	J40
+J39:
+	pushq	$1
+J40:
+	popq	%rax			# Op::If
+	cmpq	$1, %rax
+	jne	F41
+	pushq	$42			# Op::PushInt(42)
+	popq	%rdi			# Op::Print
+	call	print
+F41:
+F33:
+	jmp	F1
+F2:
+	movq	ret_stack_rsp, %rax	# Op::Ret(0)
+	jmp	*(%rax)
+main:
+	movq	$ret_stack_end, %rax
+	subq	$8, %rax
+	movq	%rax, ret_stack_rsp
+	movq	$RET_MAIN, (%rax)
+	call	fn_main
+RET_MAIN:
+	movq	$60, %rax
+	movq	$0, %rdi
+	syscall
+	.section	.rodata
+	.bss
+ret_stack_rsp:	.skip	8
+ret_stack:	.skip	65536
+ret_stack_end:
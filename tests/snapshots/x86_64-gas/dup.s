@@ -0,0 +1,68 @@
+	.globl	main
+	.text
+write:
+	movq	$1, %rax
+	movq	$1, %rdi
+	syscall
+	ret
+print:
+	movabsq	$-3689348814741910323, %r9
+	subq	$40, %rsp
+	movb	$10, 31(%rsp)
+	leaq	30(%rsp), %rcx
+.L2:
+	movq	%rdi, %rax
+	leaq	32(%rsp), %r8
+	mulq	%r9
+	movq	%rdi, %rax
+	subq	%rcx, %r8
+	shrq	$3, %rdx
+	leaq	(%rdx,%rdx,4), %rsi
+	addq	%rsi, %rsi
+	subq	%rsi, %rax
+	addl	$48, %eax
+	movb	%al, (%rcx)
+	movq	%rdi, %rax
+	movq	%rdx, %rdi
+	movq	%rcx, %rdx
+	subq	$1, %rcx
+	cmpq	$9, %rax
+	ja	.L2
+	leaq	32(%rsp), %rax
+	subq	%rax, %rdx
+	leaq	32(%rsp,%rdx), %rsi
+	movq	%r8, %rdx
+	call	write
+	addq	$40, %rsp
+	ret
+fn_main:
+	pushq	$10			# Op::PushInt(10)
+	pushq	(%rsp)			# Op::Dup
+	popq	%rdi			# Op::Print
+	call	print
+	popq	%rdi			# Op::Print
+	call	print
+	movabsq	$18446744073709551615, %rax	# Op::PushInt(18446744073709551615)
+	pushq	%rax
+	pushq	(%rsp)			# Op::Dup
+	popq	%rdi			# Op::Print
+	call	print
+	popq	%rdi			# Op::Print
+	call	print
+	movq	ret_stack_rsp, %rax	# Op::Ret(0)
+	jmp	*(%rax)
+main:
+	movq	$ret_stack_end, %rax
+	subq	$8, %rax
+	movq	%rax, ret_stack_rsp
+	movq	$RET_MAIN, (%rax)
+	call	fn_main
+RET_MAIN:
+	movq	$60, %rax
+	movq	$0, %rdi
+	syscall
+	.section	.rodata
+	.bss
+ret_stack_rsp:	.skip	8
+ret_stack:	.skip	65536
+ret_stack_end:
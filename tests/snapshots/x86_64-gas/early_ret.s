@@ -0,0 +1,100 @@
+	.globl	main
+	.text
+write:
+	movq	$1, %rax
+	movq	$1, %rdi
+	syscall
+	ret
+print:
+	movabsq	$-3689348814741910323, %r9
+	subq	$40, %rsp
+	movb	$10, 31(%rsp)
+	leaq	30(%rsp), %rcx
+.L2:
+	movq	%rdi, %rax
+	leaq	32(%rsp), %r8
+	mulq	%r9
+	movq	%rdi, %rax
+	subq	%rcx, %r8
+	shrq	$3, %rdx
+	leaq	(%rdx,%rdx,4), %rsi
+	addq	%rsi, %rsi
+	subq	%rsi, %rax
+	addl	$48, %eax
+	movb	%al, (%rcx)
+	movq	%rdi, %rax
+	movq	%rdx, %rdi
+	movq	%rcx, %rdx
+	subq	$1, %rcx
+	cmpq	$9, %rax
+	ja	.L2
+	leaq	32(%rsp), %rax
+	subq	%rax, %rdx
+	leaq	32(%rsp,%rdx), %rsi
+	movq	%r8, %rdx
+	call	write
+	addq	$40, %rsp
+	ret
+fn_foo:
+	pushq	$18			# Op::PushInt(18)
+	popq	%rax			# Op::Equals
+	popq	%rbx
+	cmpq	%rax, %rbx
+	je	J1
+	pushq	$0
+	jmp	J2
+J1:
+	pushq	$1
+J2:
+	popq	%rax			# Op::If
+	cmpq	$1, %rax
+	jne	F3
+	pushq	$0			# Op::PushInt(0)
+	movq	ret_stack_rsp, %rax	# Op::Ret(0)
+	jmp	*(%rax)
+F3:
+	pushq	$1			# Op::PushInt(1)
+	movq	ret_stack_rsp, %rax	# Op::Ret(0)
+	jmp	*(%rax)
+fn_main:
+	pushq	$1			# Op::PushInt(1)
+	movq	ret_stack_rsp, %rax	# Op::CallFn(0)
+	subq	$8, %rax
+	movq	%rax, ret_stack_rsp
+	movq	$RET10, (%rax)
+	jmp	fn_foo
+RET10:
+	movq	ret_stack_rsp, %rax
+	addq	$8, %rax
+	movq	%rax, ret_stack_rsp
+	popq	%rdi			# Op::Print
+	call	print
+	pushq	$18			# Op::PushInt(18)
+	movq	ret_stack_rsp, %rax	# Op::CallFn(0)
+	subq	$8, %rax
+	movq	%rax, ret_stack_rsp
+	movq	$RET13, (%rax)
+	jmp	fn_foo
+RET13:
+	movq	ret_stack_rsp, %rax
+	addq	$8, %rax
+	movq	%rax, ret_stack_rsp
+	popq	%rdi			# Op::Print
+	call	print
+	movq	ret_stack_rsp, %rax	# Op::Ret(0)
+	jmp	*(%rax)
+main:
+	movq	$ret_stack_end, %rax
+	subq	$8, %rax
+	movq	%rax, ret_stack_rsp
+	movq	$RET_MAIN, (%rax)
+	call	fn_main
+RET_MAIN:
+	movq	$60, %rax
+	movq	$0, %rdi
+	syscall
+	.section	.rodata
+	.bss
+ret_stack_rsp:	.skip	8
+ret_stack:	.skip	65536
+ret_stack_end:
@@ -0,0 +1,123 @@
+	.globl	main
+	.text
+write:
+	movq	$1, %rax
+	movq	$1, %rdi
+	syscall
+	ret
+print:
+	movabsq	$-3689348814741910323, %r9
+	subq	$40, %rsp
+	movb	$10, 31(%rsp)
+	leaq	30(%rsp), %rcx
+.L2:
+	movq	%rdi, %rax
+	leaq	32(%rsp), %r8
+	mulq	%r9
+	movq	%rdi, %rax
+	subq	%rcx, %r8
+	shrq	$3, %rdx
+	leaq	(%rdx,%rdx,4), %rsi
+	addq	%rsi, %rsi
+	subq	%rsi, %rax
+	addl	$48, %eax
+	movb	%al, (%rcx)
+	movq	%rdi, %rax
+	movq	%rdx, %rdi
+	movq	%rcx, %rdx
+	subq	$1, %rcx
+	cmpq	$9, %rax
+	ja	.L2
+	leaq	32(%rsp), %rax
+	subq	%rax, %rdx
+	leaq	32(%rsp,%rdx), %rsi
+	movq	%r8, %rdx
+	call	write
+	addq	$40, %rsp
+	ret
+fn_main:
+	pushq	$50			# Op::PushInt(50)
+	pushq	$8			# Op::PushInt(8)
+	popq	%rbx			# Op::Minus
+	popq	%rax
+	subq	%rbx, %rax
+	pushq	%rax
+	pushq	$42			# Op::PushInt(42)
+	popq	%rax			# Op::Equals
+	popq	%rbx
+	cmpq	%rax, %rbx
+	je	J4
+	pushq	$0
+	jmp	J5
+J4:
+	pushq	$1
+J5:
+	popq	%rax			# Op::If
+	cmpq	$1, %rax
+	jne	F6
+	pushq	$5			# Op::PushInt(5)
+	pushq	$str_0		# Op::PushStrPtr(0)
+	popq	%rsi			# Op::Puts
+	popq	%rdx
+	call	write
+	pushq	$1			# Op::PushInt(1)
+	popq	%rax			# Op::If
+	cmpq	$1, %rax
+	jne	F11
+	pushq	$0			# Op::PushInt(0)
+	popq	%rax			# Op::If
+	cmpq	$1, %rax
+	jne	F13
+	pushq	$6			# Op::PushInt(6)
+	pushq	$str_1		# Op::PushStrPtr(1)
+	popq	%rsi			# Op::Puts
+	popq	%rdx
+	call	write
+F13:
+	pushq	$10			# Op::PushInt(10)
+	pushq	$str_2		# Op::PushStrPtr(2)
+	popq	%rsi			# Op::Puts
+	popq	%rdx
+	call	write
+F11:
+	pushq	$8			# Op::PushInt(8)
+	pushq	$2			# Op::PushInt(2)
+	popq	%rax			# Op::Equals
+	popq	%rbx
+	cmpq	%rax, %rbx
+	je	J24
+	pushq	$0
+	jmp	J25
+J24:
+	pushq	$1
+J25:
+	popq	%rax			# Op::If
+	cmpq	$1, %rax
+	jne	F26
+	pushq	$6			# Op::PushInt(6)
+	pushq	$str_1		# Op::PushStrPtr(1)
+	popq	%rsi			# Op::Puts
+	popq	%rdx
+	call	write
+F26:
+F6:
+	movq	ret_stack_rsp, %rax	# Op::Ret(0)
+	jmp	*(%rax)
+main:
+	movq	$ret_stack_end, %rax
+	subq	$8, %rax
+	movq	%rax, ret_stack_rsp
+	movq	$RET_MAIN, (%rax)
+	call	fn_main
+RET_MAIN:
+	movq	$60, %rax
+	movq	$0, %rdi
+	syscall
+	.section	.rodata
+str_0:	.byte	116,114,117,101,10
+str_1:	.byte	102,97,108,115,101,10
+str_2:	.byte	97,108,115,111,32,116,114,117,101,10
+	.bss
+ret_stack_rsp:	.skip	8
+ret_stack:	.skip	65536
+ret_stack_end:
@@ -0,0 +1,94 @@
+	.globl	main
+	.text
+write:
+	movq	$1, %rax
+	movq	$1, %rdi
+	syscall
+	ret
+print:
+	movabsq	$-3689348814741910323, %r9
+	subq	$40, %rsp
+	movb	$10, 31(%rsp)
+	leaq	30(%rsp), %rcx
+.L2:
+	movq	%rdi, %rax
+	leaq	32(%rsp), %r8
+	mulq	%r9
+	movq	%rdi, %rax
+	subq	%rcx, %r8
+	shrq	$3, %rdx
+	leaq	(%rdx,%rdx,4), %rsi
+	addq	%rsi, %rsi
+	subq	%rsi, %rax
+	addl	$48, %eax
+	movb	%al, (%rcx)
+	movq	%rdi, %rax
+	movq	%rdx, %rdi
+	movq	%rcx, %rdx
+	subq	$1, %rcx
+	cmpq	$9, %rax
+	ja	.L2
+	leaq	32(%rsp), %rax
+	subq	%rax, %rdx
+	leaq	32(%rsp,%rdx), %rsi
+	movq	%r8, %rdx
+	call	write
+	addq	$40, %rsp
+	ret
+fn_main:
+	pushq	$7			# Op::PushInt(7)
+	pushq	$str_0		# Op::PushStrPtr(0)
+	pushq	$7			# Op::PushInt(7)
+	pushq	$str_1		# Op::PushStrPtr(1)
+	movq	ret_stack_rsp, %rax	# Op::Bind(4)
+	subq	$32, %rax
+	movq	%rax, ret_stack_rsp
+	movq	0(%rsp), %rbx
+	movq	%rbx, 0(%rax)
+	movq	8(%rsp), %rbx
+	movq	%rbx, 8(%rax)
+	movq	16(%rsp), %rbx
+	movq	%rbx, 16(%rax)
+	movq	24(%rsp), %rbx
+	movq	%rbx, 24(%rax)
+	addq	$32, %rsp
+	movq	ret_stack_rsp, %rax	# Op::PushBind(3)
+	addq	$24, %rax
+	pushq	(%rax)
+	movq	ret_stack_rsp, %rax	# Op::PushBind(2)
+	addq	$16, %rax
+	pushq	(%rax)
+	popq	%rsi			# Op::Puts
+	popq	%rdx
+	call	write
+	movq	ret_stack_rsp, %rax	# Op::PushBind(1)
+	addq	$8, %rax
+	pushq	(%rax)
+	movq	ret_stack_rsp, %rax	# Op::PushBind(0)
+	addq	$0, %rax
+	pushq	(%rax)
+	popq	%rsi			# Op::Puts
+	popq	%rdx
+	call	write
+	movq	ret_stack_rsp, %rax
+	addq	$32, %rax
+	movq	%rax, ret_stack_rsp
+	movq	ret_stack_rsp, %rax	# Op::Ret(0)
+	jmp	*(%rax)
+main:
+	movq	$ret_stack_end, %rax
+	subq	$8, %rax
+	movq	%rax, ret_stack_rsp
+	movq	$RET_MAIN, (%rax)
+	call	fn_main
+RET_MAIN:
+	movq	$60, %rax
+	movq	$0, %rdi
+	syscall
+	.section	.rodata
+str_0:	.byte	72,101,108,108,111,44,32
+str_1:	.byte	119,111,114,108,100,33,10
+	.bss
+ret_stack_rsp:	.skip	8
+ret_stack:	.skip	65536
+ret_stack_end:
@@ -0,0 +1,145 @@
+	.globl	main
+	.text
+write:
+	movq	$1, %rax
+	movq	$1, %rdi
+	syscall
+	ret
+print:
+	movabsq	$-3689348814741910323, %r9
+	subq	$40, %rsp
+	movb	$10, 31(%rsp)
+	leaq	30(%rsp), %rcx
+.L2:
+	movq	%rdi, %rax
+	leaq	32(%rsp), %r8
+	mulq	%r9
+	movq	%rdi, %rax
+	subq	%rcx, %r8
+	shrq	$3, %rdx
+	leaq	(%rdx,%rdx,4), %rsi
+	addq	%rsi, %rsi
+	subq	%rsi, %rax
+	addl	$48, %eax
+	movb	%al, (%rcx)
+	movq	%rdi, %rax
+	movq	%rdx, %rdi
+	movq	%rcx, %rdx
+	subq	$1, %rcx
+	cmpq	$9, %rax
+	ja	.L2
+	leaq	32(%rsp), %rax
+	subq	%rax, %rdx
+	leaq	32(%rsp,%rdx), %rsi
+	movq	%r8, %rdx
+	call	write
+	addq	$40, %rsp
+	ret
+fn_main:
+	pushq	$0			# Op::PushInt(0)
+F1:					# Op::While
+	pushq	$1			# Op::PushInt(1)
+	popq	%rax			# Op::Plus
+	popq	%rbx
+	addq	%rbx, %rax
+	pushq	%rax
+	pushq	(%rsp)			# Op::Dup
+	pushq	$101			# Op::PushInt(101)
+	popq	%rax			# Op::LessThan
+	popq	%rbx
+	cmpq	%rax, %rbx
+	jb	J7
+	pushq	$0
+	jmp	J8
+J7:
+	pushq	$1
+J8:
+	popq	%rax
+	cmpq	$1, %rax
+	jne	F2
+	pushq	(%rsp)			# Op::Dup
+	popq	%rdi			# Op::Print
+	call	print
+	pushq	(%rsp)			# Op::Dup
+	pushq	$69			# Op::PushInt(69)
+	popq	%rax			# Op::Equals
+	popq	%rbx
+	cmpq	%rax, %rbx
+	je	J13
+	pushq	$0
+	jmp	J14
+J13:
+	pushq	$1
+J14:
+	popq	%rax			# Op::If
+	cmpq	$1, %rax
+	jne	F15
+	pushq	$420			# Op::PushInt(420)
+	popq	%rdi			# Op::Print
+	call	print
+F15:
+	jmp	F1
+F2:
+	pushq	$0			# Op::PushInt(0)
+F21:					# Op::While
+	pushq	$1			# Op::PushInt(1)
+	pushq	$1			# Op::PushInt(1)
+	popq	%rax			# Op::And
+	popq	%rbx
+	cmpq	%rbx, %rax
+	jne	J25
+	cmpq	$1, %rax
+	jne	J25
+	pushq	$1
+	jmp	J26
+J25:
+	pushq	$0
+J26:
+	popq	%rax			# Op::If
+	cmpq	$1, %rax
+	jne	F27
+	pushq	$0			# Op::PushInt(0)
+	popq	%rdi			# Op::Print
+	call	print
+F27:
+	pushq	$1			# Op::PushInt(1)
+	popq	%rax			# Op::Plus
+	popq	%rbx
+	addq	%rbx, %rax
+	pushq	%rax
+	pushq	(%rsp)			# Op::Dup
+	pushq	$101			# Op::PushInt(101)
+	popq	%rax			# Op::LessThan
+	popq	%rbx
+	cmpq	%rax, %rbx
+	jb	J35
+	pushq	$0
+	jmp	J36
+J35:
+	pushq	$1
+J36:
+	popq	%rax
+	cmpq	$1, %rax
+	jne	F22
+	pushq	$1			# Op::PushInt(1)
+	popq	%rdi			# Op::Print
+	call	print
+	jmp	F21
+F22:
+	movq	ret_stack_rsp, %rax	# Op::Ret(0)
+	jmp	*(%rax)
+main:
+	movq	$ret_stack_end, %rax
+	subq	$8, %rax
+	movq	%rax, ret_stack_rsp
+	movq	$RET_MAIN, (%rax)
+	call	fn_main
+RET_MAIN:
+	movq	$60, %rax
+	movq	$0, %rdi
+	syscall
+	.section	.rodata
+	.bss
+ret_stack_rsp:	.skip	8
+ret_stack:	.skip	65536
+ret_stack_end:
@@ -0,0 +1,112 @@
+	.globl	main
+	.text
+write:
+	movq	$1, %rax
+	movq	$1, %rdi
+	syscall
+	ret
+print:
+	movabsq	$-3689348814741910323, %r9
+	subq	$40, %rsp
+	movb	$10, 31(%rsp)
+	leaq	30(%rsp), %rcx
+.L2:
+	movq	%rdi, %rax
+	leaq	32(%rsp), %r8
+	mulq	%r9
+	movq	%rdi, %rax
+	subq	%rcx, %r8
+	shrq	$3, %rdx
+	leaq	(%rdx,%rdx,4), %rsi
+	addq	%rsi, %rsi
+	subq	%rsi, %rax
+	addl	$48, %eax
+	movb	%al, (%rcx)
+	movq	%rdi, %rax
+	movq	%rdx, %rdi
+	movq	%rcx, %rdx
+	subq	$1, %rcx
+	cmpq	$9, %rax
+	ja	.L2
+	leaq	32(%rsp), %rax
+	subq	%rax, %rdx
+	leaq	32(%rsp,%rdx), %rsi
+	movq	%r8, %rdx
+	call	write
+	addq	$40, %rsp
+	ret
+fn_main:
+	pushq	$1			# Op::PushInt(1)
+	pushq	$2			# Op::PushInt(2)
+	popq	%rax			# Op::LessThan
+	popq	%rbx
+	cmpq	%rax, %rbx
+	jb	J2
+	pushq	$0
+	jmp	J3
+J2:
+	pushq	$1
+J3:
+	popq	%rax			# Op::If
+	cmpq	$1, %rax
+	jne	F4
+	pushq	$9			# Op::PushInt(9)
+	pushq	$str_0		# Op::PushStrPtr(0)
+	popq	%rsi			# Op::Puts
+	popq	%rdx
+	call	write
+F4:
+	pushq	$2			# Op::PushInt(2)
+	pushq	$1			# Op::PushInt(1)
+	popq	%rax			# Op::LessThan
+	popq	%rbx
+	cmpq	%rax, %rbx
+	jb	J11
+	pushq	$0
+	jmp	J12
+J11:
+	pushq	$1
+J12:
+	popq	%rax			# Op::If
+	cmpq	$1, %rax
+	jne	F13
+	pushq	$0			# Op::PushInt(0)
+	popq	%rdi			# Op::Print
+	call	print
+F13:
+	pushq	$2			# Op::PushInt(2)
+	pushq	$2			# Op::PushInt(2)
+	popq	%rax			# Op::LessThan
+	popq	%rbx
+	cmpq	%rax, %rbx
+	jb	J19
+	pushq	$0
+	jmp	J20
+J19:
+	pushq	$1
+J20:
+	popq	%rax			# Op::If
+	cmpq	$1, %rax
+	jne	F21
+	pushq	$0			# Op::PushInt(0)
+	popq	%rdi			# Op::Print
+	call	print
+F21:
+	movq	ret_stack_rsp, %rax	# Op::Ret(0)
+	jmp	*(%rax)
+main:
+	movq	$ret_stack_end, %rax
+	subq	$8, %rax
+	movq	%rax, ret_stack_rsp
+	movq	$RET_MAIN, (%rax)
+	call	fn_main
+RET_MAIN:
+	movq	$60, %rax
+	movq	$0, %rdi
+	syscall
+	.section	.rodata
+str_0:	.byte	115,117,99,99,101,115,115,33,10
+	.bss
+ret_stack_rsp:	.skip	8
+ret_stack:	.skip	65536
+ret_stack_end:
@@ -0,0 +1,143 @@
+	.globl	main
+	.text
+write:
+	movq	$1, %rax
+	movq	$1, %rdi
+	syscall
+	ret
+print:
+	movabsq	$-3689348814741910323, %r9
+	subq	$40, %rsp
+	movb	$10, 31(%rsp)
+	leaq	30(%rsp), %rcx
+.L2:
+	movq	%rdi, %rax
+	leaq	32(%rsp), %r8
+	mulq	%r9
+	movq	%rdi, %rax
+	subq	%rcx, %r8
+	shrq	$3, %rdx
+	leaq	(%rdx,%rdx,4), %rsi
+	addq	%rsi, %rsi
+	subq	%rsi, %rax
+	addl	$48, %eax
+	movb	%al, (%rcx)
+	movq	%rdi, %rax
+	movq	%rdx, %rdi
+	movq	%rcx, %rdx
+	subq	$1, %rcx
+	cmpq	$9, %rax
+	ja	.L2
+	leaq	32(%rsp), %rax
+	subq	%rax, %rdx
+	leaq	32(%rsp,%rdx), %rsi
+	movq	%r8, %rdx
+	call	write
+	addq	$40, %rsp
+	ret
+fn_main:
+	pushq	$0			# Op::PushInt(0)
+	pushq	$0			# Op::PushInt(0)
+	popq	%rax			# Op::Neq
+	popq	%rbx
+	cmpq	%rax, %rbx
+	jne	J2
+	pushq	$0
+	jmp	J3
+J2:
+	pushq	$1
+J3:
+	popq	%rdi			# Op::Print
+	call	print
+	pushq	$0			# Op::PushInt(0)
+	pushq	$1			# Op::PushInt(1)
+	popq	%rax			# Op::Neq
+	popq	%rbx
+	cmpq	%rax, %rbx
+	jne	J7
+	pushq	$0
+	jmp	J8
+J7:
+	pushq	$1
+J8:
+	popq	%rdi			# Op::Print
+	call	print
+	pushq	$1			# Op::PushInt(1)
+	pushq	$0			# Op::PushInt(0)
+	popq	%rax			# Op::Neq
+	popq	%rbx
+	cmpq	%rax, %rbx
+	jne	J12
+	pushq	$0
+	jmp	J13
+J12:
+	pushq	$1
+J13:
+	popq	%rdi			# Op::Print
+	call	print
+	pushq	$1			# Op::PushInt(1)
+	pushq	$1			# Op::PushInt(1)
+	popq	%rax			# Op::Neq
+	popq	%rbx
+	cmpq	%rax, %rbx
+	jne	J17
+	pushq	$0
+	jmp	J18
+J17:
+	pushq	$1
+J18:
+	popq	%rdi			# Op::Print
+	call	print
+	pushq	$5			# Op::PushInt(5)
+	pushq	$5			# Op::PushInt(5)
+	popq	%rax			# Op::Neq
+	popq	%rbx
+	cmpq	%rax, %rbx
+	jne	J22
+	pushq	$0
+	jmp	J23
+J22:
+	pushq	$1
+J23:
+	popq	%rax			# Op::If
+	cmpq	$1, %rax
+	jne	F24
+	pushq	$13			# Op::PushInt(13)
+	popq	%rdi			# Op::Print
+	call	print
+F24:
+	pushq	$5			# Op::PushInt(5)
+	pushq	$4			# Op::PushInt(4)
+	popq	%rax			# Op::Neq
+	popq	%rbx
+	cmpq	%rax, %rbx
+	jne	J30
+	pushq	$0
+	jmp	J31
+J30:
+	pushq	$1
+J31:
+	popq	%rax			# Op::If
+	cmpq	$1, %rax
+	jne	F32
+	pushq	$100			# Op::PushInt(100)
+	popq	%rdi			# Op::Print
+	call	print
+F32:
+	movq	ret_stack_rsp, %rax	# Op::Ret(0)
+	jmp	*(%rax)
+main:
+	movq	$ret_stack_end, %rax
+	subq	$8, %rax
+	movq	%rax, ret_stack_rsp
+	movq	$RET_MAIN, (%rax)
+	call	fn_main
+RET_MAIN:
+	movq	$60, %rax
+	movq	$0, %rdi
+	syscall
+	.section	.rodata
+	.bss
+ret_stack_rsp:	.skip	8
+ret_stack:	.skip	65536
+ret_stack_end:
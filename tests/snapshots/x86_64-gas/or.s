@@ -0,0 +1,135 @@
+	.globl	main
+	.text
+write:
+	movq	$1, %rax
+	movq	$1, %rdi
+	syscall
+	ret
+print:
+	movabsq	$-3689348814741910323, %r9
+	subq	$40, %rsp
+	movb	$10, 31(%rsp)
+	leaq	30(%rsp), %rcx
+.L2:
+	movq	%rdi, %rax
+	leaq	32(%rsp), %r8
+	mulq	%r9
+	movq	%rdi, %rax
+	subq	%rcx, %r8
+	shrq	$3, %rdx
+	leaq	(%rdx,%rdx,4), %rsi
+	addq	%rsi, %rsi
+	subq	%rsi, %rax
+	addl	$48, %eax
+	movb	%al, (%rcx)
+	movq	%rdi, %rax
+	movq	%rdx, %rdi
+	movq	%rcx, %rdx
+	subq	$1, %rcx
+	cmpq	$9, %rax
+	ja	.L2
+	leaq	32(%rsp), %rax
+	subq	%rax, %rdx
+	leaq	32(%rsp,%rdx), %rsi
+	movq	%r8, %rdx
+	call	write
+	addq	$40, %rsp
+	ret
+fn_main:
+	pushq	$1			# Op::PushInt(1)
+	pushq	$0			# Op::PushInt(0)
+	popq	%rax			# Op::Or
+	popq	%rbx
+	cmpq	$1, %rax
+	je	J2
+	cmpq	$1, %rbx
+	je	J2
+	pushq	$0
+	jmp	J3
+J2:
+	pushq	$1
+J3:
+	popq	%rax			# Op::If
+	cmpq	$1, %rax
+	jne	F4
+	pushq	$1			# Op::PushInt(1)
+	popq	%rdi			# Op::Print
+	call	print
+F4:
+	pushq	$0			# Op::PushInt(0)
+	pushq	$1			# Op::PushInt(1)
+	popq	%rax			# Op::Or
+	popq	%rbx
+	cmpq	$1, %rax
+	je	J10
+	cmpq	$1, %rbx
+	je	J10
+	pushq	$0
+	jmp	J11
+J10:
+	pushq	$1
+J11:
+	popq	%rax			# Op::If
+	cmpq	$1, %rax
+	jne	F12
+	pushq	$1			# Op::PushInt(1)
+	popq	%rdi			# Op::Print
+	call	print
+F12:
+	pushq	$1			# Op::PushInt(1)
+	pushq	$1			# Op::PushInt(1)
+	popq	%rax			# Op::Or
+	popq	%rbx
+	cmpq	$1, %rax
+	je	J18
+	cmpq	$1, %rbx
+	je	J18
+	pushq	$0
+	jmp	J19
+J18:
+	pushq	$1
+J19:
+	popq	%rax			# Op::If
+	cmpq	$1, %rax
+	jne	F20
+	pushq	$1			# Op::PushInt(1)
+	popq	%rdi			# Op::Print
+	call	print
+F20:
+	pushq	$0			# Op::PushInt(0)
+	pushq	$0			# Op::PushInt(0)
+	popq	%rax			# Op::Or
+	popq	%rbx
+	cmpq	$1, %rax
+	je	J26
+	cmpq	$1, %rbx
+	je	J26
+	pushq	$0
+	jmp	J27
+J26:
+	pushq	$1
+J27:
+	popq	%rax			# Op::If
+	cmpq	$1, %rax
+	jne	F28
+	pushq	$0			# Op::PushInt(0)
+	popq	%rdi			# Op::Print
+	call	print
+F28:
+	movq	ret_stack_rsp, %rax	# Op::Ret(0)
+	jmp	*(%rax)
+main:
+	movq	$ret_stack_end, %rax
+	subq	$8, %rax
+	movq	%rax, ret_stack_rsp
+	movq	$RET_MAIN, (%rax)
+	call	fn_main
+RET_MAIN:
+	movq	$60, %rax
+	movq	$0, %rdi
+	syscall
+	.section	.rodata
+	.bss
+ret_stack_rsp:	.skip	8
+ret_stack:	.skip	65536
+ret_stack_end:
@@ -0,0 +1,97 @@
+	.globl	main
+	.text
+write:
+	movq	$1, %rax
+	movq	$1, %rdi
+	syscall
+	ret
+print:
+	movabsq	$-3689348814741910323, %r9
+	subq	$40, %rsp
+	movb	$10, 31(%rsp)
+	leaq	30(%rsp), %rcx
+.L2:
+	movq	%rdi, %rax
+	leaq	32(%rsp), %r8
+	mulq	%r9
+	movq	%rdi, %rax
+	subq	%rcx, %r8
+	shrq	$3, %rdx
+	leaq	(%rdx,%rdx,4), %rsi
+	addq	%rsi, %rsi
+	subq	%rsi, %rax
+	addl	$48, %eax
+	movb	%al, (%rcx)
+	movq	%rdi, %rax
+	movq	%rdx, %rdi
+	movq	%rcx, %rdx
+	subq	$1, %rcx
+	cmpq	$9, %rax
+	ja	.L2
+	leaq	32(%rsp), %rax
+	subq	%rax, %rdx
+	leaq	32(%rsp,%rdx), %rsi
+	movq	%r8, %rdx
+	call	write
+	addq	$40, %rsp
+	ret
+fn_main:
+	pushq	$4			# Op::PushInt(4)
+	pushq	$str_0		# Op::PushStrPtr(0)
+	pushq	(%rsp)			# Op::Dup
+	popq	%rbx			# Op::ReadByte
+	movzbq	(%rbx), %rax
+	pushq	%rax
+	popq	%rdi			# Op::Print
+	call	print
+	pushq	(%rsp)			# Op::Dup
+	pushq	$1			# Op::PushInt(1)
+	popq	%rax			# Op::Plus
+	popq	%rbx
+	addq	%rbx, %rax
+	pushq	%rax
+	popq	%rbx			# Op::ReadByte
+	movzbq	(%rbx), %rax
+	pushq	%rax
+	popq	%rdi			# Op::Print
+	call	print
+	pushq	(%rsp)			# Op::Dup
+	pushq	$2			# Op::PushInt(2)
+	popq	%rax			# Op::Plus
+	popq	%rbx
+	addq	%rbx, %rax
+	pushq	%rax
+	popq	%rbx			# Op::ReadByte
+	movzbq	(%rbx), %rax
+	pushq	%rax
+	popq	%rdi			# Op::Print
+	call	print
+	pushq	(%rsp)			# Op::Dup
+	pushq	$3			# Op::PushInt(3)
+	popq	%rax			# Op::Plus
+	popq	%rbx
+	addq	%rbx, %rax
+	pushq	%rax
+	popq	%rbx			# Op::ReadByte
+	movzbq	(%rbx), %rax
+	pushq	%rax
+	popq	%rdi			# Op::Print
+	call	print
+	movq	ret_stack_rsp, %rax	# Op::Ret(0)
+	jmp	*(%rax)
+main:
+	movq	$ret_stack_end, %rax
+	subq	$8, %rax
+	movq	%rax, ret_stack_rsp
+	movq	$RET_MAIN, (%rax)
+	call	fn_main
+RET_MAIN:
+	movq	$60, %rax
+	movq	$0, %rdi
+	syscall
+	.section	.rodata
+str_0:	.byte	116,101,115,116
+	.bss
+ret_stack_rsp:	.skip	8
+ret_stack:	.skip	65536
+ret_stack_end:
@@ -0,0 +1,80 @@
+format ELF64 executable 3
+entry main
+segment readable executable
+print:
+	mov	r9, -3689348814741910323
+	sub	rsp, 40
+	mov	BYTE [rsp+31], 10
+	lea	rcx, [rsp+30]
+.L2:
+	mov	rax, rdi
+	lea	r8, [rsp+32]
+	mul	r9
+	mov	rax, rdi
+	sub	r8, rcx
+	shr	rdx, 3
+	lea	rsi, [rdx+rdx*4]
+	add	rsi, rsi
+	sub	rax, rsi
+	add	eax, 48
+	mov	BYTE [rcx], al
+	mov	rax, rdi
+	mov	rdi, rdx
+	mov	rdx, rcx
+	sub	rcx, 1
+	cmp	rax, 9
+	ja	.L2
+	lea	rax, [rsp+32]
+	sub	rdx, rax
+	lea	rsi, [rsp+32+rdx]
+	mov	rdx, r8
+	call	write
+	add	rsp, 40
+	ret
+write:
+	mov	rax, 1
+	mov	rdi, 1
+	syscall
+	ret
+fn_test_fn:
+	mov	rax, qword [ret_stack_rsp]	; Op::Ret(0)
+	jmp qword [rax]
+fn_main:
+	push	12			; Op::PushInt(12)
+	push	str_0			; Op::PushStrPtr(0)
+	pop	rsi			; Op::Puts
+	pop	rdx
+	call	write
+	mov	rax, [ret_stack_rsp]	; Op::CallFn(0)
+	sub	rax, 8
+	mov	[ret_stack_rsp], rax
+	mov	qword [rax], RET4
+	jmp	fn_test_fn
+RET4:
+	mov	rax, [ret_stack_rsp]
+	add	rax, 8
+	mov	[ret_stack_rsp], rax
+	push	9			; Op::PushInt(9)
+	push	str_1			; Op::PushStrPtr(1)
+	pop	rsi			; Op::Puts
+	pop	rdx
+	call	write
+	mov	rax, qword [ret_stack_rsp]	; Op::Ret(0)
+	jmp qword [rax]
+main:
+	mov	rax, ret_stack_end
+	sub	rax, 8
+	mov	qword [ret_stack_rsp], rax
+	mov	qword [rax], RET_MAIN
+	call	fn_main
+RET_MAIN:
+	mov	rax, 60
+	mov	rdi, 0
+	syscall
+segment readable
+str_0: db 104,101,108,108,111,32,119,111,114,108,100,10
+str_1: db 115,117,99,99,101,115,115,33,10
+segment readable writable
+ret_stack_rsp: rq 1
+ret_stack: rb 65536
+ret_stack_end:
@@ -0,0 +1,94 @@
+format ELF64 executable 3
+entry main
+segment readable executable
+print:
+	mov	r9, -3689348814741910323
+	sub	rsp, 40
+	mov	BYTE [rsp+31], 10
+	lea	rcx, [rsp+30]
+.L2:
+	mov	rax, rdi
+	lea	r8, [rsp+32]
+	mul	r9
+	mov	rax, rdi
+	sub	r8, rcx
+	shr	rdx, 3
+	lea	rsi, [rdx+rdx*4]
+	add	rsi, rsi
+	sub	rax, rsi
+	add	eax, 48
+	mov	BYTE [rcx], al
+	mov	rax, rdi
+	mov	rdi, rdx
+	mov	rdx, rcx
+	sub	rcx, 1
+	cmp	rax, 9
+	ja	.L2
+	lea	rax, [rsp+32]
+	sub	rdx, rax
+	lea	rsi, [rsp+32+rdx]
+	mov	rdx, r8
+	call	write
+	add	rsp, 40
+	ret
+write:
+	mov	rax, 1
+	mov	rdi, 1
+	syscall
+	ret
+fn_main:
+	push	10			; Op::PushInt(10)
+	push	5			; Op::PushInt(5)
+	pop	rbx			; Op::DivMod
+	pop	rax
+	mov	rdx, 0
+	div	rbx
+	push	rax
+	push	rdx
+	add	rsp, 8			; Op::Drop
+	pop	rdi			; Op::Print
+	call	print
+	push	10			; Op::PushInt(10)
+	push	5			; Op::PushInt(5)
+	pop	rbx			; Op::DivMod
+	pop	rax
+	mov	rdx, 0
+	div	rbx
+	push	rax
+	push	rdx
+	pop	rax			; Op::Swap
+	pop	rbx
+	push	rax
+	push	rbx
+	add	rsp, 8			; Op::Drop
+	pop	rdi			; Op::Print
+	call	print
+	push	10			; Op::PushInt(10)
+	push	4			; Op::PushInt(4)
+	pop	rbx			; Op::DivMod
+	pop	rax
+	mov	rdx, 0
+	div	rbx
+	push	rax
+	push	rdx
+	pop	rdi			; Op::Print
+	call	print
+	pop	rdi			; Op::Print
+	call	print
+	mov	rax, qword [ret_stack_rsp]	; Op::Ret(0)
+	jmp qword [rax]
+main:
+	mov	rax, ret_stack_end
+	sub	rax, 8
+	mov	qword [ret_stack_rsp], rax
+	mov	qword [rax], RET_MAIN
+	call	fn_main
+RET_MAIN:
+	mov	rax, 60
+	mov	rdi, 0
+	syscall
+segment readable
+segment readable writable
+ret_stack_rsp: rq 1
+ret_stack: rb 65536
+ret_stack_end:
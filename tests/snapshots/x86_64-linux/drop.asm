@@ -0,0 +1,173 @@
+format ELF64 executable 3
+entry main
+segment readable executable
+print:
+	mov	r9, -3689348814741910323
+	sub	rsp, 40
+	mov	BYTE [rsp+31], 10
+	lea	rcx, [rsp+30]
+.L2:
+	mov	rax, rdi
+	lea	r8, [rsp+32]
+	mul	r9
+	mov	rax, rdi
+	sub	r8, rcx
+	shr	rdx, 3
+	lea	rsi, [rdx+rdx*4]
+	add	rsi, rsi
+	sub	rax, rsi
+	add	eax, 48
+	mov	BYTE [rcx], al
+	mov	rax, rdi
+	mov	rdi, rdx
+	mov	rdx, rcx
+	sub	rcx, 1
+	cmp	rax, 9
+	ja	.L2
+	lea	rax, [rsp+32]
+	sub	rdx, rax
+	lea	rsi, [rsp+32+rdx]
+	mov	rdx, r8
+	call	write
+	add	rsp, 40
+	ret
+write:
+	mov	rax, 1
+	mov	rdi, 1
+	syscall
+	ret
+fn_main:
+	push	100			; Op::PushInt(100)
+F1:					; Op::While
+	push	1			; Op::PushInt(1)
+	pop	rbx			; Op::Minus
+	pop	rax
+	sub	rax, rbx
+	push	rax
+	push	qword [rsp]		; Op::Dup
+	push	0			; Op::PushInt(0)
+	pop	rax			; Op::GreaterThan
+	pop	rbx
+	cmp	rax, rbx
+	jb	J7
+	push	0
+	jmp	J8
+J7:
+	push	1
+J8:
+	pop	rax
+	cmp	rax, 1
+	jne	F2
+	push	0			; Op::PushInt(0)
+F10:					; Op::While
+	push	1			; Op::PushInt(1)
+	pop	rax			; Op::Plus
+	pop	rbx
+	add	rax, rbx
+	push	rax
+	push	qword [rsp]		; Op::Dup
+	push	5			; Op::PushInt(5)
+	pop	rax			; Op::LessThan
+	pop	rbx
+	cmp	rbx, rax
+	jb	J16
+	push	0
+	jmp	J17
+J16:
+	push	1
+J17:
+	pop	rax
+	cmp	rax, 1
+	jne	F11
+	push	8			; Op::PushInt(8)
+	pop	rdi			; Op::Print
+	call	print
+	jmp F10
+F11:
+	add	rsp, 8			; Op::Drop
+	push	50			; Op::PushInt(50)
+	push	1			; Op::PushInt(1)
+	pop	rax			; Op::Plus
+	pop	rbx
+	add	rax, rbx
+	push	rax
+	push	78			; Op::PushInt(78)
+	push	27			; Op::PushInt(27)
+	pop	rbx			; Op::Minus
+	pop	rax
+	sub	rax, rbx
+	push	rax
+	pop	rax			; Op::Equals
+	pop	rbx
+	cmp	rax, rbx
+	je	J28
+	push	0
+	jmp	J29
+J28:
+	push	1
+J29:
+	push	1			; Op::PushInt(1)
+	pop	rax			; Op::And
+	pop	rbx
+	cmp	rax, rbx
+	jne	J31
+	cmp	rax, 1
+	jne	J31
+	push	1
+	jmp	J32
+J31:
+	push	0
+J32:
+	pop	rax			; Op::If
+	cmp	rax, 1
+	jne	F33
+	push	0			; Op::PushInt(0)
+	push	8			; Op::PushInt(8)
+	push	3			; Op::PushInt(3)
+	pop	rax			; Op::GreaterThan
+	pop	rbx
+	cmp	rax, rbx
+	jb	J37
+	push	0
+	jmp	J38
+J37:
+	push	1
+J38:
+	pop	rax			; Op::Or
+	pop	rbx
+	cmp	rax, 1
+	je	J39
+	cmp	rbx, 1
+	je	J39
+	push	0
+	jmp	J40
+J39:
+	push	1
+J40:
+	pop	rax			; Op::If
+	cmp	rax, 1
+	jne	F41
+	push	42			; Op::PushInt(42)
+	pop	rdi			; Op::Print
+	call	print
+F41:
+F33:
+	jmp F1
+F2:
+	mov	rax, qword [ret_stack_rsp]	; Op::Ret(0)
+	jmp qword [rax]
+main:
+	mov	rax, ret_stack_end
+	sub	rax, 8
+	mov	qword [ret_stack_rsp], rax
+	mov	qword [rax], RET_MAIN
+	call	fn_main
+RET_MAIN:
+	mov	rax, 60
+	mov	rdi, 0
+	syscall
+segment readable
+segment readable writable
+ret_stack_rsp: rq 1
+ret_stack: rb 65536
+ret_stack_end:
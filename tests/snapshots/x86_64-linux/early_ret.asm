@@ -0,0 +1,101 @@
+format ELF64 executable 3
+entry main
+segment readable executable
+print:
+	mov	r9, -3689348814741910323
+	sub	rsp, 40
+	mov	BYTE [rsp+31], 10
+	lea	rcx, [rsp+30]
+.L2:
+	mov	rax, rdi
+	lea	r8, [rsp+32]
+	mul	r9
+	mov	rax, rdi
+	sub	r8, rcx
+	shr	rdx, 3
+	lea	rsi, [rdx+rdx*4]
+	add	rsi, rsi
+	sub	rax, rsi
+	add	eax, 48
+	mov	BYTE [rcx], al
+	mov	rax, rdi
+	mov	rdi, rdx
+	mov	rdx, rcx
+	sub	rcx, 1
+	cmp	rax, 9
+	ja	.L2
+	lea	rax, [rsp+32]
+	sub	rdx, rax
+	lea	rsi, [rsp+32+rdx]
+	mov	rdx, r8
+	call	write
+	add	rsp, 40
+	ret
+write:
+	mov	rax, 1
+	mov	rdi, 1
+	syscall
+	ret
+fn_foo:
+	push	18			; Op::PushInt(18)
+	pop	rax			; Op::Equals
+	pop	rbx
+	cmp	rax, rbx
+	je	J1
+	push	0
+	jmp	J2
+J1:
+	push	1
+J2:
+	pop	rax			; Op::If
+	cmp	rax, 1
+	jne	F3
+	push	0			; Op::PushInt(0)
+	mov	rax, qword [ret_stack_rsp]	; Op::Ret(0)
+	jmp qword [rax]
+F3:
+	push	1			; Op::PushInt(1)
+	mov	rax, qword [ret_stack_rsp]	; Op::Ret(0)
+	jmp qword [rax]
+fn_main:
+	push	1			; Op::PushInt(1)
+	mov	rax, [ret_stack_rsp]	; Op::CallFn(0)
+	sub	rax, 8
+	mov	[ret_stack_rsp], rax
+	mov	qword [rax], RET10
+	jmp	fn_foo
+RET10:
+	mov	rax, [ret_stack_rsp]
+	add	rax, 8
+	mov	[ret_stack_rsp], rax
+	pop	rdi			; Op::Print
+	call	print
+	push	18			; Op::PushInt(18)
+	mov	rax, [ret_stack_rsp]	; Op::CallFn(0)
+	sub	rax, 8
+	mov	[ret_stack_rsp], rax
+	mov	qword [rax], RET13
+	jmp	fn_foo
+RET13:
+	mov	rax, [ret_stack_rsp]
+	add	rax, 8
+	mov	[ret_stack_rsp], rax
+	pop	rdi			; Op::Print
+	call	print
+	mov	rax, qword [ret_stack_rsp]	; Op::Ret(0)
+	jmp qword [rax]
+main:
+	mov	rax, ret_stack_end
+	sub	rax, 8
+	mov	qword [ret_stack_rsp], rax
+	mov	qword [rax], RET_MAIN
+	call	fn_main
+RET_MAIN:
+	mov	rax, 60
+	mov	rdi, 0
+	syscall
+segment readable
+segment readable writable
+ret_stack_rsp: rq 1
+ret_stack: rb 65536
+ret_stack_end:
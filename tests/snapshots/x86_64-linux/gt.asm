@@ -0,0 +1,113 @@
+format ELF64 executable 3
+entry main
+segment readable executable
+print:
+	mov	r9, -3689348814741910323
+	sub	rsp, 40
+	mov	BYTE [rsp+31], 10
+	lea	rcx, [rsp+30]
+.L2:
+	mov	rax, rdi
+	lea	r8, [rsp+32]
+	mul	r9
+	mov	rax, rdi
+	sub	r8, rcx
+	shr	rdx, 3
+	lea	rsi, [rdx+rdx*4]
+	add	rsi, rsi
+	sub	rax, rsi
+	add	eax, 48
+	mov	BYTE [rcx], al
+	mov	rax, rdi
+	mov	rdi, rdx
+	mov	rdx, rcx
+	sub	rcx, 1
+	cmp	rax, 9
+	ja	.L2
+	lea	rax, [rsp+32]
+	sub	rdx, rax
+	lea	rsi, [rsp+32+rdx]
+	mov	rdx, r8
+	call	write
+	add	rsp, 40
+	ret
+write:
+	mov	rax, 1
+	mov	rdi, 1
+	syscall
+	ret
+fn_main:
+	push	1			; Op::PushInt(1)
+	push	2			; Op::PushInt(2)
+	pop	rax			; Op::GreaterThan
+	pop	rbx
+	cmp	rax, rbx
+	jb	J2
+	push	0
+	jmp	J3
+J2:
+	push	1
+J3:
+	pop	rax			; Op::If
+	cmp	rax, 1
+	jne	F4
+	push	0			; Op::PushInt(0)
+	pop	rdi			; Op::Print
+	call	print
+F4:
+	push	2			; Op::PushInt(2)
+	push	1			; Op::PushInt(1)
+	pop	rax			; Op::GreaterThan
+	pop	rbx
+	cmp	rax, rbx
+	jb	J10
+	push	0
+	jmp	J11
+J10:
+	push	1
+J11:
+	pop	rax			; Op::If
+	cmp	rax, 1
+	jne	F12
+	push	9			; Op::PushInt(9)
+	push	str_0			; Op::PushStrPtr(0)
+	pop	rsi			; Op::Puts
+	pop	rdx
+	call	write
+F12:
+	push	2			; Op::PushInt(2)
+	push	2			; Op::PushInt(2)
+	pop	rax			; Op::GreaterThan
+	pop	rbx
+	cmp	rax, rbx
+	jb	J19
+	push	0
+	jmp	J20
+J19:
+	push	1
+J20:
+	pop	rax			; Op::If
+	cmp	rax, 1
+	jne	F21
+	push	0			; Op::PushInt(0)
+	pop	rdi			; Op::Print
+	call	print
+F21:
+	mov	rax, qword [ret_stack_rsp]	; Op::Ret(0)
+	jmp qword [rax]
+main:
+	mov	rax, ret_stack_end
+	sub	rax, 8
+	mov	qword [ret_stack_rsp], rax
+	mov	qword [rax], RET_MAIN
+	call	fn_main
+RET_MAIN:
+	mov	rax, 60
+	mov	rdi, 0
+	syscall
+segment readable
+str_0: db 115,117,99,99,101,115,115,33,10
+segment readable writable
+ret_stack_rsp: rq 1
+ret_stack: rb 65536
+ret_stack_end:
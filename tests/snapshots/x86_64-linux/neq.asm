@@ -0,0 +1,144 @@
+format ELF64 executable 3
+entry main
+segment readable executable
+print:
+	mov	r9, -3689348814741910323
+	sub	rsp, 40
+	mov	BYTE [rsp+31], 10
+	lea	rcx, [rsp+30]
+.L2:
+	mov	rax, rdi
+	lea	r8, [rsp+32]
+	mul	r9
+	mov	rax, rdi
+	sub	r8, rcx
+	shr	rdx, 3
+	lea	rsi, [rdx+rdx*4]
+	add	rsi, rsi
+	sub	rax, rsi
+	add	eax, 48
+	mov	BYTE [rcx], al
+	mov	rax, rdi
+	mov	rdi, rdx
+	mov	rdx, rcx
+	sub	rcx, 1
+	cmp	rax, 9
+	ja	.L2
+	lea	rax, [rsp+32]
+	sub	rdx, rax
+	lea	rsi, [rsp+32+rdx]
+	mov	rdx, r8
+	call	write
+	add	rsp, 40
+	ret
+write:
+	mov	rax, 1
+	mov	rdi, 1
+	syscall
+	ret
+fn_main:
+	push	0			; Op::PushInt(0)
+	push	0			; Op::PushInt(0)
+	pop	rax			; Op::Neq
+	pop	rbx
+	cmp	rax, rbx
+	jne	J2
+	push	0
+	jmp	J3
+J2:
+	push	1
+J3:
+	pop	rdi			; Op::Print
+	call	print
+	push	0			; Op::PushInt(0)
+	push	1			; Op::PushInt(1)
+	pop	rax			; Op::Neq
+	pop	rbx
+	cmp	rax, rbx
+	jne	J7
+	push	0
+	jmp	J8
+J7:
+	push	1
+J8:
+	pop	rdi			; Op::Print
+	call	print
+	push	1			; Op::PushInt(1)
+	push	0			; Op::PushInt(0)
+	pop	rax			; Op::Neq
+	pop	rbx
+	cmp	rax, rbx
+	jne	J12
+	push	0
+	jmp	J13
+J12:
+	push	1
+J13:
+	pop	rdi			; Op::Print
+	call	print
+	push	1			; Op::PushInt(1)
+	push	1			; Op::PushInt(1)
+	pop	rax			; Op::Neq
+	pop	rbx
+	cmp	rax, rbx
+	jne	J17
+	push	0
+	jmp	J18
+J17:
+	push	1
+J18:
+	pop	rdi			; Op::Print
+	call	print
+	push	5			; Op::PushInt(5)
+	push	5			; Op::PushInt(5)
+	pop	rax			; Op::Neq
+	pop	rbx
+	cmp	rax, rbx
+	jne	J22
+	push	0
+	jmp	J23
+J22:
+	push	1
+J23:
+	pop	rax			; Op::If
+	cmp	rax, 1
+	jne	F24
+	push	13			; Op::PushInt(13)
+	pop	rdi			; Op::Print
+	call	print
+F24:
+	push	5			; Op::PushInt(5)
+	push	4			; Op::PushInt(4)
+	pop	rax			; Op::Neq
+	pop	rbx
+	cmp	rax, rbx
+	jne	J30
+	push	0
+	jmp	J31
+J30:
+	push	1
+J31:
+	pop	rax			; Op::If
+	cmp	rax, 1
+	jne	F32
+	push	100			; Op::PushInt(100)
+	pop	rdi			; Op::Print
+	call	print
+F32:
+	mov	rax, qword [ret_stack_rsp]	; Op::Ret(0)
+	jmp qword [rax]
+main:
+	mov	rax, ret_stack_end
+	sub	rax, 8
+	mov	qword [ret_stack_rsp], rax
+	mov	qword [rax], RET_MAIN
+	call	fn_main
+RET_MAIN:
+	mov	rax, 60
+	mov	rdi, 0
+	syscall
+segment readable
+segment readable writable
+ret_stack_rsp: rq 1
+ret_stack: rb 65536
+ret_stack_end:
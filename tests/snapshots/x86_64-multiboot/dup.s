@@ -0,0 +1,229 @@
+image_start:
+	.section	.text
+	.code32
+	.globl	start
+multiboot1_header:
+	.long	0x1badb002
+	.long	0x00010000
+	.long	-(0x1badb002 + 0x00010000)
+	.long	multiboot1_header
+	.long	image_start
+	.long	data_end
+	.long	bss_end
+	.long	start
+	.balign	8
+multiboot2_header:
+	.long	0xe85250d6
+	.long	0
+	.long	multiboot2_header_end - multiboot2_header
+	.long	-(0xe85250d6 + (multiboot2_header_end - multiboot2_header))
+	.word	2, 0
+	.long	24
+	.long	multiboot2_header
+	.long	image_start
+	.long	data_end
+	.long	bss_end
+	.word	3, 0
+	.long	12
+	.long	start
+	.balign	8
+	.word	0, 0
+	.long	8
+multiboot2_header_end:
+start:
+	cli
+	movl	$stack_end, %esp
+	movl	$pdpt, %eax
+	orl	$3, %eax
+	movl	%eax, pml4
+	movl	$pd, %eax
+	orl	$3, %eax
+	movl	%eax, pdpt
+	xorl	%ecx, %ecx
+1:
+	movl	%ecx, %eax
+	shll	$21, %eax
+	orl	$0x83, %eax
+	movl	%eax, pd(,%ecx,8)
+	incl	%ecx
+	cmpl	$512, %ecx
+	jne	1b
+	movl	$pml4, %eax
+	movl	%eax, %cr3
+	movl	%cr4, %eax
+	orl	$0x20, %eax
+	movl	%eax, %cr4
+	movl	$0xc0000080, %ecx
+	rdmsr
+	orl	$0x100, %eax
+	wrmsr
+	movl	%cr0, %eax
+	orl	$0x80000001, %eax
+	movl	%eax, %cr0
+	lgdt	gdt_ptr
+	ljmp	$8, $long_mode
+	.code64
+long_mode:
+	xorl	%eax, %eax
+	movw	%ax, %ds
+	movw	%ax, %es
+	movw	%ax, %ss
+	movw	%ax, %fs
+	movw	%ax, %gs
+	movq	$stack_end, %rsp
+	movw	$0x3f9, %dx		# Disable the serial port's interrupts.
+	movb	$0x00, %al
+	outb	%al, %dx
+	movw	$0x3fb, %dx		# Set the divisor for 38400 baud.
+	movb	$0x80, %al
+	outb	%al, %dx
+	movw	$0x3f8, %dx
+	movb	$0x03, %al
+	outb	%al, %dx
+	movw	$0x3f9, %dx
+	movb	$0x00, %al
+	outb	%al, %dx
+	movw	$0x3fb, %dx		# 8 bits, no parity, one stop bit.
+	movb	$0x03, %al
+	outb	%al, %dx
+	movw	$0x3fa, %dx		# Enable and clear the FIFOs.
+	movb	$0xc7, %al
+	outb	%al, %dx
+	movl	$0xb8000, %edi		# Clear the screen.
+	movq	$0x0f200f200f200f20, %rax
+	movl	$500, %ecx
+	rep stosq
+	movq	$ret_stack_end, %rax
+	subq	$8, %rax
+	movq	%rax, ret_stack_rsp
+	movq	$RET_MAIN, (%rax)
+	jmp	fn_main
+RET_MAIN:
+	movw	$0xf4, %dx		# Exit QEMU if it has an `isa-debug-exit` device.
+	movl	$0x10, %eax		# QEMU then exits with `(0x10 << 1) | 1`.
+	outl	%eax, %dx
+halt:
+	cli
+	hlt
+	jmp	halt
+write:
+	movq	%rdx, %rcx
+	testq	%rcx, %rcx
+	jz	.Lwrite_end
+.Lwrite_byte:
+	movb	(%rsi), %r8b
+	movw	$0x3fd, %dx
+.Lserial_wait:
+	inb	%dx, %al
+	testb	$0x20, %al
+	jz	.Lserial_wait
+	movw	$0x3f8, %dx
+	movb	%r8b, %al
+	outb	%al, %dx
+	movq	vga_pos, %rdi
+	cmpb	$10, %r8b
+	jne	.Lvga_char
+	movq	%rdi, %rax
+	xorl	%edx, %edx
+	movq	$80, %r9
+	divq	%r9
+	incq	%rax
+	imulq	$80, %rax, %rdi
+	jmp	.Lvga_scroll
+.Lvga_char:
+	movb	%r8b, %al
+	movb	$0x0f, %ah
+	movw	%ax, 0xb8000(,%rdi,2)
+	incq	%rdi
+.Lvga_scroll:
+	cmpq	$2000, %rdi
+	jb	.Lvga_done
+	pushq	%rsi
+	pushq	%rcx
+	movl	$0xb80a0, %esi
+	movl	$0xb8000, %edi
+	movl	$480, %ecx
+	rep movsq
+	movq	$0x0f200f200f200f20, %rax
+	movl	$20, %ecx
+	rep stosq
+	popq	%rcx
+	popq	%rsi
+	movq	$1920, %rdi
+.Lvga_done:
+	movq	%rdi, vga_pos
+	incq	%rsi
+	decq	%rcx
+	jnz	.Lwrite_byte
+.Lwrite_end:
+	ret
+print:
+	movabsq	$-3689348814741910323, %r9
+	subq	$40, %rsp
+	movb	$10, 31(%rsp)
+	leaq	30(%rsp), %rcx
+.L2:
+	movq	%rdi, %rax
+	leaq	32(%rsp), %r8
+	mulq	%r9
+	movq	%rdi, %rax
+	subq	%rcx, %r8
+	shrq	$3, %rdx
+	leaq	(%rdx,%rdx,4), %rsi
+	addq	%rsi, %rsi
+	subq	%rsi, %rax
+	addl	$48, %eax
+	movb	%al, (%rcx)
+	movq	%rdi, %rax
+	movq	%rdx, %rdi
+	movq	%rcx, %rdx
+	subq	$1, %rcx
+	cmpq	$9, %rax
+	ja	.L2
+	leaq	32(%rsp), %rax
+	subq	%rax, %rdx
+	leaq	32(%rsp,%rdx), %rsi
+	movq	%r8, %rdx
+	call	write
+	addq	$40, %rsp
+	ret
+fn_main:
+	pushq	$10			# Op::PushInt(10)
+	pushq	(%rsp)			# Op::Dup
+	popq	%rdi			# Op::Print
+	call	print
+	popq	%rdi			# Op::Print
+	call	print
+	movabsq	$18446744073709551615, %rax	# Op::PushInt(18446744073709551615)
+	pushq	%rax
+	pushq	(%rsp)			# Op::Dup
+	popq	%rdi			# Op::Print
+	call	print
+	popq	%rdi			# Op::Print
+	call	print
+	movq	ret_stack_rsp, %rax	# Op::Ret(0)
+	jmp	*(%rax)
+	.balign	8
+gdt:
+	.quad	0
+	.quad	0x00af9a000000ffff
+	.quad	0x00cf92000000ffff
+gdt_ptr:
+	.word	gdt_ptr - gdt - 1
+	.long	gdt
+	.section	.rodata
+	.bss
+ret_stack_rsp:	.skip	8
+ret_stack:	.skip	65536
+ret_stack_end:
+	.section	.rodata
+data_end:
+	.bss
+	.balign	4096
+pml4:	.skip	4096
+pdpt:	.skip	4096
+pd:	.skip	4096
+vga_pos:	.skip	8
+stack:	.skip	1048576
+stack_end:
+bss_end:
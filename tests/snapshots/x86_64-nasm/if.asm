@@ -0,0 +1,123 @@
+global main
+section .text
+print:
+	mov	r9, -3689348814741910323
+	sub	rsp, 40
+	mov	BYTE [rsp+31], 10
+	lea	rcx, [rsp+30]
+.L2:
+	mov	rax, rdi
+	lea	r8, [rsp+32]
+	mul	r9
+	mov	rax, rdi
+	sub	r8, rcx
+	shr	rdx, 3
+	lea	rsi, [rdx+rdx*4]
+	add	rsi, rsi
+	sub	rax, rsi
+	add	eax, 48
+	mov	BYTE [rcx], al
+	mov	rax, rdi
+	mov	rdi, rdx
+	mov	rdx, rcx
+	sub	rcx, 1
+	cmp	rax, 9
+	ja	.L2
+	lea	rax, [rsp+32]
+	sub	rdx, rax
+	lea	rsi, [rsp+32+rdx]
+	mov	rdx, r8
+	call	write
+	add	rsp, 40
+	ret
+write:
+	mov	rax, 1
+	mov	rdi, 1
+	syscall
+	ret
+fn_main:
+	push	50			; Op::PushInt(50)
+	push	8			; Op::PushInt(8)
+	pop	rbx			; Op::Minus
+	pop	rax
+	sub	rax, rbx
+	push	rax
+	push	42			; Op::PushInt(42)
+	pop	rax			; Op::Equals
+	pop	rbx
+	cmp	rax, rbx
+	je	J4
+	push	0
+	jmp	J5
+J4:
+	push	1
+J5:
+	pop	rax			; Op::If
+	cmp	rax, 1
+	jne	F6
+	push	5			; Op::PushInt(5)
+	push	str_0			; Op::PushStrPtr(0)
+	pop	rsi			; Op::Puts
+	pop	rdx
+	call	write
+	push	1			; Op::PushInt(1)
+	pop	rax			; Op::If
+	cmp	rax, 1
+	jne	F11
+	push	0			; Op::PushInt(0)
+	pop	rax			; Op::If
+	cmp	rax, 1
+	jne	F13
+	push	6			; Op::PushInt(6)
+	push	str_1			; Op::PushStrPtr(1)
+	pop	rsi			; Op::Puts
+	pop	rdx
+	call	write
+F13:
+	push	10			; Op::PushInt(10)
+	push	str_2			; Op::PushStrPtr(2)
+	pop	rsi			; Op::Puts
+	pop	rdx
+	call	write
+F11:
+	push	8			; Op::PushInt(8)
+	push	2			; Op::PushInt(2)
+	pop	rax			; Op::Equals
+	pop	rbx
+	cmp	rax, rbx
+	je	J24
+	push	0
+	jmp	J25
+J24:
+	push	1
+J25:
+	pop	rax			; Op::If
+	cmp	rax, 1
+	jne	F26
+	push	6			; Op::PushInt(6)
+	push	str_1			; Op::PushStrPtr(1)
+	pop	rsi			; Op::Puts
+	pop	rdx
+	call	write
+F26:
+F6:
+	mov	rax, qword [ret_stack_rsp]	; Op::Ret(0)
+	jmp qword [rax]
+main:
+	mov	rax, ret_stack_end
+	sub	rax, 8
+	mov	qword [ret_stack_rsp], rax
+	mov	qword [rax], RET_MAIN
+	call	fn_main
+RET_MAIN:
+	mov	rax, 60
+	mov	rdi, 0
+	syscall
+section .rodata
+str_0: db 116,114,117,101,10
+str_1: db 102,97,108,115,101,10
+str_2: db 97,108,115,111,32,116,114,117,101,10
+section .bss
+ret_stack_rsp: resq 1
+ret_stack: resb 65536
+ret_stack_end:
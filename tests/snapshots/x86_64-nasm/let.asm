@@ -0,0 +1,106 @@
+global main
+section .text
+print:
+	mov	r9, -3689348814741910323
+	sub	rsp, 40
+	mov	BYTE [rsp+31], 10
+	lea	rcx, [rsp+30]
+.L2:
+	mov	rax, rdi
+	lea	r8, [rsp+32]
+	mul	r9
+	mov	rax, rdi
+	sub	r8, rcx
+	shr	rdx, 3
+	lea	rsi, [rdx+rdx*4]
+	add	rsi, rsi
+	sub	rax, rsi
+	add	eax, 48
+	mov	BYTE [rcx], al
+	mov	rax, rdi
+	mov	rdi, rdx
+	mov	rdx, rcx
+	sub	rcx, 1
+	cmp	rax, 9
+	ja	.L2
+	lea	rax, [rsp+32]
+	sub	rdx, rax
+	lea	rsi, [rsp+32+rdx]
+	mov	rdx, r8
+	call	write
+	add	rsp, 40
+	ret
+write:
+	mov	rax, 1
+	mov	rdi, 1
+	syscall
+	ret
+fn_main:
+	push	1			; Op::PushInt(1)
+	push	2			; Op::PushInt(2)
+	mov	rax, [ret_stack_rsp]	; Op::Bind(2)
+	sub	rax, 16
+	mov	[ret_stack_rsp], rax
+	mov	rbx, [rsp + 0]
+	mov	[rax+0], rbx
+	mov	rbx, [rsp + 8]
+	mov	[rax+8], rbx
+	add	rsp, 16
+	mov	rax, [ret_stack_rsp]	; Op::PushBind(1)
+	add	rax, 8
+	push	qword [rax]
+	pop	rdi			; Op::Print
+	call	print
+	push	3			; Op::PushInt(3)
+	push	4			; Op::PushInt(4)
+	mov	rax, [ret_stack_rsp]	; Op::Bind(2)
+	sub	rax, 16
+	mov	[ret_stack_rsp], rax
+	mov	rbx, [rsp + 0]
+	mov	[rax+0], rbx
+	mov	rbx, [rsp + 8]
+	mov	[rax+8], rbx
+	add	rsp, 16
+	mov	rax, [ret_stack_rsp]	; Op::PushBind(2)
+	add	rax, 16
+	push	qword [rax]
+	pop	rdi			; Op::Print
+	call	print
+	mov	rax, [ret_stack_rsp]	; Op::PushBind(3)
+	add	rax, 24
+	push	qword [rax]
+	pop	rdi			; Op::Print
+	call	print
+	mov	rax, [ret_stack_rsp]	; Op::PushBind(1)
+	add	rax, 8
+	push	qword [rax]
+	pop	rdi			; Op::Print
+	call	print
+	mov	rax, [ret_stack_rsp]
+	add	rax, 16
+	mov	qword [ret_stack_rsp], rax
+	mov	rax, [ret_stack_rsp]	; Op::PushBind(0)
+	add	rax, 0
+	push	qword [rax]
+	pop	rdi			; Op::Print
+	call	print
+	mov	rax, [ret_stack_rsp]
+	add	rax, 16
+	mov	qword [ret_stack_rsp], rax
+	mov	rax, qword [ret_stack_rsp]	; Op::Ret(0)
+	jmp qword [rax]
+main:
+	mov	rax, ret_stack_end
+	sub	rax, 8
+	mov	qword [ret_stack_rsp], rax
+	mov	qword [rax], RET_MAIN
+	call	fn_main
+RET_MAIN:
+	mov	rax, 60
+	mov	rdi, 0
+	syscall
+section .rodata
+section .bss
+ret_stack_rsp: resq 1
+ret_stack: resb 65536
+ret_stack_end:
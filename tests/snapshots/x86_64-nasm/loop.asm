@@ -0,0 +1,145 @@
+global main
+section .text
+print:
+	mov	r9, -3689348814741910323
+	sub	rsp, 40
+	mov	BYTE [rsp+31], 10
+	lea	rcx, [rsp+30]
+.L2:
+	mov	rax, rdi
+	lea	r8, [rsp+32]
+	mul	r9
+	mov	rax, rdi
+	sub	r8, rcx
+	shr	rdx, 3
+	lea	rsi, [rdx+rdx*4]
+	add	rsi, rsi
+	sub	rax, rsi
+	add	eax, 48
+	mov	BYTE [rcx], al
+	mov	rax, rdi
+	mov	rdi, rdx
+	mov	rdx, rcx
+	sub	rcx, 1
+	cmp	rax, 9
+	ja	.L2
+	lea	rax, [rsp+32]
+	sub	rdx, rax
+	lea	rsi, [rsp+32+rdx]
+	mov	rdx, r8
+	call	write
+	add	rsp, 40
+	ret
+write:
+	mov	rax, 1
+	mov	rdi, 1
+	syscall
+	ret
+fn_main:
+	push	0			; Op::PushInt(0)
+F1:					; Op::While
+	push	1			; Op::PushInt(1)
+	pop	rax			; Op::Plus
+	pop	rbx
+	add	rax, rbx
+	push	rax
+	push	qword [rsp]		; Op::Dup
+	push	101			; Op::PushInt(101)
+	pop	rax			; Op::LessThan
+	pop	rbx
+	cmp	rbx, rax
+	jb	J7
+	push	0
+	jmp	J8
+J7:
+	push	1
+J8:
+	pop	rax
+	cmp	rax, 1
+	jne	F2
+	push	qword [rsp]		; Op::Dup
+	pop	rdi			; Op::Print
+	call	print
+	push	qword [rsp]		; Op::Dup
+	push	69			; Op::PushInt(69)
+	pop	rax			; Op::Equals
+	pop	rbx
+	cmp	rax, rbx
+	je	J13
+	push	0
+	jmp	J14
+J13:
+	push	1
+J14:
+	pop	rax			; Op::If
+	cmp	rax, 1
+	jne	F15
+	push	420			; Op::PushInt(420)
+	pop	rdi			; Op::Print
+	call	print
+F15:
+	jmp F1
+F2:
+	push	0			; Op::PushInt(0)
+F21:					; Op::While
+	push	1			; Op::PushInt(1)
+	push	1			; Op::PushInt(1)
+	pop	rax			; Op::And
+	pop	rbx
+	cmp	rax, rbx
+	jne	J25
+	cmp	rax, 1
+	jne	J25
+	push	1
+	jmp	J26
+J25:
+	push	0
+J26:
+	pop	rax			; Op::If
+	cmp	rax, 1
+	jne	F27
+	push	0			; Op::PushInt(0)
+	pop	rdi			; Op::Print
+	call	print
+F27:
+	push	1			; Op::PushInt(1)
+	pop	rax			; Op::Plus
+	pop	rbx
+	add	rax, rbx
+	push	rax
+	push	qword [rsp]		; Op::Dup
+	push	101			; Op::PushInt(101)
+	pop	rax			; Op::LessThan
+	pop	rbx
+	cmp	rbx, rax
+	jb	J35
+	push	0
+	jmp	J36
+J35:
+	push	1
+J36:
+	pop	rax
+	cmp	rax, 1
+	jne	F22
+	push	1			; Op::PushInt(1)
+	pop	rdi			; Op::Print
+	call	print
+	jmp F21
+F22:
+	mov	rax, qword [ret_stack_rsp]	; Op::Ret(0)
+	jmp qword [rax]
+main:
+	mov	rax, ret_stack_end
+	sub	rax, 8
+	mov	qword [ret_stack_rsp], rax
+	mov	qword [rax], RET_MAIN
+	call	fn_main
+RET_MAIN:
+	mov	rax, 60
+	mov	rdi, 0
+	syscall
+section .rodata
+section .bss
+ret_stack_rsp: resq 1
+ret_stack: resb 65536
+ret_stack_end:
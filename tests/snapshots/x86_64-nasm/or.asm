@@ -0,0 +1,135 @@
+global main
+section .text
+print:
+	mov	r9, -3689348814741910323
+	sub	rsp, 40
+	mov	BYTE [rsp+31], 10
+	lea	rcx, [rsp+30]
+.L2:
+	mov	rax, rdi
+	lea	r8, [rsp+32]
+	mul	r9
+	mov	rax, rdi
+	sub	r8, rcx
+	shr	rdx, 3
+	lea	rsi, [rdx+rdx*4]
+	add	rsi, rsi
+	sub	rax, rsi
+	add	eax, 48
+	mov	BYTE [rcx], al
+	mov	rax, rdi
+	mov	rdi, rdx
+	mov	rdx, rcx
+	sub	rcx, 1
+	cmp	rax, 9
+	ja	.L2
+	lea	rax, [rsp+32]
+	sub	rdx, rax
+	lea	rsi, [rsp+32+rdx]
+	mov	rdx, r8
+	call	write
+	add	rsp, 40
+	ret
+write:
+	mov	rax, 1
+	mov	rdi, 1
+	syscall
+	ret
+fn_main:
+	push	1			; Op::PushInt(1)
+	push	0			; Op::PushInt(0)
+	pop	rax			; Op::Or
+	pop	rbx
+	cmp	rax, 1
+	je	J2
+	cmp	rbx, 1
+	je	J2
+	push	0
+	jmp	J3
+J2:
+	push	1
+J3:
+	pop	rax			; Op::If
+	cmp	rax, 1
+	jne	F4
+	push	1			; Op::PushInt(1)
+	pop	rdi			; Op::Print
+	call	print
+F4:
+	push	0			; Op::PushInt(0)
+	push	1			; Op::PushInt(1)
+	pop	rax			; Op::Or
+	pop	rbx
+	cmp	rax, 1
+	je	J10
+	cmp	rbx, 1
+	je	J10
+	push	0
+	jmp	J11
+J10:
+	push	1
+J11:
+	pop	rax			; Op::If
+	cmp	rax, 1
+	jne	F12
+	push	1			; Op::PushInt(1)
+	pop	rdi			; Op::Print
+	call	print
+F12:
+	push	1			; Op::PushInt(1)
+	push	1			; Op::PushInt(1)
+	pop	rax			; Op::Or
+	pop	rbx
+	cmp	rax, 1
+	je	J18
+	cmp	rbx, 1
+	je	J18
+	push	0
+	jmp	J19
+J18:
+	push	1
+J19:
+	pop	rax			; Op::If
+	cmp	rax, 1
+	jne	F20
+	push	1			; Op::PushInt(1)
+	pop	rdi			; Op::Print
+	call	print
+F20:
+	push	0			; Op::PushInt(0)
+	push	0			; Op::PushInt(0)
+	pop	rax			; Op::Or
+	pop	rbx
+	cmp	rax, 1
+	je	J26
+	cmp	rbx, 1
+	je	J26
+	push	0
+	jmp	J27
+J26:
+	push	1
+J27:
+	pop	rax			; Op::If
+	cmp	rax, 1
+	jne	F28
+	push	0			; Op::PushInt(0)
+	pop	rdi			; Op::Print
+	call	print
+F28:
+	mov	rax, qword [ret_stack_rsp]	; Op::Ret(0)
+	jmp qword [rax]
+main:
+	mov	rax, ret_stack_end
+	sub	rax, 8
+	mov	qword [ret_stack_rsp], rax
+	mov	qword [rax], RET_MAIN
+	call	fn_main
+RET_MAIN:
+	mov	rax, 60
+	mov	rdi, 0
+	syscall
+section .rodata
+section .bss
+ret_stack_rsp: resq 1
+ret_stack: resb 65536
+ret_stack_end:
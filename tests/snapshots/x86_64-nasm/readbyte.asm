@@ -0,0 +1,101 @@
+global main
+section .text
+print:
+	mov	r9, -3689348814741910323
+	sub	rsp, 40
+	mov	BYTE [rsp+31], 10
+	lea	rcx, [rsp+30]
+.L2:
+	mov	rax, rdi
+	lea	r8, [rsp+32]
+	mul	r9
+	mov	rax, rdi
+	sub	r8, rcx
+	shr	rdx, 3
+	lea	rsi, [rdx+rdx*4]
+	add	rsi, rsi
+	sub	rax, rsi
+	add	eax, 48
+	mov	BYTE [rcx], al
+	mov	rax, rdi
+	mov	rdi, rdx
+	mov	rdx, rcx
+	sub	rcx, 1
+	cmp	rax, 9
+	ja	.L2
+	lea	rax, [rsp+32]
+	sub	rdx, rax
+	lea	rsi, [rsp+32+rdx]
+	mov	rdx, r8
+	call	write
+	add	rsp, 40
+	ret
+write:
+	mov	rax, 1
+	mov	rdi, 1
+	syscall
+	ret
+fn_main:
+	push	4			; Op::PushInt(4)
+	push	str_0			; Op::PushStrPtr(0)
+	push	qword [rsp]		; Op::Dup
+	pop	rbx			; Op::ReadByte
+	mov	rax, 0
+	mov	al, byte [rbx]
+	push	rax
+	pop	rdi			; Op::Print
+	call	print
+	push	qword [rsp]		; Op::Dup
+	push	1			; Op::PushInt(1)
+	pop	rax			; Op::Plus
+	pop	rbx
+	add	rax, rbx
+	push	rax
+	pop	rbx			; Op::ReadByte
+	mov	rax, 0
+	mov	al, byte [rbx]
+	push	rax
+	pop	rdi			; Op::Print
+	call	print
+	push	qword [rsp]		; Op::Dup
+	push	2			; Op::PushInt(2)
+	pop	rax			; Op::Plus
+	pop	rbx
+	add	rax, rbx
+	push	rax
+	pop	rbx			; Op::ReadByte
+	mov	rax, 0
+	mov	al, byte [rbx]
+	push	rax
+	pop	rdi			; Op::Print
+	call	print
+	push	qword [rsp]		; Op::Dup
+	push	3			; Op::PushInt(3)
+	pop	rax			; Op::Plus
+	pop	rbx
+	add	rax, rbx
+	push	rax
+	pop	rbx			; Op::ReadByte
+	mov	rax, 0
+	mov	al, byte [rbx]
+	push	rax
+	pop	rdi			; Op::Print
+	call	print
+	mov	rax, qword [ret_stack_rsp]	; Op::Ret(0)
+	jmp qword [rax]
+main:
+	mov	rax, ret_stack_end
+	sub	rax, 8
+	mov	qword [ret_stack_rsp], rax
+	mov	qword [rax], RET_MAIN
+	call	fn_main
+RET_MAIN:
+	mov	rax, 60
+	mov	rdi, 0
+	syscall
+section .rodata
+str_0: db 116,101,115,116
+section .bss
+ret_stack_rsp: resq 1
+ret_stack: resb 65536
+ret_stack_end:
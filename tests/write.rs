@@ -17,7 +17,7 @@ fn main() {
                 let file = Path::new(&file);
                 let file_path = file.to_str().unwrap();
                 let file_stem = file.file_stem().unwrap().to_str().unwrap();
-                run_test(&file_path, Mode::Write);
+                run_test(file_path, Mode::Write);
                 println!("Updated expected output for test case `{file_stem}`")
             }
             None => {